@@ -0,0 +1,68 @@
+//! Times reads and writes through a memory map with each memory ordering, run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use sneslib::address::Address24;
+use sneslib::cartridge::{CartridgeBuilder, ROMType};
+use sneslib::memory::{AccessOrdering, MemoryMap};
+
+const ACCESSES: u32 = 20_000_000;
+const RUNS: usize = 5;
+
+/// Returns the fastest of a few runs, the first warming up the caches.
+fn time(mut f: impl FnMut()) -> Duration {
+	(0..RUNS)
+		.map(|_| {
+			let start = Instant::now();
+			f();
+			start.elapsed()
+		})
+		.min()
+		.unwrap()
+}
+
+fn report(name: &str, ordering: AccessOrdering, elapsed: Duration) {
+	println!(
+		"{:<24}{:<16}{:>8.2} ns/access",
+		name,
+		format!("{:?}", ordering),
+		elapsed.as_nanos() as f64 / ACCESSES as f64
+	);
+}
+
+fn main() {
+	let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+		.sram_size(0x2000)
+		.build()
+		.unwrap();
+	let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+	for &ordering in [
+		AccessOrdering::Relaxed,
+		AccessOrdering::AcquireRelease,
+		AccessOrdering::SeqCst,
+	]
+	.iter()
+	{
+		memory_map.set_ordering(ordering);
+		let elapsed = time(|| {
+			for i in 0..ACCESSES {
+				black_box(memory_map.read(Address24::new(0x808000 | (i & 0x7FFF))));
+			}
+		});
+		report("read ROM", ordering, elapsed);
+		let elapsed = time(|| {
+			for i in 0..ACCESSES {
+				memory_map.write(Address24::new(0x7E0000 | (i & 0x1FFFF)), i as u8);
+			}
+		});
+		report("write WRAM", ordering, elapsed);
+		let elapsed = time(|| {
+			for i in 0..ACCESSES {
+				let address = Address24::new(0x7E0000 | (i & 0x1FFFF));
+				memory_map.write(address, memory_map.read(address).wrapping_add(1));
+			}
+		});
+		report("read and write WRAM", ordering, elapsed);
+	}
+}
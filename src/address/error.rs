@@ -1,8 +1,24 @@
-use std::{error::Error, fmt, num};
+use core::{convert::TryInto, fmt, num};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressError {
+	/// An integral conversion failed and the value could not be preserved.
 	TryFromIntError,
+	/// The value does not fit into the address type.
+	OutOfRange { value: i128, max: u32 },
+	/// The string is not a valid address notation.
+	ParseError,
+}
+
+impl AddressError {
+	/// Creates an `OutOfRange` error, falling back to `TryFromIntError`
+	/// if the value cannot be represented as an `i128`.
+	pub(crate) fn out_of_range<T: TryInto<i128>>(value: T, max: u32) -> Self {
+		match value.try_into() {
+			Ok(value) => Self::OutOfRange { value, max },
+			Err(_) => Self::TryFromIntError,
+		}
+	}
 }
 
 impl From<num::TryFromIntError> for AddressError {
@@ -11,8 +27,8 @@ impl From<num::TryFromIntError> for AddressError {
 	}
 }
 
-impl From<std::convert::Infallible> for AddressError {
-	fn from(_: std::convert::Infallible) -> Self {
+impl From<core::convert::Infallible> for AddressError {
+	fn from(_: core::convert::Infallible) -> Self {
 		unreachable!()
 	}
 }
@@ -22,8 +38,13 @@ impl fmt::Display for AddressError {
 		use AddressError::*;
 		match self {
 			TryFromIntError => "out of range integral type conversion attempted".fmt(f),
+			OutOfRange { value, max } => {
+				write!(f, "address {} out of range (maximum ${:X})", value, max)
+			}
+			ParseError => "invalid address syntax".fmt(f),
 		}
 	}
 }
 
-impl Error for AddressError {}
+#[cfg(feature = "std")]
+impl std::error::Error for AddressError {}
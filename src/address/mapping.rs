@@ -0,0 +1,408 @@
+use super::{Address16, Address24};
+use crate::cartridge::ROMType;
+
+/// Converts a SNES address to an offset into the ROM image.
+///
+/// Returns `None` if the address does not map to ROM, e.g. WRAM, SRAM or I/O registers.
+/// ```
+/// # use sneslib::address::*;
+/// # use sneslib::cartridge::ROMType;
+/// assert_eq!(snes_to_pc(Address24::new(0x808000), ROMType::LoROM), Some(0));
+/// assert_eq!(snes_to_pc(Address24::new(0xC12345), ROMType::HiROM), Some(0x12345));
+/// assert_eq!(snes_to_pc(Address24::new(0x7E0000), ROMType::LoROM), None);
+/// ```
+pub fn snes_to_pc(address: Address24, rom_type: ROMType) -> Option<usize> {
+	let bank = address.bank() as usize;
+	let offset = address.get_lower_address16().0 as usize;
+	if bank & 0xFE == 0x7E {
+		// WRAM
+		return None;
+	}
+
+	match rom_type {
+		ROMType::LoROM => {
+			if offset >= 0x8000 {
+				Some((bank & 0x7F) << 15 | (offset & 0x7FFF))
+			} else if bank & 0x40 != 0 && bank & 0x70 != 0x70 {
+				Some((bank & 0x7F) << 15 | offset)
+			} else {
+				None
+			}
+		}
+		ROMType::HiROM => {
+			if bank & 0x40 != 0 || offset >= 0x8000 {
+				Some((bank & 0x3F) << 16 | offset)
+			} else {
+				None
+			}
+		}
+		ROMType::ExHiROM => {
+			let upper = if bank & 0x80 == 0 { 0x400000 } else { 0 };
+			if bank & 0x40 != 0 || offset >= 0x8000 {
+				Some(upper | (bank & 0x3F) << 16 | offset)
+			} else {
+				None
+			}
+		}
+	}
+}
+
+/// Converts an offset into the ROM image to its canonical SNES address.
+///
+/// LoROM offsets map to banks `$00-$7D` and `$FE-$FF`, HiROM offsets to banks `$C0-$FF`,
+/// and ExHiROM offsets to banks `$C0-$FF` followed by `$40-$7D` and `$3E-$3F`.
+/// Returns `None` if the offset is outside of the addressable ROM space.
+/// ```
+/// # use sneslib::address::*;
+/// # use sneslib::cartridge::ROMType;
+/// assert_eq!(pc_to_snes(0, ROMType::LoROM), Some(Address24::new(0x008000)));
+/// assert_eq!(pc_to_snes(0x12345, ROMType::HiROM), Some(Address24::new(0xC12345)));
+/// assert_eq!(pc_to_snes(0x400000, ROMType::HiROM), None);
+/// ```
+pub fn pc_to_snes(offset: usize, rom_type: ROMType) -> Option<Address24> {
+	match rom_type {
+		ROMType::LoROM => {
+			if offset >= 0x400000 {
+				return None;
+			}
+			let mut bank = (offset >> 15) as u8;
+			if bank >= 0x7E {
+				bank |= 0x80;
+			}
+			let offset = Address16(0x8000 | (offset & 0x7FFF) as u16);
+			Some(Address24::from_bank_offset(bank, offset))
+		}
+		ROMType::HiROM => {
+			if offset >= 0x400000 {
+				return None;
+			}
+			Some(Address24(0xC00000 | offset as u32))
+		}
+		ROMType::ExHiROM => match offset {
+			0..=0x3FFFFF => Some(Address24(0xC00000 | offset as u32)),
+			0x400000..=0x7DFFFF => Some(Address24(offset as u32)),
+			0x7E0000..=0x7FFFFF if offset & 0x8000 != 0 => {
+				Some(Address24(offset as u32 & 0x3FFFFF))
+			}
+			_ => None,
+		},
+	}
+}
+
+impl Address24 {
+	/// Returns `true` if the address is in WRAM, including the low 8KiB mirrors.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert!(Address24::new(0x7E0100).is_wram());
+	/// assert!(Address24::new(0x801FFF).is_wram());
+	/// assert!(!Address24::new(0x402000).is_wram());
+	/// ```
+	#[inline]
+	pub fn is_wram(&self) -> bool {
+		let bank = self.bank();
+		bank & 0xFE == 0x7E || (self.is_system_bank() && self.get_lower_address16().0 < 0x2000)
+	}
+
+	/// Returns `true` if the address is in a system area bank, `$00-$3F` or `$80-$BF`.
+	#[inline]
+	fn is_system_bank(&self) -> bool {
+		self.bank() & 0x40 == 0
+	}
+
+	/// Returns `true` if the address is one of the PPU, APU, WRAM port, joypad, CPU or DMA registers.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert!(Address24::new(0x002100).is_hardware_register());
+	/// assert!(Address24::new(0x80420B).is_hardware_register());
+	/// assert!(!Address24::new(0x402100).is_hardware_register());
+	/// ```
+	#[inline]
+	pub fn is_hardware_register(&self) -> bool {
+		self.is_system_bank()
+			&& matches!(self.get_lower_address16().0,
+				0x2100..=0x21FF | 0x4016..=0x4017 | 0x4200..=0x421F | 0x4300..=0x437F)
+	}
+
+	/// Returns `true` if the address is where the cartridge SRAM is mapped for the given ROM type.
+	///
+	/// LoROM SRAM is mapped at `$70-$7D:0000-7FFF` and `$F0-$FF:0000-7FFF`: banks `$7E-$7F` hold the WRAM,
+	/// but the WRAM is not mirrored to `$FE-$FF`, so the cartridge decodes the SRAM there as well.
+	/// ```
+	/// # use sneslib::address::*;
+	/// # use sneslib::cartridge::ROMType;
+	/// assert!(Address24::new(0x700000).is_sram_area(ROMType::LoROM));
+	/// assert!(Address24::new(0x206000).is_sram_area(ROMType::HiROM));
+	/// assert!(!Address24::new(0x206000).is_sram_area(ROMType::LoROM));
+	/// ```
+	pub fn is_sram_area(&self, rom_type: ROMType) -> bool {
+		let bank = self.bank();
+		let offset = self.get_lower_address16().0;
+		match rom_type {
+			ROMType::LoROM => matches!(bank, 0x70..=0x7D | 0xF0..=0xFF) && offset < 0x8000,
+			ROMType::HiROM => {
+				matches!(bank & 0x7F, 0x20..=0x3F) && (0x6000..0x8000).contains(&offset)
+			}
+			ROMType::ExHiROM => matches!(bank, 0x80..=0xBF) && (0x6000..0x8000).contains(&offset),
+		}
+	}
+
+	/// Returns `true` if the address maps to ROM for the given ROM type.
+	/// ```
+	/// # use sneslib::address::*;
+	/// # use sneslib::cartridge::ROMType;
+	/// assert!(Address24::new(0x808000).is_rom_area(ROMType::LoROM));
+	/// assert!(!Address24::new(0x800000).is_rom_area(ROMType::LoROM));
+	/// assert!(Address24::new(0xC00000).is_rom_area(ROMType::HiROM));
+	/// ```
+	#[inline]
+	pub fn is_rom_area(&self, rom_type: ROMType) -> bool {
+		snes_to_pc(*self, rom_type).is_some()
+	}
+
+	/// Maps a mirrored address to its canonical form.
+	///
+	/// ROM addresses are canonicalized as by `pc_to_snes`, WRAM mirrors to banks `$7E-$7F`,
+	/// the I/O area `$2000-$5FFF` of the system banks to bank `$00`,
+	/// and other addresses in banks `$80-$FF` to the corresponding banks `$00-$7F`.
+	/// ```
+	/// # use sneslib::address::*;
+	/// # use sneslib::cartridge::ROMType;
+	/// assert_eq!(Address24::new(0x808000).canonicalize(ROMType::LoROM), Address24::new(0x008000));
+	/// assert_eq!(Address24::new(0x801234).canonicalize(ROMType::LoROM), Address24::new(0x7E1234));
+	/// assert_eq!(Address24::new(0x802100).canonicalize(ROMType::LoROM), Address24::new(0x002100));
+	/// ```
+	pub fn canonicalize(&self, rom_type: ROMType) -> Address24 {
+		let bank = self.bank();
+		if let Some(address) = snes_to_pc(*self, rom_type).and_then(|pc| pc_to_snes(pc, rom_type)) {
+			address
+		} else if self.is_wram() {
+			if bank & 0xFE == 0x7E {
+				*self
+			} else {
+				self.with_bank(0x7E)
+			}
+		} else if self.is_system_bank() && (0x2000..0x6000).contains(&self.get_lower_address16().0)
+		{
+			self.with_bank(0x00)
+		} else if bank >= 0x80
+			&& bank & 0xFE != 0xFE
+			&& !(rom_type == ROMType::ExHiROM && self.is_sram_area(rom_type))
+		{
+			self.with_bank(bank & 0x7F)
+		} else {
+			*self
+		}
+	}
+
+	/// Returns `true` if both addresses refer to the same location for the given ROM type.
+	/// ```
+	/// # use sneslib::address::*;
+	/// # use sneslib::cartridge::ROMType;
+	/// let a = Address24::new(0x808000);
+	/// assert!(a.is_mirror_of(Address24::new(0x008000), ROMType::LoROM));
+	/// assert!(!a.is_mirror_of(Address24::new(0x008000), ROMType::ExHiROM));
+	/// ```
+	#[inline]
+	pub fn is_mirror_of(&self, other: Address24, rom_type: ROMType) -> bool {
+		self.canonicalize(rom_type) == other.canonicalize(rom_type)
+	}
+
+	/// Returns an iterator over every address referring to the same location, including `self`,
+	/// in ascending order.
+	///
+	/// Mirrors only ever differ in the bank and in bit 15 of the offset.
+	/// ```
+	/// # use sneslib::address::*;
+	/// # use sneslib::cartridge::ROMType;
+	/// let mirrors = Address24::new(0x808000).mirrors(ROMType::LoROM).collect::<Vec<_>>();
+	/// assert_eq!(mirrors, vec![Address24::new(0x008000), Address24::new(0x808000)]);
+	/// ```
+	pub fn mirrors(&self, rom_type: ROMType) -> impl Iterator<Item = Address24> {
+		let canonical = self.canonicalize(rom_type);
+		let offset = self.get_lower_address16().0 & 0x7FFF;
+		(0..=0xFF)
+			.flat_map(move |bank| {
+				let lower = Address24::from_bank_offset(bank, Address16(offset));
+				let upper = Address24::from_bank_offset(bank, Address16(offset | 0x8000));
+				core::iter::once(lower).chain(core::iter::once(upper))
+			})
+			.filter(move |address| address.canonicalize(rom_type) == canonical)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn lorom() {
+		let lorom = |a| snes_to_pc(Address24(a), ROMType::LoROM);
+		assert_eq!(lorom(0x008000), Some(0x000000));
+		assert_eq!(lorom(0x00FFFF), Some(0x007FFF));
+		assert_eq!(lorom(0x018000), Some(0x008000));
+		assert_eq!(lorom(0x808000), Some(0x000000));
+		assert_eq!(lorom(0x408000), Some(0x200000));
+		assert_eq!(lorom(0x400000), Some(0x200000));
+		assert_eq!(lorom(0xC00000), Some(0x200000));
+		assert_eq!(lorom(0xFFFFFF), Some(0x3FFFFF));
+		assert_eq!(lorom(0x000000), None);
+		assert_eq!(lorom(0x007FFF), None);
+		assert_eq!(lorom(0x700000), None);
+		assert_eq!(lorom(0xF00000), None);
+		assert_eq!(lorom(0x7E8000), None);
+		assert_eq!(lorom(0x7F0000), None);
+
+		let lorom = |o| pc_to_snes(o, ROMType::LoROM);
+		assert_eq!(lorom(0x000000), Some(Address24(0x008000)));
+		assert_eq!(lorom(0x200000), Some(Address24(0x408000)));
+		assert_eq!(lorom(0x3EFFFF), Some(Address24(0x7DFFFF)));
+		assert_eq!(lorom(0x3F0000), Some(Address24(0xFE8000)));
+		assert_eq!(lorom(0x3FFFFF), Some(Address24(0xFFFFFF)));
+		assert_eq!(lorom(0x400000), None);
+		for o in (0..0x400000).step_by(0x1234) {
+			assert_eq!(snes_to_pc(lorom(o).unwrap(), ROMType::LoROM), Some(o));
+		}
+	}
+
+	#[test]
+	fn hirom() {
+		let hirom = |a| snes_to_pc(Address24(a), ROMType::HiROM);
+		assert_eq!(hirom(0xC00000), Some(0x000000));
+		assert_eq!(hirom(0x400000), Some(0x000000));
+		assert_eq!(hirom(0x008000), Some(0x008000));
+		assert_eq!(hirom(0x808000), Some(0x008000));
+		assert_eq!(hirom(0x3FFFFF), Some(0x3FFFFF));
+		assert_eq!(hirom(0xFFFFFF), Some(0x3FFFFF));
+		assert_eq!(hirom(0x006000), None);
+		assert_eq!(hirom(0x7E0000), None);
+
+		let hirom = |o| pc_to_snes(o, ROMType::HiROM);
+		assert_eq!(hirom(0x000000), Some(Address24(0xC00000)));
+		assert_eq!(hirom(0x3FFFFF), Some(Address24(0xFFFFFF)));
+		assert_eq!(hirom(0x400000), None);
+		for o in (0..0x400000).step_by(0x1234) {
+			assert_eq!(snes_to_pc(hirom(o).unwrap(), ROMType::HiROM), Some(o));
+		}
+	}
+
+	#[test]
+	fn exhirom() {
+		let exhirom = |a| snes_to_pc(Address24(a), ROMType::ExHiROM);
+		assert_eq!(exhirom(0xC00000), Some(0x000000));
+		assert_eq!(exhirom(0x808000), Some(0x008000));
+		assert_eq!(exhirom(0x400000), Some(0x400000));
+		assert_eq!(exhirom(0x008000), Some(0x408000));
+		assert_eq!(exhirom(0x3FFFFF), Some(0x7FFFFF));
+		assert_eq!(exhirom(0x7DFFFF), Some(0x7DFFFF));
+		assert_eq!(exhirom(0x7E0000), None);
+		assert_eq!(exhirom(0x000000), None);
+
+		let exhirom = |o| pc_to_snes(o, ROMType::ExHiROM);
+		assert_eq!(exhirom(0x000000), Some(Address24(0xC00000)));
+		assert_eq!(exhirom(0x400000), Some(Address24(0x400000)));
+		assert_eq!(exhirom(0x7E8000), Some(Address24(0x3E8000)));
+		assert_eq!(exhirom(0x7E0000), None);
+		assert_eq!(exhirom(0x800000), None);
+		for o in (0..0x800000).step_by(0x1234) {
+			if let Some(a) = exhirom(o) {
+				assert_eq!(snes_to_pc(a, ROMType::ExHiROM), Some(o));
+			}
+		}
+	}
+
+	#[test]
+	fn canonicalize() {
+		use ROMType::*;
+
+		let canon = |a, rom_type| Address24(a).canonicalize(rom_type);
+		assert_eq!(canon(0x808000, LoROM), Address24(0x008000));
+		assert_eq!(canon(0xC00000, LoROM), Address24(0x408000));
+		assert_eq!(canon(0xFFFFFF, LoROM), Address24(0xFFFFFF));
+		assert_eq!(canon(0xF00000, LoROM), Address24(0x700000));
+		assert_eq!(canon(0xFE0000, LoROM), Address24(0xFE0000));
+		assert_eq!(canon(0x3F1FFF, LoROM), Address24(0x7E1FFF));
+		assert_eq!(canon(0x7F1FFF, LoROM), Address24(0x7F1FFF));
+		assert_eq!(canon(0x80420B, LoROM), Address24(0x00420B));
+		assert_eq!(canon(0x3F2100, LoROM), Address24(0x002100));
+		assert_eq!(canon(0x008000, HiROM), Address24(0xC08000));
+		assert_eq!(canon(0x7D0000, HiROM), Address24(0xFD0000));
+		assert_eq!(canon(0xA06000, HiROM), Address24(0x206000));
+		assert_eq!(canon(0x008000, ExHiROM), Address24(0x408000));
+		assert_eq!(canon(0x806000, ExHiROM), Address24(0x806000));
+
+		// canonicalization is idempotent
+		for rom_type in [LoROM, HiROM, ExHiROM].iter().cloned() {
+			for a in (0..0x1000000).step_by(0x1357) {
+				let c = canon(a, rom_type);
+				assert_eq!(c.canonicalize(rom_type), c);
+				assert!(Address24(a).is_mirror_of(c, rom_type));
+			}
+		}
+	}
+
+	#[test]
+	fn mirrors() {
+		use ROMType::*;
+
+		let mirrors = |a, rom_type| {
+			Address24(a)
+				.mirrors(rom_type)
+				.map(|a| a.0)
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(
+			mirrors(0x408123, LoROM),
+			vec![0x400123, 0x408123, 0xC00123, 0xC08123]
+		);
+		assert_eq!(
+			mirrors(0xC08123, HiROM),
+			vec![0x008123, 0x408123, 0x808123, 0xC08123]
+		);
+		assert_eq!(mirrors(0x408123, ExHiROM), vec![0x008123, 0x408123]);
+		assert_eq!(mirrors(0x7F1234, LoROM), vec![0x7F1234]);
+		assert_eq!(mirrors(0x002100, HiROM).len(), 0x80);
+
+		let wram = mirrors(0x7E1234, LoROM);
+		assert_eq!(wram.len(), 0x81);
+		assert_eq!(wram[0], 0x001234);
+		assert!(wram.contains(&0x7E1234));
+		assert!(!wram.contains(&0x401234));
+		assert!(wram.iter().all(|&a| Address24(a).is_wram()));
+	}
+
+	#[test]
+	fn regions() {
+		use ROMType::*;
+
+		// ROM, SRAM, WRAM and registers never overlap
+		for bank in 0..=0xFF {
+			for offset in (0..=0xFFFF).step_by(0x100) {
+				let a = Address24::from_bank_offset(bank, Address16(offset));
+				for &rom_type in &[LoROM, HiROM, ExHiROM] {
+					let n = [
+						a.is_wram(),
+						a.is_hardware_register(),
+						a.is_sram_area(rom_type),
+						a.is_rom_area(rom_type),
+					]
+					.iter()
+					.filter(|&&b| b)
+					.count();
+					assert!(n <= 1, "{} {:?}", a, rom_type);
+				}
+			}
+		}
+
+		assert!(Address24(0x7FFFFF).is_wram());
+		assert!(!Address24(0x002000).is_wram());
+		assert!(Address24(0x004300).is_hardware_register());
+		assert!(!Address24(0x004380).is_hardware_register());
+		assert!(Address24(0xFD7FFF).is_sram_area(LoROM));
+		assert!(Address24(0xFE0000).is_sram_area(LoROM));
+		assert!(!Address24(0x7E0000).is_sram_area(LoROM));
+		assert!(Address24(0xBF7FFF).is_sram_area(HiROM));
+		assert!(!Address24(0x1F6000).is_sram_area(HiROM));
+		assert!(Address24(0x806000).is_sram_area(ExHiROM));
+		assert!(!Address24(0x006000).is_sram_area(ExHiROM));
+	}
+}
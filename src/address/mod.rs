@@ -1,15 +1,43 @@
-use std::convert::{TryFrom, TryInto};
-use std::fmt;
-use std::ops::{Add, BitAnd, Sub};
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::{Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+use core::ops::{Not, Sub, SubAssign};
+use core::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub use mapping::{pc_to_snes, snes_to_pc};
+pub use mode::{DirectPage, StackRelative};
+pub use range::{AddressRange16, AddressRange24};
+pub use slice::SliceAddressExt;
 
 pub mod error;
+pub mod mapping;
+pub mod mode;
+pub mod range;
+pub mod slice;
 
 /// 16-bit address type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serialized as a plain `u16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Address16(u16);
 
 /// 24-bit address type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serialized as a plain `u32`; values above `$FF:FFFF` are rejected on deserialization.
+///
+/// Adding an [`Address16`] wraps within the bank, whereas adding or subtracting an integer
+/// carries into the bank, wrapping at the end of the 24-bit space.
+/// ```
+/// # use sneslib::address::*;
+/// let addr = Address24::new(0x7EFFF8);
+/// assert_eq!(addr + Address16::new(0x10), Address24::new(0x7E0008));
+/// assert_eq!(addr + 0x10u16, Address24::new(0x7F0008));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
 pub struct Address24(u32);
 
 macro_rules! impl_from {
@@ -32,8 +60,10 @@ macro_rules! impl_try_from {
 				type Error = error::AddressError;
 				#[inline]
 				fn try_from(src: $ty) -> Result<Self, Self::Error> {
-					let ad: u16 = src.try_into()?;
-					Ok(Self(ad))
+					match u16::try_from(src) {
+						Ok(ad) => Ok(Self(ad)),
+						Err(_) => Err(error::AddressError::out_of_range(src, 0xFFFF)),
+					}
 				}
 			}
 		)+
@@ -44,11 +74,9 @@ macro_rules! impl_try_from {
 				type Error = error::AddressError;
 				#[inline]
 				fn try_from(src: $ty) -> Result<Self, Self::Error> {
-					let ad: u32 = src.try_into()?;
-					if ad < 1 << 24 {
-						Ok(Self(ad))
-					} else {
-						Err(error::AddressError::TryFromIntError)
+					match u32::try_from(src) {
+						Ok(ad) if ad < 1 << 24 => Ok(Self(ad)),
+						_ => Err(error::AddressError::out_of_range(src, 0xFFFFFF)),
 					}
 				}
 			}
@@ -59,10 +87,10 @@ macro_rules! impl_try_from {
 macro_rules! impl_into {
 	($($ty:ty),+; $address:ident) => {
 		$(
-			impl Into<$ty> for $address {
+			impl From<$address> for $ty {
 				#[inline]
-				fn into(self) -> $ty {
-					self.0.into()
+				fn from(src: $address) -> $ty {
+					src.0.into()
 				}
 			}
 		)+
@@ -73,10 +101,10 @@ macro_rules! impl_into_usize {
 	($address:ident; $($width:expr),+) => {
 		$(
 			#[cfg(target_pointer_width = $width)]
-			impl Into<usize> for $address {
+			impl From<$address> for usize {
 				#[inline]
-				fn into(self) -> usize {
-					self.0 as usize
+				fn from(src: $address) -> usize {
+					src.0 as usize
 				}
 			}
 		)+
@@ -100,8 +128,81 @@ impl fmt::Display for Address16 {
 }
 
 impl fmt::Display for Address24 {
+	/// Formats as `$BB:HHLL`, or as `$BBHHLL` with the alternate flag.
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		write!(f, "${:02X}:{:04X}", self.0 >> 16, self.0 as u16)
+		if f.alternate() {
+			write!(f, "${:06X}", self.0)
+		} else {
+			write!(f, "${:02X}:{:04X}", self.0 >> 16, self.0 as u16)
+		}
+	}
+}
+
+macro_rules! impl_fmt {
+	($($trait:ident),+; $address:ident) => {
+		$(
+			impl fmt::$trait for $address {
+				#[inline]
+				fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+					fmt::$trait::fmt(&self.0, f)
+				}
+			}
+		)+
+	};
+}
+
+impl_fmt![LowerHex, UpperHex, Octal, Binary; Address16];
+impl_fmt![LowerHex, UpperHex, Octal, Binary; Address24];
+
+/// Parses hexadecimal digits with an optional `$` or `0x` prefix.
+fn parse_hex(src: &str) -> Result<u128, error::AddressError> {
+	let digits = src
+		.strip_prefix('$')
+		.or_else(|| src.strip_prefix("0x"))
+		.or_else(|| src.strip_prefix("0X"))
+		.unwrap_or(src);
+	parse_digits(digits)
+}
+
+fn parse_digits(digits: &str) -> Result<u128, error::AddressError> {
+	if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+		return Err(error::AddressError::ParseError);
+	}
+	u128::from_str_radix(digits, 16).map_err(|_| error::AddressError::TryFromIntError)
+}
+
+impl FromStr for Address16 {
+	type Err = error::AddressError;
+
+	/// Parses `$1234`, `0x1234` or `1234`.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!("$1234".parse(), Ok(Address16::new(0x1234)));
+	/// ```
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::try_from(parse_hex(s)?)
+	}
+}
+
+impl FromStr for Address24 {
+	type Err = error::AddressError;
+
+	/// Parses `$7E:0100`, `$7E0100`, `0x7E0100` or `7E0100`.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!("$7E:0100".parse(), Ok(Address24::new(0x7E0100)));
+	/// ```
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.split_once(':') {
+			Some((bank, offset)) => {
+				let bank = parse_hex(bank)?;
+				let bank = u8::try_from(bank)
+					.map_err(|_| error::AddressError::out_of_range(bank, 0xFF))?;
+				let offset = Address16::try_from(parse_digits(offset)?)?;
+				Ok(Self::from_bank_offset(bank, offset))
+			}
+			None => Self::try_from(parse_hex(s)?),
+		}
 	}
 }
 
@@ -112,7 +213,7 @@ impl Address16 {
 	/// let addr = Address16::new(0x1234);
 	/// ```
 	#[inline]
-	pub fn new(address: u16) -> Self {
+	pub const fn new(address: u16) -> Self {
 		Address16(address)
 	}
 
@@ -123,7 +224,7 @@ impl Address16 {
 	/// assert_eq!(addr.low(), 0x34);
 	/// ```
 	#[inline]
-	pub fn low(&self) -> u8 {
+	pub const fn low(&self) -> u8 {
 		(self.0 & 0xFF) as u8
 	}
 
@@ -134,9 +235,110 @@ impl Address16 {
 	/// assert_eq!(addr.high(), 0x12);
 	/// ```
 	#[inline]
-	pub fn high(&self) -> u8 {
+	pub const fn high(&self) -> u8 {
 		(self.0 >> 8) as u8
 	}
+
+	/// Returns the address as little-endian bytes.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address16::new(0x1234).to_le_bytes(), [0x34, 0x12]);
+	/// ```
+	#[inline]
+	pub const fn to_le_bytes(&self) -> [u8; 2] {
+		self.0.to_le_bytes()
+	}
+
+	/// Creates an `Address16` from little-endian bytes.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address16::from_le_bytes([0x34, 0x12]), Address16::new(0x1234));
+	/// ```
+	#[inline]
+	pub const fn from_le_bytes(bytes: [u8; 2]) -> Self {
+		Address16(u16::from_le_bytes(bytes))
+	}
+
+	/// Reads a little-endian `Address16` from the start of `bytes`.
+	///
+	/// Returns `None` if `bytes` is shorter than 2 bytes.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address16::read_from(&[0x34, 0x12, 0xFF]), Some(Address16::new(0x1234)));
+	/// assert_eq!(Address16::read_from(&[0x34]), None);
+	/// ```
+	#[inline]
+	pub fn read_from(bytes: &[u8]) -> Option<Self> {
+		match *bytes {
+			[l, h, ..] => Some(Self::from_le_bytes([l, h])),
+			_ => None,
+		}
+	}
+
+	/// Returns the target of an 8-bit relative branch, e.g. `BRA` or `BEQ`.
+	///
+	/// `self` is the address following the branch instruction.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let next = Address16::new(0x8002);
+	/// assert_eq!(next.branch_rel8(-2), Address16::new(0x8000));
+	/// ```
+	#[inline]
+	pub fn branch_rel8(&self, displacement: i8) -> Self {
+		*self + displacement as i32
+	}
+
+	/// Returns the target of a 16-bit relative branch, e.g. `BRL` or `PER`.
+	///
+	/// `self` is the address following the branch instruction.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let next = Address16::new(0xFFF0);
+	/// assert_eq!(next.branch_rel16(0x20), Address16::new(0x0010));
+	/// ```
+	#[inline]
+	pub fn branch_rel16(&self, displacement: i16) -> Self {
+		*self + displacement as i32
+	}
+
+	/// Returns the 8-bit displacement branching from `self` to `target`,
+	/// or `None` if `target` is out of range.
+	///
+	/// `self` is the address following the branch instruction.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let next = Address16::new(0x8002);
+	/// assert_eq!(next.relative_to(Address16::new(0x8000)), Some(-2));
+	/// assert_eq!(next.relative_to(Address16::new(0x9000)), None);
+	/// ```
+	#[inline]
+	pub fn relative_to(&self, target: Self) -> Option<i8> {
+		let displacement = self.relative16_to(target);
+		if (i8::MIN as i16..=i8::MAX as i16).contains(&displacement) {
+			Some(displacement as i8)
+		} else {
+			None
+		}
+	}
+
+	/// Returns the signed distance from `self` to `other`, taking the shorter way around the 16-bit space.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address16::new(0x8000).distance(Address16::new(0x8010)), 0x10);
+	/// assert_eq!(Address16::new(0x0010).distance(Address16::new(0xFFF0)), -0x20);
+	/// ```
+	#[inline]
+	pub const fn distance(&self, other: Self) -> i32 {
+		other.0.wrapping_sub(self.0) as i16 as i32
+	}
+
+	/// Returns the 16-bit displacement branching from `self` to `target`.
+	///
+	/// Any target within the bank is reachable since the displacement wraps.
+	#[inline]
+	pub const fn relative16_to(&self, target: Self) -> i16 {
+		target.0.wrapping_sub(self.0) as i16
+	}
 }
 
 impl Address24 {
@@ -145,9 +347,12 @@ impl Address24 {
 	/// # use sneslib::address::*;
 	/// let addr = Address24::new(0x12345678);
 	/// assert_eq!(addr, Address24::new(0x345678));
+	///
+	/// const REG_INIDISP: Address24 = Address24::new(0x002100);
+	/// assert_eq!(REG_INIDISP.get_lower_address16(), Address16::new(0x2100));
 	/// ```
 	#[inline]
-	pub fn new(address: u32) -> Self {
+	pub const fn new(address: u32) -> Self {
 		Address24(address & 0xFFFFFF)
 	}
 
@@ -158,7 +363,7 @@ impl Address24 {
 	/// assert_eq!(addr.low(), 0x56);
 	/// ```
 	#[inline]
-	pub fn low(&self) -> u8 {
+	pub const fn low(&self) -> u8 {
 		(self.0 & 0xFF) as u8
 	}
 
@@ -169,7 +374,7 @@ impl Address24 {
 	/// assert_eq!(addr.middle(), 0x34);
 	/// ```
 	#[inline]
-	pub fn middle(&self) -> u8 {
+	pub const fn middle(&self) -> u8 {
 		(self.0 >> 8) as u8
 	}
 
@@ -180,10 +385,115 @@ impl Address24 {
 	/// assert_eq!(addr.high(), 0x12);
 	/// ```
 	#[inline]
-	pub fn high(&self) -> u8 {
+	pub const fn high(&self) -> u8 {
 		(self.0 >> 16) as u8
 	}
 
+	/// Returns the address as little-endian bytes.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address24::new(0x7E1234).to_le_bytes(), [0x34, 0x12, 0x7E]);
+	/// ```
+	#[inline]
+	pub const fn to_le_bytes(&self) -> [u8; 3] {
+		[self.low(), self.middle(), self.high()]
+	}
+
+	/// Creates an `Address24` from little-endian bytes.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address24::from_le_bytes([0x34, 0x12, 0x7E]), Address24::new(0x7E1234));
+	/// ```
+	#[inline]
+	pub const fn from_le_bytes(bytes: [u8; 3]) -> Self {
+		Address24(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+	}
+
+	/// Reads a little-endian `Address24` from the start of `bytes`.
+	///
+	/// Returns `None` if `bytes` is shorter than 3 bytes.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address24::read_from(&[0x34, 0x12, 0x7E]), Some(Address24::new(0x7E1234)));
+	/// assert_eq!(Address24::read_from(&[0x34, 0x12]), None);
+	/// ```
+	#[inline]
+	pub fn read_from(bytes: &[u8]) -> Option<Self> {
+		match *bytes {
+			[l, m, h, ..] => Some(Self::from_le_bytes([l, m, h])),
+			_ => None,
+		}
+	}
+
+	/// Creates a new `Address24` from the given bank and 16-bit offset.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let addr = Address24::from_bank_offset(0x7E, Address16::new(0x0100));
+	/// assert_eq!(addr, Address24::new(0x7E0100));
+	/// ```
+	#[inline]
+	pub const fn from_bank_offset(bank: u8, offset: Address16) -> Self {
+		Address24((bank as u32) << 16 | offset.0 as u32)
+	}
+
+	/// Returns the bank of the address.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let addr = Address24::new(0x7E0100);
+	/// assert_eq!(addr.bank(), 0x7E);
+	/// ```
+	#[inline]
+	pub const fn bank(&self) -> u8 {
+		self.high()
+	}
+
+	/// Returns a copy of the address with the bank replaced.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let addr = Address24::new(0x7E0100);
+	/// assert_eq!(addr.with_bank(0x7F), Address24::new(0x7F0100));
+	/// ```
+	#[inline]
+	pub const fn with_bank(&self, bank: u8) -> Self {
+		Self::from_bank_offset(bank, self.get_lower_address16())
+	}
+
+	/// Returns the signed distance from `self` to `other`, taking the shorter way around the 24-bit space.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(Address24::new(0x7E0000).distance(Address24::new(0x7F0000)), 0x10000);
+	/// assert_eq!(Address24::new(0x000010).distance(Address24::new(0xFFFFF0)), -0x20);
+	/// ```
+	#[inline]
+	pub const fn distance(&self, other: Self) -> i32 {
+		// sign-extend the 24-bit difference
+		(other.0.wrapping_sub(self.0) << 8) as i32 >> 8
+	}
+
+	/// Adds a 16-bit offset wrapping within the current bank.
+	///
+	/// This is the behavior of `Address24 + Address16`.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let addr = Address24::new(0x7EFFFF);
+	/// assert_eq!(addr.add_wrapping_bank(Address16::new(2)), Address24::new(0x7E0001));
+	/// ```
+	#[inline]
+	pub fn add_wrapping_bank(self, rhs: Address16) -> Self {
+		self + rhs
+	}
+
+	/// Adds a 16-bit offset carrying into the bank, wrapping at the end of the 24-bit space.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let addr = Address24::new(0x7EFFFF);
+	/// assert_eq!(addr.add_crossing_bank(Address16::new(2)), Address24::new(0x7F0001));
+	/// ```
+	#[inline]
+	pub fn add_crossing_bank(self, rhs: Address16) -> Self {
+		self + Address24::from(rhs)
+	}
+
 	/// Returns a lower 16-bit of the address.
 	/// ```
 	/// # use sneslib::address::*;
@@ -191,7 +501,7 @@ impl Address24 {
 	/// assert_eq!(addr.get_lower_address16(), Address16::new(0x3456));
 	/// ```
 	#[inline]
-	pub fn get_lower_address16(&self) -> Address16 {
+	pub const fn get_lower_address16(&self) -> Address16 {
 		Address16(self.0 as u16)
 	}
 }
@@ -201,6 +511,7 @@ macro_rules! impl_op {
 		impl $trait<Self> for $t {
 			type Output = $t;
 			#[inline]
+			#[allow(clippy::suspicious_arithmetic_impl)]
 			fn $fn(self, rhs: $t) -> $t {
 				let ad = self.0.$internalfn(rhs.0);
 				$t(ad $(& $mask)?)
@@ -209,18 +520,64 @@ macro_rules! impl_op {
 	};
 }
 
-macro_rules! impl_and {
-	($t:ident $prm:ident) => {
-		impl BitAnd<$prm> for $t {
+macro_rules! impl_bit_op {
+	($t:ident $trait:ident:$fn:ident $op:tt $prm:ident $($mask:expr)?) => {
+		impl $trait<$prm> for $t {
 			type Output = $t;
 			#[inline]
-			fn bitand(self, rhs: $prm) -> $t {
-				$t(self.0 & rhs)
+			fn $fn(self, rhs: $prm) -> $t {
+				$t((self.0 $op rhs) $(& $mask)?)
 			}
 		}
 	};
 }
 
+macro_rules! impl_not {
+	($t:ident $($mask:expr)?) => {
+		impl Not for $t {
+			type Output = $t;
+			#[inline]
+			fn not(self) -> $t {
+				$t(!self.0 $(& $mask)?)
+			}
+		}
+
+		impl<'a> Not for &'a $t {
+			type Output = $t;
+			#[inline]
+			fn not(self) -> $t {
+				Not::not(*self)
+			}
+		}
+	};
+}
+
+macro_rules! impl_int_op {
+	($t:ident $trait:ident:$fn:ident:$internalfn:ident $prm:ident as $wide:ident & $mask:expr) => {
+		impl $trait<$prm> for $t {
+			type Output = $t;
+			#[inline]
+			#[allow(clippy::suspicious_arithmetic_impl)]
+			fn $fn(self, rhs: $prm) -> $t {
+				$t(((self.0 as $wide).$internalfn(rhs as $wide) & $mask) as _)
+			}
+		}
+	};
+}
+
+macro_rules! impl_op_assign {
+	($t:ident $trait:ident:$fn:ident $op:ident:$opfn:ident $($prm:ty),+) => {
+		$(
+			impl $trait<$prm> for $t {
+				#[inline]
+				fn $fn(&mut self, rhs: $prm) {
+					*self = $op::$opfn(*self, rhs);
+				}
+			}
+		)+
+	};
+}
+
 macro_rules! forward_ref_binop {
 	(impl $imp:ident:$method:ident for $t:ty, $u:ty) => {
 		impl<'a> $imp<$u> for &'a $t {
@@ -249,12 +606,135 @@ macro_rules! forward_ref_binop {
 	};
 }
 
+macro_rules! impl_checked_ops {
+	($t:ident $max:expr) => {
+		impl $t {
+			/// The largest representable address.
+			pub const MAX: Self = $t($max);
+
+			/// Checked addition. Returns `None` if the result overflows the address space.
+			#[inline]
+			pub fn checked_add(self, rhs: Self) -> Option<Self> {
+				let ad = self.0 as u32 + rhs.0 as u32;
+				if ad <= $max {
+					Some($t(ad as _))
+				} else {
+					None
+				}
+			}
+
+			/// Checked subtraction. Returns `None` if the result underflows the address space.
+			#[inline]
+			pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+				self.0.checked_sub(rhs.0).map($t)
+			}
+
+			/// Wrapping addition, along with a boolean indicating whether the result overflowed.
+			#[inline]
+			pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+				let ad = self.0 as u32 + rhs.0 as u32;
+				($t((ad & $max) as _), ad > $max)
+			}
+
+			/// Wrapping subtraction, along with a boolean indicating whether the result underflowed.
+			#[inline]
+			pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+				let (ad, overflow) = self.0.overflowing_sub(rhs.0);
+				($t(ad & $max), overflow)
+			}
+
+			/// Saturating addition. Clamps the result to the highest address.
+			#[inline]
+			pub fn saturating_add(self, rhs: Self) -> Self {
+				let ad = self.0 as u32 + rhs.0 as u32;
+				$t(core::cmp::min(ad, $max) as _)
+			}
+
+			/// Saturating subtraction. Clamps the result to the zero address.
+			#[inline]
+			pub fn saturating_sub(self, rhs: Self) -> Self {
+				$t(self.0.saturating_sub(rhs.0))
+			}
+		}
+	};
+}
+
+impl_checked_ops!(Address16 0xFFFF);
+impl_checked_ops!(Address24 0xFFFFFF);
+
+macro_rules! impl_align {
+	($t:ident $prm:ident $max:expr) => {
+		impl $t {
+			/// Returns `true` if the address is at the start of a 256-byte page.
+			#[inline]
+			pub const fn is_page_aligned(&self) -> bool {
+				self.0 & 0xFF == 0
+			}
+
+			/// Rounds the address down to a multiple of `n`.
+			///
+			/// # Panics
+			/// Panics if `n` is zero.
+			#[inline]
+			pub fn align_down(&self, n: $prm) -> Self {
+				$t(self.0 - self.0 % n)
+			}
+
+			/// Rounds the address up to a multiple of `n`, wrapping at the end of the address space.
+			///
+			/// # Panics
+			/// Panics if `n` is zero.
+			#[inline]
+			pub fn align_up(&self, n: $prm) -> Self {
+				let (ad, n) = (self.0 as u64, n as u64);
+				$t(((ad.div_ceil(n) * n) & $max) as _)
+			}
+
+			/// Returns `true` if `self` and `other` are on different 256-byte pages.
+			#[inline]
+			pub const fn crosses_page(&self, other: Self) -> bool {
+				self.0 >> 8 != other.0 >> 8
+			}
+		}
+	};
+}
+
+impl_align!(Address16 u16 0xFFFF);
+impl_align!(Address24 u32 0xFFFFFF);
+
 impl_op!(Address16 Add:add:wrapping_add);
 impl_op!(Address16 Sub:sub:wrapping_sub);
-impl_and!(Address16 u16);
+impl_bit_op!(Address16 BitAnd:bitand & u16);
+impl_bit_op!(Address16 BitOr:bitor | u16);
+impl_bit_op!(Address16 BitXor:bitxor ^ u16);
+impl_not!(Address16);
 impl_op!(Address24 Add:add:wrapping_add 0xFFFFFF);
 impl_op!(Address24 Sub:sub:wrapping_sub 0xFFFFFF);
-impl_and!(Address24 u32);
+impl_bit_op!(Address24 BitAnd:bitand & u32);
+impl_bit_op!(Address24 BitOr:bitor | u32 0xFFFFFF);
+impl_bit_op!(Address24 BitXor:bitxor ^ u32 0xFFFFFF);
+impl_not!(Address24 0xFFFFFF);
+impl_int_op!(Address16 Add:add:wrapping_add u16 as u16 & 0xFFFF);
+impl_int_op!(Address16 Sub:sub:wrapping_sub u16 as u16 & 0xFFFF);
+impl_int_op!(Address16 Add:add:wrapping_add i32 as i32 & 0xFFFF);
+impl_int_op!(Address16 Sub:sub:wrapping_sub i32 as i32 & 0xFFFF);
+impl_int_op!(Address24 Add:add:wrapping_add u16 as u32 & 0xFFFFFF);
+impl_int_op!(Address24 Sub:sub:wrapping_sub u16 as u32 & 0xFFFFFF);
+impl_int_op!(Address24 Add:add:wrapping_add u32 as u32 & 0xFFFFFF);
+impl_int_op!(Address24 Sub:sub:wrapping_sub u32 as u32 & 0xFFFFFF);
+impl_int_op!(Address24 Add:add:wrapping_add i32 as i32 & 0xFFFFFF);
+impl_int_op!(Address24 Sub:sub:wrapping_sub i32 as i32 & 0xFFFFFF);
+
+impl_op_assign!(Address16 AddAssign:add_assign Add:add Address16, u16, i32);
+impl_op_assign!(Address16 SubAssign:sub_assign Sub:sub Address16, u16, i32);
+impl_op_assign!(Address16 BitAndAssign:bitand_assign BitAnd:bitand u16);
+impl_op_assign!(Address16 BitOrAssign:bitor_assign BitOr:bitor u16);
+impl_op_assign!(Address16 BitXorAssign:bitxor_assign BitXor:bitxor u16);
+impl_op_assign!(Address24 AddAssign:add_assign Add:add Address24, Address16, u16, u32, i32);
+impl_op_assign!(Address24 SubAssign:sub_assign Sub:sub Address24, u16, u32, i32);
+impl_op_assign!(Address24 BitAndAssign:bitand_assign BitAnd:bitand u32);
+impl_op_assign!(Address24 BitOrAssign:bitor_assign BitOr:bitor u32);
+impl_op_assign!(Address24 BitXorAssign:bitxor_assign BitXor:bitxor u32);
 
 impl Add<Address16> for Address24 {
 	type Output = Self;
@@ -267,10 +747,24 @@ impl Add<Address16> for Address24 {
 forward_ref_binop!(impl Add:add for Address16, Address16);
 forward_ref_binop!(impl Sub:sub for Address16, Address16);
 forward_ref_binop!(impl BitAnd:bitand for Address16, u16);
+forward_ref_binop!(impl BitOr:bitor for Address16, u16);
+forward_ref_binop!(impl BitXor:bitxor for Address16, u16);
 forward_ref_binop!(impl Add:add for Address24, Address24);
 forward_ref_binop!(impl Sub:sub for Address24, Address24);
 forward_ref_binop!(impl BitAnd:bitand for Address24, u32);
+forward_ref_binop!(impl BitOr:bitor for Address24, u32);
+forward_ref_binop!(impl BitXor:bitxor for Address24, u32);
 forward_ref_binop!(impl Add:add for Address24, Address16);
+forward_ref_binop!(impl Add:add for Address16, u16);
+forward_ref_binop!(impl Sub:sub for Address16, u16);
+forward_ref_binop!(impl Add:add for Address16, i32);
+forward_ref_binop!(impl Sub:sub for Address16, i32);
+forward_ref_binop!(impl Add:add for Address24, u16);
+forward_ref_binop!(impl Sub:sub for Address24, u16);
+forward_ref_binop!(impl Add:add for Address24, u32);
+forward_ref_binop!(impl Sub:sub for Address24, u32);
+forward_ref_binop!(impl Add:add for Address24, i32);
+forward_ref_binop!(impl Sub:sub for Address24, i32);
 
 #[cfg(test)]
 mod test {
@@ -283,22 +777,63 @@ mod test {
 		assert_eq!(Address16::from(0xFFu8), Address16(0xFF));
 		assert_eq!(Address16::from(0xFFFFu16), Address16(0xFFFF));
 		assert_eq!(Address16::try_from(0xFFFFu32), Ok(Address16(0xFFFF)));
-		assert_eq!(Address16::try_from(0x10000u32), Err(TryFromIntError));
+		assert_eq!(
+			Address16::try_from(0x10000u32),
+			Err(OutOfRange {
+				value: 0x10000,
+				max: 0xFFFF
+			})
+		);
 		assert_eq!(Address16::try_from(0xFFFFi32), Ok(Address16(0xFFFF)));
-		assert_eq!(Address16::try_from(-0xFFFFi32), Err(TryFromIntError));
+		assert_eq!(
+			Address16::try_from(-0xFFFFi32),
+			Err(OutOfRange {
+				value: -0xFFFF,
+				max: 0xFFFF
+			})
+		);
+		assert_eq!(Address16::try_from(u128::MAX), Err(TryFromIntError));
 		assert_eq!(Into::<usize>::into(Address16(0x1234)), 0x1234usize);
 
 		assert_eq!(Address24::from(0xFFu8), Address24(0xFF));
 		assert_eq!(Address24::from(0xFFFFu16), Address24(0xFFFF));
 		assert_eq!(Address24::try_from(0xFFFFFFu32), Ok(Address24(0xFFFFFF)));
-		assert_eq!(Address24::try_from(0x1000000u32), Err(TryFromIntError));
+		assert_eq!(
+			Address24::try_from(0x1000000u32),
+			Err(OutOfRange {
+				value: 0x1000000,
+				max: 0xFFFFFF
+			})
+		);
 		assert_eq!(Address24::try_from(0xFFFFFFi32), Ok(Address24(0xFFFFFF)));
-		assert_eq!(Address24::try_from(-0xFFFFFFi32), Err(TryFromIntError));
+		assert_eq!(
+			Address24::try_from(-0xFFFFFFi32),
+			Err(OutOfRange {
+				value: -0xFFFFFF,
+				max: 0xFFFFFF
+			})
+		);
+		assert_eq!(
+			Address24::try_from(u64::MAX),
+			Err(OutOfRange {
+				value: u64::MAX as i128,
+				max: 0xFFFFFF
+			})
+		);
+		assert_eq!(
+			OutOfRange {
+				value: -1,
+				max: 0xFFFF
+			}
+			.to_string(),
+			"address -1 out of range (maximum $FFFF)"
+		);
 		assert_eq!(Into::<usize>::into(Address24(0x123456)), 0x123456usize);
 		assert_eq!(Address24::from(Address16(0x1234)), Address24(0x1234));
 	}
 
 	#[test]
+	#[allow(clippy::op_ref)]
 	fn ops() {
 		let a = Address16::new(0x1234);
 		let b = Address16::new(0x4321);
@@ -314,6 +849,14 @@ mod test {
 		assert_eq!(&a & 0x5555, Address16::new(0x1014));
 		assert_eq!(a & &0x5555, Address16::new(0x1014));
 		assert_eq!(&a & &0x5555, Address16::new(0x1014));
+		assert_eq!(a | 0x8000, Address16::new(0x9234));
+		assert_eq!(&a | 0x8000, Address16::new(0x9234));
+		assert_eq!(a | &0x8000, Address16::new(0x9234));
+		assert_eq!(&a | &0x8000, Address16::new(0x9234));
+		assert_eq!(a ^ 0xFFFF, Address16::new(0xEDCB));
+		assert_eq!(&a ^ &0xFFFF, Address16::new(0xEDCB));
+		assert_eq!(!a, Address16::new(0xEDCB));
+		assert_eq!(!&a, Address16::new(0xEDCB));
 
 		let a = Address24::new(0x123456);
 		let b = Address24::new(0x654321);
@@ -329,6 +872,13 @@ mod test {
 		assert_eq!(&a & 0x555555, Address24::new(0x101454));
 		assert_eq!(a & &0x555555, Address24::new(0x101454));
 		assert_eq!(&a & &0x555555, Address24::new(0x101454));
+		assert_eq!(a | 0x808000, Address24::new(0x92B456));
+		assert_eq!(&a | &0x808000, Address24::new(0x92B456));
+		assert_eq!(a | 0xFF000000, Address24::new(0x123456));
+		assert_eq!(a ^ 0xFFFFFFFF, Address24::new(0xEDCBA9));
+		assert_eq!(&a ^ &0xFFFFFF, Address24::new(0xEDCBA9));
+		assert_eq!(!a, Address24::new(0xEDCBA9));
+		assert_eq!(!&a, Address24::new(0xEDCBA9));
 
 		let a = Address24::new(0x7EFF00);
 		let b = Address16::new(0x200);
@@ -336,5 +886,307 @@ mod test {
 		assert_eq!(&a + b, Address24::new(0x7E0100));
 		assert_eq!(a + &b, Address24::new(0x7E0100));
 		assert_eq!(&a + &b, Address24::new(0x7E0100));
+		assert_eq!(a.add_wrapping_bank(b), Address24::new(0x7E0100));
+		assert_eq!(a.add_crossing_bank(b), Address24::new(0x7F0100));
+		assert_eq!(
+			Address24::MAX.add_crossing_bank(Address16::new(1)),
+			Address24::new(0)
+		);
+	}
+
+	#[test]
+	#[allow(clippy::op_ref)]
+	fn int_ops() {
+		let a = Address16::new(0xFFF0);
+		assert_eq!(a + 0x10u16, Address16(0x0000));
+		assert_eq!(a - 0xFFF1u16, Address16(0xFFFF));
+		assert_eq!(a + (-5i32), Address16(0xFFEB));
+		assert_eq!(Address16(2) + (-5i32), Address16(0xFFFD));
+		assert_eq!(Address16(2) - 5i32, Address16(0xFFFD));
+		assert_eq!(Address16(2) - (-5i32), Address16(7));
+		assert_eq!(&a + 0x10u16, Address16(0x0000));
+		assert_eq!(a + &0x10u16, Address16(0x0000));
+
+		let a = Address24::new(0x7EFFF0);
+		assert_eq!(a + 0x10u32, Address24(0x7F0000));
+		assert_eq!(Address24::MAX + 1u32, Address24(0));
+		assert_eq!(Address24(0) - 1u32, Address24::MAX);
+		assert_eq!(a + (-0x10i32), Address24(0x7EFFE0));
+		assert_eq!(Address24(2) + (-5i32), Address24(0xFFFFFD));
+		assert_eq!(Address24(2) - 5, Address24(0xFFFFFD));
+		assert_eq!(&a - &0x10, Address24(0x7EFFE0));
+		assert_eq!(a + 0x10u16, Address24(0x7F0000));
+		assert_eq!(Address24(0x7F0000) - 1u16, Address24(0x7EFFFF));
+		assert_eq!(Address24::MAX + 1u16, Address24(0));
+		assert_eq!(&a + &0x10u16, Address24(0x7F0000));
+		let mut b = a;
+		b += 0x10u16;
+		assert_eq!(b, Address24(0x7F0000));
+		b -= 0x20u16;
+		assert_eq!(b, Address24(0x7EFFE0));
+	}
+
+	#[test]
+	fn branch() {
+		let next = Address16::new(0x0001);
+		assert_eq!(next.branch_rel8(-2), Address16(0xFFFF));
+		assert_eq!(next.branch_rel8(127), Address16(0x0080));
+		assert_eq!(next.relative_to(Address16(0xFFFF)), Some(-2));
+		assert_eq!(next.relative_to(Address16(0x0080)), Some(127));
+		assert_eq!(next.relative_to(Address16(0x0081)), None);
+		assert_eq!(next.relative_to(Address16(0xFF81)), Some(-128));
+		assert_eq!(next.relative_to(Address16(0xFF80)), None);
+
+		let next = Address16::new(0x8003);
+		assert_eq!(next.branch_rel16(-0x8000), Address16(0x0003));
+		assert_eq!(next.relative16_to(Address16(0x0003)), -0x8000);
+		assert_eq!(next.relative16_to(Address16(0x0002)), 0x7FFF);
+		for &d in &[i16::MIN, -1, 0, 1, i16::MAX] {
+			assert_eq!(next.relative16_to(next.branch_rel16(d)), d);
+		}
+	}
+
+	#[test]
+	fn align() {
+		assert!(Address16(0x1200).is_page_aligned());
+		assert!(!Address16(0x1201).is_page_aligned());
+		assert_eq!(Address16(0x1234).align_down(0x100), Address16(0x1200));
+		assert_eq!(Address16(0x1234).align_up(0x100), Address16(0x1300));
+		assert_eq!(Address16(0x1200).align_up(0x100), Address16(0x1200));
+		assert_eq!(Address16(0x1235).align_up(10), Address16(0x123E));
+		assert_eq!(Address16(0xFFFF).align_up(0x100), Address16(0x0000));
+		assert!(Address16(0x12FF).crosses_page(Address16(0x1300)));
+		assert!(!Address16(0x1200).crosses_page(Address16(0x12FF)));
+
+		assert!(Address24(0x7E1200).is_page_aligned());
+		assert_eq!(Address24(0x7E1234).align_down(0x8000), Address24(0x7E0000));
+		assert_eq!(Address24(0x7E1234).align_up(0x8000), Address24(0x7E8000));
+		assert_eq!(Address24(0xFFFFFF).align_up(2), Address24(0x000000));
+		assert!(Address24(0x7EFFFF).crosses_page(Address24(0x7F00FF)));
+		assert!(Address24(0x7E00FF).crosses_page(Address24(0x7F00FF)));
+		assert!(!Address24(0x7E0000).crosses_page(Address24(0x7E00FF)));
+	}
+
+	#[test]
+	fn assign_ops() {
+		let mut pc = Address16::new(0xFFFE);
+		pc += 1;
+		assert_eq!(pc, Address16(0xFFFF));
+		pc += Address16(2);
+		assert_eq!(pc, Address16(0x0001));
+		pc -= 2u16;
+		assert_eq!(pc, Address16(0xFFFF));
+		pc &= 0x00FF;
+		assert_eq!(pc, Address16(0x00FF));
+		pc |= 0x8000;
+		assert_eq!(pc, Address16(0x80FF));
+		pc ^= 0x00FF;
+		assert_eq!(pc, Address16(0x8000));
+
+		let mut pc = Address24::new(0x7EFFFF);
+		pc += Address16(1);
+		assert_eq!(pc, Address24(0x7E0000));
+		pc += 0x10000u32;
+		assert_eq!(pc, Address24(0x7F0000));
+		pc -= 1;
+		assert_eq!(pc, Address24(0x7EFFFF));
+		pc -= Address24(0x7E0000);
+		assert_eq!(pc, Address24(0x00FFFF));
+		pc &= 0xFF00;
+		pc |= 0xFF0000;
+		assert_eq!(pc, Address24(0xFFFF00));
+		pc ^= 0xFF0000;
+		assert_eq!(pc, Address24(0x00FF00));
+	}
+
+	#[test]
+	fn le_bytes() {
+		let table = [0x00, 0x80, 0x34, 0x12, 0x56, 0x34, 0x12];
+		let pointers = table
+			.chunks(2)
+			.filter_map(Address16::read_from)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			pointers,
+			vec![Address16(0x8000), Address16(0x1234), Address16(0x3456)]
+		);
+		let pointers = table
+			.chunks(3)
+			.filter_map(Address24::read_from)
+			.collect::<Vec<_>>();
+		assert_eq!(pointers, vec![Address24(0x348000), Address24(0x345612)]);
+
+		for &a in &[0, 0x1234, 0xFFFF] {
+			let a = Address16(a);
+			assert_eq!(Address16::from_le_bytes(a.to_le_bytes()), a);
+		}
+		for &a in &[0, 0x123456, 0xFFFFFF] {
+			let a = Address24(a);
+			assert_eq!(Address24::from_le_bytes(a.to_le_bytes()), a);
+		}
+	}
+
+	#[test]
+	fn distance() {
+		let a = Address16(0x1000);
+		assert_eq!(a.distance(a), 0);
+		assert_eq!(a.distance(Address16(0x9000)), -0x8000);
+		assert_eq!(a.distance(Address16(0x8FFF)), 0x7FFF);
+		for &d in &[-0x8000, -1, 0, 1, 0x7FFF] {
+			assert_eq!(a.distance(a + d), d);
+		}
+
+		let a = Address24(0x100000);
+		assert_eq!(a.distance(Address24(0x900000)), -0x800000);
+		assert_eq!(a.distance(Address24(0x8FFFFF)), 0x7FFFFF);
+		assert_eq!(Address24(0x7FFFFF).distance(Address24(0x800000)), 1);
+		for &d in &[-0x800000, -1, 0, 1, 0x7FFFFF] {
+			assert_eq!(a.distance(a + d), d);
+		}
+	}
+
+	#[test]
+	fn checked_ops() {
+		let a = Address16::new(0xFFF0);
+		assert_eq!(a.checked_add(Address16(0xF)), Some(Address16::MAX));
+		assert_eq!(a.checked_add(Address16(0x10)), None);
+		assert_eq!(a.checked_sub(Address16(0xFFF1)), None);
+		assert_eq!(a.overflowing_add(Address16(0x20)), (Address16(0x10), true));
+		assert_eq!(
+			a.overflowing_add(Address16(0x1)),
+			(Address16(0xFFF1), false)
+		);
+		assert_eq!(
+			Address16(0).overflowing_sub(Address16(1)),
+			(Address16::MAX, true)
+		);
+		assert_eq!(a.saturating_add(Address16(0x20)), Address16::MAX);
+		assert_eq!(a.saturating_sub(Address16::MAX), Address16(0));
+
+		let a = Address24::new(0xFFFFF0);
+		assert_eq!(a.checked_add(Address24(0xF)), Some(Address24::MAX));
+		assert_eq!(a.checked_add(Address24(0x10)), None);
+		assert_eq!(a.checked_sub(Address24(0xFFFFF1)), None);
+		assert_eq!(a.checked_sub(Address24(0xF0)), Some(Address24(0xFFFF00)));
+		assert_eq!(a.overflowing_add(Address24(0x20)), (Address24(0x10), true));
+		assert_eq!(
+			Address24(0).overflowing_sub(Address24(1)),
+			(Address24::MAX, true)
+		);
+		assert_eq!(a.saturating_add(Address24(0x20)), Address24::MAX);
+		assert_eq!(a.saturating_sub(Address24::MAX), Address24(0));
+	}
+
+	#[test]
+	fn collections() {
+		use std::collections::{BTreeMap, HashSet};
+
+		let mut labels = BTreeMap::new();
+		labels.insert(Address24::new(0x808000), "reset");
+		labels.insert(Address24::new(0x008000), "main");
+		labels.insert(Address24::new(0x7E0000), "wram");
+		assert_eq!(
+			labels.values().cloned().collect::<Vec<_>>(),
+			vec!["main", "wram", "reset"]
+		);
+
+		let set = [0x1234, 0x1234, 0x4321]
+			.iter()
+			.map(|&a| Address16::new(a))
+			.collect::<HashSet<_>>();
+		assert_eq!(set.len(), 2);
+		assert!(Address16::new(0x00FF) < Address16::new(0x0100));
+	}
+
+	#[test]
+	fn format() {
+		assert_eq!(format!("{}", Address16(0x12AB)), "$12AB");
+		assert_eq!(format!("{:04x}", Address16(0xAB)), "00ab");
+		assert_eq!(format!("{:#06X}", Address16(0xAB)), "0x00AB");
+		assert_eq!(format!("{:o}", Address16(0o777)), "777");
+		assert_eq!(format!("{:016b}", Address16(0x8001)), "1000000000000001");
+
+		assert_eq!(format!("{}", Address24(0x7E0100)), "$7E:0100");
+		assert_eq!(format!("{:#}", Address24(0x7E0100)), "$7E0100");
+		assert_eq!(format!("{:06x}", Address24(0xab)), "0000ab");
+		assert_eq!(format!("{:X}", Address24(0x7E0100)), "7E0100");
+		assert_eq!(format!("{:o}", Address24(0o7777)), "7777");
+		assert_eq!(format!("{:b}", Address24(0x5)), "101");
+	}
+
+	#[test]
+	fn parse() {
+		use error::AddressError::*;
+
+		assert_eq!("$1234".parse(), Ok(Address16(0x1234)));
+		assert_eq!("0x1234".parse(), Ok(Address16(0x1234)));
+		assert_eq!("ffff".parse(), Ok(Address16(0xFFFF)));
+		assert_eq!(
+			"$10000".parse::<Address16>(),
+			Err(OutOfRange {
+				value: 0x10000,
+				max: 0xFFFF
+			})
+		);
+		assert_eq!(
+			"$100000000000000000000000000000000".parse::<Address16>(),
+			Err(TryFromIntError)
+		);
+		assert_eq!("".parse::<Address16>(), Err(ParseError));
+		assert_eq!("$".parse::<Address16>(), Err(ParseError));
+		assert_eq!("$12G4".parse::<Address16>(), Err(ParseError));
+		assert_eq!("+123".parse::<Address16>(), Err(ParseError));
+
+		assert_eq!("$7E:0100".parse(), Ok(Address24(0x7E0100)));
+		assert_eq!("7E0100".parse(), Ok(Address24(0x7E0100)));
+		assert_eq!("0x7E0100".parse(), Ok(Address24(0x7E0100)));
+		assert_eq!("$7e0100".parse(), Ok(Address24(0x7E0100)));
+		assert_eq!(
+			"$1000000".parse::<Address24>(),
+			Err(OutOfRange {
+				value: 0x1000000,
+				max: 0xFFFFFF
+			})
+		);
+		assert_eq!(
+			"$100:0000".parse::<Address24>(),
+			Err(OutOfRange {
+				value: 0x100,
+				max: 0xFF
+			})
+		);
+		assert_eq!(
+			"$7E:10000".parse::<Address24>(),
+			Err(OutOfRange {
+				value: 0x10000,
+				max: 0xFFFF
+			})
+		);
+		assert_eq!("$7E:".parse::<Address24>(), Err(ParseError));
+		assert_eq!("$7E:$0100".parse::<Address24>(), Err(ParseError));
+		assert_eq!("$7E:01:00".parse::<Address24>(), Err(ParseError));
+		assert_eq!(
+			"123456789".parse::<Address24>(),
+			Err(OutOfRange {
+				value: 0x123456789,
+				max: 0xFFFFFF
+			})
+		);
+	}
+
+	#[test]
+	fn serde() {
+		let encoded = bincode::serialize(&Address16::new(0x1234)).unwrap();
+		assert_eq!(encoded, bincode::serialize(&0x1234u16).unwrap());
+		let decoded: Address16 = bincode::deserialize(&encoded).unwrap();
+		assert_eq!(decoded, Address16::new(0x1234));
+
+		let encoded = bincode::serialize(&Address24::new(0x7E0100)).unwrap();
+		assert_eq!(encoded, bincode::serialize(&0x7E0100u32).unwrap());
+		let decoded: Address24 = bincode::deserialize(&encoded).unwrap();
+		assert_eq!(decoded, Address24::new(0x7E0100));
+
+		let encoded = bincode::serialize(&0x1000000u32).unwrap();
+		assert!(bincode::deserialize::<Address24>(&encoded).is_err());
 	}
 }
@@ -0,0 +1,188 @@
+use super::{Address16, Address24};
+
+/// Direct page operand, the D register plus an 8-bit offset.
+///
+/// Direct page addresses always resolve into bank `$00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DirectPage {
+	d: u16,
+	offset: u8,
+}
+
+/// Stack relative operand, the S register plus an 8-bit offset.
+///
+/// Stack relative addresses always resolve into bank `$00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StackRelative {
+	s: u16,
+	offset: u8,
+}
+
+impl DirectPage {
+	/// Creates a new `DirectPage` with the given D register and operand.
+	#[inline]
+	pub const fn new(d: u16, offset: u8) -> Self {
+		Self { d, offset }
+	}
+
+	/// Returns the D register.
+	#[inline]
+	pub const fn d(&self) -> u16 {
+		self.d
+	}
+
+	/// Returns the 8-bit operand.
+	#[inline]
+	pub const fn offset(&self) -> u8 {
+		self.offset
+	}
+
+	/// Returns `true` if the low byte of the D register is zero.
+	///
+	/// Only then does the emulation mode page wrap apply.
+	#[inline]
+	pub const fn is_page_aligned(&self) -> bool {
+		self.d & 0xFF == 0
+	}
+
+	/// Resolves `dp` to its effective address.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let dp = DirectPage::new(0xFF80, 0x90);
+	/// assert_eq!(dp.resolve(), Address16::new(0x0010));
+	/// ```
+	#[inline]
+	pub const fn resolve(&self) -> Address16 {
+		Address16(self.d.wrapping_add(self.offset as u16))
+	}
+
+	/// Resolves `dp,X` or `dp,Y` to its effective address.
+	///
+	/// In emulation mode with a page aligned D register the result wraps within the direct page.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let dp = DirectPage::new(0x0100, 0xF0);
+	/// assert_eq!(dp.resolve_indexed(0x20, false), Address16::new(0x0210));
+	/// assert_eq!(dp.resolve_indexed(0x20, true), Address16::new(0x0110));
+	/// ```
+	#[inline]
+	pub fn resolve_indexed(&self, index: u16, emulation: bool) -> Address16 {
+		if emulation && self.is_page_aligned() {
+			let low = self.offset.wrapping_add(index as u8);
+			Address16(self.d | low as u16)
+		} else {
+			Address16(self.d.wrapping_add(self.offset as u16).wrapping_add(index))
+		}
+	}
+
+	/// Resolves the address of the `n`-th pointer byte read by `(dp)`, `[dp]` and friends.
+	///
+	/// In emulation mode with a page aligned D register the pointer wraps within the direct page.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let dp = DirectPage::new(0x0000, 0xFF);
+	/// assert_eq!(dp.resolve_pointer(1, false), Address16::new(0x0100));
+	/// assert_eq!(dp.resolve_pointer(1, true), Address16::new(0x0000));
+	/// ```
+	#[inline]
+	pub fn resolve_pointer(&self, n: u8, emulation: bool) -> Address16 {
+		self.resolve_indexed(n as u16, emulation)
+	}
+
+	/// Resolves `dp` to its effective 24-bit address in bank `$00`.
+	#[inline]
+	pub fn to_address24(&self) -> Address24 {
+		self.resolve().into()
+	}
+}
+
+impl StackRelative {
+	/// Creates a new `StackRelative` with the given S register and operand.
+	#[inline]
+	pub const fn new(s: u16, offset: u8) -> Self {
+		Self { s, offset }
+	}
+
+	/// Returns the S register.
+	#[inline]
+	pub const fn s(&self) -> u16 {
+		self.s
+	}
+
+	/// Returns the 8-bit operand.
+	#[inline]
+	pub const fn offset(&self) -> u8 {
+		self.offset
+	}
+
+	/// Resolves `sr,S` to its effective address.
+	///
+	/// Stack relative addressing never wraps within the page, even in emulation mode.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let sr = StackRelative::new(0x01F0, 0x20);
+	/// assert_eq!(sr.resolve(), Address16::new(0x0210));
+	/// ```
+	#[inline]
+	pub const fn resolve(&self) -> Address16 {
+		Address16(self.s.wrapping_add(self.offset as u16))
+	}
+
+	/// Resolves `sr,S` to its effective 24-bit address in bank `$00`.
+	#[inline]
+	pub fn to_address24(&self) -> Address24 {
+		self.resolve().into()
+	}
+
+	/// Returns the address the next push writes to.
+	///
+	/// In emulation mode the stack is confined to page `$01`.
+	/// ```
+	/// # use sneslib::address::*;
+	/// assert_eq!(StackRelative::stack_pointer(0x0100, true), Address16::new(0x0100));
+	/// assert_eq!(StackRelative::stack_pointer(0x0000, true), Address16::new(0x0100));
+	/// assert_eq!(StackRelative::stack_pointer(0x0000, false), Address16::new(0x0000));
+	/// ```
+	#[inline]
+	pub const fn stack_pointer(s: u16, emulation: bool) -> Address16 {
+		if emulation {
+			Address16(0x0100 | (s & 0xFF))
+		} else {
+			Address16(s)
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn direct_page() {
+		let dp = DirectPage::new(0x0000, 0xFF);
+		assert_eq!(dp.resolve(), Address16(0x00FF));
+		assert_eq!(dp.resolve_indexed(1, false), Address16(0x0100));
+		assert_eq!(dp.resolve_indexed(1, true), Address16(0x0000));
+		assert_eq!(dp.to_address24(), Address24(0x0000FF));
+
+		// no page wrap unless DL is zero
+		let dp = DirectPage::new(0x0001, 0xFF);
+		assert_eq!(dp.resolve_indexed(1, true), Address16(0x0101));
+
+		// bank 0 wrap
+		let dp = DirectPage::new(0xFFFF, 0x01);
+		assert_eq!(dp.resolve(), Address16(0x0000));
+		assert_eq!(dp.resolve_indexed(0xFFFF, false), Address16(0xFFFF));
+		assert_eq!(dp.to_address24(), Address24(0x000000));
+	}
+
+	#[test]
+	fn stack_relative() {
+		let sr = StackRelative::new(0xFFF0, 0x20);
+		assert_eq!(sr.resolve(), Address16(0x0010));
+		assert_eq!(sr.to_address24(), Address24(0x000010));
+
+		let sr = StackRelative::new(0x01FF, 0x01);
+		assert_eq!(sr.resolve(), Address16(0x0200));
+	}
+}
@@ -0,0 +1,252 @@
+use core::iter::FusedIterator;
+
+use super::{Address16, Address24};
+
+/// Inclusive range of 16-bit addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddressRange16 {
+	start: Address16,
+	end: Address16,
+}
+
+/// Inclusive range of 24-bit addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddressRange24 {
+	start: Address24,
+	end: Address24,
+}
+
+/// Iterator over the addresses of an `AddressRange16`.
+#[derive(Debug, Clone)]
+pub struct Iter16 {
+	next: u32,
+	end: u32,
+}
+
+/// Iterator over the addresses of an `AddressRange24`.
+#[derive(Debug, Clone)]
+pub struct Iter24 {
+	next: u32,
+	end: u32,
+}
+
+macro_rules! impl_range {
+	($range:ident, $address:ident, $iter:ident) => {
+		impl $range {
+			/// Creates a new range from `start` to `end` inclusive.
+			///
+			/// Returns `None` if `start` is greater than `end`.
+			#[inline]
+			pub fn new(start: $address, end: $address) -> Option<Self> {
+				if start <= end {
+					Some(Self { start, end })
+				} else {
+					None
+				}
+			}
+
+			/// Returns the first address of the range.
+			#[inline]
+			pub fn start(&self) -> $address {
+				self.start
+			}
+
+			/// Returns the last address of the range.
+			#[inline]
+			pub fn end(&self) -> $address {
+				self.end
+			}
+
+			/// Returns the number of addresses in the range.
+			#[inline]
+			pub fn size(&self) -> u32 {
+				self.end.0 as u32 - self.start.0 as u32 + 1
+			}
+
+			/// Returns `true` if the range contains the given address.
+			#[inline]
+			pub fn contains(&self, address: $address) -> bool {
+				self.start <= address && address <= self.end
+			}
+
+			/// Returns `true` if the range contains the whole `other` range.
+			#[inline]
+			pub fn contains_range(&self, other: &Self) -> bool {
+				self.start <= other.start && other.end <= self.end
+			}
+
+			/// Returns `true` if the ranges share at least one address.
+			#[inline]
+			pub fn overlaps(&self, other: &Self) -> bool {
+				self.start <= other.end && other.start <= self.end
+			}
+
+			/// Returns the addresses shared by both ranges.
+			#[inline]
+			pub fn intersect(&self, other: &Self) -> Option<Self> {
+				Self::new(
+					core::cmp::max(self.start, other.start),
+					core::cmp::min(self.end, other.end),
+				)
+			}
+
+			/// Returns an iterator over the addresses of the range.
+			#[inline]
+			pub fn iter(&self) -> $iter {
+				$iter {
+					next: self.start.0 as u32,
+					end: self.end.0 as u32,
+				}
+			}
+		}
+
+		impl IntoIterator for $range {
+			type Item = $address;
+			type IntoIter = $iter;
+			#[inline]
+			fn into_iter(self) -> $iter {
+				self.iter()
+			}
+		}
+
+		impl<'a> IntoIterator for &'a $range {
+			type Item = $address;
+			type IntoIter = $iter;
+			#[inline]
+			fn into_iter(self) -> $iter {
+				self.iter()
+			}
+		}
+
+		impl Iterator for $iter {
+			type Item = $address;
+			#[inline]
+			fn next(&mut self) -> Option<$address> {
+				if self.next <= self.end {
+					let address = $address(self.next as _);
+					self.next += 1;
+					Some(address)
+				} else {
+					None
+				}
+			}
+
+			#[inline]
+			fn size_hint(&self) -> (usize, Option<usize>) {
+				let n = (self.end + 1).saturating_sub(self.next) as usize;
+				(n, Some(n))
+			}
+		}
+
+		impl DoubleEndedIterator for $iter {
+			#[inline]
+			fn next_back(&mut self) -> Option<$address> {
+				if self.next <= self.end {
+					let address = $address(self.end as _);
+					// `end` cannot step below zero, so move `next` past it instead
+					if self.end == 0 {
+						self.next = 1;
+					} else {
+						self.end -= 1;
+					}
+					Some(address)
+				} else {
+					None
+				}
+			}
+		}
+
+		impl ExactSizeIterator for $iter {}
+
+		impl FusedIterator for $iter {}
+	};
+}
+
+impl_range!(AddressRange16, Address16, Iter16);
+impl_range!(AddressRange24, Address24, Iter24);
+
+impl AddressRange24 {
+	/// Splits the range into sub-ranges that do not cross bank boundaries.
+	/// ```
+	/// # use sneslib::address::*;
+	/// let range = AddressRange24::new(Address24::new(0x7EFF00), Address24::new(0x7F00FF)).unwrap();
+	/// let banks = range.split_at_banks().collect::<Vec<_>>();
+	/// assert_eq!(banks.len(), 2);
+	/// assert_eq!(banks[0].end(), Address24::new(0x7EFFFF));
+	/// assert_eq!(banks[1].start(), Address24::new(0x7F0000));
+	/// ```
+	pub fn split_at_banks(&self) -> impl Iterator<Item = AddressRange24> {
+		let range = *self;
+		(range.start.bank()..=range.end.bank()).map(move |bank| {
+			let start =
+				core::cmp::max(range.start, Address24::from_bank_offset(bank, Address16(0)));
+			let end = core::cmp::min(
+				range.end,
+				Address24::from_bank_offset(bank, Address16(0xFFFF)),
+			);
+			AddressRange24 { start, end }
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn range16() {
+		let range = AddressRange16::new(Address16(0xFFFD), Address16(0xFFFF)).unwrap();
+		assert_eq!(range.size(), 3);
+		assert_eq!(
+			range.iter().collect::<Vec<_>>(),
+			vec![Address16(0xFFFD), Address16(0xFFFE), Address16(0xFFFF)]
+		);
+		assert_eq!(range.iter().len(), 3);
+		assert_eq!(range.iter().next_back(), Some(Address16(0xFFFF)));
+		assert!(range.contains(Address16(0xFFFE)));
+		assert!(!range.contains(Address16(0xFFFC)));
+		assert!(AddressRange16::new(Address16(1), Address16(0)).is_none());
+
+		let full = AddressRange16::new(Address16(0), Address16(0xFFFF)).unwrap();
+		assert_eq!(full.size(), 0x10000);
+		assert_eq!(full.iter().count(), 0x10000);
+		assert_eq!(full.iter().rev().count(), 0x10000);
+		assert!(full.contains_range(&range));
+		assert!(!range.contains_range(&full));
+	}
+
+	#[test]
+	fn range24() {
+		let a = AddressRange24::new(Address24(0x7E0000), Address24(0x7EFFFF)).unwrap();
+		let b = AddressRange24::new(Address24(0x7EFF00), Address24(0x7F00FF)).unwrap();
+		let c = AddressRange24::new(Address24(0x7F0100), Address24(0x7F01FF)).unwrap();
+		assert!(a.overlaps(&b));
+		assert!(!a.overlaps(&c));
+		assert_eq!(
+			a.intersect(&b),
+			AddressRange24::new(Address24(0x7EFF00), Address24(0x7EFFFF))
+		);
+		assert_eq!(a.intersect(&c), None);
+
+		let banks = b.split_at_banks().collect::<Vec<_>>();
+		assert_eq!(
+			banks,
+			vec![
+				AddressRange24::new(Address24(0x7EFF00), Address24(0x7EFFFF)).unwrap(),
+				AddressRange24::new(Address24(0x7F0000), Address24(0x7F00FF)).unwrap(),
+			]
+		);
+		assert_eq!(a.split_at_banks().count(), 1);
+
+		let mut iter = b.iter();
+		assert_eq!(iter.next(), Some(Address24(0x7EFF00)));
+		assert_eq!(iter.next_back(), Some(Address24(0x7F00FF)));
+		assert_eq!(iter.len(), 0x1FE);
+
+		let zero = AddressRange24::new(Address24(0), Address24(0)).unwrap();
+		let mut iter = zero.iter();
+		assert_eq!(iter.next_back(), Some(Address24(0)));
+		assert_eq!(iter.next_back(), None);
+		assert_eq!(iter.next(), None);
+	}
+}
@@ -0,0 +1,114 @@
+use core::ops::{Index, IndexMut};
+
+use super::{Address16, Address24};
+
+/// Address based access to slices such as raw ROM buffers.
+///
+/// The inherent `get` of slices takes precedence over a trait method of the same name,
+/// hence the `_at` suffix.
+/// ```
+/// # use sneslib::address::*;
+/// let rom = [0x00, 0x11, 0x22];
+/// assert_eq!(*rom.at(Address16::new(1)), 0x11);
+/// assert_eq!(rom.get_at(Address24::new(3)), None);
+/// assert_eq!(rom[Address16::new(2)], 0x22);
+/// ```
+pub trait SliceAddressExt<T> {
+	/// Returns a reference to the element at the address.
+	///
+	/// # Panics
+	/// Panics if the address is out of bounds.
+	fn at<A: Into<usize>>(&self, address: A) -> &T;
+
+	/// Returns a mutable reference to the element at the address.
+	///
+	/// # Panics
+	/// Panics if the address is out of bounds.
+	fn at_mut<A: Into<usize>>(&mut self, address: A) -> &mut T;
+
+	/// Returns a reference to the element at the address, or `None` if out of bounds.
+	fn get_at<A: Into<usize>>(&self, address: A) -> Option<&T>;
+
+	/// Returns a mutable reference to the element at the address, or `None` if out of bounds.
+	fn get_at_mut<A: Into<usize>>(&mut self, address: A) -> Option<&mut T>;
+}
+
+impl<T> SliceAddressExt<T> for [T] {
+	#[inline]
+	fn at<A: Into<usize>>(&self, address: A) -> &T {
+		&self[address.into()]
+	}
+
+	#[inline]
+	fn at_mut<A: Into<usize>>(&mut self, address: A) -> &mut T {
+		&mut self[address.into()]
+	}
+
+	#[inline]
+	fn get_at<A: Into<usize>>(&self, address: A) -> Option<&T> {
+		self.get(address.into())
+	}
+
+	#[inline]
+	fn get_at_mut<A: Into<usize>>(&mut self, address: A) -> Option<&mut T> {
+		self.get_mut(address.into())
+	}
+}
+
+macro_rules! impl_index {
+	($($address:ident),+; $container:ty) => {
+		$(
+			impl<T> Index<$address> for $container {
+				type Output = T;
+				#[inline]
+				fn index(&self, address: $address) -> &T {
+					self.at(address)
+				}
+			}
+
+			impl<T> IndexMut<$address> for $container {
+				#[inline]
+				fn index_mut(&mut self, address: $address) -> &mut T {
+					self.at_mut(address)
+				}
+			}
+		)+
+	};
+}
+
+impl_index![Address16, Address24; [T]];
+#[cfg(feature = "std")]
+impl_index![Address16, Address24; Vec<T>];
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn index() {
+		let mut rom = vec![0u8; 0x10000];
+		rom[..][Address16(0xFFFC)] = 0x00;
+		rom[..][Address16(0xFFFD)] = 0x80;
+		*rom.at_mut(Address24(0x1234)) = 0x56;
+		assert_eq!(rom[..][Address24(0xFFFD)], 0x80);
+		assert_eq!(rom[..][Address16(0x1234)], 0x56);
+		#[cfg(feature = "std")]
+		{
+			rom[Address16(0xFFFC)] = 0x01;
+			assert_eq!(rom[Address24(0xFFFC)], 0x01);
+		}
+		assert_eq!(rom.get_at(Address24(0x10000)), None);
+		assert_eq!(rom.get_at(Address16(0xFFFF)), Some(&0));
+		if let Some(b) = rom.get_at_mut(Address16(0)) {
+			*b = 1;
+		}
+		assert_eq!(*rom.at(Address16(0)), 1);
+	}
+
+	#[test]
+	#[should_panic]
+	fn out_of_bounds() {
+		let rom = [0u8; 0x100];
+		let _ = rom[..][Address16(0x100)];
+	}
+}
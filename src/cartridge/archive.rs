@@ -0,0 +1,132 @@
+use std::path::Path;
+
+#[cfg(feature = "zip")]
+use super::error::ArchiveError;
+use super::{Cartridge, CartridgeResult, TestFlags};
+
+/// File extensions of ROM entries picked from archives, compared case-insensitively.
+pub const ROM_EXTENSIONS: [&str; 4] = ["sfc", "smc", "swc", "fig"];
+
+/// Returns `true` if the file name has one of the [`ROM_EXTENSIONS`].
+pub fn is_rom_name(name: &str) -> bool {
+	Path::new(name)
+		.extension()
+		.and_then(|extension| extension.to_str())
+		.is_some_and(|extension| {
+			ROM_EXTENSIONS
+				.iter()
+				.any(|rom| extension.eq_ignore_ascii_case(rom))
+		})
+}
+
+impl Cartridge {
+	/// Loads the first ROM entry of a zip archive, see [`ROM_EXTENSIONS`].
+	#[cfg(feature = "zip")]
+	pub fn from_zip<R>(reader: R, test_flags: TestFlags) -> CartridgeResult
+	where
+		R: std::io::Read + std::io::Seek,
+	{
+		let mut archive = zip::ZipArchive::new(reader)?;
+		let index = (0..archive.len())
+			.find(|&i| {
+				archive
+					.by_index_raw(i)
+					.is_ok_and(|entry| entry.is_file() && is_rom_name(entry.name()))
+			})
+			.ok_or(ArchiveError::NoRom)?;
+		let entry = archive.by_index(index)?;
+		Self::from_reader(entry, test_flags)
+	}
+
+	/// Loads a gzip compressed ROM.
+	#[cfg(feature = "gzip")]
+	pub fn from_gzip<R>(reader: R, test_flags: TestFlags) -> CartridgeResult
+	where
+		R: std::io::Read,
+	{
+		Self::from_reader(flate2::read::GzDecoder::new(reader), test_flags)
+	}
+
+	/// Loads the archive at `path`, or returns `None` if the extension names no enabled archive format.
+	pub(super) fn from_archive_file(path: &Path, test_flags: TestFlags) -> Option<CartridgeResult> {
+		let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+		let open = || std::fs::File::open(path).map_err(Into::into);
+		match extension.as_str() {
+			#[cfg(feature = "zip")]
+			"zip" => Some(open().and_then(|file| Self::from_zip(file, test_flags))),
+			#[cfg(feature = "gzip")]
+			"gz" => Some(open().and_then(|file| Self::from_gzip(file, test_flags))),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	#[cfg(feature = "zip")]
+	use crate::cartridge::CartridgeError;
+	use crate::cartridge::{test::make_rom, ROMType};
+
+	#[test]
+	fn rom_name() {
+		assert!(is_rom_name("Game (USA).sfc"));
+		assert!(is_rom_name("roms/GAME.SMC"));
+		assert!(!is_rom_name("readme.txt"));
+		assert!(!is_rom_name("sfc"));
+	}
+
+	#[cfg(feature = "zip")]
+	#[test]
+	fn zip() {
+		use std::io::{Cursor, Write};
+		use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		writer.start_file("readme.txt", options).unwrap();
+		writer.write_all(b"SNESLIB TEST").unwrap();
+		writer.start_file("Test.SFC", options).unwrap();
+		writer.write_all(&rom).unwrap();
+		let bytes = writer.finish().unwrap().into_inner();
+
+		let cartridge = Cartridge::from_zip(Cursor::new(&bytes), TestFlags::default()).unwrap();
+		assert_eq!(cartridge.rom(), &rom[..]);
+
+		let path = std::env::temp_dir().join(format!("sneslib-{}.zip", std::process::id()));
+		std::fs::write(&path, &bytes).unwrap();
+		let loaded = Cartridge::from_file(&path, TestFlags::default());
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(loaded.unwrap().rom(), &rom[..]);
+
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		writer.start_file("readme.txt", options).unwrap();
+		let bytes = writer.finish().unwrap().into_inner();
+		assert!(matches!(
+			Cartridge::from_zip(Cursor::new(bytes), TestFlags::default()),
+			Err(CartridgeError::Archive(ArchiveError::NoRom))
+		));
+	}
+
+	#[cfg(feature = "gzip")]
+	#[test]
+	fn gzip() {
+		use flate2::{write::GzEncoder, Compression};
+		use std::io::Write;
+
+		let rom = make_rom(ROMType::HiROM, 0x80000);
+		let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(&rom).unwrap();
+		let bytes = encoder.finish().unwrap();
+
+		let cartridge = Cartridge::from_gzip(&bytes[..], TestFlags::default()).unwrap();
+		assert_eq!(cartridge.rom(), &rom[..]);
+
+		let path = std::env::temp_dir().join(format!("sneslib-{}.sfc.gz", std::process::id()));
+		std::fs::write(&path, &bytes).unwrap();
+		let loaded = Cartridge::from_file(&path, TestFlags::default());
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(loaded.unwrap().rom(), &rom[..]);
+	}
+}
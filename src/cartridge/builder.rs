@@ -0,0 +1,336 @@
+use core::ops::Range;
+
+use super::error::*;
+use super::header::MAX_SRAM_SIZE;
+use super::{compute_checksum, Cartridge, CartridgeResult, ROMType, TestFlags, Vectors};
+use crate::address::{snes_to_pc, Address16, Address24};
+
+/// Address of the `RTI` the default interrupt vectors point to, right below the extended header.
+const RTI_ADDRESS: u16 = 0xFFAF;
+
+/// Builds a bootable cartridge with a valid header, vectors and checksum from code and data.
+///
+/// The code is placed at `$00:8000`, where the RESET vector points to by default.
+/// The other vectors default to an `RTI` instruction at `$00:FFAF`.
+/// ```
+/// # use sneslib::cartridge::*;
+/// let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+///     .title("HOMEBREW")
+///     .code(&[0x78, 0x18, 0xFB, 0x80, 0xFE]) // SEI; CLC; XCE; BRA -2
+///     .build()
+///     .unwrap();
+/// assert_eq!(cartridge.detect_rom_type(), (ROMType::LoROM, Score::MAX));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CartridgeBuilder {
+	rom_type: ROMType,
+	fast_rom: bool,
+	title: [u8; 21],
+	sram_size: usize,
+	country: u8,
+	developer_id: u8,
+	version: u8,
+	code: Vec<u8>,
+	data: Vec<(usize, Vec<u8>)>,
+	vectors: Option<Vectors>,
+}
+
+impl CartridgeBuilder {
+	/// Creates a builder for an empty cartridge of the given ROM type.
+	pub fn new(rom_type: ROMType) -> Self {
+		Self {
+			rom_type,
+			fast_rom: false,
+			title: [b' '; 21],
+			sram_size: 0,
+			country: 0x01,
+			developer_id: 0x00,
+			version: 0x00,
+			code: Vec::new(),
+			data: Vec::new(),
+			vectors: None,
+		}
+	}
+
+	/// Sets the internal name, truncated to 21 bytes and padded with spaces.
+	pub fn title<T: AsRef<[u8]>>(mut self, title: T) -> Self {
+		let title = title.as_ref();
+		let len = title.len().min(self.title.len());
+		self.title = [b' '; 21];
+		self.title[..len].copy_from_slice(&title[..len]);
+		self
+	}
+
+	/// Sets whether the cartridge runs at FastROM speed.
+	pub fn fast_rom(mut self, fast_rom: bool) -> Self {
+		self.fast_rom = fast_rom;
+		self
+	}
+
+	/// Sets the battery backed SRAM size in bytes, rounded up to a power of two of at least 2KB
+	/// and capped at the 256KB the SRAM windows can map.
+	pub fn sram_size(mut self, sram_size: usize) -> Self {
+		self.sram_size = sram_size;
+		self
+	}
+
+	/// Sets the country code.
+	pub fn country(mut self, country: u8) -> Self {
+		self.country = country;
+		self
+	}
+
+	/// Sets the developer ID.
+	pub fn developer_id(mut self, developer_id: u8) -> Self {
+		self.developer_id = developer_id;
+		self
+	}
+
+	/// Sets the version number.
+	pub fn version(mut self, version: u8) -> Self {
+		self.version = version;
+		self
+	}
+
+	/// Sets the code placed at `$00:8000`.
+	pub fn code<T: Into<Vec<u8>>>(mut self, code: T) -> Self {
+		self.code = code.into();
+		self
+	}
+
+	/// Places data at the given ROM offset, growing the ROM as needed.
+	///
+	/// The data must not overlap the code, the header, the interrupt vectors or other data.
+	pub fn data<T: Into<Vec<u8>>>(mut self, offset: usize, data: T) -> Self {
+		self.data.push((offset, data.into()));
+		self
+	}
+
+	/// Overrides the interrupt vectors.
+	pub fn vectors(mut self, vectors: Vectors) -> Self {
+		self.vectors = Some(vectors);
+		self
+	}
+
+	/// Lays out the ROM, pads it to a legal size and computes the checksum.
+	pub fn build(self) -> CartridgeResult {
+		let rom_type = self.rom_type;
+		let to_pc = |address: u16| snes_to_pc(Address24::from(Address16::new(address)), rom_type);
+		let code_offset = to_pc(0x8000).unwrap();
+		let rti_offset = to_pc(RTI_ADDRESS).unwrap();
+		let header = rom_type.header_offset();
+		let reserved = rti_offset..rom_type.vectors_offset() + 0x20;
+
+		if code_offset + self.code.len() > rti_offset {
+			return Err(BuildError::CodeTooLarge {
+				len: self.code.len(),
+				max: rti_offset - code_offset,
+			}
+			.into());
+		}
+		let code = code_offset..code_offset + self.code.len();
+		let mut placed: Vec<Range<usize>> = Vec::with_capacity(self.data.len());
+		for (offset, data) in self.data.iter() {
+			let (offset, len) = (*offset, data.len());
+			let end = offset
+				.checked_add(len)
+				.ok_or(BuildError::RomTooLarge(usize::MAX))?;
+			let overlaps = |range: &Range<usize>| offset < range.end && end > range.start;
+			if overlaps(&reserved) {
+				return Err(BuildError::DataOverlapsHeader { offset, len }.into());
+			}
+			if overlaps(&code) {
+				return Err(BuildError::DataOverlapsCode { offset, len }.into());
+			}
+			if placed.iter().any(overlaps) {
+				return Err(BuildError::DataOverlapsData { offset, len }.into());
+			}
+			placed.push(offset..end);
+		}
+
+		let end = placed
+			.iter()
+			.map(|range| range.end)
+			.fold(reserved.end, usize::max);
+		let max = match rom_type {
+			ROMType::LoROM | ROMType::HiROM => 0x400000,
+			ROMType::ExHiROM => 0x800000,
+		};
+		if end > max {
+			return Err(BuildError::RomTooLarge(end).into());
+		}
+		let size = match rom_type {
+			ROMType::LoROM | ROMType::HiROM => end.next_power_of_two(),
+			ROMType::ExHiROM => (end + 0xFFFFF) & !0xFFFFF,
+		};
+
+		let mut rom = vec![0xFF; size.max(0x8000)];
+		rom[code_offset..code_offset + self.code.len()].copy_from_slice(&self.code);
+		for (offset, data) in self.data.iter() {
+			rom[*offset..offset + data.len()].copy_from_slice(data);
+		}
+
+		// header
+		let sram_size = match self.sram_size {
+			0 => 0,
+			n => n.clamp(0x800, MAX_SRAM_SIZE).next_power_of_two(),
+		};
+		let log2_kib = |size: usize| (size / 1024).next_power_of_two().trailing_zeros() as u8;
+		rom[header..header + 21].copy_from_slice(&self.title);
+		rom[header + 0x15] = match rom_type {
+			ROMType::LoROM => 0x20,
+			ROMType::HiROM => 0x21,
+			ROMType::ExHiROM => 0x25,
+		} | if self.fast_rom { 0x10 } else { 0x00 };
+		rom[header + 0x16] = if sram_size > 0 { 0x02 } else { 0x00 };
+		rom[header + 0x17] = log2_kib(rom.len());
+		rom[header + 0x18] = if sram_size > 0 {
+			log2_kib(sram_size)
+		} else {
+			0
+		};
+		rom[header + 0x19] = self.country;
+		rom[header + 0x1A] = self.developer_id;
+		rom[header + 0x1B] = self.version;
+
+		// vectors
+		rom[rti_offset] = 0x40;
+		let rti = Address16::new(RTI_ADDRESS);
+		let entries = match self.vectors {
+			Some(Vectors { native, emulation }) => [
+				native.cop,
+				native.brk,
+				native.abort,
+				native.nmi,
+				native.reset,
+				native.irq,
+				emulation.cop,
+				rti,
+				emulation.abort,
+				emulation.nmi,
+				emulation.reset,
+				emulation.irq,
+			],
+			None => {
+				let mut entries = [rti; 12];
+				entries[10] = Address16::new(0x8000);
+				entries
+			}
+		};
+		let vectors_offset = rom_type.vectors_offset();
+		for (i, entry) in entries.iter().enumerate() {
+			// the native vectors at $xFE4 and the emulation vectors at $xFF4
+			let offset = vectors_offset + if i < 6 { 0x04 } else { 0x08 } + 2 * i;
+			rom[offset..offset + 2].copy_from_slice(&entry.to_le_bytes());
+		}
+
+		// checksum
+		let checksum = compute_checksum(&rom, rom_type);
+		rom[header + 0x1C..header + 0x1E].copy_from_slice(&(checksum ^ 0xFFFF).to_le_bytes());
+		rom[header + 0x1E..header + 0x20].copy_from_slice(&checksum.to_le_bytes());
+
+		Cartridge::new(rom, TestFlags::default())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::Score;
+
+	#[test]
+	fn build() {
+		let code = [0x78, 0x18, 0xFB, 0x80, 0xFE];
+		for &rom_type in [ROMType::LoROM, ROMType::HiROM, ROMType::ExHiROM].iter() {
+			let cartridge = CartridgeBuilder::new(rom_type)
+				.title("SNESLIB BUILDER TEST WITH A LONG TITLE")
+				.fast_rom(true)
+				.sram_size(0x1000)
+				.country(0x02)
+				.version(3)
+				.code(&code[..])
+				.build()
+				.unwrap();
+			assert_eq!(cartridge.detect_rom_type(), (rom_type, Score::MAX));
+			let header = cartridge.header().unwrap();
+			assert_eq!(&header.title, b"SNESLIB BUILDER TEST ");
+			assert!(header.is_fast_rom());
+			assert!(header.rom_size_bytes().unwrap() >= cartridge.rom.len());
+			assert_eq!(header.sram_size_bytes(), Some(0x1000));
+			assert_eq!((header.country, header.version), (0x02, 3));
+			assert_eq!(cartridge.compute_checksum(), header.checksum);
+
+			let vectors = cartridge.vectors().unwrap();
+			assert_eq!(vectors.emulation.reset, Address16::new(0x8000));
+			assert_eq!(vectors.native.nmi, Address16::new(RTI_ADDRESS));
+			let entry = snes_to_pc(Address24::new(0x8000), rom_type).unwrap();
+			assert_eq!(&cartridge.rom[entry..entry + code.len()], &code);
+		}
+
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.data(0x8000, vec![1; 0x10000])
+			.build()
+			.unwrap();
+		assert_eq!(cartridge.rom.len(), 0x20000);
+		assert_eq!(cartridge.sram_size(), 0);
+		assert!(cartridge.rom[0x8000..0x18000].iter().all(|&b| b == 1));
+
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(usize::MAX)
+			.build()
+			.unwrap();
+		assert_eq!(cartridge.sram_size(), MAX_SRAM_SIZE);
+	}
+
+	#[test]
+	fn errors() {
+		let build = |builder: CartridgeBuilder| match builder.build() {
+			Err(CartridgeError::Build(e)) => e,
+			r => panic!("{:?}", r),
+		};
+		assert!(matches!(
+			build(CartridgeBuilder::new(ROMType::LoROM).code(vec![0; 0x8000])),
+			BuildError::CodeTooLarge {
+				len: 0x8000,
+				max: 0x7FAF
+			}
+		));
+		assert!(matches!(
+			build(CartridgeBuilder::new(ROMType::HiROM).data(0xFF00, vec![0; 0x100])),
+			BuildError::DataOverlapsHeader {
+				offset: 0xFF00,
+				len: 0x100
+			}
+		));
+		assert!(matches!(
+			build(CartridgeBuilder::new(ROMType::LoROM).data(0x400000, vec![0])),
+			BuildError::RomTooLarge(0x400001)
+		));
+		assert!(matches!(
+			build(CartridgeBuilder::new(ROMType::LoROM).data(usize::MAX, vec![0])),
+			BuildError::RomTooLarge(usize::MAX)
+		));
+		assert!(matches!(
+			build(
+				CartridgeBuilder::new(ROMType::LoROM)
+					.code(vec![0xEA; 0x10])
+					.data(0x000F, vec![0; 2])
+			),
+			BuildError::DataOverlapsCode {
+				offset: 0x000F,
+				len: 2
+			}
+		));
+		assert!(matches!(
+			build(
+				CartridgeBuilder::new(ROMType::HiROM)
+					.data(0x10000, vec![0; 0x100])
+					.data(0x100FF, vec![0; 0x100])
+			),
+			BuildError::DataOverlapsData {
+				offset: 0x100FF,
+				len: 0x100
+			}
+		));
+	}
+}
@@ -0,0 +1,141 @@
+#[cfg(feature = "std")]
+use super::Cartridge;
+use super::ROMType;
+
+/// Computes the checksum the header at the location for `rom_type` should hold.
+///
+/// ROMs with a size other than a power of two are summed as if their upper part was mirrored up
+/// to the next power of two, as done by the cartridge mapping, so a 3MB ROM counts its last 1MB
+/// twice. The checksum and its complement are summed as `$FFFF` and `$0000` so that the result
+/// does not depend on their current values.
+/// ```
+/// # use sneslib::cartridge::*;
+/// let mut rom = vec![0; 0x18000];
+/// rom[0x10000] = 1;
+/// // $0000-$FFFF once, $10000-$17FFF twice, plus $FF twice for the complement
+/// assert_eq!(compute_checksum(&rom, ROMType::LoROM), 2 + 0x1FE);
+/// ```
+pub fn compute_checksum(rom: &[u8], rom_type: ROMType) -> u16 {
+	mirrored_sum(rom, 0, rom_type.header_offset() + 0x1C)
+}
+
+/// Sums `rom` mirrored up to the next power of two, `start` being the offset of `rom` in the image.
+fn mirrored_sum(rom: &[u8], start: usize, checksum_offset: usize) -> u16 {
+	if rom.is_empty() || rom.len().is_power_of_two() {
+		return plain_sum(rom, start, checksum_offset);
+	}
+	let base = 1 << (usize::BITS - 1 - rom.len().leading_zeros());
+	let (lower, upper) = rom.split_at(base);
+	let mirrors = base / upper.len().next_power_of_two();
+	plain_sum(lower, start, checksum_offset).wrapping_add(
+		mirrored_sum(upper, start + base, checksum_offset).wrapping_mul(mirrors as u16),
+	)
+}
+
+fn plain_sum(rom: &[u8], start: usize, checksum_offset: usize) -> u16 {
+	rom.iter().enumerate().fold(0u16, |r, (i, &b)| {
+		let b = match (start + i).wrapping_sub(checksum_offset) {
+			0 | 1 => 0xFF,
+			2 | 3 => 0x00,
+			_ => b,
+		};
+		r.wrapping_add(b as u16)
+	})
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Computes the checksum for the header of the detected ROM type.
+	///
+	/// See [`compute_checksum`](fn.compute_checksum.html).
+	#[inline]
+	pub fn compute_checksum(&self) -> u16 {
+		compute_checksum(&self.rom, self.detect_rom_type().0)
+	}
+
+	/// Writes the computed checksum and its complement into the header of the detected ROM type.
+	///
+	/// Returns the checksum, or `None` if the ROM is too small to hold the header.
+	pub fn fix_checksum(&mut self) -> Option<u16> {
+		let rom_type = self.detect_rom_type().0;
+		let checksum = compute_checksum(&self.rom, rom_type);
+		let offset = rom_type.header_offset() + 0x1C;
+		let bytes = self.rom.get_mut(offset..offset + 4)?;
+		bytes[..2].copy_from_slice(&(checksum ^ 0xFFFF).to_le_bytes());
+		bytes[2..].copy_from_slice(&checksum.to_le_bytes());
+		self.rom_changed();
+		Some(checksum)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::TestFlags;
+
+	#[test]
+	fn checksum() {
+		let rom = vec![1; 0x8000];
+		assert_eq!(compute_checksum(&rom, ROMType::LoROM), 0x8000 - 4 + 0x1FE);
+
+		// 1.5MB, the upper 512KB is mirrored once
+		let mut rom = vec![0; 0x180000];
+		rom[0x100000] = 3;
+		assert_eq!(compute_checksum(&rom, ROMType::HiROM), 6 + 0x1FE);
+
+		// 2.5MB, the upper 512KB is mirrored up to 2MB
+		let mut rom = vec![0; 0x280000];
+		rom[0x1FFFFF] = 1;
+		rom[0x27FFFF] = 1;
+		assert_eq!(compute_checksum(&rom, ROMType::HiROM), 1 + 4 + 0x1FE);
+
+		// 3MB, the upper 1MB is counted twice
+		let mut rom = vec![0; 0x300000];
+		rom[0x2FFFFF] = 1;
+		assert_eq!(compute_checksum(&rom, ROMType::LoROM), 2 + 0x1FE);
+
+		// 6MB ExHiROM like Tales of Phantasia, the upper 2MB holding the header is counted twice
+		let mut rom = vec![0; 0x600000];
+		rom[0x3FFFFF] = 1;
+		rom[0x400000] = 1;
+		assert_eq!(
+			compute_checksum(&rom, ROMType::ExHiROM),
+			1 + 2 * (1 + 0x1FE)
+		);
+
+		// 3MB and 256KB, the 256KB part is mirrored up to 1MB
+		let mut rom = vec![0; 0x340000];
+		rom[0x300000] = 1;
+		rom[0x200000] = 1;
+		assert_eq!(compute_checksum(&rom, ROMType::LoROM), 1 + 4 + 0x1FE);
+
+		// the checksum bytes themselves do not matter
+		let mut rom = make_rom(ROMType::HiROM, 0x80000);
+		let checksum = compute_checksum(&rom, ROMType::HiROM);
+		rom[0xFFDC..0xFFE0].copy_from_slice(&[1, 2, 3, 4]);
+		assert_eq!(compute_checksum(&rom, ROMType::HiROM), checksum);
+	}
+
+	#[test]
+	fn fix_checksum() {
+		let mut rom = make_rom(ROMType::ExHiROM, 0x600000);
+		rom[0x500000] ^= 0xFF;
+		let mut cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		assert!(!Cartridge::run_tests(&cartridge.rom)
+			.passed()
+			.contains(TestFlags::CHECKSUM_EXHI));
+
+		let checksum = cartridge.fix_checksum().unwrap();
+		assert_eq!(checksum, cartridge.compute_checksum());
+		let header = cartridge.header().unwrap();
+		assert_eq!(header.checksum, checksum);
+		assert!(header.is_checksum_consistent());
+		assert!(Cartridge::run_tests(&cartridge.rom)
+			.passed()
+			.contains(TestFlags::CHECKSUM_EXHI));
+
+		let mut cartridge = Cartridge::new(vec![0; 0x4000], TestFlags::default()).unwrap();
+		assert_eq!(cartridge.fix_checksum(), None);
+	}
+}
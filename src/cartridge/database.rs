@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use super::{crc32, Cartridge, Enhancement, Region};
+
+/// Key identifying a game by its header title and checksum, and optionally the CRC-32 of the ROM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameKey {
+	pub title: [u8; 21],
+	pub checksum: u16,
+	/// CRC-32 of the ROM without a copier header, `None` to match any dump with the title and checksum.
+	pub crc32: Option<u32>,
+}
+
+impl GameKey {
+	/// Creates the key of a cartridge, or `None` if the ROM is too small to hold a header.
+	pub fn from_cartridge(cartridge: &Cartridge) -> Option<Self> {
+		let header = cartridge.header()?;
+		Some(Self {
+			title: header.title,
+			checksum: header.checksum,
+			crc32: Some(crc32(cartridge.rom())),
+		})
+	}
+
+	/// Returns `true` if the title and checksum are equal and the CRC-32 of either key is absent or equal.
+	pub fn matches(&self, other: &GameKey) -> bool {
+		self.title == other.title
+			&& self.checksum == other.checksum
+			&& match (self.crc32, other.crc32) {
+				(Some(a), Some(b)) => a == b,
+				_ => true,
+			}
+	}
+}
+
+/// Per-game overrides of what the header declares, each `None` to keep the header value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameQuirks {
+	pub name: Option<String>,
+	/// SRAM size in bytes.
+	pub sram_size: Option<usize>,
+	pub enhancement: Option<Enhancement>,
+	pub region: Option<Region>,
+}
+
+/// Source of per-game quirks looked up by [`GameKey`].
+pub trait GameDatabase {
+	/// Returns the quirks of the game, or `None` if the game is unknown.
+	fn lookup(&self, key: &GameKey) -> Option<&GameQuirks>;
+}
+
+/// In-memory game database, to which entries can be added at runtime.
+///
+/// Entries with a CRC-32 take precedence over entries matching only the title and checksum.
+/// ```
+/// # use sneslib::cartridge::*;
+/// let cartridge = CartridgeBuilder::new(ROMType::LoROM).title("HOMEBREW").build().unwrap();
+/// let mut key = GameKey::from_cartridge(&cartridge).unwrap();
+/// key.crc32 = None;
+///
+/// let mut database = MemoryDatabase::new();
+/// database.insert(key, GameQuirks {
+///     sram_size: Some(0x2000),
+///     ..Default::default()
+/// });
+/// assert_eq!(cartridge.quirks(&database).unwrap().sram_size, Some(0x2000));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDatabase {
+	entries: Vec<(GameKey, GameQuirks)>,
+}
+
+impl MemoryDatabase {
+	/// Creates an empty database.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds an entry, replacing the quirks of an entry with an equal key.
+	pub fn insert(&mut self, key: GameKey, quirks: GameQuirks) {
+		match self.entries.iter_mut().find(|(k, _)| *k == key) {
+			Some(entry) => entry.1 = quirks,
+			None => self.entries.push((key, quirks)),
+		}
+	}
+
+	/// Removes the entry with an equal key, returning its quirks.
+	pub fn remove(&mut self, key: &GameKey) -> Option<GameQuirks> {
+		let index = self.entries.iter().position(|(k, _)| k == key)?;
+		Some(self.entries.remove(index).1)
+	}
+
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+impl GameDatabase for MemoryDatabase {
+	fn lookup(&self, key: &GameKey) -> Option<&GameQuirks> {
+		self.entries
+			.iter()
+			.filter(|(k, _)| k.matches(key))
+			.max_by_key(|(k, _)| k.crc32.is_some())
+			.map(|(_, quirks)| quirks)
+	}
+}
+
+impl Extend<(GameKey, GameQuirks)> for MemoryDatabase {
+	fn extend<T: IntoIterator<Item = (GameKey, GameQuirks)>>(&mut self, iter: T) {
+		for (key, quirks) in iter {
+			self.insert(key, quirks);
+		}
+	}
+}
+
+impl core::iter::FromIterator<(GameKey, GameQuirks)> for MemoryDatabase {
+	fn from_iter<T: IntoIterator<Item = (GameKey, GameQuirks)>>(iter: T) -> Self {
+		let mut database = Self::new();
+		database.extend(iter);
+		database
+	}
+}
+
+impl Cartridge {
+	/// Looks up the quirks of the cartridge in a game database.
+	pub fn quirks<'a, D>(&self, database: &'a D) -> Option<&'a GameQuirks>
+	where
+		D: GameDatabase + ?Sized,
+	{
+		database.lookup(&GameKey::from_cartridge(self)?)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::{ROMType, TestFlags};
+
+	#[test]
+	fn database() {
+		let rom = make_rom(ROMType::HiROM, 0x80000);
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let key = GameKey::from_cartridge(&cartridge).unwrap();
+		assert_eq!(&key.title, b"SNESLIB TEST         ");
+		assert_eq!(key.crc32, Some(crc32(&rom)));
+
+		let any_dump = GameKey { crc32: None, ..key };
+		let other_dump = GameKey {
+			crc32: Some(!crc32(&rom)),
+			..key
+		};
+		let quirks = |name: &str| GameQuirks {
+			name: Some(name.into()),
+			..Default::default()
+		};
+		let mut database = vec![(any_dump, quirks("any")), (other_dump, quirks("other"))]
+			.into_iter()
+			.collect::<MemoryDatabase>();
+		assert_eq!(cartridge.quirks(&database), Some(&quirks("any")));
+
+		database.extend(Some((
+			key,
+			GameQuirks {
+				enhancement: Some(Enhancement::DSP1),
+				region: Some(Region::Europe),
+				..quirks("exact")
+			},
+		)));
+		let found = cartridge.quirks(&database).unwrap();
+		assert_eq!(found.name.as_deref(), Some("exact"));
+		assert_eq!(found.region, Some(Region::Europe));
+
+		database.insert(key, quirks("replaced"));
+		assert_eq!(database.len(), 3);
+		assert_eq!(cartridge.quirks(&database), Some(&quirks("replaced")));
+		assert_eq!(database.remove(&key), Some(quirks("replaced")));
+		assert_eq!(database.remove(&any_dump), Some(quirks("any")));
+		assert_eq!(cartridge.quirks(&database), None);
+
+		let dyn_database: &dyn GameDatabase = &database;
+		assert_eq!(cartridge.quirks(dyn_database), None);
+	}
+}
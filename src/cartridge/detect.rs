@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use super::{compute_checksum, Cartridge, Header, ROMType, Vectors};
+use crate::address::{snes_to_pc, Address24};
+
+/// Confidence that a ROM uses a given ROM type, higher is more likely.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Score(u8);
+
+impl Score {
+	/// The score of a header passing every heuristic.
+	pub const MAX: Score = Score(13);
+
+	/// Returns the raw score value.
+	#[inline]
+	pub const fn value(&self) -> u8 {
+		self.0
+	}
+}
+
+impl Cartridge {
+	/// Scores how likely the ROM uses the given ROM type.
+	///
+	/// The header at the location for `rom_type` is checked for a valid checksum,
+	/// a matching map mode, a plausible reset vector, a printable title and a plausible ROM size.
+	pub fn score(&self, rom_type: ROMType) -> Score {
+		let header = match self.header_for(rom_type) {
+			Some(header) => header,
+			None => return Score(0),
+		};
+		let mut score = 0;
+
+		if header.checksum == compute_checksum(&self.rom, rom_type) {
+			score += 4;
+		}
+		if header.is_checksum_consistent() {
+			score += 2;
+		}
+
+		if header.mode().mapping.rom_type() == Some(rom_type) {
+			score += 2;
+		}
+
+		if let Some(vectors) = self
+			.vectors_for(rom_type)
+			.filter(Vectors::is_reset_plausible)
+		{
+			score += 2;
+			let reset = Address24::from(vectors.emulation.reset);
+			let opcode = snes_to_pc(reset, rom_type).and_then(|offset| self.rom.get(offset));
+			// SEI, CLC, REP, SEP, STZ, JMP, JML
+			if let Some(0x78 | 0x18 | 0xC2 | 0xE2 | 0x9C | 0x4C | 0x5C) = opcode {
+				score += 1;
+			}
+		}
+
+		if is_printable(&header) {
+			score += 1;
+		}
+
+		if header
+			.rom_size_bytes()
+			.is_some_and(|size| size >= self.rom.len() && size / 2 < self.rom.len())
+		{
+			score += 1;
+		}
+
+		Score(score)
+	}
+
+	/// Detects the ROM type from the candidate headers, preferring LoROM on ties.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// # fn f(cartridge: &Cartridge) {
+	/// let (rom_type, score) = cartridge.detect_rom_type();
+	/// println!("{:?} ({}/{})", rom_type, score.value(), Score::MAX.value());
+	/// # }
+	/// ```
+	///
+	/// The result is kept until the ROM is modified, and detected again afterwards.
+	pub fn detect_rom_type(&self) -> (ROMType, Score) {
+		*self.rom_type.get_or_init(|| {
+			[ROMType::LoROM, ROMType::HiROM, ROMType::ExHiROM]
+				.iter()
+				.map(|&rom_type| (rom_type, self.score(rom_type)))
+				.fold((ROMType::LoROM, Score(0)), |best, candidate| {
+					if candidate.1 > best.1 {
+						candidate
+					} else {
+						best
+					}
+				})
+		})
+	}
+
+	/// Forgets the detected ROM type after the ROM was modified.
+	#[inline]
+	pub(crate) fn rom_changed(&mut self) {
+		self.rom_type.take();
+	}
+}
+
+/// Returns `true` if the title consists of ASCII or JIS X 0201 katakana.
+fn is_printable(header: &Header) -> bool {
+	header
+		.title
+		.iter()
+		.all(|&b| matches!(b, 0x00 | 0x20..=0x7E | 0xA1..=0xDF))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::TestFlags;
+
+	#[test]
+	fn detect() {
+		for &(rom_type, size) in [
+			(ROMType::LoROM, 0x80000),
+			(ROMType::HiROM, 0x80000),
+			(ROMType::ExHiROM, 0x800000),
+		]
+		.iter()
+		{
+			let cartridge = Cartridge::new(make_rom(rom_type, size), TestFlags::default()).unwrap();
+			assert_eq!(cartridge.detect_rom_type(), (rom_type, Score::MAX));
+		}
+
+		let mut rom = make_rom(ROMType::HiROM, 0x80000);
+		rom[0xFFD5] = 0x20;
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		assert_eq!(
+			cartridge.score(ROMType::HiROM).value(),
+			Score::MAX.value() - 6
+		);
+		assert_eq!(cartridge.detect_rom_type().0, ROMType::HiROM);
+
+		// modifying the ROM detects it again
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let mut cartridge = cartridge;
+		cartridge.rom_mut().copy_from_slice(&rom);
+		assert_eq!(cartridge.detect_rom_type(), (ROMType::LoROM, Score::MAX));
+
+		let cartridge = Cartridge::new(vec![0; 0x8000], TestFlags::default()).unwrap();
+		assert_eq!(cartridge.detect_rom_type().0, ROMType::LoROM);
+		assert_eq!(cartridge.score(ROMType::HiROM), Score(0));
+	}
+}
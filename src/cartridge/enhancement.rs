@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use super::Cartridge;
+use super::Header;
+
+/// Enhancement chip on the cartridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Enhancement {
+	/// No enhancement chip.
+	#[default]
+	None,
+	DSP1,
+	DSP2,
+	DSP3,
+	DSP4,
+	SA1,
+	SuperFX,
+	SDD1,
+	CX4,
+	SPC7110,
+	OBC1,
+	ST010,
+	ST011,
+	ST018,
+	SRTC,
+	/// Any other chip, holding the chipset byte at `$xFD6`.
+	Other(u8),
+}
+
+impl Enhancement {
+	/// Derives the enhancement chip from the header and the chipset subtype at `$xFBF`.
+	///
+	/// The subtype identifies custom chips with a chipset byte of `$Fx`.
+	/// Without it, the SPC7110 and CX4 are recognized by their map mode and chipset bytes.
+	/// The DSP variants share a chipset byte and are told apart by the title of the games using them.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let mut bytes = [0x20; 0x20];
+	/// bytes[0x15] = 0x23;
+	/// bytes[0x16] = 0x35;
+	/// let header = Header::from_bytes(&bytes).unwrap();
+	/// assert_eq!(Enhancement::from_header(&header, None), Enhancement::SA1);
+	///
+	/// bytes[0x16] = 0xF5;
+	/// let header = Header::from_bytes(&bytes).unwrap();
+	/// assert_eq!(Enhancement::from_header(&header, Some(0x02)), Enhancement::ST018);
+	/// ```
+	pub fn from_header(header: &Header, subtype: Option<u8>) -> Self {
+		if header.chipset & 0x0F < 0x03 {
+			return Enhancement::None;
+		}
+		let title = &header.title[..];
+		match (header.chipset >> 4, subtype) {
+			(0x0, _) => {
+				if title.starts_with(b"DUNGEON MASTER") {
+					Enhancement::DSP2
+				} else if title.starts_with(b"SD\xB6\xDE\xDD\xC0\xDE\xD1GX") {
+					Enhancement::DSP3
+				} else if title.starts_with(b"TOP GEAR 3000") {
+					Enhancement::DSP4
+				} else {
+					Enhancement::DSP1
+				}
+			}
+			(0x1, _) => Enhancement::SuperFX,
+			(0x2, _) => Enhancement::OBC1,
+			(0x3, _) => Enhancement::SA1,
+			(0x4, _) => Enhancement::SDD1,
+			(0x5, _) => Enhancement::SRTC,
+			(0xF, Some(0x00)) => Enhancement::SPC7110,
+			(0xF, Some(0x01)) if title.starts_with(b"2DAN MORITA SHOUGI") => Enhancement::ST011,
+			(0xF, Some(0x01)) => Enhancement::ST010,
+			(0xF, Some(0x02)) => Enhancement::ST018,
+			(0xF, Some(0x10)) => Enhancement::CX4,
+			(0xF, None) if header.map_mode & 0x0F == 0x0A => Enhancement::SPC7110,
+			(0xF, None) if header.chipset == 0xF3 => Enhancement::CX4,
+			_ => Enhancement::Other(header.chipset),
+		}
+	}
+
+	/// Returns `true` if the cartridge has an enhancement chip.
+	#[inline]
+	pub fn is_some(&self) -> bool {
+		*self != Enhancement::None
+	}
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Returns the chipset subtype at `$xFBF`, present if the chipset byte is `$Fx`.
+	pub fn chipset_subtype(&self) -> Option<u8> {
+		let rom_type = self.detect_rom_type().0;
+		self.header_for(rom_type)
+			.filter(|header| header.chipset >> 4 == 0xF)
+			.and_then(|_| self.rom.get(rom_type.header_offset() - 1).copied())
+	}
+
+	/// Returns the enhancement chip declared by the header.
+	pub fn enhancement(&self) -> Enhancement {
+		self.header()
+			.map(|header| Enhancement::from_header(&header, self.chipset_subtype()))
+			.unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn enhancement(title: &[u8], map_mode: u8, chipset: u8, subtype: Option<u8>) -> Enhancement {
+		let mut bytes = [0x20; 0x20];
+		bytes[..title.len()].copy_from_slice(title);
+		bytes[0x15] = map_mode;
+		bytes[0x16] = chipset;
+		Enhancement::from_header(&Header::from_bytes(&bytes).unwrap(), subtype)
+	}
+
+	#[test]
+	fn from_header() {
+		use Enhancement as E;
+		assert_eq!(enhancement(b"SUPER MARIOWORLD", 0x20, 0x02, None), E::None);
+		assert_eq!(enhancement(b"PILOTWINGS", 0x20, 0x05, None), E::DSP1);
+		assert_eq!(enhancement(b"DUNGEON MASTER", 0x20, 0x03, None), E::DSP2);
+		assert_eq!(
+			enhancement(b"SD\xB6\xDE\xDD\xC0\xDE\xD1GX", 0x20, 0x05, None),
+			E::DSP3
+		);
+		assert_eq!(enhancement(b"TOP GEAR 3000", 0x30, 0x03, None), E::DSP4);
+		assert_eq!(enhancement(b"STAR FOX", 0x20, 0x13, None), E::SuperFX);
+		assert_eq!(enhancement(b"METAL COMBAT", 0x30, 0x25, None), E::OBC1);
+		assert_eq!(enhancement(b"SUPER MARIO RPG", 0x23, 0x35, None), E::SA1);
+		assert_eq!(
+			enhancement(b"STREET FIGHTER ALPHA2", 0x32, 0x43, None),
+			E::SDD1
+		);
+		assert_eq!(
+			enhancement(b"DAIKAIJYUMONOGATARI2", 0x35, 0x55, None),
+			E::SRTC
+		);
+		assert_eq!(
+			enhancement(b"SATELLAVIEW", 0x20, 0xE5, None),
+			E::Other(0xE5)
+		);
+
+		// custom chips
+		assert_eq!(
+			enhancement(b"MOMOTETSU HAPPY", 0x3A, 0xF5, None),
+			E::SPC7110
+		);
+		assert_eq!(
+			enhancement(b"MOMOTETSU HAPPY", 0x3A, 0xF5, Some(0x00)),
+			E::SPC7110
+		);
+		assert_eq!(enhancement(b"MEGAMAN X2", 0x20, 0xF3, None), E::CX4);
+		assert_eq!(enhancement(b"MEGAMAN X2", 0x20, 0xF3, Some(0x10)), E::CX4);
+		assert_eq!(enhancement(b"F1 ROC II", 0x30, 0xF6, Some(0x01)), E::ST010);
+		assert_eq!(
+			enhancement(b"2DAN MORITA SHOUGI", 0x30, 0xF6, Some(0x01)),
+			E::ST011
+		);
+		assert_eq!(
+			enhancement(b"HAYAZASHI NIDAN", 0x30, 0xF5, Some(0x02)),
+			E::ST018
+		);
+		assert_eq!(enhancement(b"UNKNOWN", 0x20, 0xF5, None), E::Other(0xF5));
+		assert_eq!(
+			enhancement(b"UNKNOWN", 0x20, 0xF5, Some(0x7F)),
+			E::Other(0xF5)
+		);
+
+		assert!(!Enhancement::default().is_some());
+	}
+}
@@ -1,11 +1,14 @@
 use std::{error::Error, fmt, io};
 
-use super::TestFlags;
+use super::{TestFlags, TestReport};
 
 #[derive(Debug)]
 pub enum CartridgeError {
 	Io(std::io::Error),
 	NotProbableCartridge(NotProbableCartridgeError),
+	Build(BuildError),
+	#[cfg(feature = "zip")]
+	Archive(ArchiveError),
 }
 
 impl From<io::Error> for CartridgeError {
@@ -16,13 +19,28 @@ impl From<io::Error> for CartridgeError {
 /// Indicates the loaded ROM is probably not a SuperNES cartridge.
 #[derive(Debug)]
 pub struct NotProbableCartridgeError {
-	passed: TestFlags,
+	report: TestReport,
 	required: TestFlags,
 }
 
 impl NotProbableCartridgeError {
-	pub fn new(passed: TestFlags, required: TestFlags) -> Self {
-		Self { passed, required }
+	pub fn new(report: TestReport, required: TestFlags) -> Self {
+		Self { report, required }
+	}
+
+	/// Returns the tests the ROM passed.
+	pub fn passed(&self) -> TestFlags {
+		self.report.passed()
+	}
+
+	/// Returns the tests the ROM was allowed to pass.
+	pub fn required(&self) -> TestFlags {
+		self.required
+	}
+
+	/// Returns the outcomes of the ROM tests.
+	pub fn report(&self) -> &TestReport {
+		&self.report
 	}
 }
 
@@ -40,12 +58,113 @@ impl fmt::Display for NotProbableCartridgeError {
 
 impl Error for NotProbableCartridgeError {}
 
+/// Indicates the cartridge builder could not lay out the ROM.
+#[derive(Debug)]
+pub enum BuildError {
+	/// The code does not fit between `$00:8000` and the header.
+	CodeTooLarge { len: usize, max: usize },
+	/// Data overlaps the header or the interrupt vectors.
+	DataOverlapsHeader { offset: usize, len: usize },
+	/// Data overlaps the code at `$00:8000`.
+	DataOverlapsCode { offset: usize, len: usize },
+	/// Data overlaps data placed before it.
+	DataOverlapsData { offset: usize, len: usize },
+	/// The ROM exceeds the maximum size of its ROM type.
+	RomTooLarge(usize),
+}
+
+impl From<BuildError> for CartridgeError {
+	fn from(e: BuildError) -> Self {
+		Self::Build(e)
+	}
+}
+
+impl fmt::Display for BuildError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use BuildError::*;
+		match self {
+			CodeTooLarge { len, max } => {
+				write!(
+					f,
+					"code of {} bytes exceeds the maximum of {} bytes",
+					len, max
+				)
+			}
+			DataOverlapsHeader { offset, len } => write!(
+				f,
+				"data of {} bytes at ${:06X} overlaps the header",
+				len, offset
+			),
+			DataOverlapsCode { offset, len } => write!(
+				f,
+				"data of {} bytes at ${:06X} overlaps the code",
+				len, offset
+			),
+			DataOverlapsData { offset, len } => write!(
+				f,
+				"data of {} bytes at ${:06X} overlaps other data",
+				len, offset
+			),
+			RomTooLarge(size) => write!(f, "ROM size of {} bytes is too large", size),
+		}
+	}
+}
+
+impl Error for BuildError {}
+
+/// Indicates a ROM could not be extracted from an archive.
+#[cfg(feature = "zip")]
+#[derive(Debug)]
+pub enum ArchiveError {
+	Zip(zip::result::ZipError),
+	/// The archive has no entry with a ROM file extension.
+	NoRom,
+}
+
+#[cfg(feature = "zip")]
+impl From<ArchiveError> for CartridgeError {
+	fn from(e: ArchiveError) -> Self {
+		Self::Archive(e)
+	}
+}
+
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for CartridgeError {
+	fn from(e: zip::result::ZipError) -> Self {
+		Self::Archive(ArchiveError::Zip(e))
+	}
+}
+
+#[cfg(feature = "zip")]
+impl fmt::Display for ArchiveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use ArchiveError::*;
+		match self {
+			Zip(e) => e.fmt(f),
+			NoRom => write!(f, "the archive contains no ROM"),
+		}
+	}
+}
+
+#[cfg(feature = "zip")]
+impl Error for ArchiveError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ArchiveError::Zip(e) => Some(e),
+			ArchiveError::NoRom => None,
+		}
+	}
+}
+
 impl fmt::Display for CartridgeError {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		use CartridgeError::*;
 		match self {
 			Io(e) => e.fmt(f),
 			NotProbableCartridge(e) => e.fmt(f),
+			Build(e) => e.fmt(f),
+			#[cfg(feature = "zip")]
+			Archive(e) => e.fmt(f),
 		}
 	}
 }
@@ -56,6 +175,9 @@ impl std::error::Error for CartridgeError {
 		match self {
 			Io(e) => e.source(),
 			NotProbableCartridge(e) => e.source(),
+			Build(e) => e.source(),
+			#[cfg(feature = "zip")]
+			Archive(e) => e.source(),
 		}
 	}
 }
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "hashes")]
+use sha2::Digest;
+
+#[cfg(feature = "std")]
+use super::Cartridge;
+
+const fn crc32_table() -> [u32; 256] {
+	let mut table = [0; 256];
+	let mut i = 0;
+	while i < 256 {
+		let mut crc = i as u32;
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 1 != 0 {
+				0xEDB88320 ^ crc >> 1
+			} else {
+				crc >> 1
+			};
+			bit += 1;
+		}
+		table[i] = crc;
+		i += 1;
+	}
+	table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// Incremental CRC-32 as used by zip archives and ROM databases.
+/// ```
+/// # use sneslib::cartridge::*;
+/// let mut crc = Crc32::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(crc.finish(), 0xCBF43926);
+/// assert_eq!(crc32(b"123456789"), 0xCBF43926);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crc32(u32);
+
+impl Crc32 {
+	#[inline]
+	pub fn new() -> Self {
+		Self(0xFFFFFFFF)
+	}
+
+	pub fn update(&mut self, bytes: &[u8]) {
+		self.0 = bytes.iter().fold(self.0, |crc, &b| {
+			CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ crc >> 8
+		});
+	}
+
+	#[inline]
+	pub fn finish(&self) -> u32 {
+		!self.0
+	}
+}
+
+impl Default for Crc32 {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Computes the CRC-32 of the bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+	let mut crc = Crc32::new();
+	crc.update(bytes);
+	crc.finish()
+}
+
+/// Hashes of a ROM image, the digests other than CRC-32 requiring the `hashes` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Hashes {
+	pub crc32: u32,
+	#[cfg(feature = "hashes")]
+	#[serde(with = "hex")]
+	pub md5: [u8; 16],
+	#[cfg(feature = "hashes")]
+	#[serde(with = "hex")]
+	pub sha1: [u8; 20],
+	#[cfg(feature = "hashes")]
+	#[serde(with = "hex")]
+	pub sha256: [u8; 32],
+}
+
+/// Serializes digests as lowercase hex digits to human readable formats, and as bytes otherwise.
+#[cfg(feature = "hashes")]
+mod hex {
+	use core::fmt;
+	use serde::de::{self, Deserializer, Unexpected, Visitor};
+	use serde::{Deserialize, Serialize, Serializer};
+
+	struct Hex<'a>(&'a [u8]);
+
+	impl fmt::Display for Hex<'_> {
+		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+		}
+	}
+
+	struct HexVisitor<const N: usize>;
+
+	impl<const N: usize> Visitor<'_> for HexVisitor<N> {
+		type Value = [u8; N];
+
+		fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			write!(f, "{} hex digits", 2 * N)
+		}
+
+		fn visit_str<E: de::Error>(self, digits: &str) -> Result<Self::Value, E> {
+			if digits.len() != 2 * N {
+				return Err(E::invalid_length(digits.len(), &self));
+			}
+			let nibble = |c: u8| (c as char).to_digit(16);
+			let mut digest = [0; N];
+			for (b, pair) in digest.iter_mut().zip(digits.as_bytes().chunks_exact(2)) {
+				*b = match (nibble(pair[0]), nibble(pair[1])) {
+					(Some(high), Some(low)) => (high << 4 | low) as u8,
+					_ => return Err(E::invalid_value(Unexpected::Str(digits), &self)),
+				};
+			}
+			Ok(digest)
+		}
+	}
+
+	pub(super) fn serialize<S, const N: usize>(
+		digest: &[u8; N],
+		serializer: S,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+		[u8; N]: Serialize,
+	{
+		if serializer.is_human_readable() {
+			serializer.collect_str(&Hex(digest))
+		} else {
+			digest.serialize(serializer)
+		}
+	}
+
+	pub(super) fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+	where
+		D: Deserializer<'de>,
+		[u8; N]: Deserialize<'de>,
+	{
+		if deserializer.is_human_readable() {
+			deserializer.deserialize_str(HexVisitor)
+		} else {
+			<[u8; N]>::deserialize(deserializer)
+		}
+	}
+}
+
+impl Hashes {
+	/// Hashes the concatenation of the parts.
+	pub fn from_parts(parts: &[&[u8]]) -> Self {
+		let mut crc = Crc32::new();
+		#[cfg(feature = "hashes")]
+		let (mut md5, mut sha1, mut sha256) = (md5::Md5::new(), sha1::Sha1::new(), sha2::Sha256::new());
+		for part in parts.iter() {
+			crc.update(part);
+			#[cfg(feature = "hashes")]
+			{
+				md5.update(part);
+				sha1.update(part);
+				sha256.update(part);
+			}
+		}
+		Self {
+			crc32: crc.finish(),
+			#[cfg(feature = "hashes")]
+			md5: md5.finalize().into(),
+			#[cfg(feature = "hashes")]
+			sha1: sha1.finalize().into(),
+			#[cfg(feature = "hashes")]
+			sha256: sha256.finalize().into(),
+		}
+	}
+
+	/// Hashes the bytes.
+	#[inline]
+	pub fn from_bytes(bytes: &[u8]) -> Self {
+		Self::from_parts(&[bytes])
+	}
+}
+
+/// Hashes of a cartridge with and without its copier header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RomHashes {
+	/// Hashes of the ROM without a copier header, as keyed by No-Intro.
+	pub rom: Hashes,
+	/// Hashes of the ROM prefixed with the copier header stripped on loading, if any.
+	pub headered: Option<Hashes>,
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Computes the hashes of the ROM, with and without the copier header.
+	pub fn hashes(&self) -> RomHashes {
+		RomHashes {
+			rom: Hashes::from_bytes(&self.rom),
+			headered: self
+				.copier_header()
+				.map(|header| Hashes::from_parts(&[header, &self.rom])),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn crc32() {
+		assert_eq!(super::crc32(b""), 0);
+		assert_eq!(
+			super::crc32(b"The quick brown fox jumps over the lazy dog"),
+			0x414FA339
+		);
+		assert_eq!(
+			Hashes::from_parts(&[b"The quick brown ", b"fox jumps over the lazy dog"]).crc32,
+			0x414FA339
+		);
+	}
+
+	#[cfg(feature = "hashes")]
+	#[test]
+	fn digests() {
+		let hex = |bytes: &[u8]| {
+			bytes
+				.iter()
+				.map(|b| format!("{:02x}", b))
+				.collect::<String>()
+		};
+		let hashes = Hashes::from_bytes(b"abc");
+		assert_eq!(hex(&hashes.md5), "900150983cd24fb0d6963f7d28e17f72");
+		assert_eq!(
+			hex(&hashes.sha1),
+			"a9993e364706816aba3e25717850c26c9cd0d89d"
+		);
+		assert_eq!(
+			hex(&hashes.sha256),
+			"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+		);
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn hashes() {
+		use crate::cartridge::{test::make_rom, ROMType, TestFlags, COPIER_HEADER_SIZE};
+
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let hashes = cartridge.hashes();
+		assert_eq!(hashes.rom, Hashes::from_bytes(&rom));
+		assert_eq!(hashes.headered, None);
+
+		let headered = cartridge.to_bytes(true);
+		let cartridge = Cartridge::new(&headered, TestFlags::default()).unwrap();
+		let hashes = cartridge.hashes();
+		assert_eq!(hashes.rom, Hashes::from_bytes(&rom));
+		assert_eq!(hashes.headered, Some(Hashes::from_bytes(&headered)));
+		assert_eq!(headered.len(), COPIER_HEADER_SIZE + rom.len());
+	}
+}
@@ -0,0 +1,292 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{MapMode, ROMType};
+
+/// Size of the standard header at `$xFC0-$xFDF`.
+pub const HEADER_SIZE: usize = 0x20;
+
+/// Size of the extended header at `$xFB0-$xFBF`.
+pub const EXTENDED_HEADER_SIZE: usize = 0x10;
+
+/// Largest SRAM size the SRAM windows of the mappings can hold, 256KB.
+pub const MAX_SRAM_SIZE: usize = 0x40000;
+
+/// Cartridge header at `$xFC0-$xFDF`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Header {
+	/// Internal name at `$xFC0`, 21 bytes padded with spaces.
+	///
+	/// Serialized as text to human readable formats, see [`title_chars`](#method.title_chars).
+	#[serde(with = "title")]
+	pub title: [u8; 21],
+	/// Map mode and ROM speed at `$xFD5`.
+	pub map_mode: u8,
+	/// Chipset at `$xFD6`.
+	pub chipset: u8,
+	/// ROM size at `$xFD7`, `log2` of the size in KiB.
+	pub rom_size: u8,
+	/// SRAM size at `$xFD8`, `log2` of the size in KiB or zero without SRAM.
+	pub sram_size: u8,
+	/// Country code at `$xFD9`.
+	pub country: u8,
+	/// Developer ID at `$xFDA`. `$33` indicates an extended header.
+	pub developer_id: u8,
+	/// Version number at `$xFDB`.
+	pub version: u8,
+	/// Checksum complement at `$xFDC-$xFDD`.
+	pub checksum_complement: u16,
+	/// Checksum at `$xFDE-$xFDF`.
+	pub checksum: u16,
+}
+
+/// Extended cartridge header at `$xFB0-$xFBF`, present if the developer ID is `$33`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExtendedHeader {
+	/// Maker code at `$xFB0`, two ASCII characters.
+	pub maker_code: [u8; 2],
+	/// Game code at `$xFB2`, four ASCII characters.
+	pub game_code: [u8; 4],
+	/// Expansion flash size at `$xFBC`, `log2` of the size in KiB or zero without flash.
+	pub expansion_flash_size: u8,
+	/// Expansion RAM size at `$xFBD`, `log2` of the size in KiB or zero without expansion RAM.
+	pub expansion_ram_size: u8,
+	/// Special version at `$xFBE`.
+	pub special_version: u8,
+	/// Cartridge subtype at `$xFBF`, identifying custom chips.
+	pub subtype: u8,
+}
+
+impl ROMType {
+	/// Returns the ROM offset of the header at `$xFC0`.
+	#[inline]
+	pub const fn header_offset(&self) -> usize {
+		match self {
+			ROMType::LoROM => 0x7FC0,
+			ROMType::HiROM => 0xFFC0,
+			ROMType::ExHiROM => 0x40FFC0,
+		}
+	}
+}
+
+fn kib_to_bytes(log2: u8) -> Option<usize> {
+	if log2 < 16 {
+		Some(1024 << log2)
+	} else {
+		None
+	}
+}
+
+fn optional_kib_to_bytes(log2: u8) -> Option<usize> {
+	match log2 {
+		0 => Some(0),
+		n => kib_to_bytes(n),
+	}
+}
+
+/// Converts the size of a RAM mapped through the SRAM windows, rejecting what they cannot hold.
+fn ram_kib_to_bytes(log2: u8) -> Option<usize> {
+	optional_kib_to_bytes(log2).filter(|&size| size <= MAX_SRAM_SIZE)
+}
+
+impl Header {
+	/// Parses a header from the bytes starting at `$xFC0`.
+	///
+	/// Returns `None` if `bytes` is shorter than the header.
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let bytes = bytes.get(..HEADER_SIZE)?;
+		let read_u16 = |offset: usize| (bytes[offset + 1] as u16) << 8 | bytes[offset] as u16;
+
+		let mut title = [0; 21];
+		title.copy_from_slice(&bytes[..21]);
+		Some(Self {
+			title,
+			map_mode: bytes[0x15],
+			chipset: bytes[0x16],
+			rom_size: bytes[0x17],
+			sram_size: bytes[0x18],
+			country: bytes[0x19],
+			developer_id: bytes[0x1A],
+			version: bytes[0x1B],
+			checksum_complement: read_u16(0x1C),
+			checksum: read_u16(0x1E),
+		})
+	}
+
+	/// Returns `true` if the cartridge runs at FastROM speed.
+	#[inline]
+	pub fn is_fast_rom(&self) -> bool {
+		self.map_mode & 0x10 != 0
+	}
+
+	/// Returns the typed map mode and ROM speed.
+	#[inline]
+	pub fn mode(&self) -> MapMode {
+		self.map_mode.into()
+	}
+
+	/// Returns the ROM size in bytes as declared by the header,
+	/// or `None` if the size byte is implausibly large.
+	#[inline]
+	pub fn rom_size_bytes(&self) -> Option<usize> {
+		kib_to_bytes(self.rom_size)
+	}
+
+	/// Returns the SRAM size in bytes as declared by the header,
+	/// or `None` if larger than the 256KB the SRAM windows can map.
+	#[inline]
+	pub fn sram_size_bytes(&self) -> Option<usize> {
+		ram_kib_to_bytes(self.sram_size)
+	}
+
+	/// Returns `true` if the checksum and its complement are consistent.
+	#[inline]
+	pub fn is_checksum_consistent(&self) -> bool {
+		self.checksum ^ self.checksum_complement == 0xFFFF
+	}
+
+	/// Decodes the title as JIS X 0201, mapping half-width katakana to their Unicode forms
+	/// and `$5C` and `$7E` to `¥` and `‾`.
+	///
+	/// Bytes outside of printable JIS X 0201 decode as `?`.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let mut bytes = [0x20; 0x20];
+	/// bytes[..9].copy_from_slice(b"SD\xB6\xDE\xDD\xC0\xDE\\~");
+	/// let header = Header::from_bytes(&bytes).unwrap();
+	/// assert!(header.title_chars().eq("SDｶﾞﾝﾀﾞ¥‾            ".chars()));
+	/// ```
+	pub fn title_chars(&self) -> impl Iterator<Item = char> + '_ {
+		self.title.iter().map(|&b| title::decode(b))
+	}
+
+	/// Returns `true` if the developer ID indicates an extended header at `$xFB0-$xFBF`.
+	#[inline]
+	pub fn has_extended_header(&self) -> bool {
+		self.developer_id == 0x33
+	}
+}
+
+/// Serializes titles as JIS X 0201 text to human readable formats, and as bytes otherwise.
+mod title {
+	use super::*;
+	use serde::de::{self, Deserializer, Unexpected, Visitor};
+	use serde::Serializer;
+
+	pub(super) fn decode(b: u8) -> char {
+		match b {
+			0x5C => '¥',
+			0x7E => '‾',
+			0x20..=0x7E => b as char,
+			0xA1..=0xDF => core::char::from_u32(0xFF61 + (b - 0xA1) as u32).unwrap(),
+			_ => '?',
+		}
+	}
+
+	fn encode(c: char) -> Option<u8> {
+		match c {
+			'¥' => Some(0x5C),
+			'‾' => Some(0x7E),
+			' '..='~' => Some(c as u8),
+			'\u{FF61}'..='\u{FF9F}' => Some((c as u32 - 0xFF61) as u8 + 0xA1),
+			_ => None,
+		}
+	}
+
+	struct Text<'a>(&'a [u8; 21]);
+
+	impl fmt::Display for Text<'_> {
+		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			self.0
+				.iter()
+				.try_for_each(|&b| fmt::Write::write_char(f, decode(b)))
+		}
+	}
+
+	struct TextVisitor;
+
+	impl Visitor<'_> for TextVisitor {
+		type Value = [u8; 21];
+
+		fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			write!(f, "a title of at most 21 JIS X 0201 characters")
+		}
+
+		fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+			let mut title = [b' '; 21];
+			let mut chars = text.chars();
+			for (b, c) in title.iter_mut().zip(chars.by_ref()) {
+				*b = encode(c).ok_or_else(|| E::invalid_value(Unexpected::Str(text), &self))?;
+			}
+			if chars.next().is_some() {
+				return Err(E::invalid_length(text.chars().count(), &self));
+			}
+			Ok(title)
+		}
+	}
+
+	pub(super) fn serialize<S: Serializer>(
+		title: &[u8; 21],
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		if serializer.is_human_readable() {
+			serializer.collect_str(&Text(title))
+		} else {
+			title.serialize(serializer)
+		}
+	}
+
+	pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<[u8; 21], D::Error> {
+		if deserializer.is_human_readable() {
+			deserializer.deserialize_str(TextVisitor)
+		} else {
+			<[u8; 21]>::deserialize(deserializer)
+		}
+	}
+}
+
+impl ExtendedHeader {
+	/// Parses an extended header from the bytes starting at `$xFB0`.
+	///
+	/// Returns `None` if `bytes` is shorter than the extended header.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let bytes = *b"01ABCJ\0\0\0\0\0\0\0\x03\0\0";
+	/// let header = ExtendedHeader::from_bytes(&bytes).unwrap();
+	/// assert_eq!(&header.maker_code, b"01");
+	/// assert_eq!(&header.game_code, b"ABCJ");
+	/// assert_eq!(header.expansion_ram_size_bytes(), Some(0x2000));
+	/// ```
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let bytes = bytes.get(..EXTENDED_HEADER_SIZE)?;
+		let mut maker_code = [0; 2];
+		maker_code.copy_from_slice(&bytes[..2]);
+		let mut game_code = [0; 4];
+		game_code.copy_from_slice(&bytes[2..6]);
+		Some(Self {
+			maker_code,
+			game_code,
+			expansion_flash_size: bytes[0xC],
+			expansion_ram_size: bytes[0xD],
+			special_version: bytes[0xE],
+			subtype: bytes[0xF],
+		})
+	}
+
+	/// Returns the expansion flash size in bytes,
+	/// or `None` if the size byte is implausibly large.
+	#[inline]
+	pub fn expansion_flash_size_bytes(&self) -> Option<usize> {
+		optional_kib_to_bytes(self.expansion_flash_size)
+	}
+
+	/// Returns the expansion RAM size in bytes,
+	/// or `None` if larger than the 256KB the SRAM windows can map.
+	#[inline]
+	pub fn expansion_ram_size_bytes(&self) -> Option<usize> {
+		ram_kib_to_bytes(self.expansion_ram_size)
+	}
+}
@@ -0,0 +1,97 @@
+use super::{Cartridge, Header, ROMType};
+
+/// Returns `true` if the ROM looks like a HiROM dump in the SWC/FIG interleaved format.
+///
+/// Interleaved dumps store the upper half of each 64KB bank before the lower halves,
+/// so the HiROM header ends up at the LoROM header location.
+pub fn is_interleaved(rom: &[u8]) -> bool {
+	let is_hirom = |rom_type: ROMType| {
+		rom.get(rom_type.header_offset()..)
+			.and_then(Header::from_bytes)
+			.is_some_and(|header| {
+				header.is_checksum_consistent() && matches!(header.map_mode & 0xEF, 0x21 | 0x25)
+			})
+	};
+	rom.len() >= 0x10000
+		&& rom.len() % 0x10000 == 0
+		&& is_hirom(ROMType::LoROM)
+		&& !is_hirom(ROMType::HiROM)
+}
+
+/// Converts a SWC/FIG interleaved dump to a plain HiROM image.
+///
+/// The `i`-th 32KB block of the dump holds the upper half of bank `i`,
+/// and the block `i` past the middle holds the lower half.
+/// ROMs which are not a multiple of 64KB are returned unchanged.
+pub fn deinterleave(rom: &[u8]) -> Vec<u8> {
+	if rom.len() % 0x10000 != 0 {
+		return rom.to_vec();
+	}
+	let (upper, lower) = rom.split_at(rom.len() / 2);
+	lower
+		.chunks(0x8000)
+		.zip(upper.chunks(0x8000))
+		.flat_map(|(lower, upper)| lower.iter().chain(upper))
+		.copied()
+		.collect()
+}
+
+/// Converts a plain HiROM image to the SWC/FIG interleaved format, reversing [`deinterleave`].
+pub fn interleave(rom: &[u8]) -> Vec<u8> {
+	if rom.len() % 0x10000 != 0 {
+		return rom.to_vec();
+	}
+	let banks = rom.chunks(0x10000);
+	let upper = banks.clone().flat_map(|bank| &bank[0x8000..]);
+	let lower = banks.flat_map(|bank| &bank[..0x8000]);
+	upper.chain(lower).copied().collect()
+}
+
+impl Cartridge {
+	/// Returns `true` if the ROM looks like an interleaved dump, see [`is_interleaved`].
+	#[inline]
+	pub fn is_interleaved(&self) -> bool {
+		is_interleaved(&self.rom)
+	}
+
+	/// Converts an interleaved dump to a plain HiROM image.
+	///
+	/// Returns `false` and leaves the ROM untouched if it does not look interleaved.
+	pub fn deinterleave(&mut self) -> bool {
+		if self.is_interleaved() {
+			self.rom = deinterleave(&self.rom);
+			self.rom_changed();
+			true
+		} else {
+			false
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::TestFlags;
+
+	#[test]
+	fn deinterleave() {
+		let rom = make_rom(ROMType::HiROM, 0x100000);
+		let interleaved = interleave(&rom);
+		assert_eq!(&interleaved[..0x8000], &rom[0x8000..0x10000]);
+		assert_eq!(&interleaved[0x80000..0x88000], &rom[..0x8000]);
+		assert_eq!(super::deinterleave(&interleaved), rom);
+		assert!(is_interleaved(&interleaved));
+		assert!(!is_interleaved(&rom));
+		assert!(!is_interleaved(&make_rom(ROMType::LoROM, 0x100000)));
+
+		let passed = Cartridge::run_tests(&interleaved).passed();
+		assert!(passed.contains(TestFlags::SIZE | TestFlags::HEADER_HI));
+		let mut cartridge = Cartridge::new(&interleaved, TestFlags::default()).unwrap();
+		assert!(cartridge.is_interleaved());
+		assert!(cartridge.deinterleave());
+		assert!(!cartridge.deinterleave());
+		assert_eq!(cartridge.rom, rom);
+		assert_eq!(cartridge.detect_rom_type().0, ROMType::HiROM);
+	}
+}
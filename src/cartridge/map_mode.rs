@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+use super::ROMType;
+
+/// ROM access speed from bit 4 of the map mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RomSpeed {
+	/// 200ns ROM, accessed in 8 master cycles.
+	Slow,
+	/// 120ns ROM, accessed in 6 master cycles in banks `$80-$FF` once enabled through `MEMSEL`.
+	Fast,
+}
+
+/// Memory mapping from bits 0-3 of the map mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mapping {
+	/// Mode `$20`.
+	LoROM,
+	/// Mode `$21`.
+	HiROM,
+	/// Mode `$22`, LoROM with the S-DD1.
+	SDD1,
+	/// Mode `$23`, LoROM with the SA-1.
+	SA1,
+	/// Mode `$25`.
+	ExHiROM,
+	/// Mode `$2A`, HiROM with the SPC7110.
+	SPC7110,
+	/// Any other map mode, holding the byte at `$xFD5`.
+	Other(u8),
+}
+
+/// Map mode and ROM speed at `$xFD5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MapMode {
+	pub speed: RomSpeed,
+	pub mapping: Mapping,
+}
+
+impl RomSpeed {
+	/// Returns the master cycles of a ROM access at this speed.
+	#[inline]
+	pub const fn master_cycles(&self) -> u32 {
+		match self {
+			RomSpeed::Slow => 8,
+			RomSpeed::Fast => 6,
+		}
+	}
+}
+
+impl Mapping {
+	/// Returns the ROM type whose address mapping this mapping follows.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// assert_eq!(Mapping::SA1.rom_type(), Some(ROMType::LoROM));
+	/// assert_eq!(Mapping::SPC7110.rom_type(), Some(ROMType::HiROM));
+	/// assert_eq!(Mapping::Other(0x00).rom_type(), None);
+	/// ```
+	pub fn rom_type(&self) -> Option<ROMType> {
+		match self {
+			Mapping::LoROM | Mapping::SDD1 | Mapping::SA1 => Some(ROMType::LoROM),
+			Mapping::HiROM | Mapping::SPC7110 => Some(ROMType::HiROM),
+			Mapping::ExHiROM => Some(ROMType::ExHiROM),
+			Mapping::Other(_) => None,
+		}
+	}
+}
+
+impl From<u8> for MapMode {
+	fn from(map_mode: u8) -> Self {
+		let speed = if map_mode & 0x10 != 0 {
+			RomSpeed::Fast
+		} else {
+			RomSpeed::Slow
+		};
+		let mapping = match map_mode & 0xEF {
+			0x20 => Mapping::LoROM,
+			0x21 => Mapping::HiROM,
+			0x22 => Mapping::SDD1,
+			0x23 => Mapping::SA1,
+			0x25 => Mapping::ExHiROM,
+			0x2A => Mapping::SPC7110,
+			_ => Mapping::Other(map_mode),
+		};
+		Self { speed, mapping }
+	}
+}
+
+impl From<MapMode> for u8 {
+	/// Encodes the map mode, `Mapping::Other` keeping its mapping bits.
+	fn from(map_mode: MapMode) -> u8 {
+		let mapping = match map_mode.mapping {
+			Mapping::LoROM => 0x20,
+			Mapping::HiROM => 0x21,
+			Mapping::SDD1 => 0x22,
+			Mapping::SA1 => 0x23,
+			Mapping::ExHiROM => 0x25,
+			Mapping::SPC7110 => 0x2A,
+			Mapping::Other(b) => b & 0xEF,
+		};
+		match map_mode.speed {
+			RomSpeed::Slow => mapping,
+			RomSpeed::Fast => mapping | 0x10,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn map_mode() {
+		let mode = MapMode::from(0x31);
+		assert_eq!(mode.speed, RomSpeed::Fast);
+		assert_eq!(mode.mapping, Mapping::HiROM);
+		assert_eq!(mode.speed.master_cycles(), 6);
+		assert_eq!(MapMode::from(0x23).mapping, Mapping::SA1);
+		assert_eq!(MapMode::from(0x3A).mapping, Mapping::SPC7110);
+		assert_eq!(
+			MapMode::from(0x35).mapping.rom_type(),
+			Some(ROMType::ExHiROM)
+		);
+		assert_eq!(MapMode::from(0x00).mapping, Mapping::Other(0x00));
+		assert_eq!(MapMode::from(0x00).speed.master_cycles(), 8);
+
+		for b in 0..=0xFF {
+			let mode = MapMode::from(b);
+			if mode.mapping != Mapping::Other(b) {
+				assert_eq!(u8::from(mode), b);
+			}
+		}
+	}
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+
+use super::{
+	Cartridge, Enhancement, ExtendedHeader, Header, ROMType, Region, RomHashes, Score, TestReport,
+};
+
+/// Parsed metadata of a cartridge, serializable to catalog a ROM collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Metadata {
+	/// ROM size in bytes without a copier header.
+	pub size: usize,
+	pub rom_type: ROMType,
+	pub score: Score,
+	pub header: Option<Header>,
+	pub extended_header: Option<ExtendedHeader>,
+	/// Decoded title, see [`Cartridge::title`].
+	pub title: Option<String>,
+	pub enhancement: Enhancement,
+	pub region: Option<Region>,
+	pub report: TestReport,
+	pub hashes: RomHashes,
+}
+
+impl Cartridge {
+	/// Collects the parsed metadata of the cartridge.
+	pub fn metadata(&self) -> Metadata {
+		let (rom_type, score) = self.detect_rom_type();
+		let header = self.header();
+		Metadata {
+			size: self.rom.len(),
+			rom_type,
+			score,
+			header,
+			extended_header: self.extended_header(),
+			title: self.title(),
+			enhancement: self.enhancement(),
+			region: header.map(|header| header.region()),
+			report: self.report.clone(),
+			hashes: self.hashes(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::{crc32, Hashes, TestFlags};
+
+	#[test]
+	fn serde() {
+		let rom = make_rom(ROMType::HiROM, 0x80000);
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let metadata = cartridge.metadata();
+		assert_eq!(metadata.size, 0x80000);
+		assert_eq!(metadata.rom_type, ROMType::HiROM);
+		assert_eq!(metadata.title.as_deref(), Some("SNESLIB TEST"));
+		assert_eq!(metadata.region, Some(Region::NorthAmerica));
+
+		let encoded = bincode::serialize(&metadata).unwrap();
+		let mut expected = bincode::serialize(&0x80000usize).unwrap();
+		expected.extend(bincode::serialize(&ROMType::HiROM).unwrap());
+		assert!(encoded.starts_with(&expected));
+
+		let header = metadata.header.unwrap();
+		let decoded: Header = bincode::deserialize(&bincode::serialize(&header).unwrap()).unwrap();
+		assert_eq!(decoded, header);
+		let hashes = Hashes::from_bytes(&rom);
+		let decoded: Hashes = bincode::deserialize(&bincode::serialize(&hashes).unwrap()).unwrap();
+		assert_eq!(decoded, hashes);
+
+		let flags = TestFlags::SIZE | TestFlags::HEADER_HI;
+		let encoded = bincode::serialize(&flags).unwrap();
+		assert_eq!(encoded, bincode::serialize(&flags.bits()).unwrap());
+		let decoded: TestFlags = bincode::deserialize(&encoded).unwrap();
+		assert_eq!(decoded, flags);
+		let encoded = bincode::serialize(&u32::MAX).unwrap();
+		let decoded: TestFlags = bincode::deserialize(&encoded).unwrap();
+		assert_eq!(decoded, TestFlags::all());
+	}
+
+	#[test]
+	fn json() {
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let json = serde_json::to_value(cartridge.metadata()).unwrap();
+		assert_eq!(json["header"]["title"], "SNESLIB TEST         ");
+		assert_eq!(json["hashes"]["rom"]["crc32"], crc32(&rom));
+		let header: Header = serde_json::from_value(json["header"].clone()).unwrap();
+		assert_eq!(header, cartridge.header().unwrap());
+
+		let mut header = header;
+		header.title = *b"SD\xB6\xDE\xDD\xC0\xDE\\~            ";
+		let json = serde_json::to_string(&header).unwrap();
+		assert!(json.contains(r#""title":"SDｶﾞﾝﾀﾞ¥‾            ""#));
+		assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+		for title in ["漢字", "A TITLE LONGER THAN 21 CHARACTERS"].iter() {
+			let json = json.replace("SDｶﾞﾝﾀﾞ¥‾            ", title);
+			assert!(serde_json::from_str::<Header>(&json).is_err());
+		}
+
+		#[cfg(feature = "hashes")]
+		{
+			let hashes = Hashes::from_bytes(b"abc");
+			let json = serde_json::to_value(hashes).unwrap();
+			assert_eq!(json["md5"], "900150983cd24fb0d6963f7d28e17f72");
+			assert_eq!(json["sha1"], "a9993e364706816aba3e25717850c26c9cd0d89d");
+			assert_eq!(
+				json["sha256"],
+				"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+			);
+			assert_eq!(serde_json::from_value::<Hashes>(json).unwrap(), hashes);
+			let json =
+				r#"{"crc32":0,"md5":"900150983cd24fb0d6963f7d28e17fzz","sha1":"","sha256":""}"#;
+			assert!(serde_json::from_str::<Hashes>(json).is_err());
+		}
+	}
+}
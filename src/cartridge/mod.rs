@@ -1,12 +1,80 @@
+#[cfg(feature = "std")]
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "zip", feature = "gzip"))]
+pub mod archive;
+#[cfg(feature = "std")]
+pub mod builder;
+pub mod checksum;
+#[cfg(feature = "std")]
+pub mod database;
+#[cfg(feature = "std")]
+pub mod detect;
+pub mod enhancement;
+#[cfg(feature = "std")]
 pub mod error;
+pub mod hash;
+pub mod header;
+#[cfg(feature = "std")]
+pub mod interleave;
+pub mod map_mode;
+#[cfg(feature = "std")]
+pub mod metadata;
+pub mod region;
+#[cfg(feature = "std")]
+pub mod report;
+pub mod sa1;
+pub mod satellaview;
+pub mod vectors;
+
+#[cfg(any(feature = "zip", feature = "gzip"))]
+pub use archive::ROM_EXTENSIONS;
+#[cfg(feature = "std")]
+pub use builder::CartridgeBuilder;
+pub use checksum::compute_checksum;
+#[cfg(feature = "std")]
+pub use database::{GameDatabase, GameKey, GameQuirks, MemoryDatabase};
+#[cfg(feature = "std")]
+pub use detect::Score;
+pub use enhancement::Enhancement;
+pub use hash::{crc32, Crc32, Hashes, RomHashes};
+pub use header::{ExtendedHeader, Header};
+#[cfg(feature = "std")]
+pub use interleave::{deinterleave, interleave, is_interleaved};
+pub use map_mode::{MapMode, Mapping, RomSpeed};
+#[cfg(feature = "std")]
+pub use metadata::Metadata;
+pub use region::{Region, RegionConversion, TimingCaveat, VideoStandard};
+#[cfg(feature = "std")]
+pub use report::{TestDetail, TestReport, TestResult};
+pub use sa1::SA1Config;
+pub use satellaview::SatellaviewHeader;
+pub use vectors::{VectorTable, Vectors};
 
+#[cfg(feature = "std")]
 use error::*;
+#[cfg(feature = "std")]
 pub type CartridgeResult = Result<Cartridge, CartridgeError>;
 
+/// Size of the header prepended by copier devices such as the Super Magicom.
+pub const COPIER_HEADER_SIZE: usize = 0x200;
+
+/// Returns `true` if the ROM is prefixed with a copier header.
+///
+/// Dumps are a multiple of `0x8000` bytes, so a remainder of exactly `0x200` indicates a copier header.
+#[inline]
+pub fn has_copier_header(rom: &[u8]) -> bool {
+	rom.len() % 0x8000 == COPIER_HEADER_SIZE
+}
+
 bitflags::bitflags! {
 	/// Flags for a ROM test on loading.
+	///
+	/// Serialized as a plain `u32`; unknown bits are dropped on deserialization.
+	#[derive(Serialize, Deserialize)]
+	#[serde(from = "u32", into = "u32")]
 	pub struct TestFlags: u32 {
 		/// Tests if the ROM size is a multiple of `0x8000`
 		const SIZE = 1 << 0;
@@ -26,6 +94,63 @@ bitflags::bitflags! {
 		const COUNTRY_LO = 1 << 7;
 		/// Tests the country code at the offset `FFD9h`
 		const COUNTRY_HI = 1 << 8;
+		/// Tests the ROM checksum with values at the offset `40FFDCh-40FFDFh`
+		const CHECKSUM_EXHI = 1 << 9;
+		/// Tests the ROM Speed and Map Mode at the offset `40FFD5h`
+		const ROM_SPEED_AND_MAP_EXHI = 1 << 10;
+		/// Tests the chipset at the offset `40FFD6h`
+		const CHIPSET_EXHI = 1 << 11;
+		/// Tests the country code at the offset `40FFD9h`
+		const COUNTRY_EXHI = 1 << 12;
+
+		/// All tests of the header at `7FC0h`
+		const HEADER_LO = Self::CHECKSUM_LO.bits
+			| Self::ROM_SPEED_AND_MAP_LO.bits
+			| Self::CHIPSET_LO.bits
+			| Self::COUNTRY_LO.bits;
+		/// All tests of the header at `FFC0h`
+		const HEADER_HI = Self::CHECKSUM_HI.bits
+			| Self::ROM_SPEED_AND_MAP_HI.bits
+			| Self::CHIPSET_HI.bits
+			| Self::COUNTRY_HI.bits;
+		/// All tests of the header at `40FFC0h`
+		const HEADER_EXHI = Self::CHECKSUM_EXHI.bits
+			| Self::ROM_SPEED_AND_MAP_EXHI.bits
+			| Self::CHIPSET_EXHI.bits
+			| Self::COUNTRY_EXHI.bits;
+	}
+}
+
+impl From<u32> for TestFlags {
+	#[inline]
+	fn from(bits: u32) -> Self {
+		Self::from_bits_truncate(bits)
+	}
+}
+
+impl From<TestFlags> for u32 {
+	#[inline]
+	fn from(flags: TestFlags) -> Self {
+		flags.bits()
+	}
+}
+
+impl TestFlags {
+	/// Returns the ROM type whose header location passed the most tests, preferring LoROM on ties.
+	pub fn probable_rom_type(&self) -> ROMType {
+		let count = |header: TestFlags| (*self & header).bits().count_ones();
+		let (lo, hi, exhi) = (
+			count(TestFlags::HEADER_LO),
+			count(TestFlags::HEADER_HI),
+			count(TestFlags::HEADER_EXHI),
+		);
+		if exhi > hi && exhi > lo {
+			ROMType::ExHiROM
+		} else if hi > lo {
+			ROMType::HiROM
+		} else {
+			ROMType::LoROM
+		}
 	}
 }
 
@@ -35,104 +160,380 @@ impl Default for TestFlags {
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ROMType {
 	LoROM,
 	HiROM,
+	ExHiROM,
 }
 
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub struct Cartridge {
 	pub(crate) rom: Vec<u8>,
-	pub(crate) passed: TestFlags,
+	pub(crate) report: TestReport,
+	pub(crate) copier_header: Option<Vec<u8>>,
+	/// The detected ROM type, cleared whenever the ROM may have changed.
+	pub(crate) rom_type: std::sync::OnceLock<(ROMType, Score)>,
 }
 
+#[cfg(feature = "std")]
 impl Cartridge {
+	/// Loads a ROM file, extracting it first if the extension names an archive of an enabled feature.
 	pub fn from_file<P>(path: P, test_flags: TestFlags) -> CartridgeResult
 	where
 		P: AsRef<std::path::Path>,
 	{
-		let mut file = std::fs::File::open(path)?;
+		let path = path.as_ref();
+		#[cfg(any(feature = "zip", feature = "gzip"))]
+		if let Some(result) = Self::from_archive_file(path, test_flags) {
+			return result;
+		}
+		Self::from_reader(std::fs::File::open(path)?, test_flags)
+	}
 
-		use std::io::Read;
+	/// Loads a ROM from a reader until the end of its data.
+	pub fn from_reader<R>(mut reader: R, test_flags: TestFlags) -> CartridgeResult
+	where
+		R: std::io::Read,
+	{
 		let mut rom = Vec::new();
-		file.read_to_end(&mut rom)?;
+		reader.read_to_end(&mut rom)?;
 
 		Self::new(rom, test_flags)
 	}
 
+	/// Loads a ROM split across multiple files, e.g. `.1`, `.2` and so on, in the given order.
+	///
+	/// A copier header is stripped from each part, the one of the first part is kept.
+	pub fn from_files<I, P>(paths: I, test_flags: TestFlags) -> CartridgeResult
+	where
+		I: IntoIterator<Item = P>,
+		P: AsRef<std::path::Path>,
+	{
+		let mut rom = Vec::new();
+		let mut first_header = None;
+		for (i, path) in paths.into_iter().enumerate() {
+			let part = std::fs::read(path)?;
+			let (header, part) = Self::strip_copier_header(&part);
+			if i == 0 {
+				first_header = header.map(<[u8]>::to_vec);
+			}
+			rom.extend_from_slice(part);
+		}
+
+		let mut cartridge = Self::new(rom, test_flags)?;
+		cartridge.copier_header = cartridge.copier_header.or(first_header);
+		Ok(cartridge)
+	}
+
+	/// Creates a cartridge from the ROM bytes.
+	///
+	/// A copier header is stripped before testing the ROM, see [`copier_header`](#method.copier_header),
+	/// unless created by [`new_raw`](#method.new_raw).
+	/// The ROM is rejected if it passes a test left out of `test_flags`, so the default accepts any ROM.
 	pub fn new<T>(rom: T, test_flags: TestFlags) -> CartridgeResult
 	where
 		T: AsRef<[u8]>,
 	{
-		let passed = Self::rom_test(rom.as_ref());
+		let (copier_header, rom) = Self::strip_copier_header(rom.as_ref());
+		Self::with_copier_header(copier_header, rom, test_flags)
+	}
 
-		if !test_flags.contains(passed) {
-			return Err(NotProbableCartridgeError::new(passed, test_flags).into());
+	/// Creates a cartridge from the ROM bytes as they are, without stripping a copier header,
+	/// e.g. for an image whose size only happens to look like one follows.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let cartridge = Cartridge::new_raw(vec![0; 0x8200], TestFlags::default()).unwrap();
+	/// assert_eq!(cartridge.rom().len(), 0x8200);
+	/// assert_eq!(cartridge.copier_header(), None);
+	/// ```
+	pub fn new_raw<T>(rom: T, test_flags: TestFlags) -> CartridgeResult
+	where
+		T: AsRef<[u8]>,
+	{
+		Self::with_copier_header(None, rom.as_ref(), test_flags)
+	}
+
+	fn with_copier_header(
+		copier_header: Option<&[u8]>,
+		rom: &[u8],
+		test_flags: TestFlags,
+	) -> CartridgeResult {
+		let report = Self::run_tests(rom);
+
+		if !test_flags.contains(report.passed()) {
+			return Err(NotProbableCartridgeError::new(report, test_flags).into());
 		}
 
-		let rom = rom.as_ref().into();
-		Ok(Cartridge { rom, passed })
+		let cartridge = Cartridge {
+			rom: rom.into(),
+			report,
+			copier_header: copier_header.map(Into::into),
+			rom_type: Default::default(),
+		};
+		cartridge.detect_rom_type();
+		Ok(cartridge)
 	}
 
-	fn rom_test(rom: &[u8]) -> TestFlags {
-		let flag_size = if rom.len() % 0x8000 == 0 && rom.len() > 0 {
-			TestFlags::SIZE
+	/// Splits the ROM into its copier header, if any, and the ROM without it.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let rom = vec![0; 0x8200];
+	/// let (header, rom) = Cartridge::strip_copier_header(&rom);
+	/// assert_eq!(header.map(<[u8]>::len), Some(COPIER_HEADER_SIZE));
+	/// assert_eq!(rom.len(), 0x8000);
+	/// ```
+	pub fn strip_copier_header(rom: &[u8]) -> (Option<&[u8]>, &[u8]) {
+		if has_copier_header(rom) {
+			let (header, rom) = rom.split_at(COPIER_HEADER_SIZE);
+			(Some(header), rom)
 		} else {
-			TestFlags::empty()
-		};
+			(None, rom)
+		}
+	}
+
+	/// Returns the copier header stripped on loading, or `None` if the ROM had none.
+	#[inline]
+	pub fn copier_header(&self) -> Option<&[u8]> {
+		self.copier_header.as_deref()
+	}
+
+	/// Returns the ROM bytes without a copier header.
+	#[inline]
+	pub fn rom(&self) -> &[u8] {
+		&self.rom
+	}
+
+	/// Returns the ROM bytes without a copier header for patching in place.
+	///
+	/// The tests are not run again, see [`fix_checksum`](#method.fix_checksum) after patching,
+	/// but the ROM type is detected again when next needed.
+	#[inline]
+	pub fn rom_mut(&mut self) -> &mut [u8] {
+		self.rom_changed();
+		&mut self.rom
+	}
+
+	/// Returns the ROM bytes without a copier header.
+	#[inline]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.rom
+	}
+
+	/// Returns the ROM bytes, optionally prefixed with a copier header.
+	///
+	/// The copier header stripped on loading is reused if there was one,
+	/// otherwise a Super Wild Card header declaring the ROM size is generated.
+	pub fn to_bytes(&self, copier_header: bool) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(COPIER_HEADER_SIZE + self.rom.len());
+		if copier_header {
+			match &self.copier_header {
+				Some(header) => bytes.extend_from_slice(header),
+				None => {
+					let mut header = [0; COPIER_HEADER_SIZE];
+					let blocks = (self.rom.len() / 0x2000) as u16;
+					header[..2].copy_from_slice(&blocks.to_le_bytes());
+					header[8..11].copy_from_slice(&[0xAA, 0xBB, 0x04]);
+					bytes.extend_from_slice(&header);
+				}
+			}
+		}
+		bytes.extend_from_slice(&self.rom);
+		bytes
+	}
+
+	/// Splits the ROM into chunks of `chunk_size` bytes, the last one possibly shorter.
+	///
+	/// # Panics
+	/// Panics if `chunk_size` is zero.
+	#[inline]
+	pub fn split(&self, chunk_size: usize) -> std::slice::Chunks<'_, u8> {
+		self.rom.chunks(chunk_size)
+	}
+
+	/// Writes the ROM to a file, optionally prefixed with a copier header.
+	///
+	/// See [`to_bytes`](#method.to_bytes).
+	pub fn save_to_file<P>(&self, path: P, copier_header: bool) -> std::io::Result<()>
+	where
+		P: AsRef<std::path::Path>,
+	{
+		std::fs::write(path, self.to_bytes(copier_header))
+	}
+
+	/// Runs all ROM tests, on the deinterleaved ROM if it looks interleaved.
+	fn run_tests(rom: &[u8]) -> TestReport {
+		if is_interleaved(rom) {
+			let mut report = TestReport::new(true);
+			Self::run_tests_plain(&deinterleave(rom), &mut report);
+			report
+		} else {
+			let mut report = TestReport::new(false);
+			Self::run_tests_plain(rom, &mut report);
+			report
+		}
+	}
+
+	fn run_tests_plain(rom: &[u8], report: &mut TestReport) {
+		report.push(
+			TestFlags::SIZE,
+			rom.len() % 0x8000 == 0 && !rom.is_empty(),
+			TestDetail::Size(rom.len()),
+		);
 
 		let read_u16 = |offset: usize| {
 			rom.get(offset)
 				.zip(rom.get(offset + 1))
 				.map(|(&l, &h)| (h as u16) << 8 | (l as u16))
 		};
-
-		macro_rules! test {
-			($test:ident $offset:expr, $loflag:ident, $hiflag:ident) => {
-				if $test($offset) { TestFlags::$loflag } else { TestFlags::empty() } |
-				if $test($offset | 0x8000) { TestFlags::$hiflag } else { TestFlags::empty() }
-			}
-		}
+		let locations = [
+			(ROMType::LoROM, TestFlags::HEADER_LO),
+			(ROMType::HiROM, TestFlags::HEADER_HI),
+			(ROMType::ExHiROM, TestFlags::HEADER_EXHI),
+		];
 
 		// checksum
-		let sum = rom.iter().fold(0u16, |r, &b| r.wrapping_add(b as u16));
-		let test_checksum = |offset| {
-			let compl = read_u16(offset);
+		for &(rom_type, header) in locations.iter() {
+			let offset = rom_type.header_offset() + 0x1C;
+			let complement = read_u16(offset);
 			let checksum = read_u16(offset + 2);
-			Some(sum ^ 0xFFFF) == compl && Some(sum) == checksum
-		};
-		let flag_checksum = test!(test_checksum 0x7FDC, CHECKSUM_LO, CHECKSUM_HI);
+			let computed = compute_checksum(rom, rom_type);
+			report.push(
+				header
+					& (TestFlags::CHECKSUM_LO | TestFlags::CHECKSUM_HI | TestFlags::CHECKSUM_EXHI),
+				Some(computed ^ 0xFFFF) == complement && Some(computed) == checksum,
+				TestDetail::Checksum {
+					offset,
+					complement,
+					checksum,
+					computed,
+				},
+			);
+		}
 
-		// ROM makeup
-		let test_rom_makeup = |offset| {
-			rom.get(offset).map_or(false, |b| {
-				b & 0xE0 == 0x20 && matches!(b & 0xF, 0 | 1 | 2 | 3 | 5 | 0xA)
-			})
+		// header bytes
+		let mut test_byte = |name, field, flags, test: fn(u8) -> bool| {
+			for &(rom_type, header) in locations.iter() {
+				let offset = rom_type.header_offset() + field;
+				let value = rom.get(offset).copied();
+				report.push(
+					header & flags,
+					value.is_some_and(test),
+					TestDetail::Byte {
+						name,
+						offset,
+						value,
+					},
+				);
+			}
 		};
-		let flag_rommakeup =
-			test!(test_rom_makeup 0x7FD5, ROM_SPEED_AND_MAP_LO, ROM_SPEED_AND_MAP_HI);
-
-		// chipset
-		let test_chipset = |offset| {
-			rom.get(offset).map_or(false, |&b| {
+		test_byte(
+			"map mode",
+			0x15,
+			TestFlags::ROM_SPEED_AND_MAP_LO
+				| TestFlags::ROM_SPEED_AND_MAP_HI
+				| TestFlags::ROM_SPEED_AND_MAP_EXHI,
+			|b| b & 0xE0 == 0x20 && matches!(b & 0xF, 0 | 1 | 2 | 3 | 5 | 0xA),
+		);
+		test_byte(
+			"chipset",
+			0x16,
+			TestFlags::CHIPSET_LO | TestFlags::CHIPSET_HI | TestFlags::CHIPSET_EXHI,
+			|b| {
 				matches!(b,
 					0x00..=0x05 | 0x13..=0x15 | 0x1A | 0x25 | 0x32 | 0x34 | 0x35 |
 					0x43 | 0x45 | 0x55 | 0xE3 | 0xE5 | 0xF3 | 0xF5 | 0xF6 | 0xF9)
-			})
-		};
-		let flag_chipset = test!(test_chipset 0x7FD6, CHIPSET_LO, CHIPSET_HI);
+			},
+		);
+		test_byte(
+			"country",
+			0x19,
+			TestFlags::COUNTRY_LO | TestFlags::COUNTRY_HI | TestFlags::COUNTRY_EXHI,
+			|b| matches!(b, 0..=0x14),
+		);
 
-		// country
-		let test_country = |offset| rom.get(offset).map_or(false, |&b| matches!(b, 0..=0x14));
-		let flag_country = test!(test_country 0x7FD9, COUNTRY_LO, COUNTRY_HI);
+		// Satellaview data packs move the map mode and replace the chipset and country bytes
+		for &(rom_type, header) in locations[..2].iter() {
+			let offset = rom_type.header_offset() - 0x10;
+			let valid = rom
+				.get(offset..)
+				.and_then(SatellaviewHeader::from_bytes)
+				.is_some_and(|header| header.is_valid());
+			if valid {
+				report.push(
+					header - (TestFlags::CHECKSUM_LO | TestFlags::CHECKSUM_HI),
+					true,
+					TestDetail::Satellaview { offset },
+				);
+			}
+		}
+	}
+
+	/// Returns the outcomes of the ROM tests run on loading.
+	#[inline]
+	pub fn test_report(&self) -> &TestReport {
+		&self.report
+	}
+
+	/// Returns the tests the ROM passed on loading.
+	#[inline]
+	pub fn passed(&self) -> TestFlags {
+		self.report.passed()
+	}
+
+	/// Returns the header at the location of the detected ROM type.
+	pub fn header(&self) -> Option<Header> {
+		self.header_for(self.detect_rom_type().0)
+	}
 
-		flag_size | flag_checksum | flag_rommakeup | flag_chipset | flag_country
+	/// Returns the header at the location for the given ROM type.
+	pub fn header_for(&self, rom_type: ROMType) -> Option<Header> {
+		Header::from_bytes(self.rom.get(rom_type.header_offset()..)?)
+	}
+
+	/// Returns the map mode and ROM speed declared by the header.
+	pub fn map_mode(&self) -> Option<MapMode> {
+		Some(self.header()?.mode())
+	}
+
+	/// Returns the ROM speed declared by the header.
+	pub fn speed(&self) -> Option<RomSpeed> {
+		Some(self.map_mode()?.speed)
+	}
+
+	/// Returns the decoded title with trailing spaces removed, see [`Header::title_chars`].
+	pub fn title(&self) -> Option<String> {
+		let title = self.header()?.title_chars().collect::<String>();
+		Some(title.trim_end().into())
+	}
+
+	/// Returns the raw 21 bytes of the title.
+	pub fn title_raw(&self) -> Option<[u8; 21]> {
+		Some(self.header()?.title)
+	}
+
+	/// Returns the extended header of the detected ROM type, if the header declares one.
+	pub fn extended_header(&self) -> Option<ExtendedHeader> {
+		let rom_type = self.detect_rom_type().0;
+		self.header_for(rom_type)
+			.filter(Header::has_extended_header)?;
+		let offset = rom_type.header_offset() - header::EXTENDED_HEADER_SIZE;
+		ExtendedHeader::from_bytes(self.rom.get(offset..)?)
+	}
+
+	/// Returns the SRAM size in bytes declared by the header, or zero without SRAM.
+	pub fn sram_size(&self) -> usize {
+		self.header()
+			.and_then(|header| header.sram_size_bytes())
+			.unwrap_or(0)
 	}
 
 	pub fn get_header_rom_size(&self, hint: Option<ROMType>) -> Option<u8> {
 		match hint {
 			Some(ROMType::LoROM) => self.rom.get(0x7FD7).cloned(),
 			Some(ROMType::HiROM) => self.rom.get(0xFFD7).cloned(),
+			Some(ROMType::ExHiROM) => self.rom.get(0x40FFD7).cloned(),
 			None => None,
 		}
 	}
@@ -141,16 +542,258 @@ impl Cartridge {
 		match hint {
 			Some(ROMType::LoROM) => self.rom.get(0x7FD8).cloned(),
 			Some(ROMType::HiROM) => self.rom.get(0xFFD8).cloned(),
+			Some(ROMType::ExHiROM) => self.rom.get(0x40FFD8).cloned(),
 			None => None,
 		}
 	}
 }
 
+#[cfg(feature = "std")]
+impl AsRef<[u8]> for Cartridge {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		&self.rom
+	}
+}
+
+#[cfg(feature = "std")]
 impl std::fmt::Debug for Cartridge {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.debug_struct("Cartridge")
 			.field("rom", &self.rom.len())
-			.field("passed", &self.passed)
+			.field("passed", &self.passed())
+			.field("copier_header", &self.copier_header.is_some())
 			.finish()
 	}
 }
+
+#[cfg(all(test, feature = "std"))]
+pub(crate) mod test {
+	use super::*;
+	use crate::address::{snes_to_pc, Address24};
+
+	/// Builds a ROM of the given size with a valid header and checksum.
+	pub(crate) fn make_rom(rom_type: ROMType, size: usize) -> Vec<u8> {
+		make_rom_with(rom_type, size, |_| {})
+	}
+
+	/// Builds a ROM as [`make_rom`] does, applying `patch` before the checksum is computed.
+	pub(crate) fn make_rom_with(
+		rom_type: ROMType,
+		size: usize,
+		patch: impl FnOnce(&mut [u8]),
+	) -> Vec<u8> {
+		let mut rom = (0..size).map(|i| (i * 7 % 251) as u8).collect::<Vec<_>>();
+		let header = rom_type.header_offset();
+		rom[header..header + 21].copy_from_slice(b"SNESLIB TEST         ");
+		rom[header + 0x15] = match rom_type {
+			ROMType::LoROM => 0x20,
+			ROMType::HiROM => 0x31,
+			ROMType::ExHiROM => 0x35,
+		};
+		rom[header + 0x16] = 0x02;
+		rom[header + 0x17] = (size / 1024).trailing_zeros() as u8;
+		rom[header + 0x18] = 0x03;
+		rom[header + 0x19] = 0x01;
+		rom[header + 0x1A] = 0x01;
+		rom[header + 0x1B] = 0x00;
+		// reset to $00:8000 starting with SEI
+		rom[header + 0x3C..header + 0x3E].copy_from_slice(&[0x00, 0x80]);
+		rom[snes_to_pc(Address24::new(0x8000), rom_type).unwrap()] = 0x78;
+		patch(&mut rom);
+		let sum = compute_checksum(&rom, rom_type);
+		rom[header + 0x1C..header + 0x1E].copy_from_slice(&(sum ^ 0xFFFF).to_le_bytes());
+		rom[header + 0x1E..header + 0x20].copy_from_slice(&sum.to_le_bytes());
+		rom
+	}
+
+	#[test]
+	fn header() {
+		let cartridge =
+			Cartridge::new(make_rom(ROMType::LoROM, 0x80000), TestFlags::all()).unwrap();
+		let header = cartridge.header().unwrap();
+		assert_eq!(&header.title, b"SNESLIB TEST         ");
+		assert_eq!(header.map_mode, 0x20);
+		assert!(!header.is_fast_rom());
+		assert_eq!(header.chipset, 0x02);
+		assert_eq!(header.rom_size_bytes(), Some(0x80000));
+		assert_eq!(header.sram_size_bytes(), Some(0x2000));
+		assert_eq!(cartridge.sram_size(), 0x2000);
+		// SRAM sizes beyond what the SRAM windows map are rejected rather than allocated
+		for &(sram_size, bytes) in [(8, Some(0x40000)), (9, None), (15, None)].iter() {
+			assert_eq!(
+				Header {
+					sram_size,
+					..header
+				}
+				.sram_size_bytes(),
+				bytes
+			);
+		}
+		let mut rom = make_rom(ROMType::LoROM, 0x80000);
+		rom[0x7FD8] = 0x0F;
+		let oversized = Cartridge::new(rom, TestFlags::default()).unwrap();
+		assert_eq!(oversized.sram_size(), 0);
+		assert_eq!(cartridge.enhancement(), Enhancement::None);
+		assert_eq!(cartridge.title().as_deref(), Some("SNESLIB TEST"));
+		assert_eq!(
+			cartridge.title_raw().as_ref(),
+			Some(b"SNESLIB TEST         ")
+		);
+		assert_eq!(cartridge.chipset_subtype(), None);
+		assert_eq!(cartridge.extended_header(), None);
+		assert_eq!(header.country, 0x01);
+		assert!(header.is_checksum_consistent());
+		assert_eq!(cartridge.header_for(ROMType::ExHiROM), None);
+
+		let cartridge =
+			Cartridge::new(make_rom(ROMType::HiROM, 0x80000), TestFlags::all()).unwrap();
+		let header = cartridge.header().unwrap();
+		assert_eq!(header.map_mode, 0x31);
+		assert!(header.is_fast_rom());
+		assert_eq!(Some(header), cartridge.header_for(ROMType::HiROM));
+		assert_eq!(cartridge.speed(), Some(RomSpeed::Fast));
+		assert_eq!(cartridge.map_mode().unwrap().mapping, Mapping::HiROM);
+
+		let mut rom = make_rom(ROMType::HiROM, 0x80000);
+		rom[0xFFC0..0xFFD5]
+			.copy_from_slice(b"\xCA\xDF\xB2\xDB\xAF\xC4\xB3\xA8\xDD\xB8\xDE\xBD\x00\xFFTEST!!\x7F");
+		rom[0xFFB0..0xFFC0].copy_from_slice(b"01SNLE\0\0\0\0\0\0\0\x05\0\0");
+		rom[0xFFDA] = 0x33;
+		rom[0xFFD8] = 0;
+		let cartridge = Cartridge::new(rom, TestFlags::default()).unwrap();
+		assert_eq!(cartridge.sram_size(), 0);
+		assert_eq!(cartridge.title().as_deref(), Some("ﾊﾟｲﾛｯﾄｳｨﾝｸﾞｽ??TEST!!?"));
+		let extended = cartridge.extended_header().unwrap();
+		assert_eq!(&extended.maker_code, b"01");
+		assert_eq!(&extended.game_code, b"SNLE");
+		assert_eq!(extended.expansion_ram_size_bytes(), Some(0x8000));
+		assert_eq!(extended.expansion_flash_size_bytes(), Some(0));
+	}
+
+	#[test]
+	fn rom_test() {
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let passed = Cartridge::run_tests(&rom).passed();
+		assert_eq!(passed, TestFlags::SIZE | TestFlags::HEADER_LO);
+		assert_eq!(passed.probable_rom_type(), ROMType::LoROM);
+		assert!(Cartridge::new(&rom, TestFlags::default()).is_ok());
+
+		let rom = make_rom(ROMType::HiROM, 0x80000);
+		let passed = Cartridge::run_tests(&rom).passed();
+		assert!(passed.contains(TestFlags::SIZE | TestFlags::HEADER_HI));
+		assert_eq!(passed.probable_rom_type(), ROMType::HiROM);
+		assert!(Cartridge::new(&rom, TestFlags::default()).is_ok());
+
+		let rom = make_rom(ROMType::ExHiROM, 0x600000);
+		let passed = Cartridge::run_tests(&rom).passed();
+		assert!(passed.contains(TestFlags::SIZE | TestFlags::HEADER_EXHI));
+		assert_eq!(passed.probable_rom_type(), ROMType::ExHiROM);
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		assert_eq!(cartridge.header().unwrap().map_mode, 0x35);
+
+		// the default accepts any ROM, while a ROM passing a test left out is rejected
+		let mut rom = make_rom(ROMType::LoROM, 0x80000);
+		rom[0x7FDC] ^= 1;
+		assert!(Cartridge::new(&rom, TestFlags::default()).is_ok());
+		assert!(Cartridge::new(&rom[..0x7FFF], TestFlags::default()).is_ok());
+		assert!(Cartridge::new(vec![0xFF; 0x8001], TestFlags::empty()).is_ok());
+		match Cartridge::new(&rom, TestFlags::SIZE | TestFlags::HEADER_HI) {
+			Err(CartridgeError::NotProbableCartridge(e)) => {
+				assert!(!e.passed().contains(TestFlags::CHECKSUM_LO));
+				assert!(e.passed().contains(TestFlags::ROM_SPEED_AND_MAP_LO));
+				assert_eq!(e.required(), TestFlags::SIZE | TestFlags::HEADER_HI);
+			}
+			r => panic!("{:?}", r),
+		}
+		assert!(Cartridge::new(&rom, TestFlags::all() - TestFlags::SIZE).is_err());
+	}
+
+	#[test]
+	fn copier_header() {
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let mut headered = vec![0xAA; COPIER_HEADER_SIZE];
+		headered.extend_from_slice(&rom);
+		assert!(has_copier_header(&headered));
+		assert!(!has_copier_header(&rom));
+		assert!(!Cartridge::run_tests(&headered)
+			.passed()
+			.contains(TestFlags::SIZE));
+
+		let cartridge = Cartridge::new(&headered, TestFlags::default()).unwrap();
+		assert_eq!(
+			cartridge.copier_header(),
+			Some(&[0xAA; COPIER_HEADER_SIZE][..])
+		);
+		assert_eq!(cartridge.rom, rom);
+		assert_eq!(cartridge.header().unwrap().map_mode, 0x20);
+
+		assert_eq!(cartridge.to_bytes(true), headered);
+		assert_eq!(cartridge.to_bytes(false), rom);
+
+		// the raw image keeps the bytes in front
+		let cartridge = Cartridge::new_raw(&headered, TestFlags::default()).unwrap();
+		assert_eq!(cartridge.copier_header(), None);
+		assert_eq!(cartridge.rom, headered);
+		assert!(!cartridge.test_report().passed().contains(TestFlags::SIZE));
+
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		assert_eq!(cartridge.copier_header(), None);
+		let bytes = cartridge.to_bytes(true);
+		assert!(has_copier_header(&bytes));
+		assert_eq!(&bytes[..3], &[0x40, 0x00, 0x00]);
+		assert_eq!(&bytes[8..11], &[0xAA, 0xBB, 0x04]);
+		assert_eq!(cartridge.into_bytes(), rom);
+	}
+
+	#[test]
+	fn split_and_join() {
+		let rom = make_rom(ROMType::HiROM, 0x180000);
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let parts = cartridge.split(0x80000).collect::<Vec<_>>();
+		assert_eq!(parts.len(), 3);
+		assert!(parts.iter().all(|part| part.len() == 0x80000));
+		assert_eq!(cartridge.split(0x100000).last().unwrap().len(), 0x80000);
+
+		let dir = std::env::temp_dir();
+		let paths = (1..=parts.len())
+			.map(|i| dir.join(format!("sneslib-{}.{}", std::process::id(), i)))
+			.collect::<Vec<_>>();
+		for (i, (path, part)) in paths.iter().zip(parts).enumerate() {
+			let mut bytes = vec![i as u8; COPIER_HEADER_SIZE];
+			bytes.extend_from_slice(part);
+			std::fs::write(path, bytes).unwrap();
+		}
+		let joined = Cartridge::from_files(&paths, TestFlags::default());
+		for path in paths.iter() {
+			std::fs::remove_file(path).unwrap();
+		}
+		let joined = joined.unwrap();
+		assert_eq!(joined.copier_header(), Some(&[0; COPIER_HEADER_SIZE][..]));
+		assert_eq!(joined.into_bytes(), rom);
+	}
+
+	#[test]
+	fn save_to_file() {
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let mut cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		assert_eq!(cartridge.passed(), TestFlags::SIZE | TestFlags::HEADER_LO);
+		cartridge.rom_mut()[0] ^= 0xFF;
+		assert_eq!(cartridge.rom()[0], rom[0] ^ 0xFF);
+		cartridge.fix_checksum();
+		assert_eq!(
+			Cartridge::run_tests(cartridge.as_ref()).passed(),
+			cartridge.passed()
+		);
+
+		let path = std::env::temp_dir().join(format!("sneslib-{}.smc", std::process::id()));
+		cartridge.save_to_file(&path, true).unwrap();
+		let loaded = Cartridge::from_file(&path, TestFlags::default());
+		std::fs::remove_file(&path).unwrap();
+		let loaded = loaded.unwrap();
+		assert!(loaded.copier_header().is_some());
+		let read = Cartridge::from_reader(&cartridge.to_bytes(true)[..], TestFlags::default());
+		assert_eq!(read.unwrap().rom(), loaded.rom());
+		assert_eq!(loaded.into_bytes(), cartridge.into_bytes());
+	}
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use super::Cartridge;
+use super::Header;
+
+/// Destination region from the country code at `$xFD9`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Region {
+	Japan,
+	NorthAmerica,
+	Europe,
+	Scandinavia,
+	Finland,
+	Denmark,
+	France,
+	Netherlands,
+	Spain,
+	Germany,
+	Italy,
+	China,
+	Indonesia,
+	SouthKorea,
+	International,
+	Canada,
+	Brazil,
+	Australia,
+	/// Any other country code.
+	Other(u8),
+}
+
+/// Video standard, determining the frame rate and timing of the console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VideoStandard {
+	/// 60Hz with 262 scanlines.
+	NTSC,
+	/// 50Hz with 312 scanlines.
+	PAL,
+}
+
+impl From<u8> for Region {
+	fn from(country: u8) -> Self {
+		match country {
+			0x00 => Region::Japan,
+			0x01 => Region::NorthAmerica,
+			0x02 => Region::Europe,
+			0x03 => Region::Scandinavia,
+			0x04 => Region::Finland,
+			0x05 => Region::Denmark,
+			0x06 => Region::France,
+			0x07 => Region::Netherlands,
+			0x08 => Region::Spain,
+			0x09 => Region::Germany,
+			0x0A => Region::Italy,
+			0x0B => Region::China,
+			0x0C => Region::Indonesia,
+			0x0D => Region::SouthKorea,
+			0x0E => Region::International,
+			0x0F => Region::Canada,
+			0x10 => Region::Brazil,
+			0x11 => Region::Australia,
+			n => Region::Other(n),
+		}
+	}
+}
+
+impl From<Region> for u8 {
+	fn from(region: Region) -> u8 {
+		match region {
+			Region::Japan => 0x00,
+			Region::NorthAmerica => 0x01,
+			Region::Europe => 0x02,
+			Region::Scandinavia => 0x03,
+			Region::Finland => 0x04,
+			Region::Denmark => 0x05,
+			Region::France => 0x06,
+			Region::Netherlands => 0x07,
+			Region::Spain => 0x08,
+			Region::Germany => 0x09,
+			Region::Italy => 0x0A,
+			Region::China => 0x0B,
+			Region::Indonesia => 0x0C,
+			Region::SouthKorea => 0x0D,
+			Region::International => 0x0E,
+			Region::Canada => 0x0F,
+			Region::Brazil => 0x10,
+			Region::Australia => 0x11,
+			Region::Other(n) => n,
+		}
+	}
+}
+
+impl Region {
+	/// Returns the video standard of consoles sold in the region, or `None` for unknown country codes.
+	///
+	/// Brazilian consoles use PAL-M, which has NTSC timing.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// assert_eq!(Region::Japan.video_standard(), Some(VideoStandard::NTSC));
+	/// assert_eq!(Region::from(0x09).video_standard(), Some(VideoStandard::PAL));
+	/// ```
+	pub fn video_standard(&self) -> Option<VideoStandard> {
+		match self {
+			Region::Japan
+			| Region::NorthAmerica
+			| Region::SouthKorea
+			| Region::International
+			| Region::Canada
+			| Region::Brazil => Some(VideoStandard::NTSC),
+			Region::Other(_) => None,
+			_ => Some(VideoStandard::PAL),
+		}
+	}
+}
+
+/// Known problems of running a game on a console of another video standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimingCaveat {
+	/// Game logic and music tied to the frame rate run at 50Hz instead of 60Hz or vice versa.
+	FrameRate,
+	/// NTSC has 50 fewer scanlines of vertical blanking, so PAL games may overrun it on DMA.
+	VBlankLength,
+	/// Games reading the video standard from `$213F` may refuse to run.
+	RegionCheck,
+}
+
+/// Outcome of rewriting the country code of a cartridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct RegionConversion {
+	pub from: Region,
+	pub to: Region,
+	/// Checksum after the conversion.
+	pub checksum: u16,
+	/// Caveats if the video standard changes, empty otherwise.
+	pub caveats: &'static [TimingCaveat],
+}
+
+impl RegionConversion {
+	/// Returns `true` if the video standard changes.
+	#[inline]
+	pub fn changes_video_standard(&self) -> bool {
+		!self.caveats.is_empty()
+	}
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Rewrites the country code in the header of the detected ROM type and fixes the checksum.
+	///
+	/// Returns `None` if the ROM is too small to hold a header.
+	/// The game code itself is not patched, so games may still behave differently,
+	/// see [`RegionConversion::caveats`].
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let mut cartridge = CartridgeBuilder::new(ROMType::LoROM).country(0x02).build().unwrap();
+	/// let conversion = cartridge.convert_region(Region::NorthAmerica).unwrap();
+	/// assert_eq!(conversion.from, Region::Europe);
+	/// assert!(conversion.caveats.contains(&TimingCaveat::FrameRate));
+	/// assert_eq!(cartridge.header().unwrap().video_standard(), Some(VideoStandard::NTSC));
+	/// assert_eq!(cartridge.compute_checksum(), conversion.checksum);
+	/// ```
+	pub fn convert_region(&mut self, region: Region) -> Option<RegionConversion> {
+		let rom_type = self.detect_rom_type().0;
+		let from = self.header_for(rom_type)?.region();
+		self.rom[rom_type.header_offset() + 0x19] = region.into();
+		let checksum = self.fix_checksum()?;
+		let caveats: &[_] = if from.video_standard() == region.video_standard() {
+			&[]
+		} else {
+			&[
+				TimingCaveat::FrameRate,
+				TimingCaveat::VBlankLength,
+				TimingCaveat::RegionCheck,
+			]
+		};
+		Some(RegionConversion {
+			from,
+			to: region,
+			checksum,
+			caveats,
+		})
+	}
+}
+
+impl Header {
+	/// Returns the destination region from the country code.
+	#[inline]
+	pub fn region(&self) -> Region {
+		self.country.into()
+	}
+
+	/// Returns the video standard of the destination region, or `None` for unknown country codes.
+	#[inline]
+	pub fn video_standard(&self) -> Option<VideoStandard> {
+		self.region().video_standard()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn region() {
+		assert_eq!(Region::from(0x00), Region::Japan);
+		assert_eq!(Region::from(0x01), Region::NorthAmerica);
+		assert_eq!(Region::from(0x11), Region::Australia);
+		assert_eq!(Region::from(0x12), Region::Other(0x12));
+		for country in 0..=0xFF {
+			assert_eq!(u8::from(Region::from(country)), country);
+		}
+
+		let pal = (0x00..=0x11)
+			.filter(|&country| Region::from(country).video_standard() == Some(VideoStandard::PAL))
+			.count();
+		assert_eq!(pal, 12);
+		assert_eq!(Region::Other(0xFF).video_standard(), None);
+
+		let mut bytes = [0; 0x20];
+		bytes[0x19] = 0x02;
+		let header = Header::from_bytes(&bytes).unwrap();
+		assert_eq!(header.region(), Region::Europe);
+		assert_eq!(header.video_standard(), Some(VideoStandard::PAL));
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn convert_region() {
+		use crate::cartridge::test::make_rom;
+		use crate::cartridge::{ROMType, TestFlags};
+
+		let rom = make_rom(ROMType::HiROM, 0x80000);
+		let mut cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let conversion = cartridge.convert_region(Region::Canada).unwrap();
+		assert_eq!(
+			(conversion.from, conversion.to),
+			(Region::NorthAmerica, Region::Canada)
+		);
+		assert!(!conversion.changes_video_standard());
+		assert_eq!(cartridge.rom()[0xFFD9], 0x0F);
+		let changed = cartridge
+			.rom()
+			.iter()
+			.zip(rom.iter())
+			.filter(|(a, b)| a != b)
+			.count();
+		assert!((1..=5).contains(&changed));
+
+		let conversion = cartridge.convert_region(Region::Germany).unwrap();
+		assert!(conversion.changes_video_standard());
+		assert_eq!(
+			Cartridge::run_tests(cartridge.rom()).passed(),
+			Cartridge::run_tests(&rom).passed()
+		);
+
+		let mut cartridge = Cartridge::new(vec![0; 0x4000], TestFlags::default()).unwrap();
+		assert_eq!(cartridge.convert_region(Region::Japan), None);
+	}
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use super::TestFlags;
+
+/// Values inspected by a single ROM test.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum TestDetail {
+	/// ROM size in bytes.
+	Size(usize),
+	/// Checksum complement and checksum at `offset`, `None` past the end of the ROM,
+	/// and the checksum computed over the ROM.
+	Checksum {
+		offset: usize,
+		complement: Option<u16>,
+		checksum: Option<u16>,
+		computed: u16,
+	},
+	/// Header byte named `name` at `offset`, `None` past the end of the ROM.
+	Byte {
+		name: &'static str,
+		offset: usize,
+		value: Option<u8>,
+	},
+	/// Satellaview data pack header at `offset`,
+	/// standing in for the map mode, chipset and country tests of its location.
+	Satellaview { offset: usize },
+}
+
+/// Outcome of a single ROM test.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct TestResult {
+	/// The flags the test sets when passing.
+	pub test: TestFlags,
+	pub passed: bool,
+	pub detail: TestDetail,
+}
+
+/// Outcomes of all ROM tests run on loading.
+///
+/// A test flag passes if any result carrying it passed.
+/// The report is serializable but not deserializable, as the test names are static strings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct TestReport {
+	results: Vec<TestResult>,
+	interleaved: bool,
+}
+
+impl TestReport {
+	pub(crate) fn new(interleaved: bool) -> Self {
+		Self {
+			results: Vec::new(),
+			interleaved,
+		}
+	}
+
+	pub(crate) fn push(&mut self, test: TestFlags, passed: bool, detail: TestDetail) {
+		self.results.push(TestResult {
+			test,
+			passed,
+			detail,
+		});
+	}
+
+	/// Returns the outcomes in the order the tests ran.
+	#[inline]
+	pub fn results(&self) -> &[TestResult] {
+		&self.results
+	}
+
+	/// Returns the tests the ROM passed.
+	pub fn passed(&self) -> TestFlags {
+		self.results
+			.iter()
+			.filter(|result| result.passed)
+			.fold(TestFlags::empty(), |r, result| r | result.test)
+	}
+
+	/// Returns the outcomes of the tests the ROM failed.
+	pub fn failures(&self) -> impl Iterator<Item = &TestResult> {
+		let passed = self.passed();
+		self.results
+			.iter()
+			.filter(move |result| !passed.contains(result.test))
+	}
+
+	/// Returns `true` if the tests ran on the deinterleaved ROM.
+	#[inline]
+	pub fn is_interleaved(&self) -> bool {
+		self.interleaved
+	}
+}
+
+fn fmt_u16(value: Option<u16>) -> String {
+	value.map_or_else(|| "missing".into(), |value| format!("${:04X}", value))
+}
+
+impl fmt::Display for TestDetail {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use TestDetail::*;
+		match self {
+			Size(len) => write!(f, "ROM size was ${:X}", len),
+			Checksum {
+				offset,
+				complement,
+				checksum,
+				computed,
+			} => write!(
+				f,
+				"checksum at ${:X} was {} with complement {}, computed ${:04X}",
+				offset + 2,
+				fmt_u16(*checksum),
+				fmt_u16(*complement),
+				computed
+			),
+			Byte {
+				name,
+				offset,
+				value: Some(value),
+			} => write!(f, "{} at ${:X} was ${:02X}", name, offset, value),
+			Byte { name, offset, .. } => write!(f, "{} at ${:X} was missing", name, offset),
+			Satellaview { offset } => write!(f, "Satellaview header at ${:X}", offset),
+		}
+	}
+}
+
+impl fmt::Display for TestResult {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let status = if self.passed { "pass" } else { "FAIL" };
+		write!(f, "[{}] {}", status, self.detail)
+	}
+}
+
+impl fmt::Display for TestReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.interleaved {
+			writeln!(f, "ROM is interleaved, tested deinterleaved")?;
+		}
+		for result in self.results.iter() {
+			writeln!(f, "{}", result)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::{Cartridge, CartridgeError, ROMType};
+
+	#[test]
+	fn report() {
+		let cartridge =
+			Cartridge::new(make_rom(ROMType::LoROM, 0x80000), TestFlags::default()).unwrap();
+		let report = cartridge.test_report();
+		assert_eq!(report.passed(), TestFlags::SIZE | TestFlags::HEADER_LO);
+		assert!(!report.is_interleaved());
+		assert_eq!(report.failures().count(), 8);
+		assert!(report.failures().all(|result| !result.passed));
+
+		let mut rom = make_rom(ROMType::HiROM, 0x80000);
+		rom[0xFFDE] ^= 0xFF;
+		let report = match Cartridge::new(&rom, TestFlags::SIZE | TestFlags::HEADER_LO) {
+			Err(CartridgeError::NotProbableCartridge(e)) => e.report().clone(),
+			r => panic!("{:?}", r),
+		};
+		let result = report
+			.failures()
+			.find(|result| result.test == TestFlags::CHECKSUM_HI)
+			.unwrap();
+		let checksum = u16::from_le_bytes([rom[0xFFDE], rom[0xFFDF]]);
+		match result.detail {
+			TestDetail::Checksum {
+				offset: 0xFFDC,
+				checksum: Some(c),
+				computed,
+				..
+			} => {
+				assert_eq!(c, checksum);
+				assert_eq!(computed, checksum ^ 0xFF);
+			}
+			ref detail => panic!("{:?}", detail),
+		}
+		assert_eq!(
+			result.to_string(),
+			format!(
+				"[FAIL] checksum at $FFDE was ${:04X} with complement ${:04X}, computed ${:04X}",
+				checksum,
+				u16::from_le_bytes([rom[0xFFDC], rom[0xFFDD]]),
+				checksum ^ 0xFF
+			)
+		);
+
+		let report = Cartridge::new(vec![0x21; 0x7FD6], TestFlags::default())
+			.unwrap()
+			.test_report()
+			.clone();
+		let lines = report.to_string();
+		assert!(lines.contains("[FAIL] ROM size was $7FD6"));
+		assert!(lines.contains("[pass] map mode at $7FD5 was $21"));
+		assert!(lines.contains("[FAIL] chipset at $7FD6 was missing"));
+		assert!(lines.contains("[FAIL] checksum at $7FDE was missing with complement missing"));
+	}
+}
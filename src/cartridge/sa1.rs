@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use super::Cartridge;
+use super::{Enhancement, Header};
+
+/// Size of the I-RAM inside the SA-1, mapped at `$3000-$37FF`.
+pub const IRAM_SIZE: usize = 0x800;
+
+/// Memory configuration of an SA-1 cartridge implied by its header.
+///
+/// The Super MMC maps 1MB blocks of ROM into banks `$00-$1F`, `$20-$3F`, `$80-$9F` and `$A0-$BF`
+/// through `CXB`, `DXB`, `EXB` and `FXB`, which select the blocks 0 to 3 on reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SA1Config {
+	/// BW-RAM size in bytes from the SRAM size at `$xFD8`, mapped at `$40-$4F`.
+	pub bwram_size: usize,
+	/// Whether the BW-RAM is battery backed.
+	pub battery: bool,
+	/// I-RAM size in bytes, always [`IRAM_SIZE`] as the I-RAM is part of the SA-1.
+	pub iram_size: usize,
+	/// ROM blocks selected by `CXB`, `DXB`, `EXB` and `FXB` on reset.
+	pub mmc_banks: [u8; 4],
+	/// BW-RAM block mapped at `$00-$3F:6000-7FFF` on reset, selected by `BMAPS`.
+	pub bwram_block: u8,
+}
+
+impl SA1Config {
+	/// Derives the SA-1 configuration from the header, or `None` if the cartridge has no SA-1.
+	/// ```
+	/// # use sneslib::cartridge::*;
+	/// let mut bytes = [0x20; 0x20];
+	/// bytes[0x15] = 0x23;
+	/// bytes[0x16] = 0x35;
+	/// bytes[0x18] = 0x03;
+	/// let config = SA1Config::from_header(&Header::from_bytes(&bytes).unwrap()).unwrap();
+	/// assert_eq!(config.bwram_size, 0x2000);
+	/// assert!(config.battery);
+	/// assert_eq!(config.mmc_banks, [0, 1, 2, 3]);
+	/// ```
+	pub fn from_header(header: &Header) -> Option<Self> {
+		if Enhancement::from_header(header, None) != Enhancement::SA1 {
+			return None;
+		}
+		Some(Self {
+			bwram_size: header.sram_size_bytes().unwrap_or(0),
+			battery: matches!(header.chipset & 0x0F, 0x05 | 0x06),
+			iram_size: IRAM_SIZE,
+			mmc_banks: [0, 1, 2, 3],
+			bwram_block: 0,
+		})
+	}
+
+	/// Returns the ROM offset of the block selected by the Super MMC register `index`,
+	/// 0 to 3 for `CXB` to `FXB`.
+	///
+	/// # Panics
+	/// Panics if `index` is greater than 3.
+	#[inline]
+	pub fn mmc_offset(&self, index: usize) -> usize {
+		(self.mmc_banks[index] as usize & 0x07) << 20
+	}
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Returns the SA-1 configuration, or `None` if the cartridge has no SA-1.
+	pub fn sa1_config(&self) -> Option<SA1Config> {
+		SA1Config::from_header(&self.header()?)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::test::{make_rom, make_rom_with};
+	use crate::cartridge::{ROMType, TestFlags};
+
+	#[test]
+	fn sa1_config() {
+		let cartridge =
+			Cartridge::new(make_rom(ROMType::LoROM, 0x100000), TestFlags::default()).unwrap();
+		assert_eq!(cartridge.sa1_config(), None);
+
+		let rom = make_rom_with(ROMType::LoROM, 0x400000, |rom| {
+			rom[0x7FD5..0x7FD9].copy_from_slice(&[0x23, 0x34, 0x0C, 0x05])
+		});
+		let cartridge = Cartridge::new(rom, TestFlags::default()).unwrap();
+		let config = cartridge.sa1_config().unwrap();
+		assert_eq!(config.bwram_size, 0x8000);
+		assert!(!config.battery);
+		assert_eq!(config.iram_size, IRAM_SIZE);
+		assert_eq!(config.bwram_block, 0);
+		assert_eq!(config.mmc_offset(0), 0);
+		assert_eq!(config.mmc_offset(3), 0x300000);
+	}
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use super::{Cartridge, ROMType};
+
+/// Size of the Satellaview header at `$xFB0-$xFDF`.
+pub const SATELLAVIEW_HEADER_SIZE: usize = 0x30;
+
+/// Header of a Satellaview data pack at `$xFB0-$xFDF`.
+///
+/// Data packs reuse the header location of regular cartridges with a different layout,
+/// so the map mode moves to `$xFD8` and the chipset and country bytes are replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SatellaviewHeader {
+	/// Maker code at `$xFB0`.
+	pub maker_code: [u8; 2],
+	/// Title at `$xFC0`, 16 bytes of Shift-JIS.
+	pub title: [u8; 16],
+	/// Flash blocks of 128KB used by the file at `$xFD0-$xFD3`, one bit per block.
+	pub block_allocation: u32,
+	/// Limited starts at `$xFD4-$xFD5`.
+	pub limited_starts: u16,
+	/// Broadcast month at `$xFD6`, bits 4-7.
+	pub month: u8,
+	/// Broadcast day at `$xFD7`, bits 3-7.
+	pub day: u8,
+	/// Map mode and ROM speed at `$xFD8`.
+	pub map_mode: u8,
+	/// File type at `$xFD9`.
+	pub file_type: u8,
+	/// Fixed value `$33` at `$xFDA`.
+	pub developer_id: u8,
+	/// Version number at `$xFDB`.
+	pub version: u8,
+	/// Checksum complement at `$xFDC-$xFDD`.
+	pub checksum_complement: u16,
+	/// Checksum at `$xFDE-$xFDF`.
+	pub checksum: u16,
+}
+
+impl SatellaviewHeader {
+	/// Parses a Satellaview header from the bytes starting at `$xFB0`.
+	///
+	/// Returns `None` if `bytes` is shorter than the header.
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let bytes = bytes.get(..SATELLAVIEW_HEADER_SIZE)?;
+		let read_u16 = |offset: usize| (bytes[offset + 1] as u16) << 8 | bytes[offset] as u16;
+
+		let mut maker_code = [0; 2];
+		maker_code.copy_from_slice(&bytes[..2]);
+		let mut title = [0; 16];
+		title.copy_from_slice(&bytes[0x10..0x20]);
+		Some(Self {
+			maker_code,
+			title,
+			block_allocation: (read_u16(0x22) as u32) << 16 | read_u16(0x20) as u32,
+			limited_starts: read_u16(0x24),
+			month: bytes[0x26] >> 4,
+			day: bytes[0x27] >> 3,
+			map_mode: bytes[0x28],
+			file_type: bytes[0x29],
+			developer_id: bytes[0x2A],
+			version: bytes[0x2B],
+			checksum_complement: read_u16(0x2C),
+			checksum: read_u16(0x2E),
+		})
+	}
+
+	/// Returns `true` if the fixed byte, the map mode and the broadcast date are plausible.
+	pub fn is_valid(&self) -> bool {
+		self.developer_id == 0x33
+			&& matches!(self.map_mode, 0x20 | 0x21 | 0x30 | 0x31)
+			&& self.month <= 12
+			&& self.day <= 31
+	}
+
+	/// Returns `true` if the file can be started any number of times.
+	#[inline]
+	pub fn is_unlimited(&self) -> bool {
+		self.limited_starts & 0x8000 == 0
+	}
+
+	/// Returns the number of remaining starts, or `None` if unlimited.
+	#[inline]
+	pub fn remaining_starts(&self) -> Option<u32> {
+		if self.is_unlimited() {
+			None
+		} else {
+			Some((self.limited_starts & 0x7FFF).count_ones())
+		}
+	}
+
+	/// Returns the number of 128KB flash blocks used by the file.
+	#[inline]
+	pub fn blocks(&self) -> u32 {
+		self.block_allocation.count_ones()
+	}
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Returns the Satellaview data pack header at the LoROM or HiROM location, if valid.
+	pub fn satellaview_header(&self) -> Option<SatellaviewHeader> {
+		[ROMType::LoROM, ROMType::HiROM]
+			.iter()
+			.filter_map(|rom_type| self.rom.get(rom_type.header_offset() - 0x10..))
+			.filter_map(SatellaviewHeader::from_bytes)
+			.find(SatellaviewHeader::is_valid)
+	}
+
+	/// Returns `true` if the cartridge is the BS-X base cartridge with the Satellaview BIOS.
+	pub fn is_bsx_base(&self) -> bool {
+		self.header()
+			.is_some_and(|header| &header.title == b"Satellaview BS-X     ")
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom_with;
+	use crate::cartridge::{compute_checksum, TestFlags};
+
+	#[test]
+	fn satellaview() {
+		let mut rom = vec![0; 0x100000];
+		let header = &mut rom[0x7FB0..0x7FE0];
+		header[..2].copy_from_slice(b"01");
+		header[0x10..0x20].copy_from_slice(b"SNESLIB BS TEST ");
+		header[0x20..0x24].copy_from_slice(&[0x0F, 0x00, 0x00, 0x00]);
+		header[0x24..0x26].copy_from_slice(&[0x07, 0x80]);
+		header[0x26..0x2C].copy_from_slice(&[0x40, 0xA8, 0x20, 0x00, 0x33, 0x01]);
+		let checksum = compute_checksum(&rom, ROMType::LoROM);
+		rom[0x7FDC..0x7FDE].copy_from_slice(&(checksum ^ 0xFFFF).to_le_bytes());
+		rom[0x7FDE..0x7FE0].copy_from_slice(&checksum.to_le_bytes());
+
+		let passed = Cartridge::run_tests(&rom).passed();
+		assert!(passed.contains(TestFlags::SIZE | TestFlags::HEADER_LO));
+		let cartridge = Cartridge::new(rom, TestFlags::default()).unwrap();
+		let header = cartridge.satellaview_header().unwrap();
+		assert_eq!(&header.title, b"SNESLIB BS TEST ");
+		assert_eq!(header.blocks(), 4);
+		assert!(!header.is_unlimited());
+		assert_eq!(header.remaining_starts(), Some(3));
+		assert_eq!((header.month, header.day), (4, 21));
+		assert_eq!(header.map_mode, 0x20);
+		assert!(!cartridge.is_bsx_base());
+
+		let cartridge = Cartridge::new(vec![0; 0x8000], TestFlags::default()).unwrap();
+		assert_eq!(cartridge.satellaview_header(), None);
+
+		let rom = make_rom_with(ROMType::LoROM, 0x100000, |rom| {
+			rom[0x7FC0..0x7FD5].copy_from_slice(b"Satellaview BS-X     ")
+		});
+		let cartridge = Cartridge::new(rom, TestFlags::default()).unwrap();
+		assert!(cartridge.is_bsx_base());
+	}
+}
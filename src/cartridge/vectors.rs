@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use super::Cartridge;
+use super::ROMType;
+use crate::address::Address16;
+
+/// Size of the interrupt vector table at `$xFE0-$xFFF`.
+pub const VECTORS_SIZE: usize = 0x20;
+
+/// Interrupt vectors of one processor mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VectorTable {
+	pub cop: Address16,
+	pub brk: Address16,
+	pub abort: Address16,
+	pub nmi: Address16,
+	pub reset: Address16,
+	pub irq: Address16,
+}
+
+/// Interrupt vector table at `$xFE0-$xFFF`.
+///
+/// The native mode has no RESET vector and the emulation mode shares its IRQ vector with BRK,
+/// so `native.reset` holds the unused entry at `$xFEC` and `emulation.brk` equals `emulation.irq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Vectors {
+	/// Vectors at `$xFE4-$xFEF`.
+	pub native: VectorTable,
+	/// Vectors at `$xFF4-$xFFF`.
+	pub emulation: VectorTable,
+}
+
+impl ROMType {
+	/// Returns the ROM offset of the interrupt vectors at `$xFE0`.
+	#[inline]
+	pub const fn vectors_offset(&self) -> usize {
+		self.header_offset() + 0x20
+	}
+}
+
+impl Vectors {
+	/// Parses the vectors from the bytes starting at `$xFE0`.
+	///
+	/// Returns `None` if `bytes` is shorter than the vector table.
+	/// ```
+	/// # use sneslib::address::Address16;
+	/// # use sneslib::cartridge::*;
+	/// let mut bytes = [0; 0x20];
+	/// bytes[0x1C..0x1E].copy_from_slice(&[0x00, 0x80]);
+	/// let vectors = Vectors::from_bytes(&bytes).unwrap();
+	/// assert_eq!(vectors.emulation.reset, Address16::new(0x8000));
+	/// ```
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let bytes = bytes.get(..VECTORS_SIZE)?;
+		let read = |offset: usize| Address16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+		Some(Self {
+			native: VectorTable {
+				cop: read(0x04),
+				brk: read(0x06),
+				abort: read(0x08),
+				nmi: read(0x0A),
+				reset: read(0x0C),
+				irq: read(0x0E),
+			},
+			emulation: VectorTable {
+				cop: read(0x14),
+				brk: read(0x1E),
+				abort: read(0x18),
+				nmi: read(0x1A),
+				reset: read(0x1C),
+				irq: read(0x1E),
+			},
+		})
+	}
+
+	/// Returns `true` if the RESET vector points into the ROM area `$8000-$FFFF` of bank `$00`.
+	#[inline]
+	pub fn is_reset_plausible(&self) -> bool {
+		self.emulation.reset >= Address16::new(0x8000)
+	}
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Returns the interrupt vectors of the detected ROM type.
+	pub fn vectors(&self) -> Option<Vectors> {
+		self.vectors_for(self.detect_rom_type().0)
+	}
+
+	/// Returns the interrupt vectors at the location for the given ROM type.
+	pub fn vectors_for(&self, rom_type: ROMType) -> Option<Vectors> {
+		Vectors::from_bytes(self.rom.get(rom_type.vectors_offset()..)?)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom;
+	use crate::cartridge::TestFlags;
+
+	#[test]
+	fn vectors() {
+		let mut rom = make_rom(ROMType::HiROM, 0x80000);
+		for (i, b) in rom[0xFFE0..0x10000].iter_mut().enumerate() {
+			*b = i as u8;
+		}
+		let cartridge = Cartridge::new(rom, TestFlags::default()).unwrap();
+		let vectors = cartridge.vectors_for(ROMType::HiROM).unwrap();
+		assert_eq!(vectors.native.cop, Address16::new(0x0504));
+		assert_eq!(vectors.native.brk, Address16::new(0x0706));
+		assert_eq!(vectors.native.abort, Address16::new(0x0908));
+		assert_eq!(vectors.native.nmi, Address16::new(0x0B0A));
+		assert_eq!(vectors.native.irq, Address16::new(0x0F0E));
+		assert_eq!(vectors.emulation.cop, Address16::new(0x1514));
+		assert_eq!(vectors.emulation.abort, Address16::new(0x1918));
+		assert_eq!(vectors.emulation.nmi, Address16::new(0x1B1A));
+		assert_eq!(vectors.emulation.reset, Address16::new(0x1D1C));
+		assert_eq!(vectors.emulation.irq, Address16::new(0x1F1E));
+		assert_eq!(vectors.emulation.brk, vectors.emulation.irq);
+		assert!(!vectors.is_reset_plausible());
+
+		let cartridge =
+			Cartridge::new(make_rom(ROMType::LoROM, 0x8000), TestFlags::default()).unwrap();
+		let vectors = cartridge.vectors().unwrap();
+		assert_eq!(vectors.emulation.reset, Address16::new(0x8000));
+		assert!(vectors.is_reset_plausible());
+		assert_eq!(cartridge.vectors_for(ROMType::HiROM), None);
+	}
+}
@@ -0,0 +1,24 @@
+use core::fmt;
+
+use crate::address::Address24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheatError {
+	/// The string is not a valid Game Genie or Pro Action Replay code.
+	ParseError,
+	/// The code targets an address outside of the ROM, e.g. WRAM.
+	NotRom(Address24),
+}
+
+impl fmt::Display for CheatError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use CheatError::*;
+		match self {
+			ParseError => "invalid cheat code syntax".fmt(f),
+			NotRom(address) => write!(f, "address {} is not in ROM", address),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CheatError {}
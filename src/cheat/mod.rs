@@ -0,0 +1,250 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::address::{snes_to_pc, Address24};
+#[cfg(feature = "std")]
+use crate::cartridge::Cartridge;
+use crate::cartridge::ROMType;
+
+pub use error::CheatError;
+
+pub mod error;
+
+/// Hexadecimal digits `0-F` as written in Game Genie codes.
+const GAME_GENIE_DIGITS: &[u8; 16] = b"DF4709156BC8A23E";
+
+/// Notation a cheat code was written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheatFormat {
+	/// `XXXX-YYYY` with substituted digits and scrambled address bits.
+	GameGenie,
+	/// `AAAAAA:VV` or `AAAAAAVV` in plain hexadecimal.
+	ProActionReplay,
+}
+
+/// Decoded cheat code writing `value` to `address`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cheat {
+	pub address: Address24,
+	pub value: u8,
+	pub format: CheatFormat,
+}
+
+fn parse_hex_digits(digits: &[u8], map: impl Fn(u8) -> Option<u8>) -> Result<u32, CheatError> {
+	digits.iter().try_fold(0, |r, &b| {
+		map(b)
+			.map(|digit| r << 4 | digit as u32)
+			.ok_or(CheatError::ParseError)
+	})
+}
+
+/// Game Genie address bits in code order `ijklqrst opabcduv wxefghmn` to `abcdefgh ijklmnop qrstuvwx`.
+fn descramble(raw: u32) -> u32 {
+	(raw & 0x003C00) << 10
+		| (raw & 0x00003C) << 14
+		| (raw & 0xF00000) >> 8
+		| (raw & 0x000003) << 10
+		| (raw & 0x00C000) >> 6
+		| (raw & 0x0F0000) >> 12
+		| (raw & 0x0003C0) >> 6
+}
+
+fn scramble(address: u32) -> u32 {
+	(address & 0xF00000) >> 10
+		| (address & 0x0F0000) >> 14
+		| (address & 0x00F000) << 8
+		| (address & 0x000C00) >> 10
+		| (address & 0x000300) << 6
+		| (address & 0x0000F0) << 12
+		| (address & 0x00000F) << 6
+}
+
+impl Cheat {
+	/// Decodes a Game Genie code `XXXX-YYYY`, the dash being optional.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cheat::*;
+	/// let cheat = Cheat::from_game_genie("3C6D-DDDD").unwrap();
+	/// assert_eq!(cheat.address, Address24::new(0x008000));
+	/// assert_eq!(cheat.value, 0xEA);
+	/// ```
+	pub fn from_game_genie(code: &str) -> Result<Self, CheatError> {
+		let code = code.as_bytes();
+		let digits = match code.len() {
+			9 if code[4] == b'-' => [&code[..4], &code[5..]],
+			8 => [&code[..4], &code[4..]],
+			_ => return Err(CheatError::ParseError),
+		};
+		let digit = |b: u8| {
+			GAME_GENIE_DIGITS
+				.iter()
+				.position(|&d| d == b.to_ascii_uppercase())
+				.map(|i| i as u8)
+		};
+		let code = parse_hex_digits(digits[0], digit)? << 16 | parse_hex_digits(digits[1], digit)?;
+		Ok(Self {
+			address: Address24::new(descramble(code & 0xFFFFFF)),
+			value: (code >> 24) as u8,
+			format: CheatFormat::GameGenie,
+		})
+	}
+
+	/// Decodes a Pro Action Replay code `AAAAAA:VV`, the colon being optional.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cheat::*;
+	/// let cheat = Cheat::from_pro_action_replay("7E0DBF:09").unwrap();
+	/// assert_eq!(cheat.address, Address24::new(0x7E0DBF));
+	/// assert_eq!(cheat.value, 0x09);
+	/// ```
+	pub fn from_pro_action_replay(code: &str) -> Result<Self, CheatError> {
+		let code = code.as_bytes();
+		let (address, value) = match code.len() {
+			9 if code[6] == b':' => (&code[..6], &code[7..]),
+			8 => code.split_at(6),
+			_ => return Err(CheatError::ParseError),
+		};
+		let digit = |b: u8| (b as char).to_digit(16).map(|digit| digit as u8);
+		Ok(Self {
+			address: Address24::new(parse_hex_digits(address, digit)?),
+			value: parse_hex_digits(value, digit)? as u8,
+			format: CheatFormat::ProActionReplay,
+		})
+	}
+
+	/// Returns `true` if the code patches ROM rather than RAM for the given ROM type.
+	#[inline]
+	pub fn is_rom(&self, rom_type: ROMType) -> bool {
+		self.address.is_rom_area(rom_type)
+	}
+
+	/// Returns the offset into the ROM image the code patches, or `None` if it does not target ROM.
+	#[inline]
+	pub fn rom_offset(&self, rom_type: ROMType) -> Option<usize> {
+		snes_to_pc(self.address, rom_type)
+	}
+}
+
+impl FromStr for Cheat {
+	type Err = CheatError;
+
+	/// Parses a Game Genie code if it contains a dash, a Pro Action Replay code otherwise.
+	/// ```
+	/// # use sneslib::cheat::*;
+	/// assert_eq!("3C6D-DDDD".parse::<Cheat>().unwrap().format, CheatFormat::GameGenie);
+	/// assert_eq!("7E0DBF09".parse::<Cheat>().unwrap().format, CheatFormat::ProActionReplay);
+	/// ```
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.contains('-') {
+			Self::from_game_genie(s)
+		} else {
+			Self::from_pro_action_replay(s)
+		}
+	}
+}
+
+impl fmt::Display for Cheat {
+	/// Writes the code in its original format.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let address = u32::from(self.address);
+		match self.format {
+			CheatFormat::GameGenie => {
+				let code = (self.value as u32) << 24 | scramble(address);
+				for i in (0..8).rev() {
+					if i == 3 {
+						write!(f, "-")?;
+					}
+					let digit = GAME_GENIE_DIGITS[(code >> (4 * i) & 0xF) as usize];
+					write!(f, "{}", digit as char)?;
+				}
+				Ok(())
+			}
+			CheatFormat::ProActionReplay => write!(f, "{:06X}:{:02X}", address, self.value),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Patches a ROM-targeting cheat into the image of the detected ROM type,
+	/// returning the byte it replaced.
+	///
+	/// The checksum is left as is, see [`fix_checksum`](crate::cartridge::Cartridge::fix_checksum).
+	pub fn apply_cheat(&mut self, cheat: &Cheat) -> Result<u8, CheatError> {
+		let offset = cheat
+			.rom_offset(self.detect_rom_type().0)
+			.ok_or(CheatError::NotRom(cheat.address))?;
+		let byte = self
+			.rom
+			.get_mut(offset)
+			.ok_or(CheatError::NotRom(cheat.address))?;
+		let replaced = core::mem::replace(byte, cheat.value);
+		self.rom_changed();
+		Ok(replaced)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn game_genie() {
+		for &address in [0x000000, 0x008000, 0xC0FFEE, 0x123456, 0xFFFFFF].iter() {
+			assert_eq!(descramble(scramble(address)), address);
+		}
+		assert_eq!(scramble(0x008000), 0x800000);
+
+		let cheat = Cheat::from_game_genie("3c6ddddd").unwrap();
+		assert_eq!(cheat.address, Address24::new(0x008000));
+		assert_eq!(cheat.value, 0xEA);
+		assert_eq!(cheat.to_string(), "3C6D-DDDD");
+		for code in ["1DB4-D7A6", "F4C1-6FE7", "EE61-3DA0"].iter() {
+			assert_eq!(&code.parse::<Cheat>().unwrap().to_string(), code);
+		}
+		assert_eq!(
+			Cheat::from_game_genie("3C6D-DDDG"),
+			Err(CheatError::ParseError)
+		);
+		assert_eq!(
+			Cheat::from_game_genie("3C6DD-DDD"),
+			Err(CheatError::ParseError)
+		);
+	}
+
+	#[test]
+	fn pro_action_replay() {
+		let cheat = "7e0dbf09".parse::<Cheat>().unwrap();
+		assert_eq!(cheat.address, Address24::new(0x7E0DBF));
+		assert_eq!(cheat.value, 0x09);
+		assert_eq!(cheat.to_string(), "7E0DBF:09");
+		assert!(!cheat.is_rom(ROMType::LoROM));
+		let cheat = Cheat::from_pro_action_replay("C12345:EA").unwrap();
+		assert_eq!(cheat.rom_offset(ROMType::HiROM), Some(0x12345));
+		assert_eq!("7E0DBF:0".parse::<Cheat>(), Err(CheatError::ParseError));
+		assert_eq!("7E0DBF;09".parse::<Cheat>(), Err(CheatError::ParseError));
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn apply_cheat() {
+		use crate::cartridge::{test::make_rom, TestFlags};
+
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let mut cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let cheat = "3C6D-DDDD".parse().unwrap();
+		assert_eq!(cartridge.apply_cheat(&cheat), Ok(0x78));
+		assert_eq!(cartridge.rom()[0], 0xEA);
+
+		let cheat = "7E0DBF:09".parse().unwrap();
+		assert_eq!(
+			cartridge.apply_cheat(&cheat),
+			Err(CheatError::NotRom(Address24::new(0x7E0DBF)))
+		);
+		let cheat = "BF8000:09".parse().unwrap();
+		assert_eq!(
+			cartridge.apply_cheat(&cheat),
+			Err(CheatError::NotRom(Address24::new(0xBF8000)))
+		);
+	}
+}
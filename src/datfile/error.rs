@@ -0,0 +1,51 @@
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+pub enum DatError {
+	Io(io::Error),
+	Xml(roxmltree::Error),
+	/// The root element is not a `datafile`.
+	NotDatFile,
+	/// An attribute of a `rom` element is missing or malformed.
+	InvalidAttribute {
+		name: &'static str,
+		game: String,
+	},
+}
+
+impl From<io::Error> for DatError {
+	fn from(e: io::Error) -> Self {
+		Self::Io(e)
+	}
+}
+
+impl From<roxmltree::Error> for DatError {
+	fn from(e: roxmltree::Error) -> Self {
+		Self::Xml(e)
+	}
+}
+
+impl fmt::Display for DatError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use DatError::*;
+		match self {
+			Io(e) => e.fmt(f),
+			Xml(e) => e.fmt(f),
+			NotDatFile => write!(f, "the root element is not a datafile"),
+			InvalidAttribute { name, game } => {
+				write!(f, "invalid {} attribute of a rom of {}", name, game)
+			}
+		}
+	}
+}
+
+impl Error for DatError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		use DatError::*;
+		match self {
+			Io(e) => Some(e),
+			Xml(e) => Some(e),
+			NotDatFile | InvalidAttribute { .. } => None,
+		}
+	}
+}
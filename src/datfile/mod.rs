@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use crate::cartridge::{Cartridge, Hashes};
+
+pub use error::DatError;
+
+pub mod error;
+
+/// Dump status declared for a ROM by the DAT file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DumpStatus {
+	/// No status, the dump is believed to be good.
+	Good,
+	/// The dump was verified against multiple cartridges.
+	Verified,
+	/// The dump is known to be bad.
+	BadDump,
+	/// No dump exists, the entry has no hashes.
+	NoDump,
+}
+
+/// ROM entry of a game.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RomEntry {
+	pub name: String,
+	pub size: usize,
+	pub crc32: Option<u32>,
+	pub md5: Option<[u8; 16]>,
+	pub sha1: Option<[u8; 20]>,
+	pub sha256: Option<[u8; 32]>,
+	pub status: DumpStatus,
+}
+
+/// Game entry with its canonical name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Game {
+	pub name: String,
+	pub description: Option<String>,
+	pub roms: Vec<RomEntry>,
+}
+
+/// Parsed No-Intro or Redump DAT file in the Logiqx XML format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatFile {
+	/// Name from the header, e.g. `Nintendo - Super Nintendo Entertainment System`.
+	pub name: Option<String>,
+	/// Version from the header.
+	pub version: Option<String>,
+	pub games: Vec<Game>,
+}
+
+/// Status of a ROM matched against a DAT file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchStatus {
+	Good,
+	Verified,
+	BadDump,
+	/// The ROM is larger than the entry, which matches the leading bytes.
+	Overdump,
+}
+
+/// Game and ROM entry a ROM matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatMatch<'a> {
+	pub game: &'a Game,
+	pub rom: &'a RomEntry,
+	pub status: MatchStatus,
+}
+
+impl<'a> DatMatch<'a> {
+	/// Returns the canonical name of the game.
+	#[inline]
+	pub fn name(&self) -> &'a str {
+		&self.game.name
+	}
+}
+
+fn parse_hex<const N: usize>(s: &str) -> Option<[u8; N]> {
+	if s.len() != 2 * N || !s.is_ascii() {
+		return None;
+	}
+	let mut bytes = [0; N];
+	for (i, b) in bytes.iter_mut().enumerate() {
+		*b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+	}
+	Some(bytes)
+}
+
+impl RomEntry {
+	fn from_node(node: roxmltree::Node, game: &str) -> Result<Self, DatError> {
+		let invalid = |name| DatError::InvalidAttribute {
+			name,
+			game: game.into(),
+		};
+		let status = match node.attribute("status") {
+			None | Some("good") => DumpStatus::Good,
+			Some("verified") => DumpStatus::Verified,
+			Some("baddump") => DumpStatus::BadDump,
+			Some("nodump") => DumpStatus::NoDump,
+			Some(_) => return Err(invalid("status")),
+		};
+		Ok(Self {
+			name: node
+				.attribute("name")
+				.ok_or_else(|| invalid("name"))?
+				.into(),
+			size: node
+				.attribute("size")
+				.and_then(|size| size.parse().ok())
+				.ok_or_else(|| invalid("size"))?,
+			crc32: node
+				.attribute("crc")
+				.map(|crc| u32::from_str_radix(crc, 16).map_err(|_| invalid("crc")))
+				.transpose()?,
+			md5: node
+				.attribute("md5")
+				.map(|md5| parse_hex(md5).ok_or_else(|| invalid("md5")))
+				.transpose()?,
+			sha1: node
+				.attribute("sha1")
+				.map(|sha1| parse_hex(sha1).ok_or_else(|| invalid("sha1")))
+				.transpose()?,
+			sha256: node
+				.attribute("sha256")
+				.map(|sha256| parse_hex(sha256).ok_or_else(|| invalid("sha256")))
+				.transpose()?,
+			status,
+		})
+	}
+
+	/// Returns `true` if the hashes agree with every hash of the entry.
+	///
+	/// Digests other than CRC-32 are only compared with the `hashes` feature.
+	/// An entry without hashes matches nothing.
+	pub fn matches(&self, hashes: &Hashes) -> bool {
+		if self.status == DumpStatus::NoDump || self.crc32.is_none() {
+			return false;
+		}
+		#[cfg(feature = "hashes")]
+		{
+			if self.md5.is_some_and(|md5| md5 != hashes.md5)
+				|| self.sha1.is_some_and(|sha1| sha1 != hashes.sha1)
+				|| self.sha256.is_some_and(|sha256| sha256 != hashes.sha256)
+			{
+				return false;
+			}
+		}
+		self.crc32 == Some(hashes.crc32)
+	}
+}
+
+impl DatFile {
+	/// Parses a DAT file from its XML text.
+	pub fn parse(xml: &str) -> Result<Self, DatError> {
+		let options = roxmltree::ParsingOptions {
+			allow_dtd: true,
+			..Default::default()
+		};
+		let document = roxmltree::Document::parse_with_options(xml, options)?;
+		let root = document.root_element();
+		if !root.has_tag_name("datafile") {
+			return Err(DatError::NotDatFile);
+		}
+
+		let child_text = |node: roxmltree::Node, name: &str| {
+			node.children()
+				.find(|child| child.has_tag_name(name))
+				.and_then(|child| child.text())
+				.map(String::from)
+		};
+		let header = root.children().find(|node| node.has_tag_name("header"));
+		let mut dat = Self {
+			name: header.and_then(|header| child_text(header, "name")),
+			version: header.and_then(|header| child_text(header, "version")),
+			games: Vec::new(),
+		};
+		for node in root
+			.children()
+			.filter(|node| node.has_tag_name("game") || node.has_tag_name("machine"))
+		{
+			let name = node.attribute("name").unwrap_or_default();
+			let roms = node
+				.children()
+				.filter(|child| child.has_tag_name("rom"))
+				.map(|rom| RomEntry::from_node(rom, name))
+				.collect::<Result<_, _>>()?;
+			dat.games.push(Game {
+				name: name.into(),
+				description: child_text(node, "description"),
+				roms,
+			});
+		}
+		Ok(dat)
+	}
+
+	/// Loads and parses a DAT file.
+	pub fn from_file<P>(path: P) -> Result<Self, DatError>
+	where
+		P: AsRef<std::path::Path>,
+	{
+		Self::parse(&std::fs::read_to_string(path)?)
+	}
+
+	fn entries(&self) -> impl Iterator<Item = (&Game, &RomEntry)> {
+		self.games
+			.iter()
+			.flat_map(|game| game.roms.iter().map(move |rom| (game, rom)))
+	}
+
+	/// Matches a ROM image by size and hashes.
+	///
+	/// A ROM larger than an entry matching its leading bytes is reported as an overdump.
+	pub fn find(&self, rom: &[u8]) -> Option<DatMatch<'_>> {
+		let hashes = Hashes::from_bytes(rom);
+		let exact = self
+			.entries()
+			.find(|(_, entry)| entry.size == rom.len() && entry.matches(&hashes));
+		if let Some((game, entry)) = exact {
+			let status = match entry.status {
+				DumpStatus::Verified => MatchStatus::Verified,
+				DumpStatus::BadDump => MatchStatus::BadDump,
+				DumpStatus::Good | DumpStatus::NoDump => MatchStatus::Good,
+			};
+			return Some(DatMatch {
+				game,
+				rom: entry,
+				status,
+			});
+		}
+
+		let mut prefixes = HashMap::new();
+		self.entries()
+			.filter(|(_, entry)| entry.size > 0 && entry.size < rom.len())
+			.find(|(_, entry)| {
+				let prefix = prefixes
+					.entry(entry.size)
+					.or_insert_with(|| Hashes::from_bytes(&rom[..entry.size]));
+				entry.matches(prefix)
+			})
+			.map(|(game, rom)| DatMatch {
+				game,
+				rom,
+				status: MatchStatus::Overdump,
+			})
+	}
+
+	/// Matches a cartridge by the hashes of its ROM without the copier header.
+	#[inline]
+	pub fn find_cartridge(&self, cartridge: &Cartridge) -> Option<DatMatch<'_>> {
+		self.find(cartridge.rom())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::cartridge::{test::make_rom, ROMType, TestFlags};
+
+	fn dat_xml(rom: &[u8], status: &str) -> String {
+		let hashes = Hashes::from_bytes(rom);
+		#[cfg(feature = "hashes")]
+		let digests = {
+			let hex = |bytes: &[u8]| {
+				bytes
+					.iter()
+					.map(|b| format!("{:02x}", b))
+					.collect::<String>()
+			};
+			format!(
+				r#" md5="{}" sha1="{}""#,
+				hex(&hashes.md5),
+				hex(&hashes.sha1)
+			)
+		};
+		#[cfg(not(feature = "hashes"))]
+		let digests = "";
+		format!(
+			r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
+<datafile>
+	<header>
+		<name>Nintendo - Super Nintendo Entertainment System</name>
+		<version>20201016-000000</version>
+	</header>
+	<game name="Other Game (Japan)">
+		<description>Other Game (Japan)</description>
+		<rom name="Other Game (Japan).sfc" size="524288" crc="00000000"/>
+	</game>
+	<game name="Sneslib Test (USA) &amp; (Europe)">
+		<description>Sneslib Test (USA) &amp; (Europe)</description>
+		<rom name="Sneslib Test (USA).sfc" size="{}" crc="{:08x}"{}{}/>
+	</game>
+</datafile>"#,
+			rom.len(),
+			hashes.crc32,
+			digests,
+			status
+		)
+	}
+
+	#[test]
+	fn parse() {
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let dat = DatFile::parse(&dat_xml(&rom, r#" status="verified""#)).unwrap();
+		assert_eq!(dat.version.as_deref(), Some("20201016-000000"));
+		assert_eq!(dat.games.len(), 2);
+		let game = &dat.games[1];
+		assert_eq!(game.name, "Sneslib Test (USA) & (Europe)");
+		assert_eq!(game.roms[0].size, 0x80000);
+		assert_eq!(game.roms[0].crc32, Some(Hashes::from_bytes(&rom).crc32));
+		assert_eq!(game.roms[0].status, DumpStatus::Verified);
+
+		assert!(matches!(
+			DatFile::parse("<softwarelist/>"),
+			Err(DatError::NotDatFile)
+		));
+		assert!(matches!(
+			DatFile::parse(r#"<datafile><game name="A"><rom name="a.sfc"/></game></datafile>"#),
+			Err(DatError::InvalidAttribute { name: "size", .. })
+		));
+		assert!(matches!(
+			DatFile::parse("<datafile>"),
+			Err(DatError::Xml(_))
+		));
+	}
+
+	#[test]
+	fn find() {
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let dat = DatFile::parse(&dat_xml(&rom, "")).unwrap();
+		let cartridge = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let found = dat.find_cartridge(&cartridge).unwrap();
+		assert_eq!(found.name(), "Sneslib Test (USA) & (Europe)");
+		assert_eq!(found.status, MatchStatus::Good);
+
+		let dat = DatFile::parse(&dat_xml(&rom, r#" status="baddump""#)).unwrap();
+		assert_eq!(dat.find(&rom).unwrap().status, MatchStatus::BadDump);
+
+		let mut overdump = rom.clone();
+		overdump.extend_from_slice(&rom);
+		let found = dat.find(&overdump).unwrap();
+		assert_eq!(found.status, MatchStatus::Overdump);
+		assert_eq!(found.rom.size, rom.len());
+
+		let mut modified = rom.clone();
+		modified[0] ^= 0xFF;
+		assert_eq!(dat.find(&modified), None);
+	}
+}
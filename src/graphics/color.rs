@@ -7,7 +7,7 @@ pub struct RGB(pub u8, pub u8, pub u8);
 /// 15-bit SNES color type.
 ///
 /// `0bbbbbgggggrrrrr`
-#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct SNESColor(pub u16);
 
 impl From<SNESColor> for RGB {
@@ -23,9 +23,9 @@ impl From<SNESColor> for RGB {
 impl From<RGB> for SNESColor {
 	fn from(color: RGB) -> SNESColor {
 		let RGB(r, g, b) = color;
-		let r = std::cmp::min(r as u16 + 4, 0xF8) & 0xF8;
-		let g = std::cmp::min(g as u16 + 4, 0xF8) & 0xF8;
-		let b = std::cmp::min(b as u16 + 4, 0xF8) & 0xF8;
+		let r = core::cmp::min(r as u16 + 4, 0xF8) & 0xF8;
+		let g = core::cmp::min(g as u16 + 4, 0xF8) & 0xF8;
+		let b = core::cmp::min(b as u16 + 4, 0xF8) & 0xF8;
 		SNESColor(r >> 3 | g << 2 | b << 7)
 	}
 }
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+use super::SNESColor;
+
+impl SNESColor {
+	#[inline]
+	fn components(self) -> [u8; 3] {
+		[self.r(), self.g(), self.b()]
+	}
+
+	#[inline]
+	fn from_components([r, g, b]: [u8; 3]) -> Self {
+		SNESColor(r as u16 | (g as u16) << 5 | (b as u16) << 10)
+	}
+
+	#[inline]
+	fn zip_with(self, other: Self, f: impl Fn(u8, u8) -> u8) -> Self {
+		let (a, b) = (self.components(), other.components());
+		Self::from_components([0, 1, 2].map(|i| f(a[i], b[i])))
+	}
+
+	/// Adds the components, each saturating at 31.
+	/// ```
+	/// # use sneslib::graphics::SNESColor;
+	/// assert_eq!(SNESColor(0x4210).saturating_add(SNESColor(0x7C01)), SNESColor(0x7E11));
+	/// ```
+	#[inline]
+	pub fn saturating_add(self, other: Self) -> Self {
+		self.zip_with(other, |a, b| (a + b).min(0x1F))
+	}
+
+	/// Subtracts the components, each saturating at 0.
+	#[inline]
+	pub fn saturating_sub(self, other: Self) -> Self {
+		self.zip_with(other, u8::saturating_sub)
+	}
+
+	/// Adds the components and halves them, rounding down.
+	#[inline]
+	pub fn half_add(self, other: Self) -> Self {
+		self.zip_with(other, |a, b| (a + b) >> 1)
+	}
+
+	/// Subtracts the components, each saturating at 0, and halves them, rounding down.
+	#[inline]
+	pub fn half_sub(self, other: Self) -> Self {
+		self.zip_with(other, |a, b| a.saturating_sub(b) >> 1)
+	}
+
+	/// Updates the components selected by a write of `value` to `COLDATA` (`$2132`),
+	/// as the fixed color of the color math.
+	///
+	/// Bits 5, 6 and 7 select red, green and blue, which take the intensity in bits 0-4.
+	/// ```
+	/// # use sneslib::graphics::SNESColor;
+	/// let mut fixed = SNESColor::default();
+	/// fixed.write_coldata(0xE0 | 0x04);
+	/// fixed.write_coldata(0x20 | 0x1F);
+	/// assert_eq!(fixed, SNESColor(0x109F));
+	/// ```
+	#[inline]
+	pub fn write_coldata(&mut self, value: u8) {
+		let mut components = self.components();
+		for (i, component) in components.iter_mut().enumerate() {
+			if value & 0x20 << i != 0 {
+				*component = value & 0x1F;
+			}
+		}
+		*self = Self::from_components(components);
+	}
+}
+
+/// Color math of the S-PPU, combining a main screen color with a sub screen or fixed color,
+/// as set by `CGADSUB` (`$2131`).
+///
+/// Which layers take part, whether the sub screen or the fixed color is used and the color window
+/// are left to the renderer, which also skips halving where the sub screen shows the backdrop.
+/// ```
+/// # use sneslib::graphics::color_math::ColorMath;
+/// # use sneslib::graphics::SNESColor;
+/// let math = ColorMath::from_cgadsub(0xC1);
+/// assert!(math.subtract && math.half);
+/// assert_eq!(math.apply(SNESColor(0x7FFF), SNESColor(0x0421)), SNESColor(0x3DEF));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ColorMath {
+	/// Subtracts the sub screen color rather than adding it.
+	pub subtract: bool,
+	/// Halves the result.
+	pub half: bool,
+}
+
+impl ColorMath {
+	/// Returns the color math set by bits 7 and 6 of `CGADSUB`, ignoring the layer bits.
+	#[inline]
+	pub fn from_cgadsub(value: u8) -> Self {
+		Self {
+			subtract: value & 0x80 != 0,
+			half: value & 0x40 != 0,
+		}
+	}
+
+	/// Combines a main screen color with a sub screen or fixed color.
+	#[inline]
+	pub fn apply(self, main: SNESColor, sub: SNESColor) -> SNESColor {
+		match (self.subtract, self.half) {
+			(false, false) => main.saturating_add(sub),
+			(false, true) => main.half_add(sub),
+			(true, false) => main.saturating_sub(sub),
+			(true, true) => main.half_sub(sub),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn color_math() {
+		let (a, b) = (SNESColor(0x7C1F), SNESColor(0x03F0));
+		assert_eq!(a.saturating_add(b), SNESColor(0x7FFF));
+		assert_eq!(a.saturating_sub(b), SNESColor(0x7C0F));
+		assert_eq!(b.saturating_sub(a), SNESColor(0x03E0));
+		assert_eq!(a.half_add(b), SNESColor(0x3DF7));
+		assert_eq!(a.half_sub(b), SNESColor(0x3C07));
+		// the unused top bit is dropped
+		assert_eq!(SNESColor(0x8000).saturating_add(b), b);
+
+		for value in 0..=0xFF {
+			let math = ColorMath::from_cgadsub(value);
+			let expected = match value >> 6 {
+				0 => a.saturating_add(b),
+				1 => a.half_add(b),
+				2 => a.saturating_sub(b),
+				_ => a.half_sub(b),
+			};
+			assert_eq!(math.apply(a, b), expected);
+		}
+	}
+
+	#[test]
+	fn coldata() {
+		let mut fixed = SNESColor(0x7FFF);
+		fixed.write_coldata(0x1F);
+		assert_eq!(fixed, SNESColor(0x7FFF));
+		fixed.write_coldata(0x40);
+		assert_eq!(fixed, SNESColor(0x7C1F));
+		fixed.write_coldata(0xA3);
+		assert_eq!(fixed, SNESColor(0x0C03));
+	}
+}
@@ -1,3 +1,21 @@
 pub use color::{SNESColor, RGB};
+pub use color_math::ColorMath;
+pub use oam::{OamEntry, OamTable};
+pub use palette::Palette;
+#[cfg(feature = "alloc")]
+pub use palette_file::PaletteError;
+pub use tile::{Tile2bpp, Tile4bpp, Tile8bpp};
+#[cfg(feature = "alloc")]
+pub use tilemap::Tilemap;
+pub use tilemap::TilemapEntry;
 
 pub mod color;
+pub mod color_math;
+pub mod oam;
+pub mod palette;
+#[cfg(feature = "alloc")]
+pub mod palette_file;
+#[cfg(feature = "image")]
+pub mod png;
+pub mod tile;
+pub mod tilemap;
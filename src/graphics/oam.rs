@@ -0,0 +1,208 @@
+use core::ops::{Index, IndexMut};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of sprites in OAM.
+pub const SPRITES: usize = 128;
+
+/// Size of OAM in bytes, the 512-byte low table followed by the 32-byte high table.
+pub const OAM_SIZE: usize = SPRITES * 4 + SPRITES / 4;
+
+/// Sprite of OAM, decoded from its 4 bytes in the low table and 2 bits in the high table.
+/// ```
+/// # use sneslib::graphics::oam::OamEntry;
+/// let entry = OamEntry::from_bytes([0xF0, 0x20, 0x42, 0x7B], 0b11);
+/// assert_eq!((entry.x, entry.y, entry.character), (-16, 0x20, 0x142));
+/// assert_eq!((entry.palette, entry.priority), (5, 3));
+/// assert!(entry.h_flip && !entry.v_flip && entry.large);
+/// assert_eq!(entry.to_bytes(), ([0xF0, 0x20, 0x42, 0x7B], 0b11));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OamEntry {
+	/// Horizontal position, -256 to 255, of which only the low 9 bits are stored.
+	pub x: i16,
+	/// Vertical position, sprites below the screen wrapping around to the top.
+	pub y: u8,
+	/// Character number, 0 to 511, the first 256 in the first name table and the rest in the second.
+	pub character: u16,
+	/// Palette row, 0 to 7, among the OBJ palettes.
+	pub palette: u8,
+	/// Priority against the BG layers, 0 to 3.
+	pub priority: u8,
+	pub h_flip: bool,
+	pub v_flip: bool,
+	/// Whether the sprite takes the large of the two sizes set by `OBSEL`.
+	pub large: bool,
+}
+
+impl OamEntry {
+	/// Decodes a sprite from its bytes in the low table and its 2 bits in the high table,
+	/// bit 0 holding bit 8 of X and bit 1 the size.
+	pub fn from_bytes(low: [u8; 4], high: u8) -> Self {
+		let x = u16::from(low[0]) | u16::from(high & 1) << 8;
+		Self {
+			// sign-extend the 9-bit position
+			x: (x << 7) as i16 >> 7,
+			y: low[1],
+			character: u16::from(low[2]) | u16::from(low[3] & 1) << 8,
+			palette: low[3] >> 1 & 7,
+			priority: low[3] >> 4 & 3,
+			h_flip: low[3] & 0x40 != 0,
+			v_flip: low[3] & 0x80 != 0,
+			large: high & 2 != 0,
+		}
+	}
+
+	/// Encodes the sprite as read by [`from_bytes`](#method.from_bytes), keeping the low bits of each field.
+	pub fn to_bytes(&self) -> ([u8; 4], u8) {
+		let attributes = (self.character >> 8 & 1) as u8
+			| (self.palette & 7) << 1
+			| (self.priority & 3) << 4
+			| (self.h_flip as u8) << 6
+			| (self.v_flip as u8) << 7;
+		let high = (self.x >> 8 & 1) as u8 | (self.large as u8) << 1;
+		(
+			[self.x as u8, self.y, self.character as u8, attributes],
+			high,
+		)
+	}
+}
+
+/// The 128 sprites of OAM.
+///
+/// Where sprites overlap, the one of lower index is drawn on top, starting from sprite 0
+/// or, with priority rotation enabled in `OAMADDH`, from the sprite set by `OAMADDL`,
+/// see [`by_priority`](#method.by_priority).
+/// ```
+/// # use sneslib::graphics::oam::*;
+/// let mut oam = [0; OAM_SIZE];
+/// oam[4..8].copy_from_slice(&[0x10, 0x20, 0x01, 0x30]);
+/// oam[0x200] = 0b1000;
+/// let table = OamTable::from_bytes(&oam);
+/// assert_eq!(table[1].character, 0x01);
+/// assert!(table[1].large);
+/// assert_eq!(table.to_bytes(), oam);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OamTable([OamEntry; SPRITES]);
+
+impl Default for OamTable {
+	fn default() -> Self {
+		Self([OamEntry::default(); SPRITES])
+	}
+}
+
+impl Index<usize> for OamTable {
+	type Output = OamEntry;
+
+	#[inline]
+	fn index(&self, index: usize) -> &OamEntry {
+		&self.0[index]
+	}
+}
+
+impl IndexMut<usize> for OamTable {
+	#[inline]
+	fn index_mut(&mut self, index: usize) -> &mut OamEntry {
+		&mut self.0[index]
+	}
+}
+
+impl OamTable {
+	/// Decodes all sprites from OAM.
+	pub fn from_bytes(oam: &[u8; OAM_SIZE]) -> Self {
+		let (low, high) = oam.split_at(SPRITES * 4);
+		let mut table = Self::default();
+		for (i, entry) in table.0.iter_mut().enumerate() {
+			let low = [low[i * 4], low[i * 4 + 1], low[i * 4 + 2], low[i * 4 + 3]];
+			*entry = OamEntry::from_bytes(low, high[i / 4] >> (i % 4 * 2) & 3);
+		}
+		table
+	}
+
+	/// Encodes all sprites as OAM.
+	pub fn to_bytes(&self) -> [u8; OAM_SIZE] {
+		let mut oam = [0; OAM_SIZE];
+		let (low, high) = oam.split_at_mut(SPRITES * 4);
+		for (i, entry) in self.0.iter().enumerate() {
+			let (bytes, bits) = entry.to_bytes();
+			low[i * 4..][..4].copy_from_slice(&bytes);
+			high[i / 4] |= (bits & 3) << (i % 4 * 2);
+		}
+		oam
+	}
+
+	#[inline]
+	pub fn entries(&self) -> &[OamEntry; SPRITES] {
+		&self.0
+	}
+
+	#[inline]
+	pub fn entries_mut(&mut self) -> &mut [OamEntry; SPRITES] {
+		&mut self.0
+	}
+
+	/// Iterates over the sprites and their indices from the one drawn on top, sprite `first`,
+	/// wrapping around after sprite 127.
+	///
+	/// `first` is 0, or `OAMADDL >> 1` with priority rotation enabled.
+	///
+	/// # Panics
+	/// Panics if `first` is greater than 127.
+	pub fn by_priority(
+		&self,
+		first: usize,
+	) -> impl ExactSizeIterator<Item = (usize, &OamEntry)> + '_ {
+		assert!(first < SPRITES, "sprite out of range");
+		(0..SPRITES).map(move |i| {
+			let index = (first + i) % SPRITES;
+			(index, &self.0[index])
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn entry() {
+		let entry = OamEntry::from_bytes([0x00, 0xF0, 0xFF, 0x81], 0b01);
+		assert_eq!(entry.x, -256);
+		assert_eq!(entry.character, 0x1FF);
+		assert!(entry.v_flip && !entry.h_flip && !entry.large);
+		assert_eq!(OamEntry::from_bytes([0xFF, 0, 0, 0], 0).x, 255);
+
+		let entry = OamEntry {
+			x: -1,
+			palette: 0xFF,
+			priority: 2,
+			..OamEntry::default()
+		};
+		assert_eq!(entry.to_bytes(), ([0xFF, 0x00, 0x00, 0x2E], 0b01));
+	}
+
+	#[test]
+	fn table() {
+		let oam: [u8; OAM_SIZE] = core::array::from_fn(|i| (i * 37 + 11) as u8);
+		let table = OamTable::from_bytes(&oam);
+		assert_eq!(table.to_bytes(), oam);
+		// the high table of sprite 127 is in the top bits of the last byte
+		assert_eq!(oam[OAM_SIZE - 1] >> 6, 0b10);
+		assert!(table[127].large && table[127].x >= 0);
+
+		let mut table = OamTable::default();
+		table[126].y = 0xE0;
+		table.entries_mut()[3].character = 0x100;
+		let oam = table.to_bytes();
+		assert_eq!(oam[126 * 4 + 1], 0xE0);
+		assert_eq!(oam[3 * 4 + 3], 0x01);
+		assert_eq!(table.entries()[126].y, 0xE0);
+
+		let order = table.by_priority(126).map(|(i, _)| i).collect::<Vec<_>>();
+		assert_eq!(order.len(), SPRITES);
+		assert_eq!(order[..3], [126, 127, 0]);
+		assert_eq!(table.by_priority(126).next().unwrap().1.y, 0xE0);
+		assert_eq!(table.by_priority(0).last().unwrap().0, 127);
+	}
+}
@@ -0,0 +1,175 @@
+use core::convert::TryInto;
+use core::ops::{Index, IndexMut};
+
+use super::SNESColor;
+
+/// Number of colors in CGRAM.
+pub const PALETTE_SIZE: usize = 256;
+
+/// Size of CGRAM in bytes, each color taking two bytes.
+pub const CGRAM_SIZE: usize = PALETTE_SIZE * 2;
+
+/// The 256 colors of CGRAM.
+///
+/// Colors 0-127 hold the BG palettes and 128-255 the OBJ palettes, split into rows of 16 colors for 4bpp tiles
+/// or 4 colors for 2bpp tiles. Color 0 of each row is transparent, and color 0 of the palette is the backdrop.
+/// ```
+/// # use sneslib::graphics::*;
+/// let mut palette = Palette::default();
+/// palette[0x81] = SNESColor(0x001F);
+/// assert_eq!(palette.obj_row(0)[1], SNESColor(0x001F));
+/// assert_eq!(palette.row::<4>(0x20)[1], SNESColor(0x001F));
+///
+/// let cgram = palette.to_cgram();
+/// assert_eq!(&cgram[0x102..0x104], &[0x1F, 0x00]);
+/// assert_eq!(Palette::from_cgram(&cgram), palette);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Palette([SNESColor; PALETTE_SIZE]);
+
+impl Default for Palette {
+	fn default() -> Self {
+		Self([SNESColor::default(); PALETTE_SIZE])
+	}
+}
+
+impl From<[SNESColor; PALETTE_SIZE]> for Palette {
+	#[inline]
+	fn from(colors: [SNESColor; PALETTE_SIZE]) -> Self {
+		Self(colors)
+	}
+}
+
+impl Index<usize> for Palette {
+	type Output = SNESColor;
+
+	#[inline]
+	fn index(&self, index: usize) -> &SNESColor {
+		&self.0[index]
+	}
+}
+
+impl IndexMut<usize> for Palette {
+	#[inline]
+	fn index_mut(&mut self, index: usize) -> &mut SNESColor {
+		&mut self.0[index]
+	}
+}
+
+impl Palette {
+	/// Reads CGRAM as little-endian colors, ignoring the unused top bit of each.
+	pub fn from_cgram(cgram: &[u8; CGRAM_SIZE]) -> Self {
+		let mut palette = Self::default();
+		for (color, bytes) in palette.0.iter_mut().zip(cgram.chunks_exact(2)) {
+			*color = SNESColor(u16::from_le_bytes([bytes[0], bytes[1]]) & 0x7FFF);
+		}
+		palette
+	}
+
+	/// Writes the colors as CGRAM, each little-endian.
+	pub fn to_cgram(&self) -> [u8; CGRAM_SIZE] {
+		let mut cgram = [0; CGRAM_SIZE];
+		for (bytes, color) in cgram.chunks_exact_mut(2).zip(self.0.iter()) {
+			bytes.copy_from_slice(&color.0.to_le_bytes());
+		}
+		cgram
+	}
+
+	/// Returns all 256 colors.
+	#[inline]
+	pub fn colors(&self) -> &[SNESColor; PALETTE_SIZE] {
+		&self.0
+	}
+
+	#[inline]
+	pub fn colors_mut(&mut self) -> &mut [SNESColor; PALETTE_SIZE] {
+		&mut self.0
+	}
+
+	/// Returns row `index` of the palette split into rows of `N` colors,
+	/// e.g. `row::<16>(9)` for the colors of OBJ palette 1.
+	///
+	/// # Panics
+	/// Panics if the row lies beyond the palette.
+	#[inline]
+	pub fn row<const N: usize>(&self, index: usize) -> &[SNESColor; N] {
+		self.0[index * N..][..N].try_into().unwrap()
+	}
+
+	/// Returns row `index` of `N` colors as [`row`](#method.row) does.
+	///
+	/// # Panics
+	/// Panics if the row lies beyond the palette.
+	#[inline]
+	pub fn row_mut<const N: usize>(&mut self, index: usize) -> &mut [SNESColor; N] {
+		(&mut self.0[index * N..][..N]).try_into().unwrap()
+	}
+
+	/// Returns BG palette `index` of 16 colors for 4bpp tiles, 0 to 7 at colors 0-127.
+	///
+	/// # Panics
+	/// Panics if `index` is greater than 7.
+	#[inline]
+	pub fn bg_row(&self, index: usize) -> &[SNESColor; 16] {
+		assert!(index < 8, "BG palette out of range");
+		self.row(index)
+	}
+
+	/// Returns OBJ palette `index` of 16 colors, 0 to 7 at colors 128-255.
+	///
+	/// # Panics
+	/// Panics if `index` is greater than 7.
+	#[inline]
+	pub fn obj_row(&self, index: usize) -> &[SNESColor; 16] {
+		assert!(index < 8, "OBJ palette out of range");
+		self.row(8 + index)
+	}
+
+	/// Returns BG palette `index` of 4 colors for 2bpp tiles, 0 to 7, of the BG layer `layer`, 0 to 3,
+	/// as laid out in Mode 0 with the palettes of each layer at colors `32 * layer` onwards.
+	///
+	/// In other modes, the 2bpp layer takes the palettes of layer 0.
+	///
+	/// # Panics
+	/// Panics if `layer` is greater than 3 or `index` greater than 7.
+	#[inline]
+	pub fn bg_row4(&self, layer: usize, index: usize) -> &[SNESColor; 4] {
+		assert!(layer < 4 && index < 8, "BG palette out of range");
+		self.row(layer * 8 + index)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn palette() {
+		let palette = Palette::from(core::array::from_fn(|i| SNESColor(i as u16)));
+		assert_eq!(palette.bg_row(7)[15], SNESColor(127));
+		assert_eq!(palette.obj_row(7)[0], SNESColor(240));
+		assert_eq!(palette.bg_row4(0, 1), &[4, 5, 6, 7].map(SNESColor));
+		assert_eq!(palette.bg_row4(3, 7)[3], SNESColor(127));
+		assert_eq!(palette.row::<2>(127), &[254, 255].map(SNESColor));
+
+		let mut cgram = palette.to_cgram();
+		assert_eq!(&cgram[0x1FE..], &[0xFF, 0x00]);
+		assert_eq!(Palette::from_cgram(&cgram), palette);
+		// the top bit is not stored
+		cgram[1] = 0x80;
+		assert_eq!(Palette::from_cgram(&cgram)[0], SNESColor(0));
+
+		let mut palette = palette;
+		palette.row_mut::<16>(1).fill(SNESColor(0x7FFF));
+		assert_eq!(palette[16], SNESColor(0x7FFF));
+		assert_eq!(palette.colors()[31], SNESColor(0x7FFF));
+		palette.colors_mut()[31] = SNESColor(0);
+		assert_eq!(palette.bg_row(1)[15], SNESColor(0));
+	}
+
+	#[test]
+	#[should_panic(expected = "OBJ palette out of range")]
+	fn obj_row() {
+		Palette::default().obj_row(8);
+	}
+}
@@ -0,0 +1,246 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use super::{Palette, SNESColor, RGB};
+
+/// Magic of a Tile Layer Pro palette.
+const TPL_MAGIC: &[u8; 3] = b"TPL";
+
+/// First line of a GIMP palette.
+const GPL_MAGIC: &str = "GIMP Palette";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteError {
+	/// The file does not start with the magic of its format.
+	InvalidHeader,
+	/// The file ends in the middle of a color.
+	UnexpectedEnd,
+	/// The colors are in a format not supported, e.g. the NES colors of a TPL palette.
+	Unsupported,
+	/// The line, counted from 1, holds no valid color.
+	InvalidLine(usize),
+}
+
+impl fmt::Display for PaletteError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use PaletteError::*;
+		match self {
+			InvalidHeader => write!(f, "invalid palette header"),
+			UnexpectedEnd => write!(f, "unexpected end of palette"),
+			Unsupported => write!(f, "unsupported palette format"),
+			InvalidLine(line) => write!(f, "invalid color on line {}", line),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PaletteError {}
+
+/// Creates a palette of `colors`, ignoring colors past the 256th and leaving missing colors black.
+fn collect(
+	colors: impl Iterator<Item = Result<SNESColor, PaletteError>>,
+) -> Result<Palette, PaletteError> {
+	let mut palette = Palette::default();
+	for (dst, color) in palette.colors_mut().iter_mut().zip(colors) {
+		*dst = color?;
+	}
+	Ok(palette)
+}
+
+fn from_rgb24(bytes: &[u8]) -> Result<Palette, PaletteError> {
+	if bytes.len() % 3 != 0 {
+		return Err(PaletteError::UnexpectedEnd);
+	}
+	collect(
+		bytes
+			.chunks_exact(3)
+			.map(|rgb| Ok(RGB(rgb[0], rgb[1], rgb[2]).into())),
+	)
+}
+
+fn to_rgb24(palette: &Palette) -> impl Iterator<Item = u8> + '_ {
+	palette.colors().iter().flat_map(|&color| {
+		let RGB(r, g, b) = color.into();
+		[r, g, b]
+	})
+}
+
+impl Palette {
+	/// Reads a Tile Layer Pro palette of 24-bit RGB or 15-bit SNES colors.
+	///
+	/// Colors past the 256th are ignored, and colors missing from the file left black.
+	/// ```
+	/// # use sneslib::graphics::*;
+	/// let palette = Palette::from_tpl(b"TPL\x02\x00\x00\xFF\x7F").unwrap();
+	/// assert_eq!(palette[1], SNESColor(0x7FFF));
+	/// assert_eq!(Palette::from_tpl(&palette.to_tpl()), Ok(palette));
+	/// ```
+	pub fn from_tpl(bytes: &[u8]) -> Result<Self, PaletteError> {
+		let bytes = bytes
+			.strip_prefix(&TPL_MAGIC[..])
+			.ok_or(PaletteError::InvalidHeader)?;
+		match bytes.split_first() {
+			Some((0, colors)) => from_rgb24(colors),
+			Some((2, colors)) => {
+				if colors.len() % 2 != 0 {
+					return Err(PaletteError::UnexpectedEnd);
+				}
+				collect(
+					colors.chunks_exact(2).map(|color| {
+						Ok(SNESColor(u16::from_le_bytes([color[0], color[1]]) & 0x7FFF))
+					}),
+				)
+			}
+			Some(_) => Err(PaletteError::Unsupported),
+			None => Err(PaletteError::InvalidHeader),
+		}
+	}
+
+	/// Writes a Tile Layer Pro palette of the 256 colors as 15-bit SNES colors.
+	pub fn to_tpl(&self) -> Vec<u8> {
+		let mut bytes = TPL_MAGIC.to_vec();
+		bytes.push(2);
+		bytes.extend_from_slice(&self.to_cgram());
+		bytes
+	}
+
+	/// Reads a YY-CHR palette of 24-bit RGB colors, without a header.
+	///
+	/// Colors past the 256th are ignored, and colors missing from the file left black.
+	pub fn from_yychr(bytes: &[u8]) -> Result<Self, PaletteError> {
+		from_rgb24(bytes)
+	}
+
+	/// Writes a YY-CHR palette of the 256 colors as 24-bit RGB colors.
+	pub fn to_yychr(&self) -> Vec<u8> {
+		to_rgb24(self).collect()
+	}
+
+	/// Reads a GIMP palette, a text file of a color on each line after the header.
+	///
+	/// Colors past the 256th are ignored, and colors missing from the file left black.
+	/// ```
+	/// # use sneslib::graphics::*;
+	/// let gpl = "GIMP Palette\nName: Test\nColumns: 16\n#\n  0   0   0\tBackdrop\n248 248 248\n";
+	/// let palette = Palette::from_gpl(gpl).unwrap();
+	/// assert_eq!(palette[1], SNESColor(0x7FFF));
+	/// assert_eq!(Palette::from_gpl(&palette.to_gpl("Test")), Ok(palette));
+	/// ```
+	pub fn from_gpl(text: &str) -> Result<Self, PaletteError> {
+		let mut lines = text.lines().enumerate();
+		match lines.next() {
+			Some((_, line)) if line.trim_end() == GPL_MAGIC => {}
+			_ => return Err(PaletteError::InvalidHeader),
+		}
+		let colors = lines
+			.map(|(i, line)| (i, line.trim()))
+			.filter(|(_, line)| {
+				!line.is_empty()
+					&& !line.starts_with('#')
+					&& !line.starts_with("Name:")
+					&& !line.starts_with("Columns:")
+			})
+			.map(|(i, line)| {
+				let mut channels = line.split_whitespace().map(str::parse::<u8>);
+				let mut channel = || {
+					channels
+						.next()
+						.and_then(Result::ok)
+						.ok_or(PaletteError::InvalidLine(i + 1))
+				};
+				Ok(RGB(channel()?, channel()?, channel()?).into())
+			});
+		collect(colors)
+	}
+
+	/// Writes a GIMP palette of the 256 colors, in 16 columns.
+	pub fn to_gpl(&self, name: &str) -> String {
+		let mut text = format!("{}\nName: {}\nColumns: 16\n#\n", GPL_MAGIC, name);
+		for (i, color) in to_rgb24(self)
+			.collect::<Vec<_>>()
+			.chunks_exact(3)
+			.enumerate()
+		{
+			writeln!(
+				text,
+				"{:3} {:3} {:3}\tIndex {}",
+				color[0], color[1], color[2], i
+			)
+			.unwrap();
+		}
+		text
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn palette() -> Palette {
+		Palette::from(core::array::from_fn(|i| {
+			SNESColor((i * 0x0123) as u16 & 0x7FFF)
+		}))
+	}
+
+	#[test]
+	fn tpl() {
+		let palette = palette();
+		let tpl = palette.to_tpl();
+		assert_eq!(tpl.len(), 4 + 512);
+		assert_eq!(tpl[4..8], [0x00, 0x00, 0x23, 0x01]);
+		assert_eq!(Palette::from_tpl(&tpl), Ok(palette));
+
+		let palette = Palette::from_tpl(b"TPL\x00\xF8\x00\x00\x00\xF8\x00").unwrap();
+		assert_eq!(palette.row::<3>(0), &[0x001F, 0x03E0, 0].map(SNESColor));
+		assert_eq!(
+			Palette::from_tpl(b"TPL\x01\x0F"),
+			Err(PaletteError::Unsupported)
+		);
+		assert_eq!(Palette::from_tpl(b"TPL"), Err(PaletteError::InvalidHeader));
+		assert_eq!(
+			Palette::from_tpl(b"TPM\x02"),
+			Err(PaletteError::InvalidHeader)
+		);
+		assert_eq!(
+			Palette::from_tpl(b"TPL\x02\x00"),
+			Err(PaletteError::UnexpectedEnd)
+		);
+	}
+
+	#[test]
+	fn yychr() {
+		let palette = palette();
+		let pal = palette.to_yychr();
+		assert_eq!(pal.len(), 768);
+		assert_eq!(pal[3..6], [0x18, 0x48, 0x00]);
+		assert_eq!(Palette::from_yychr(&pal), Ok(palette.clone()));
+		// extra colors are ignored
+		assert_eq!(
+			Palette::from_yychr(&[pal.clone(), pal].concat()),
+			Ok(palette)
+		);
+		assert_eq!(
+			Palette::from_yychr(&[0; 4]),
+			Err(PaletteError::UnexpectedEnd)
+		);
+	}
+
+	#[test]
+	fn gpl() {
+		let palette = palette();
+		let gpl = palette.to_gpl("Test");
+		assert!(gpl.starts_with("GIMP Palette\nName: Test\nColumns: 16\n#\n  0   0   0\tIndex 0\n 24  72   0\tIndex 1\n"));
+		assert_eq!(Palette::from_gpl(&gpl), Ok(palette));
+
+		let gpl = "GIMP Palette\r\n\r\n# comment\r\n255 0 0\r\n0 255\r\n";
+		assert_eq!(Palette::from_gpl(gpl), Err(PaletteError::InvalidLine(5)));
+		assert_eq!(
+			Palette::from_gpl("GIMP\n"),
+			Err(PaletteError::InvalidHeader)
+		);
+		let palette = Palette::from_gpl("GIMP Palette\n0 0 256\n");
+		assert_eq!(palette, Err(PaletteError::InvalidLine(2)));
+	}
+}
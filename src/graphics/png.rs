@@ -0,0 +1,834 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+
+use super::tile::Tile;
+use super::{Palette, SNESColor, RGB};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+#[derive(Debug)]
+pub enum PngError {
+	Io(io::Error),
+	/// The image is not a valid PNG image.
+	InvalidFormat,
+	/// The image uses a PNG feature not supported, e.g. interlacing.
+	Unsupported(&'static str),
+	/// The width or height of the image is not a multiple of 8 pixels.
+	NotTileAligned {
+		width: usize,
+		height: usize,
+	},
+}
+
+impl From<io::Error> for PngError {
+	fn from(e: io::Error) -> Self {
+		Self::Io(e)
+	}
+}
+
+impl fmt::Display for PngError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use PngError::*;
+		match self {
+			Io(e) => write!(f, "{}", e),
+			InvalidFormat => write!(f, "invalid PNG image"),
+			Unsupported(feature) => write!(f, "unsupported PNG image: {}", feature),
+			NotTileAligned { width, height } => write!(
+				f,
+				"image of {}x{} pixels does not split into 8x8 tiles",
+				width, height
+			),
+		}
+	}
+}
+
+impl std::error::Error for PngError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			PngError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+/// Size in pixels of the square drawn for each color by [`write_palette`].
+pub const SWATCH_SIZE: usize = 8;
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+	let mut crc = Crc::new();
+	crc.update(kind);
+	crc.update(data);
+	writer.write_all(&(data.len() as u32).to_be_bytes())?;
+	writer.write_all(kind)?;
+	writer.write_all(data)?;
+	writer.write_all(&crc.sum().to_be_bytes())
+}
+
+/// Writes an image of 8-bit color indices into `colors`, `width` pixels per row.
+fn write_indexed<W: Write>(
+	mut writer: W,
+	width: usize,
+	pixels: &[u8],
+	colors: &[SNESColor],
+) -> io::Result<()> {
+	let height = pixels.len() / width;
+	let mut header = [0; 13];
+	header[..4].copy_from_slice(&(width as u32).to_be_bytes());
+	header[4..8].copy_from_slice(&(height as u32).to_be_bytes());
+	// 8-bit indexed color
+	header[8] = 8;
+	header[9] = 3;
+	let palette = colors
+		.iter()
+		.flat_map(|&color| {
+			let RGB(r, g, b) = color.into();
+			[r, g, b]
+		})
+		.collect::<Vec<_>>();
+	let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+	for row in pixels.chunks(width) {
+		// no filter
+		encoder.write_all(&[0])?;
+		encoder.write_all(row)?;
+	}
+
+	writer.write_all(&SIGNATURE)?;
+	write_chunk(&mut writer, b"IHDR", &header)?;
+	write_chunk(&mut writer, b"PLTE", &palette)?;
+	write_chunk(&mut writer, b"IDAT", &encoder.finish()?)?;
+	write_chunk(&mut writer, b"IEND", &[])
+}
+
+/// Renders tiles left to right and top to bottom, `tiles_per_row` to a row, as an indexed PNG image.
+///
+/// The image has a color for each pixel value of the tiles, taken from `colors`, e.g. a row
+/// of a [`Palette`] or the whole palette for 8bpp tiles, black past the end of `colors`.
+/// Space left in the last row is drawn with color 0.
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] if there are no tiles or `tiles_per_row` is zero.
+/// ```
+/// # use sneslib::graphics::png;
+/// # use sneslib::graphics::*;
+/// let mut palette = Palette::default();
+/// palette[1] = SNESColor(0x7FFF);
+/// let tiles = Tile4bpp::from_vram(&[0xFF; 0x60]);
+/// let mut image = Vec::new();
+/// png::write_tiles(&mut image, &tiles, palette.bg_row(0), 2).unwrap();
+/// assert_eq!(&image[1..4], b"PNG");
+/// ```
+pub fn write_tiles<T: Tile, W: Write>(
+	writer: W,
+	tiles: &[T],
+	colors: &[SNESColor],
+	tiles_per_row: usize,
+) -> io::Result<()> {
+	if tiles.is_empty() || tiles_per_row == 0 {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"no tiles to render",
+		));
+	}
+	let tiles_per_row = tiles_per_row.min(tiles.len());
+	let width = tiles_per_row * 8;
+	let rows = tiles.len().div_ceil(tiles_per_row);
+	let mask = ((1u16 << T::BPP) - 1) as u8;
+	let mut pixels = vec![0; width * rows * 8];
+	for (i, tile) in tiles.iter().enumerate() {
+		let (x, y) = (i % tiles_per_row * 8, i / tiles_per_row * 8);
+		for (dy, row) in tile.pixels().iter().enumerate() {
+			let start = (y + dy) * width + x;
+			for (dst, &pixel) in pixels[start..start + 8].iter_mut().zip(row.iter()) {
+				*dst = pixel & mask;
+			}
+		}
+	}
+	let colors = (0..=mask as usize)
+		.map(|i| colors.get(i).copied().unwrap_or_default())
+		.collect::<Vec<_>>();
+	write_indexed(writer, width, &pixels, &colors)
+}
+
+/// Renders the palette as an indexed PNG image of 16 by 16 squares of [`SWATCH_SIZE`] pixels,
+/// a row of the palette to each row of squares.
+pub fn write_palette<W: Write>(writer: W, palette: &Palette) -> io::Result<()> {
+	let width = 16 * SWATCH_SIZE;
+	let pixels = (0..width * width)
+		.map(|i| {
+			let (x, y) = (i % width / SWATCH_SIZE, i / width / SWATCH_SIZE);
+			(y * 16 + x) as u8
+		})
+		.collect::<Vec<_>>();
+	write_indexed(writer, width, &pixels, palette.colors())
+}
+
+/// Image decoded to 8-bit RGBA pixels, row by row.
+struct Image {
+	width: usize,
+	height: usize,
+	pixels: Vec<[u8; 4]>,
+	/// Color indices and palette of an indexed image.
+	indexed: Option<(Vec<u8>, Vec<[u8; 4]>)>,
+}
+
+/// Predicts a byte from the bytes to the left, above and above left of it.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+	let p = a as i16 + b as i16 - c as i16;
+	let (pa, pb, pc) = (
+		(p - a as i16).abs(),
+		(p - b as i16).abs(),
+		(p - c as i16).abs(),
+	);
+	if pa <= pb && pa <= pc {
+		a
+	} else if pb <= pc {
+		b
+	} else {
+		c
+	}
+}
+
+/// Reverses the filter of each row of `stride` bytes, `bpp` bytes to a pixel.
+fn unfilter(data: &[u8], height: usize, stride: usize, bpp: usize) -> Result<Vec<u8>, PngError> {
+	let len = height
+		.checked_mul(stride + 1)
+		.ok_or(PngError::InvalidFormat)?;
+	if data.len() < len {
+		return Err(PngError::InvalidFormat);
+	}
+	let mut rows = vec![0; height * stride];
+	for (y, line) in data.chunks_exact(stride + 1).take(height).enumerate() {
+		let (above, row) = rows.split_at_mut(y * stride);
+		let above = above.get(above.len().wrapping_sub(stride)..);
+		let row = &mut row[..stride];
+		for i in 0..stride {
+			let a = if i >= bpp { row[i - bpp] } else { 0 };
+			let b = above.map_or(0, |above| above[i]);
+			let c = match above {
+				Some(above) if i >= bpp => above[i - bpp],
+				_ => 0,
+			};
+			let prediction = match line[0] {
+				0 => 0,
+				1 => a,
+				2 => b,
+				3 => ((a as u16 + b as u16) / 2) as u8,
+				4 => paeth(a, b, c),
+				_ => return Err(PngError::InvalidFormat),
+			};
+			row[i] = line[1 + i].wrapping_add(prediction);
+		}
+	}
+	Ok(rows)
+}
+
+/// Returns sample `index` of a row of samples of `depth` bits.
+fn sample(row: &[u8], index: usize, depth: usize) -> u16 {
+	match depth {
+		16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+		8 => row[index].into(),
+		_ => {
+			let bit = index * depth;
+			(row[bit / 8] >> (8 - depth - bit % 8) & ((1 << depth) - 1)).into()
+		}
+	}
+}
+
+fn decode(bytes: &[u8]) -> Result<Image, PngError> {
+	let mut rest = bytes
+		.strip_prefix(&SIGNATURE[..])
+		.ok_or(PngError::InvalidFormat)?;
+	let mut chunks = HashMap::new();
+	let mut data = Vec::new();
+	while rest.len() >= 12 {
+		let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+		let body = rest.get(4..8 + len).ok_or(PngError::InvalidFormat)?;
+		let mut crc = Crc::new();
+		crc.update(body);
+		if rest.get(8 + len..12 + len) != Some(&crc.sum().to_be_bytes()[..]) {
+			return Err(PngError::InvalidFormat);
+		}
+		let (kind, body) = body.split_at(4);
+		match kind {
+			b"IDAT" => data.extend_from_slice(body),
+			b"IEND" => break,
+			_ => {
+				chunks.entry(kind).or_insert(body);
+			}
+		}
+		rest = &rest[12 + len..];
+	}
+
+	let header = chunks.get(&b"IHDR"[..]).ok_or(PngError::InvalidFormat)?;
+	if header.len() != 13 {
+		return Err(PngError::InvalidFormat);
+	}
+	let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+	let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+	// the PNG specification limits both to 2^31 - 1
+	if !(1..=0x7FFF_FFFF).contains(&width) || !(1..=0x7FFF_FFFF).contains(&height) {
+		return Err(PngError::InvalidFormat);
+	}
+	let (depth, color_type) = (header[8] as usize, header[9]);
+	let channels = match (color_type, depth) {
+		(0, 1 | 2 | 4 | 8 | 16) => 1,
+		(2, 8 | 16) => 3,
+		(3, 1 | 2 | 4 | 8) => 1,
+		(4, 8 | 16) => 2,
+		(6, 8 | 16) => 4,
+		_ => return Err(PngError::InvalidFormat),
+	};
+	if header[12] != 0 {
+		return Err(PngError::Unsupported("interlaced images"));
+	}
+	let stride = width
+		.checked_mul(channels * depth)
+		.ok_or(PngError::InvalidFormat)?
+		.div_ceil(8);
+	// inflate no more than the rows, each led by its filter type, so the data cannot blow up
+	let len = height
+		.checked_mul(stride + 1)
+		.ok_or(PngError::InvalidFormat)?;
+	let mut inflated = Vec::new();
+	ZlibDecoder::new(&data[..])
+		.take(len as u64)
+		.read_to_end(&mut inflated)
+		.map_err(|_| PngError::InvalidFormat)?;
+	if inflated.len() < len {
+		return Err(PngError::InvalidFormat);
+	}
+	let rows = unfilter(&inflated, height, stride, (channels * depth).div_ceil(8))?;
+
+	let transparent = chunks.get(&b"tRNS"[..]).copied().unwrap_or_default();
+	let palette = match color_type {
+		3 => {
+			let palette = chunks.get(&b"PLTE"[..]).ok_or(PngError::InvalidFormat)?;
+			palette
+				.chunks_exact(3)
+				.enumerate()
+				.map(|(i, rgb)| [rgb[0], rgb[1], rgb[2], *transparent.get(i).unwrap_or(&0xFF)])
+				.collect()
+		}
+		_ => Vec::new(),
+	};
+	// the samples of the color keyed transparent, if any
+	let key = transparent
+		.chunks_exact(2)
+		.map(|sample| u16::from_be_bytes([sample[0], sample[1]]))
+		.collect::<Vec<_>>();
+	let scale = |sample: u16| match depth {
+		16 => (sample >> 8) as u8,
+		_ => (sample as usize * 0xFF / ((1 << depth) - 1)) as u8,
+	};
+
+	let mut pixels = Vec::with_capacity(width.checked_mul(height).ok_or(PngError::InvalidFormat)?);
+	let mut indices = Vec::new();
+	for row in rows.chunks_exact(stride.max(1)).take(height) {
+		for x in 0..width {
+			let samples = [0, 1, 2, 3].map(|i| {
+				if i < channels {
+					sample(row, x * channels + i, depth)
+				} else {
+					0
+				}
+			});
+			let keyed = key.len() == channels && key[..] == samples[..channels];
+			let alpha = |sample| if keyed { 0 } else { sample };
+			pixels.push(match color_type {
+				0 => {
+					let gray = scale(samples[0]);
+					[gray, gray, gray, alpha(0xFF)]
+				}
+				2 => [
+					scale(samples[0]),
+					scale(samples[1]),
+					scale(samples[2]),
+					alpha(0xFF),
+				],
+				3 => {
+					let index = samples[0] as u8;
+					indices.push(index);
+					*palette.get(index as usize).ok_or(PngError::InvalidFormat)?
+				}
+				4 => {
+					let gray = scale(samples[0]);
+					[gray, gray, gray, scale(samples[1])]
+				}
+				_ => samples.map(scale),
+			});
+		}
+	}
+	Ok(Image {
+		width,
+		height,
+		pixels,
+		indexed: (color_type == 3).then_some((indices, palette)),
+	})
+}
+
+/// Splits the colors into at most `max` boxes along the channel of the widest range,
+/// returning the average color of each box.
+fn median_cut(colors: &[(SNESColor, usize)], max: usize) -> Vec<SNESColor> {
+	let channels = |color: SNESColor| [color.r(), color.g(), color.b()];
+	let range = |colors: &[(SNESColor, usize)], channel: usize| {
+		let values = colors.iter().map(|&(color, _)| channels(color)[channel]);
+		values.clone().max().unwrap_or(0) - values.min().unwrap_or(0)
+	};
+	let mut boxes = vec![colors.to_vec()];
+	while boxes.len() < max {
+		let widest = (0..boxes.len())
+			.flat_map(|i| (0..3).map(move |channel| (i, channel)))
+			.max_by_key(|&(i, channel)| range(&boxes[i], channel))
+			.filter(|&(i, channel)| range(&boxes[i], channel) > 0);
+		let (i, channel) = match widest {
+			Some(widest) => widest,
+			None => break,
+		};
+		let mut colors = boxes.swap_remove(i);
+		colors.sort_by_key(|&(color, _)| channels(color)[channel]);
+		// split after the median pixel, leaving a color on either side
+		let half = colors.iter().map(|&(_, count)| count).sum::<usize>() / 2;
+		let mut seen = 0;
+		let median = colors.iter().position(|&(_, count)| {
+			seen += count;
+			seen > half
+		});
+		let split = (median.unwrap_or(0) + 1).min(colors.len() - 1);
+		let upper = colors.split_off(split);
+		boxes.push(colors);
+		boxes.push(upper);
+	}
+	boxes
+		.iter()
+		.map(|colors| {
+			let total = colors.iter().map(|&(_, count)| count).sum::<usize>();
+			let [r, g, b] = [0, 1, 2].map(|channel| {
+				let sum = colors
+					.iter()
+					.map(|&(color, count)| channels(color)[channel] as usize * count)
+					.sum::<usize>();
+				((sum + total / 2) / total) as u16
+			});
+			SNESColor(r | g << 5 | b << 10)
+		})
+		.collect()
+}
+
+/// Tiles read from a PNG image by [`read_tiles`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedTiles<T> {
+	/// Distinct tiles in order of first appearance if deduplicated, or else every tile.
+	pub tiles: Vec<T>,
+	/// Index into `tiles` of each tile of the image, left to right and top to bottom.
+	pub map: Vec<usize>,
+	/// Width of the image in tiles.
+	pub tiles_per_row: usize,
+	/// Colors of the pixel values of the tiles, at most one for each.
+	pub palette: Vec<SNESColor>,
+}
+
+/// Reads an image of 8×8 tiles, e.g. as written by [`write_tiles`], converting it to tiles of the bit depth of `T`.
+///
+/// An indexed image using no more colors than the bit depth allows keeps its color indices and palette.
+/// Any other image has its opaque colors converted to [`SNESColor`]s and given indices from 1,
+/// its transparent pixels taking index 0, the colors being reduced by median cut
+/// to the nearest of as many as fit if there are more.
+///
+/// With `deduplicate` set, each distinct tile is kept once.
+/// ```
+/// # use sneslib::graphics::png::{self, ImportedTiles};
+/// # use sneslib::graphics::*;
+/// let mut tiles = [Tile2bpp::default(); 3];
+/// tiles[1].0[0][0] = 3;
+/// let colors = [SNESColor(0), SNESColor(0x001F), SNESColor(0x03E0), SNESColor(0x7C00)];
+/// let mut image = Vec::new();
+/// png::write_tiles(&mut image, &tiles, &colors, 3).unwrap();
+///
+/// let imported: ImportedTiles<Tile2bpp> = png::read_tiles(&image[..], true).unwrap();
+/// assert_eq!(imported.tiles, tiles[..2]);
+/// assert_eq!(imported.map, [0, 1, 0]);
+/// assert_eq!(imported.palette[3], SNESColor(0x7C00));
+/// ```
+pub fn read_tiles<T: Tile, R: Read>(
+	mut reader: R,
+	deduplicate: bool,
+) -> Result<ImportedTiles<T>, PngError> {
+	let mut bytes = Vec::new();
+	reader.read_to_end(&mut bytes)?;
+	let image = decode(&bytes)?;
+	let (width, height) = (image.width, image.height);
+	if width % 8 != 0 || height % 8 != 0 || width == 0 || height == 0 {
+		return Err(PngError::NotTileAligned { width, height });
+	}
+
+	let max = 1 << T::BPP;
+	let rgb = |[r, g, b, _]: [u8; 4]| SNESColor::from(RGB(r, g, b));
+	let (indices, palette) = match image.indexed {
+		Some((indices, palette)) if indices.iter().all(|&index| (index as usize) < max) => {
+			let palette = palette.into_iter().take(max).map(rgb).collect::<Vec<_>>();
+			(indices, palette)
+		}
+		_ => {
+			let opaque = |pixel: &[u8; 4]| pixel[3] >= 0x80;
+			let mut counts = Vec::<(SNESColor, usize)>::new();
+			let mut seen = HashMap::new();
+			for pixel in image.pixels.iter().filter(|pixel| opaque(pixel)) {
+				let color = rgb(*pixel);
+				let i = *seen.entry(color).or_insert_with(|| {
+					counts.push((color, 0));
+					counts.len() - 1
+				});
+				counts[i].1 += 1;
+			}
+			let colors = if counts.len() < max {
+				counts.iter().map(|&(color, _)| color).collect()
+			} else {
+				median_cut(&counts, max - 1)
+			};
+			let nearest = |color: SNESColor| {
+				let distance = |other: &SNESColor| {
+					[
+						(color.r(), other.r()),
+						(color.g(), other.g()),
+						(color.b(), other.b()),
+					]
+					.iter()
+					.map(|&(a, b)| (a as i32 - b as i32).pow(2))
+					.sum::<i32>()
+				};
+				let (i, _) = colors
+					.iter()
+					.enumerate()
+					.min_by_key(|(_, other)| distance(other))
+					.unwrap();
+				i as u8 + 1
+			};
+			let mut lookup = HashMap::new();
+			let indices = image
+				.pixels
+				.iter()
+				.map(|pixel| {
+					if opaque(pixel) {
+						*lookup
+							.entry(rgb(*pixel))
+							.or_insert_with(|| nearest(rgb(*pixel)))
+					} else {
+						0
+					}
+				})
+				.collect();
+			let mut palette = vec![SNESColor::default()];
+			palette.extend(colors);
+			(indices, palette)
+		}
+	};
+
+	let tiles_per_row = width / 8;
+	let mut imported = ImportedTiles {
+		tiles: Vec::new(),
+		map: Vec::new(),
+		tiles_per_row,
+		palette,
+	};
+	let mut seen = HashMap::new();
+	for i in 0..tiles_per_row * (height / 8) {
+		let (x, y) = (i % tiles_per_row * 8, i / tiles_per_row * 8);
+		let pixels: [[u8; 8]; 8] = core::array::from_fn(|dy| {
+			core::array::from_fn(|dx| indices[(y + dy) * width + x + dx])
+		});
+		let tiles = &mut imported.tiles;
+		let mut push = || {
+			tiles.push(T::from_pixels(pixels));
+			tiles.len() - 1
+		};
+		let index = if deduplicate {
+			*seen.entry(pixels).or_insert_with(push)
+		} else {
+			push()
+		};
+		imported.map.push(index);
+	}
+	Ok(imported)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::graphics::{Tile2bpp, Tile4bpp, Tile8bpp};
+
+	/// Splits an image into its chunks, checking their CRCs.
+	fn split_chunks(image: &[u8]) -> HashMap<[u8; 4], Vec<u8>> {
+		assert_eq!(image[..8], SIGNATURE);
+		let mut chunks = HashMap::new();
+		let mut rest = &image[8..];
+		while !rest.is_empty() {
+			let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+			let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+			let mut crc = Crc::new();
+			crc.update(&rest[4..8 + len]);
+			assert_eq!(rest[8 + len..12 + len], crc.sum().to_be_bytes());
+			chunks.insert([kind[0], kind[1], kind[2], kind[3]], data.to_vec());
+			rest = &rest[12 + len..];
+		}
+		chunks
+	}
+
+	fn pixels(chunks: &HashMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+		let mut pixels = Vec::new();
+		ZlibDecoder::new(&chunks[b"IDAT"][..])
+			.read_to_end(&mut pixels)
+			.unwrap();
+		pixels
+	}
+
+	#[test]
+	fn tiles() {
+		let mut tiles = [Tile2bpp::default(); 3];
+		tiles[1].0[0] = [1, 2, 3, 0xFF, 0, 0, 0, 1];
+		tiles[2].0[7][7] = 2;
+		let colors = [SNESColor(0), SNESColor(0x001F), SNESColor(0x03E0)];
+		let mut image = Vec::new();
+		write_tiles(&mut image, &tiles, &colors, 2).unwrap();
+		let chunks = split_chunks(&image);
+		assert_eq!(chunks[b"IHDR"][..8], [0, 0, 0, 16, 0, 0, 0, 16]);
+		assert_eq!(chunks[b"PLTE"], [0, 0, 0, 0xF8, 0, 0, 0, 0xF8, 0, 0, 0, 0]);
+		assert!(chunks[b"IEND"].is_empty());
+		let pixels = pixels(&chunks);
+		// a filter byte before each row
+		assert_eq!(pixels.len(), 17 * 16);
+		assert_eq!(pixels[9..17], [1, 2, 3, 3, 0, 0, 0, 1]);
+		assert_eq!(pixels[15 * 17 + 8], 2);
+		assert_eq!(pixels[15 * 17 + 16], 0);
+
+		// a single row at most as wide as the tiles
+		let mut image = Vec::new();
+		write_tiles(&mut image, &[Tile8bpp::default()], &colors, 16).unwrap();
+		let chunks = split_chunks(&image);
+		assert_eq!(chunks[b"IHDR"][..8], [0, 0, 0, 8, 0, 0, 0, 8]);
+		assert_eq!(chunks[b"PLTE"].len(), 256 * 3);
+
+		let error = write_tiles::<Tile2bpp, _>(Vec::new(), &[], &colors, 1).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+	}
+
+	#[test]
+	fn palette() {
+		let mut palette = Palette::default();
+		palette[0x12] = SNESColor(0x7C00);
+		let mut image = Vec::new();
+		write_palette(&mut image, &palette).unwrap();
+		let chunks = split_chunks(&image);
+		assert_eq!(chunks[b"IHDR"][..8], [0, 0, 0, 128, 0, 0, 0, 128]);
+		assert_eq!(chunks[b"PLTE"][0x12 * 3..0x13 * 3], [0, 0, 0xF8]);
+		let pixels = pixels(&chunks);
+		let row = &pixels[SWATCH_SIZE * 129..][..129];
+		assert_eq!(row[1 + 2 * SWATCH_SIZE], 0x12);
+		assert_eq!(row[128], 0x1F);
+	}
+
+	/// Encodes an image of raw rows, filtering them with each filter in turn.
+	fn encode(
+		width: u32,
+		height: u32,
+		color_type: u8,
+		depth: u8,
+		raw: &[u8],
+		extra: &[(&[u8; 4], &[u8])],
+	) -> Vec<u8> {
+		let channels = [1, 0, 3, 1, 2, 0, 4][color_type as usize];
+		let bpp = (channels * depth as usize).div_ceil(8);
+		let stride = raw.len() / height as usize;
+		let mut data = Vec::new();
+		for (y, row) in raw.chunks(stride).enumerate() {
+			let filter = y as u8 % 5;
+			data.push(filter);
+			for i in 0..stride {
+				let a = if i >= bpp { row[i - bpp] } else { 0 };
+				let b = if y > 0 { raw[(y - 1) * stride + i] } else { 0 };
+				let c = if y > 0 && i >= bpp {
+					raw[(y - 1) * stride + i - bpp]
+				} else {
+					0
+				};
+				let prediction = match filter {
+					0 => 0,
+					1 => a,
+					2 => b,
+					3 => ((a as u16 + b as u16) / 2) as u8,
+					_ => paeth(a, b, c),
+				};
+				data.push(row[i].wrapping_sub(prediction));
+			}
+		}
+		let mut header = [0; 13];
+		header[..4].copy_from_slice(&width.to_be_bytes());
+		header[4..8].copy_from_slice(&height.to_be_bytes());
+		header[8] = depth;
+		header[9] = color_type;
+		let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(&data).unwrap();
+		let mut image = SIGNATURE.to_vec();
+		write_chunk(&mut image, b"IHDR", &header).unwrap();
+		for (kind, data) in extra {
+			write_chunk(&mut image, kind, data).unwrap();
+		}
+		write_chunk(&mut image, b"IDAT", &encoder.finish().unwrap()).unwrap();
+		write_chunk(&mut image, b"IEND", &[]).unwrap();
+		image
+	}
+
+	#[test]
+	fn indexed() {
+		let tiles =
+			Tile4bpp::from_vram(&(0..0x80).map(|i| (i * 37 + 11) as u8).collect::<Vec<_>>());
+		let colors = (0..16).map(|i| SNESColor(i * 0x0421)).collect::<Vec<_>>();
+		let mut image = Vec::new();
+		write_tiles(&mut image, &tiles, &colors, 3).unwrap();
+		let imported = read_tiles::<Tile4bpp, _>(&image[..], false).unwrap();
+		assert_eq!(imported.tiles[..4], tiles[..]);
+		// blank tiles fill the last row
+		assert_eq!(imported.tiles[4..], [Tile4bpp::default(); 2]);
+		assert_eq!(imported.map, [0, 1, 2, 3, 4, 5]);
+		assert_eq!(imported.tiles_per_row, 3);
+		assert_eq!(imported.palette, colors);
+		let imported = read_tiles::<Tile4bpp, _>(&image[..], true).unwrap();
+		assert_eq!(imported.tiles.len(), 5);
+		assert_eq!(imported.map[5], 4);
+
+		// indices beyond the bit depth are taken as colors
+		let imported = read_tiles::<Tile2bpp, _>(&image[..], false).unwrap();
+		assert_eq!(imported.palette.len(), 4);
+		assert_eq!(imported.palette[0], SNESColor(0));
+
+		// 1-bit palette with a transparent entry
+		let raw = [0b1000_0001; 8];
+		let image = encode(
+			8,
+			8,
+			3,
+			1,
+			&raw,
+			&[(b"PLTE", &[0, 0, 0, 0xFF, 0xFF, 0xFF]), (b"tRNS", &[0])],
+		);
+		let imported = read_tiles::<Tile2bpp, _>(&image[..], false).unwrap();
+		assert_eq!(imported.tiles[0].0[3], [1, 0, 0, 0, 0, 0, 0, 1]);
+		assert_eq!(imported.palette, [SNESColor(0), SNESColor(0x7FFF)]);
+	}
+
+	#[test]
+	fn true_color() {
+		// RGBA with a transparent pixel and 3 colors
+		let mut raw = Vec::new();
+		for y in 0..8 {
+			for x in 0..8 {
+				raw.extend_from_slice(&match (x + y) % 4 {
+					0 => [0xFF, 0, 0, 0xFF],
+					1 => [0, 0xFF, 0, 0xFF],
+					2 => [0, 0, 0xFF, 0xFF],
+					_ => [0x12, 0x34, 0x56, 0],
+				});
+			}
+		}
+		let image = encode(8, 8, 6, 8, &raw, &[]);
+		let imported = read_tiles::<Tile2bpp, _>(&image[..], false).unwrap();
+		assert_eq!(
+			imported.palette,
+			[
+				SNESColor(0),
+				SNESColor(0x001F),
+				SNESColor(0x03E0),
+				SNESColor(0x7C00)
+			]
+		);
+		assert_eq!(imported.tiles[0].0[1], [2, 3, 0, 1, 2, 3, 0, 1]);
+
+		// 16-bit RGB with a color key, reduced to 3 colors
+		let mut raw = Vec::new();
+		for y in 0..8u16 {
+			for x in 0..16u16 {
+				let rgb: [u16; 3] = match x / 4 {
+					0 => [0xFFFF, 0, 0],
+					1 => [0xF000, 0, 0],
+					2 => [0, 0, 0xFFFF],
+					_ if y < 4 => [0x1234, 0x1234, 0x1234],
+					_ => [0, 0xFFFF, 0],
+				};
+				raw.extend(rgb.iter().flat_map(|sample| sample.to_be_bytes()));
+			}
+		}
+		let key = [0x12, 0x34, 0x12, 0x34, 0x12, 0x34];
+		let image = encode(16, 8, 2, 16, &raw, &[(b"tRNS", &key)]);
+		let imported = read_tiles::<Tile2bpp, _>(&image[..], false).unwrap();
+		assert_eq!(imported.palette.len(), 4);
+		assert_eq!(imported.tiles[1].0[0][4], 0);
+		let color = |x: usize| imported.palette[imported.tiles[x / 8].0[7][x % 8] as usize];
+		// the two reds share a color between them
+		assert_eq!(color(0), color(4));
+		assert_eq!(color(0), SNESColor(0x001F));
+		assert_eq!(color(8), SNESColor(0x7C00));
+		assert_eq!(color(12), SNESColor(0x03E0));
+
+		// gray with alpha
+		let raw = [[0x80, 0xFF], [0xFF, 0x7F]].repeat(32).concat();
+		let image = encode(8, 8, 4, 8, &raw, &[]);
+		let imported = read_tiles::<Tile8bpp, _>(&image[..], false).unwrap();
+		assert_eq!(imported.palette, [SNESColor(0), SNESColor(0x4210)]);
+		assert_eq!(imported.tiles[0].0[0][..2], [1, 0]);
+	}
+
+	#[test]
+	fn errors() {
+		let read = |image: &[u8]| read_tiles::<Tile2bpp, _>(image, false).unwrap_err();
+		let image = encode(8, 4, 0, 8, &[0; 32], &[]);
+		assert!(matches!(
+			read(&image),
+			PngError::NotTileAligned {
+				width: 8,
+				height: 4
+			}
+		));
+		assert!(matches!(read(&image[1..]), PngError::InvalidFormat));
+		let mut image = encode(8, 8, 0, 8, &[0; 64], &[]);
+		image[0x20] ^= 1;
+		assert!(matches!(read(&image), PngError::InvalidFormat));
+		let mut image = encode(8, 8, 0, 8, &[0; 64], &[]);
+		image[0x1C] = 1;
+		let crc = {
+			let mut crc = Crc::new();
+			crc.update(&image[0x0C..0x1D]);
+			crc.sum()
+		};
+		image[0x1D..0x21].copy_from_slice(&crc.to_be_bytes());
+		assert!(matches!(read(&image), PngError::Unsupported(_)));
+
+		// dimensions out of range or whose buffers overflow
+		for size in [0, 0x8000_0000, 0xFFFF_FFF8, 0x7FFF_FFFF] {
+			let mut image = encode(8, 8, 6, 16, &[0; 512], &[]);
+			image[0x10..0x14].copy_from_slice(&u32::to_be_bytes(size));
+			image[0x14..0x18].copy_from_slice(&u32::to_be_bytes(size));
+			let mut crc = Crc::new();
+			crc.update(&image[0x0C..0x1D]);
+			image[0x1D..0x21].copy_from_slice(&crc.sum().to_be_bytes());
+			assert!(matches!(read(&image), PngError::InvalidFormat));
+		}
+
+		// only the declared image is inflated, so too little data fails and too much is ignored
+		let patch = |image: &mut Vec<u8>, offset: usize, value: u32| {
+			image[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+			let mut crc = Crc::new();
+			crc.update(&image[0x0C..0x1D]);
+			image[0x1D..0x21].copy_from_slice(&crc.sum().to_be_bytes());
+		};
+		let mut image = encode(8, 8, 0, 8, &[0; 64], &[]);
+		patch(&mut image, 0x14, 0x10_0000);
+		assert!(matches!(read(&image), PngError::InvalidFormat));
+		let mut image = encode(0x10_0000, 8, 0, 8, &vec![0; 0x80_0000], &[]);
+		patch(&mut image, 0x10, 8);
+		let imported = read_tiles::<Tile2bpp, _>(&image[..], false).unwrap();
+		assert_eq!((imported.tiles.len(), imported.tiles_per_row), (1, 1));
+	}
+}
@@ -0,0 +1,237 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+/// Reads the 8×8 pixels of a tile from `planes` bitplanes, stored as in VRAM:
+/// each pair of planes interleaved row by row, and the pairs one after another.
+fn decode_planes(data: &[u8], planes: usize) -> [[u8; 8]; 8] {
+	let mut pixels = [[0; 8]; 8];
+	for (y, row) in pixels.iter_mut().enumerate() {
+		for plane in 0..planes {
+			let byte = data[plane / 2 * 16 + y * 2 + plane % 2];
+			for (x, pixel) in row.iter_mut().enumerate() {
+				*pixel |= (byte >> (7 - x) & 1) << plane;
+			}
+		}
+	}
+	pixels
+}
+
+/// Writes the 8×8 pixels of a tile as `planes` bitplanes, as read by [`decode_planes`].
+fn encode_planes(pixels: &[[u8; 8]; 8], planes: usize, data: &mut [u8]) {
+	for (y, row) in pixels.iter().enumerate() {
+		for plane in 0..planes {
+			data[plane / 2 * 16 + y * 2 + plane % 2] = row
+				.iter()
+				.fold(0, |byte, &pixel| byte << 1 | (pixel >> plane & 1));
+		}
+	}
+}
+
+/// Tile of any bit depth, e.g. to render tiles alike.
+pub trait Tile: Sized {
+	/// Number of bits per pixel.
+	const BPP: usize;
+
+	/// Returns the pixels, indexed by row, then column.
+	fn pixels(&self) -> &[[u8; 8]; 8];
+
+	/// Creates a tile of `pixels`, which keep their bits beyond the bit depth until encoded.
+	fn from_pixels(pixels: [[u8; 8]; 8]) -> Self;
+}
+
+macro_rules! impl_tile {
+	($name:ident, $planes:expr) => {
+		impl Tile for $name {
+			const BPP: usize = $planes;
+
+			#[inline]
+			fn pixels(&self) -> &[[u8; 8]; 8] {
+				&self.0
+			}
+
+			#[inline]
+			fn from_pixels(pixels: [[u8; 8]; 8]) -> Self {
+				Self(pixels)
+			}
+		}
+
+		impl $name {
+			/// Size of an encoded tile in bytes.
+			pub const SIZE: usize = $planes * 8;
+
+			#[doc = concat!("Decodes a tile from its ", $planes, " bitplanes.")]
+			pub fn from_bytes(data: &[u8; Self::SIZE]) -> Self {
+				Self(decode_planes(data, $planes))
+			}
+
+			#[doc = concat!("Encodes the tile as ", $planes, " bitplanes.")]
+			pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+				let mut data = [0; Self::SIZE];
+				encode_planes(&self.0, $planes, &mut data);
+				data
+			}
+
+			/// Decodes consecutive tiles, e.g. a character area read from VRAM,
+			/// ignoring trailing bytes short of a tile.
+			#[cfg(feature = "alloc")]
+			pub fn from_vram(data: &[u8]) -> Vec<Self> {
+				use core::convert::TryInto;
+
+				data.chunks_exact(Self::SIZE)
+					.map(|data| Self::from_bytes(data.try_into().unwrap()))
+					.collect()
+			}
+
+			/// Encodes tiles one after another, as decoded by [`from_vram`](#method.from_vram).
+			#[cfg(feature = "alloc")]
+			pub fn to_vram(tiles: &[Self]) -> Vec<u8> {
+				tiles.iter().flat_map(|tile| tile.to_bytes()).collect()
+			}
+		}
+	};
+}
+
+/// 8×8 tile of 2-bit color indices, as used by Mode 0 layers and BG3 in Mode 1.
+///
+/// The pixels are indexed by row, then column, with the top left pixel at `[0][0]`.
+/// Only the low 2 bits of each pixel are encoded.
+/// ```
+/// # use sneslib::graphics::tile::Tile2bpp;
+/// let mut data = [0; 16];
+/// data[0] = 0b1100_0011;
+/// data[1] = 0b1010_0101;
+/// let tile = Tile2bpp::from_bytes(&data);
+/// assert_eq!(tile.0[0], [3, 1, 2, 0, 0, 2, 1, 3]);
+/// assert_eq!(tile.to_bytes(), data);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tile2bpp(pub [[u8; 8]; 8]);
+
+impl_tile!(Tile2bpp, 2);
+
+/// 8×8 tile of 4-bit color indices, as used by sprites and most BG layers.
+///
+/// The first 16 bytes hold bitplanes 0 and 1 as a [`Tile2bpp`] does, the next 16 bitplanes 2 and 3.
+/// Only the low 4 bits of each pixel are encoded.
+/// ```
+/// # use sneslib::graphics::tile::Tile4bpp;
+/// let mut vram = vec![0; 0x48];
+/// vram[0x20 + 0x10] = 0x80;
+/// let tiles = Tile4bpp::from_vram(&vram);
+/// assert_eq!(tiles.len(), 2);
+/// assert_eq!(tiles[1].0[0][0], 0b0100);
+/// assert_eq!(Tile4bpp::to_vram(&tiles), &vram[..0x40]);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tile4bpp(pub [[u8; 8]; 8]);
+
+impl_tile!(Tile4bpp, 4);
+
+/// 8×8 tile of 8-bit color indices, as used by BG1 in Modes 3 and 4.
+///
+/// Bitplanes 0-3 are stored as a [`Tile4bpp`] does, followed by bitplanes 4-7 alike.
+/// ```
+/// # use sneslib::graphics::tile::Tile8bpp;
+/// let mut tile = Tile8bpp::default();
+/// tile.0[0][0] = 0x35;
+/// assert_eq!(tile.to_bytes()[0x20..0x22], [0x80, 0x80]);
+/// assert_eq!(tile.to_4bpp().0[0][0], 0x5);
+/// assert_eq!(tile.to_2bpp().0[0][0], 0x1);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tile8bpp(pub [[u8; 8]; 8]);
+
+impl_tile!(Tile8bpp, 8);
+
+impl Tile8bpp {
+	/// Keeps the low 4 bits of each pixel, its color within a 16-color palette row
+	/// when the high bits select the row.
+	pub fn to_4bpp(&self) -> Tile4bpp {
+		Tile4bpp(self.0.map(|row| row.map(|pixel| pixel & 0x0F)))
+	}
+
+	/// Keeps the low 2 bits of each pixel, its color within a 4-color palette row
+	/// when the high bits select the row.
+	pub fn to_2bpp(&self) -> Tile2bpp {
+		Tile2bpp(self.0.map(|row| row.map(|pixel| pixel & 0x03)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use core::convert::TryInto;
+
+	#[test]
+	fn tile2bpp() {
+		let data: [u8; 16] = core::array::from_fn(|i| (i * 37 + 11) as u8);
+		let tile = Tile2bpp::from_bytes(&data);
+		assert!(tile.0.iter().flatten().all(|&pixel| pixel < 4));
+		assert_eq!(tile.to_bytes(), data);
+		// row 7 from $11 and $36
+		assert_eq!(tile.0[7], [0, 0, 2, 3, 0, 2, 2, 1]);
+
+		let mut tile = Tile2bpp::default();
+		tile.0[1][7] = 0xFE;
+		tile.0[6][0] = 0x03;
+		let data = tile.to_bytes();
+		assert_eq!(data[2..4], [0x00, 0x01]);
+		assert_eq!(data[12..14], [0x80, 0x80]);
+		assert_eq!(Tile2bpp::from_bytes(&data).0[1][7], 0x02);
+	}
+
+	#[test]
+	fn tile4bpp() {
+		let data: [u8; 32] = core::array::from_fn(|i| (i * 37 + 11) as u8);
+		let tile = Tile4bpp::from_bytes(&data);
+		assert!(tile.0.iter().flatten().all(|&pixel| pixel < 16));
+		assert_eq!(tile.to_bytes(), data);
+		// the low planes decode as a 2bpp tile
+		let low = Tile2bpp::from_bytes(data[..16].try_into().unwrap());
+		for (row, low) in tile.0.iter().zip(low.0.iter()) {
+			assert!(row.iter().zip(low.iter()).all(|(&a, &b)| a & 3 == b));
+		}
+
+		let mut tile = Tile4bpp::default();
+		tile.0[2][1] = 0x1C;
+		let data = tile.to_bytes();
+		assert_eq!(data[4..6], [0x00, 0x00]);
+		assert_eq!(data[20..22], [0x40, 0x40]);
+	}
+
+	#[test]
+	fn tile8bpp() {
+		let data: [u8; 64] = core::array::from_fn(|i| (i * 37 + 11) as u8);
+		let tile = Tile8bpp::from_bytes(&data);
+		assert_eq!(tile.to_bytes(), data);
+		// the low planes decode as the smaller tiles
+		assert_eq!(
+			tile.to_4bpp(),
+			Tile4bpp::from_bytes(data[..32].try_into().unwrap())
+		);
+		assert_eq!(
+			tile.to_2bpp(),
+			Tile2bpp::from_bytes(data[..16].try_into().unwrap())
+		);
+
+		let mut tile = Tile8bpp::default();
+		tile.0[7][7] = 0xFF;
+		assert!(tile.to_bytes()[0x0E..]
+			.chunks(0x10)
+			.all(|planes| planes[..2] == [1, 1]));
+	}
+
+	#[test]
+	#[cfg(feature = "alloc")]
+	fn vram() {
+		let mut tile = Tile4bpp::default();
+		tile.0[2][1] = 0x0C;
+		let tiles = [Tile4bpp::default(), tile];
+		let vram = Tile4bpp::to_vram(&tiles);
+		assert_eq!(vram.len(), 0x40);
+		assert_eq!(Tile4bpp::from_vram(&vram), tiles);
+		assert!(Tile4bpp::from_vram(&vram[..0x1F]).is_empty());
+	}
+}
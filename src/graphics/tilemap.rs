@@ -0,0 +1,293 @@
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of entries of a 32×32 screen.
+pub const SCREEN_ENTRIES: usize = 32 * 32;
+
+/// Entry of a BG tilemap.
+///
+/// `vhopppcc cccccccc`: vertical flip, horizontal flip, priority, palette and character number.
+/// ```
+/// # use sneslib::graphics::tilemap::TilemapEntry;
+/// let mut entry = TilemapEntry(0x6C12);
+/// assert_eq!(entry.character(), 0x012);
+/// assert_eq!(entry.palette(), 3);
+/// assert!(entry.h_flip() && entry.priority() && !entry.v_flip());
+/// entry.set_palette(7);
+/// entry.set_v_flip(true);
+/// assert_eq!(entry, TilemapEntry(0xFC12));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TilemapEntry(pub u16);
+
+impl TilemapEntry {
+	const CHARACTER: u16 = 0x03FF;
+	const PALETTE: u16 = 0x1C00;
+	const PRIORITY: u16 = 0x2000;
+	const H_FLIP: u16 = 0x4000;
+	const V_FLIP: u16 = 0x8000;
+
+	/// Returns the character number, 0 to 1023, counted in tiles from the character base of the layer.
+	#[inline]
+	pub fn character(&self) -> u16 {
+		self.0 & Self::CHARACTER
+	}
+
+	/// Sets the character number, keeping its low 10 bits.
+	#[inline]
+	pub fn set_character(&mut self, character: u16) {
+		self.0 = self.0 & !Self::CHARACTER | character & Self::CHARACTER;
+	}
+
+	/// Returns the palette row, 0 to 7.
+	#[inline]
+	pub fn palette(&self) -> u8 {
+		((self.0 & Self::PALETTE) >> 10) as u8
+	}
+
+	/// Sets the palette row, keeping its low 3 bits.
+	#[inline]
+	pub fn set_palette(&mut self, palette: u8) {
+		self.0 = self.0 & !Self::PALETTE | (palette as u16) << 10 & Self::PALETTE;
+	}
+
+	#[inline]
+	pub fn priority(&self) -> bool {
+		self.0 & Self::PRIORITY != 0
+	}
+
+	#[inline]
+	pub fn set_priority(&mut self, priority: bool) {
+		self.set(Self::PRIORITY, priority);
+	}
+
+	#[inline]
+	pub fn h_flip(&self) -> bool {
+		self.0 & Self::H_FLIP != 0
+	}
+
+	#[inline]
+	pub fn set_h_flip(&mut self, flip: bool) {
+		self.set(Self::H_FLIP, flip);
+	}
+
+	#[inline]
+	pub fn v_flip(&self) -> bool {
+		self.0 & Self::V_FLIP != 0
+	}
+
+	#[inline]
+	pub fn set_v_flip(&mut self, flip: bool) {
+		self.set(Self::V_FLIP, flip);
+	}
+
+	#[inline]
+	fn set(&mut self, bit: u16, value: bool) {
+		if value {
+			self.0 |= bit;
+		} else {
+			self.0 &= !bit;
+		}
+	}
+}
+
+/// Arrangement of the 32×32 screens of a BG tilemap, as set by the low 2 bits of `BGnSC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScreenSize {
+	/// A single screen.
+	S32x32,
+	/// Two screens side by side.
+	S64x32,
+	/// Two screens one above the other.
+	S32x64,
+	/// Four screens, the top two followed by the bottom two.
+	S64x64,
+}
+
+impl ScreenSize {
+	/// Returns the arrangement set by the low 2 bits of `BGnSC`.
+	#[inline]
+	pub fn from_bits(bits: u8) -> Self {
+		match bits & 3 {
+			0 => ScreenSize::S32x32,
+			1 => ScreenSize::S64x32,
+			2 => ScreenSize::S32x64,
+			_ => ScreenSize::S64x64,
+		}
+	}
+
+	/// Returns the width in tiles.
+	#[inline]
+	pub fn width(self) -> usize {
+		match self {
+			ScreenSize::S32x32 | ScreenSize::S32x64 => 32,
+			ScreenSize::S64x32 | ScreenSize::S64x64 => 64,
+		}
+	}
+
+	/// Returns the height in tiles.
+	#[inline]
+	pub fn height(self) -> usize {
+		match self {
+			ScreenSize::S32x32 | ScreenSize::S64x32 => 32,
+			ScreenSize::S32x64 | ScreenSize::S64x64 => 64,
+		}
+	}
+
+	/// Returns the number of screens.
+	#[inline]
+	pub fn screens(self) -> usize {
+		self.width() / 32 * self.height() / 32
+	}
+}
+
+/// BG tilemap of one to four 32×32 screens, as stored in VRAM.
+///
+/// Entries are addressed by tile coordinates across the screens, which wrap around as the PPU scrolls.
+/// ```
+/// # use sneslib::graphics::tilemap::*;
+/// let mut vram = vec![0; 0x1000];
+/// vram[0x800 + 2] = 0x34;
+/// vram[0x800 + 3] = 0x12;
+/// let tilemap = Tilemap::read_from(&vram, ScreenSize::S64x32).unwrap();
+/// assert_eq!(tilemap.get(33, 0), TilemapEntry(0x1234));
+/// assert_eq!(tilemap.get(97, 32), TilemapEntry(0x1234));
+/// assert_eq!(tilemap.to_bytes(), vram);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tilemap {
+	size: ScreenSize,
+	/// Entries of each screen in turn, row by row.
+	entries: Vec<TilemapEntry>,
+}
+
+#[cfg(feature = "alloc")]
+impl Tilemap {
+	/// Creates a tilemap of blank entries.
+	pub fn new(size: ScreenSize) -> Self {
+		Self {
+			size,
+			entries: vec![TilemapEntry::default(); size.screens() * SCREEN_ENTRIES],
+		}
+	}
+
+	/// Reads the little-endian entries of the screens one after another,
+	/// or returns `None` if `bytes` is too short.
+	pub fn read_from(bytes: &[u8], size: ScreenSize) -> Option<Self> {
+		let bytes = bytes.get(..size.screens() * SCREEN_ENTRIES * 2)?;
+		let entries = bytes
+			.chunks_exact(2)
+			.map(|entry| TilemapEntry(u16::from_le_bytes([entry[0], entry[1]])))
+			.collect();
+		Some(Self { size, entries })
+	}
+
+	/// Writes the entries as read by [`read_from`](#method.read_from).
+	pub fn to_bytes(&self) -> Vec<u8> {
+		self.entries
+			.iter()
+			.flat_map(|entry| entry.0.to_le_bytes())
+			.collect()
+	}
+
+	#[inline]
+	pub fn size(&self) -> ScreenSize {
+		self.size
+	}
+
+	#[inline]
+	fn index(&self, x: usize, y: usize) -> usize {
+		let (x, y) = (x % self.size.width(), y % self.size.height());
+		let screen = y / 32 * (self.size.width() / 32) + x / 32;
+		screen * SCREEN_ENTRIES + y % 32 * 32 + x % 32
+	}
+
+	/// Returns the entry at tile `x`, `y`, wrapping around the tilemap.
+	#[inline]
+	pub fn get(&self, x: usize, y: usize) -> TilemapEntry {
+		self.entries[self.index(x, y)]
+	}
+
+	/// Returns the entry at tile `x`, `y` as [`get`](#method.get) does.
+	#[inline]
+	pub fn get_mut(&mut self, x: usize, y: usize) -> &mut TilemapEntry {
+		let index = self.index(x, y);
+		&mut self.entries[index]
+	}
+
+	/// Returns the entries of screen `index` row by row.
+	///
+	/// # Panics
+	/// Panics if there is no such screen.
+	#[inline]
+	pub fn screen(&self, index: usize) -> &[TilemapEntry] {
+		&self.entries[index * SCREEN_ENTRIES..][..SCREEN_ENTRIES]
+	}
+
+	/// Returns the entries of each screen in turn.
+	#[inline]
+	pub fn entries(&self) -> &[TilemapEntry] {
+		&self.entries
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn entry() {
+		let mut entry = TilemapEntry::default();
+		entry.set_character(0xFFFF);
+		entry.set_palette(5);
+		entry.set_priority(true);
+		entry.set_h_flip(true);
+		assert_eq!(entry, TilemapEntry(0x77FF));
+		assert_eq!(
+			(entry.character(), entry.palette(), entry.v_flip()),
+			(0x3FF, 5, false)
+		);
+		entry.set_character(0x001);
+		entry.set_priority(false);
+		assert_eq!(entry, TilemapEntry(0x5401));
+	}
+
+	#[test]
+	fn screen_size() {
+		assert_eq!(ScreenSize::from_bits(0x5A), ScreenSize::S32x64);
+		assert_eq!(ScreenSize::S32x32.screens(), 1);
+		assert_eq!(ScreenSize::S64x32.screens(), 2);
+		assert_eq!(ScreenSize::S64x64.screens(), 4);
+	}
+
+	#[test]
+	#[cfg(feature = "alloc")]
+	fn tilemap() {
+		let mut tilemap = Tilemap::new(ScreenSize::S64x64);
+		*tilemap.get_mut(0, 32) = TilemapEntry(1);
+		*tilemap.get_mut(63, 63) = TilemapEntry(2);
+		*tilemap.get_mut(32, 1) = TilemapEntry(3);
+		assert_eq!(tilemap.screen(2)[0], TilemapEntry(1));
+		assert_eq!(tilemap.screen(3)[SCREEN_ENTRIES - 1], TilemapEntry(2));
+		assert_eq!(tilemap.screen(1)[32], TilemapEntry(3));
+		assert_eq!(tilemap.get(64, 96), TilemapEntry(1));
+
+		let bytes = tilemap.to_bytes();
+		assert_eq!(bytes.len(), 0x2000);
+		assert_eq!(bytes[0x1000..0x1002], [1, 0]);
+		assert_eq!(
+			Tilemap::read_from(&bytes, ScreenSize::S64x64),
+			Some(tilemap)
+		);
+		assert!(Tilemap::read_from(&bytes[..0x1FFF], ScreenSize::S64x64).is_none());
+
+		let tilemap = Tilemap::read_from(&bytes, ScreenSize::S32x64).unwrap();
+		assert_eq!(tilemap.get(0, 33), TilemapEntry(3));
+		assert_eq!(tilemap.entries().len(), 2 * SCREEN_ENTRIES);
+	}
+}
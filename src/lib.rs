@@ -1,4 +1,15 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod address;
 pub mod cartridge;
+pub mod cheat;
+#[cfg(feature = "datfile")]
+pub mod datfile;
 pub mod graphics;
+#[cfg(feature = "alloc")]
 pub mod memory;
+#[cfg(feature = "alloc")]
+pub mod patch;
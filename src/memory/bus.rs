@@ -0,0 +1,177 @@
+use super::MemoryMap;
+use crate::address::{Address16, Address24};
+
+/// Address space as seen by a core, implemented by [`MemoryMap`] and by memory standing in for it,
+/// e.g. instrumented, mocked or remote memory.
+/// ```
+/// # use sneslib::address::Address24;
+/// # use sneslib::cartridge::*;
+/// # use sneslib::memory::*;
+/// /// Pushes the return address of a `JSL` onto the stack.
+/// fn push_return<B: Bus>(bus: &mut B, stack: u16, pc: Address24) {
+///     bus.write(Address24::new(stack as u32), pc.bank());
+///     bus.write16(Address24::new(stack as u32 - 2), pc.get_lower_address16().into());
+///     bus.tick(6);
+/// }
+///
+/// let mut memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+/// push_return(&mut memory_map, 0x1FFF, Address24::new(0x808123));
+/// assert_eq!(memory_map.read24(Address24::new(0x001FFD)), Address24::new(0x808123));
+/// ```
+pub trait Bus {
+	/// Reads a byte.
+	fn read(&mut self, address: Address24) -> u8;
+
+	/// Writes a byte.
+	fn write(&mut self, address: Address24, value: u8);
+
+	/// Reads a byte fetched as part of an instruction, as a read unless told apart.
+	#[inline]
+	fn fetch(&mut self, address: Address24) -> u8 {
+		self.read(address)
+	}
+
+	/// Reads a little-endian word, the high byte carrying into the next bank.
+	#[inline]
+	fn read16(&mut self, address: Address24) -> u16 {
+		let low = self.read(address);
+		let high = self.read(address.add_crossing_bank(Address16::new(1)));
+		u16::from_le_bytes([low, high])
+	}
+
+	/// Writes a little-endian word, the low byte first and the high byte carrying into the next bank.
+	#[inline]
+	fn write16(&mut self, address: Address24, value: u16) {
+		let [low, high] = value.to_le_bytes();
+		self.write(address, low);
+		self.write(address.add_crossing_bank(Address16::new(1)), high);
+	}
+
+	/// Lets `cycles` master clock cycles elapse, e.g. for timers or DMA driven by the bus.
+	/// Does nothing by default.
+	#[inline]
+	fn tick(&mut self, cycles: u32) {
+		let _ = cycles;
+	}
+}
+
+/// A memory map shared by several cores, e.g. through a [`SharedMemoryMap`](super::SharedMemoryMap).
+impl Bus for &MemoryMap {
+	#[inline]
+	fn read(&mut self, address: Address24) -> u8 {
+		MemoryMap::read(self, address)
+	}
+
+	#[inline]
+	fn write(&mut self, address: Address24, value: u8) {
+		MemoryMap::write(self, address, value)
+	}
+
+	#[inline]
+	fn fetch(&mut self, address: Address24) -> u8 {
+		MemoryMap::fetch(self, address)
+	}
+
+	#[inline]
+	fn read16(&mut self, address: Address24) -> u16 {
+		MemoryMap::read16(self, address)
+	}
+
+	#[inline]
+	fn write16(&mut self, address: Address24, value: u16) {
+		MemoryMap::write16(self, address, value)
+	}
+}
+
+impl Bus for MemoryMap {
+	#[inline]
+	fn read(&mut self, address: Address24) -> u8 {
+		MemoryMap::read(self, address)
+	}
+
+	#[inline]
+	fn write(&mut self, address: Address24, value: u8) {
+		MemoryMap::write(self, address, value)
+	}
+
+	#[inline]
+	fn fetch(&mut self, address: Address24) -> u8 {
+		MemoryMap::fetch(self, address)
+	}
+
+	#[inline]
+	fn read16(&mut self, address: Address24) -> u16 {
+		MemoryMap::read16(self, address)
+	}
+
+	#[inline]
+	fn write16(&mut self, address: Address24, value: u16) {
+		MemoryMap::write16(self, address, value)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::ROMType;
+	use std::collections::HashMap;
+
+	/// Memory recording the accesses reaching it.
+	#[derive(Default)]
+	struct Mock {
+		memory: HashMap<Address24, u8>,
+		accesses: Vec<(Address24, Option<u8>)>,
+		cycles: u32,
+	}
+
+	impl Bus for Mock {
+		fn read(&mut self, address: Address24) -> u8 {
+			self.accesses.push((address, None));
+			self.memory.get(&address).copied().unwrap_or(0)
+		}
+
+		fn write(&mut self, address: Address24, value: u8) {
+			self.accesses.push((address, Some(value)));
+			self.memory.insert(address, value);
+		}
+
+		fn tick(&mut self, cycles: u32) {
+			self.cycles += cycles;
+		}
+	}
+
+	fn copy_word<B: Bus>(bus: &mut B, from: Address24, to: Address24) -> u16 {
+		let value = bus.read16(from);
+		bus.write16(to, value);
+		bus.tick(8);
+		bus.fetch(from).into()
+	}
+
+	#[test]
+	fn bus() {
+		let mut mock = Mock::default();
+		mock.write16(Address24::new(0x7EFFFF), 0x1234);
+		assert_eq!(
+			copy_word(
+				&mut mock,
+				Address24::new(0x7EFFFF),
+				Address24::new(0x000010)
+			),
+			0x34
+		);
+		assert_eq!(mock.memory[&Address24::new(0x7F0000)], 0x12);
+		assert_eq!(mock.memory[&Address24::new(0x000011)], 0x12);
+		assert_eq!(mock.accesses.len(), 7);
+		assert_eq!(mock.cycles, 8);
+
+		let mut memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+		memory_map.write16(Address24::new(0x7EFFFF), 0x1234);
+		copy_word(
+			&mut memory_map,
+			Address24::new(0x7EFFFF),
+			Address24::new(0x000010),
+		);
+		let mut shared = &memory_map;
+		assert_eq!(Bus::read16(&mut shared, Address24::new(0x7E0010)), 0x1234);
+	}
+}
@@ -0,0 +1,16 @@
+use super::RegisterWindow;
+
+/// Returns the RAM at `$6000-$6BFF` and the registers at `$7F40-$7FFF` of the CX4,
+/// as one window in each of `$00-$3F/$80-$BF`.
+pub(super) fn registers() -> Vec<RegisterWindow> {
+	vec![
+		RegisterWindow {
+			banks: 0x00..=0x3F,
+			offsets: 0x6000..=0x7FFF,
+		},
+		RegisterWindow {
+			banks: 0x80..=0xBF,
+			offsets: 0x6000..=0x7FFF,
+		},
+	]
+}
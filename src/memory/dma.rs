@@ -0,0 +1,288 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use super::{MapInfo, MemoryMap};
+use crate::address::Address24;
+
+/// Offset of `HDMAEN` in the DMA registers, after the 16 registers of each of the 8 channels.
+pub(super) const HDMAEN: usize = 0x80;
+/// Offset of the internal flags of the channels still running HDMA this frame.
+const HDMA_ACTIVE: usize = 0x81;
+/// Offset of the internal flags of the channels transferring on the next scanline.
+const HDMA_TRANSFER: usize = 0x82;
+/// Size of the DMA registers and the internal HDMA state.
+pub(super) const REGISTERS_SIZE: usize = 0x83;
+
+// registers of a channel at `$43x0-$43xF`
+const DMAP: usize = 0x0;
+const BBAD: usize = 0x1;
+const A1B: usize = 0x4;
+const DAS: usize = 0x5;
+const DASB: usize = 0x7;
+const A2A: usize = 0x8;
+const NLTR: usize = 0xA;
+
+/// Offsets from `BBAD` of the B-bus registers written by each transfer mode.
+const TRANSFER_UNITS: [&[u8]; 8] = [
+	&[0],
+	&[0, 1],
+	&[0, 0],
+	&[0, 0, 1, 1],
+	&[0, 1, 2, 3],
+	&[0, 1, 0, 1],
+	&[0, 0],
+	&[0, 0, 1, 1],
+];
+
+/// Lays out the DMA registers, `HDMAEN` at `$420C` and the registers of the 8 channels
+/// at `$4300-$437F` with `$43xF` mirroring `$43xB`, in `$00-$3F/$80-$BF`.
+pub(super) fn map_info() -> impl Iterator<Item = MapInfo> {
+	(0x00..=0x3F).chain(0x80..=0xBF).flat_map(|i: usize| {
+		let channels = (0..8).flat_map(move |channel: usize| {
+			[
+				MapInfo::DMA {
+					src: channel << 4,
+					dst: i << 16 | 0x4300 | channel << 4,
+					len: 0xC,
+				},
+				MapInfo::DMA {
+					src: channel << 4 | 0xB,
+					dst: i << 16 | 0x430F | channel << 4,
+					len: 1,
+				},
+			]
+		});
+		core::iter::once(MapInfo::DMA {
+			src: HDMAEN,
+			dst: i << 16 | 0x420C,
+			len: 1,
+		})
+		.chain(channels)
+	})
+}
+
+impl MemoryMap {
+	#[inline]
+	fn dma_register(&self, channel: usize, register: usize) -> &AtomicU8 {
+		&self.dma[channel << 4 | register]
+	}
+
+	fn dma_register16(&self, channel: usize, register: usize) -> u16 {
+		let low = self.dma_register(channel, register).load(Ordering::Relaxed);
+		let high = self
+			.dma_register(channel, register + 1)
+			.load(Ordering::Relaxed);
+		u16::from_le_bytes([low, high])
+	}
+
+	fn set_dma_register16(&self, channel: usize, register: usize, value: u16) {
+		let [low, high] = value.to_le_bytes();
+		self.dma_register(channel, register)
+			.store(low, Ordering::Relaxed);
+		self.dma_register(channel, register + 1)
+			.store(high, Ordering::Relaxed);
+	}
+
+	/// Reads the byte at the table address of a channel, advancing it.
+	fn read_hdma_table(&self, channel: usize) -> u8 {
+		let bank = self.dma_register(channel, A1B).load(Ordering::Relaxed);
+		let address = self.dma_register16(channel, A2A);
+		self.set_dma_register16(channel, A2A, address.wrapping_add(1));
+		self.read(Address24::new(u32::from(bank) << 16 | u32::from(address)))
+	}
+
+	/// Loads the line counter of the next table entry of a channel, along with its data address in indirect mode.
+	///
+	/// Returns `false` if the line counter is zero, ending the table.
+	fn load_hdma_entry(&self, channel: usize) -> bool {
+		let line_counter = self.read_hdma_table(channel);
+		self.dma_register(channel, NLTR)
+			.store(line_counter, Ordering::Relaxed);
+		if line_counter != 0 && self.dma_register(channel, DMAP).load(Ordering::Relaxed) & 0x40 != 0
+		{
+			let low = self.read_hdma_table(channel);
+			let high = self.read_hdma_table(channel);
+			self.set_dma_register16(channel, DAS, u16::from_le_bytes([low, high]));
+		}
+		line_counter != 0
+	}
+
+	/// Transfers one unit of the transfer mode of a channel between its data and the B-bus.
+	fn transfer_hdma_unit(&self, channel: usize) {
+		let parameters = self.dma_register(channel, DMAP).load(Ordering::Relaxed);
+		let b_address = self.dma_register(channel, BBAD).load(Ordering::Relaxed);
+		let indirect = parameters & 0x40 != 0;
+		for &offset in TRANSFER_UNITS[parameters as usize & 0x07].iter() {
+			let b_bus = Address24::new(0x002100 | u32::from(b_address.wrapping_add(offset)));
+			let a_bus = if indirect {
+				let bank = self.dma_register(channel, DASB).load(Ordering::Relaxed);
+				let address = self.dma_register16(channel, DAS);
+				self.set_dma_register16(channel, DAS, address.wrapping_add(1));
+				Address24::new(u32::from(bank) << 16 | u32::from(address))
+			} else {
+				let bank = self.dma_register(channel, A1B).load(Ordering::Relaxed);
+				let address = self.dma_register16(channel, A2A);
+				self.set_dma_register16(channel, A2A, address.wrapping_add(1));
+				Address24::new(u32::from(bank) << 16 | u32::from(address))
+			};
+			if parameters & 0x80 == 0 {
+				self.write(b_bus, self.read(a_bus));
+			} else {
+				self.write(a_bus, self.read(b_bus));
+			}
+		}
+	}
+
+	/// Reloads the tables of the channels enabled in `HDMAEN`, as at the start of a frame.
+	fn init_hdma(&self) {
+		let enabled = self.dma[HDMAEN].load(Ordering::Relaxed);
+		let mut active = 0;
+		for channel in (0..8).filter(|channel| enabled & 1 << channel != 0) {
+			let table = self.dma_register16(channel, 0x2);
+			self.set_dma_register16(channel, A2A, table);
+			if self.load_hdma_entry(channel) {
+				active |= 1 << channel;
+			}
+		}
+		self.dma[HDMA_ACTIVE].store(active, Ordering::Relaxed);
+		self.dma[HDMA_TRANSFER].store(active, Ordering::Relaxed);
+	}
+
+	/// Runs HDMA for a scanline, walking the tables of the channels enabled in `HDMAEN` at `$420C`
+	/// and writing their data to the B-bus through the memory map, e.g. to PPU handlers.
+	///
+	/// Meant to be called by a scheduler for each line from 0 to the last visible line, in order.
+	/// Line 0 first reloads the tables from the addresses at `$43x2-$43x4`.
+	///
+	/// Each table entry starts with a line counter byte, ending the table if zero.
+	/// Its lower 7 bits count the lines the entry lasts. A clear bit 7 transfers one unit of data
+	/// on the first line only, and a set bit 7 repeats the transfer with the next unit on every line.
+	/// The data follows the line counter, or in indirect mode is pointed to by the 16-bit address
+	/// following it in the bank at `$43x7`.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// // a gradient written to WRAM: one byte on each of 3 lines, then the end of the table
+	/// memory_map.write_slice(Address24::new(0x7E1000), &[0x83, 0x0F, 0x0E, 0x0D, 0x00]);
+	/// // channel 0 writes to INIDISP at $2100 from $7E:1000
+	/// for (offset, value) in [0x00, 0x00, 0x00, 0x10, 0x7E].iter().enumerate() {
+	///     memory_map.write(Address24::new(0x004300 + offset as u32), *value);
+	/// }
+	/// memory_map.write(Address24::new(0x00420C), 0x01);
+	///
+	/// memory_map.run_hdma_for_scanline(0);
+	/// memory_map.run_hdma_for_scanline(1);
+	/// // the current table address at $4308-$4309
+	/// assert_eq!(memory_map.read(Address24::new(0x004308)), 0x03);
+	/// ```
+	pub fn run_hdma_for_scanline(&self, line: u16) {
+		if line == 0 {
+			self.init_hdma();
+		}
+		let enabled = self.dma[HDMAEN].load(Ordering::Relaxed)
+			& self.dma[HDMA_ACTIVE].load(Ordering::Relaxed);
+		let mut active = self.dma[HDMA_ACTIVE].load(Ordering::Relaxed);
+		let mut transfer = self.dma[HDMA_TRANSFER].load(Ordering::Relaxed);
+		for channel in (0..8).filter(|channel| enabled & 1 << channel != 0) {
+			let bit = 1 << channel;
+			if transfer & bit != 0 {
+				self.transfer_hdma_unit(channel);
+			}
+
+			let counter = self.dma_register(channel, NLTR);
+			let line_counter = counter.load(Ordering::Relaxed).wrapping_sub(1);
+			counter.store(line_counter, Ordering::Relaxed);
+			if line_counter & 0x80 != 0 {
+				transfer |= bit;
+			} else {
+				transfer &= !bit;
+			}
+			if line_counter & 0x7F == 0 {
+				if self.load_hdma_entry(channel) {
+					transfer |= bit;
+				} else {
+					active &= !bit;
+				}
+			}
+		}
+		self.dma[HDMA_ACTIVE].store(active, Ordering::Relaxed);
+		self.dma[HDMA_TRANSFER].store(transfer, Ordering::Relaxed);
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::MmioHandler;
+	use std::sync::{Arc, Mutex};
+
+	/// Records the B-bus writes with the line they happen on.
+	struct Ppu(Arc<Mutex<Vec<(u16, u8, u8)>>>, Arc<Mutex<u16>>);
+
+	impl MmioHandler for Ppu {
+		fn read(&mut self, _: Address24) -> u8 {
+			0
+		}
+
+		fn write(&mut self, address: Address24, value: u8) {
+			let line = *self.1.lock().unwrap();
+			self.0.lock().unwrap().push((line, address.low(), value));
+		}
+	}
+
+	#[test]
+	fn hdma() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let (writes, line) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(0)));
+		let id = memory_map.register_mmio(Ppu(writes.clone(), line.clone()));
+		memory_map.map_mmio(id, 0x00..=0x00, 0x2100..=0x21FF);
+
+		// channel 0: direct, one register
+		memory_map.write_slice(
+			Address24::new(0x7E1000),
+			&[0x02, 0x11, 0x83, 0x21, 0x22, 0x23, 0x01, 0x31, 0x00],
+		);
+		memory_map.write_slice(Address24::new(0x004300), &[0x00, 0x00, 0x00, 0x10, 0x7E]);
+		// channel 1: indirect, two registers
+		memory_map.write_slice(Address24::new(0x7E1100), &[0x82, 0x00, 0x20, 0x00]);
+		memory_map.write_slice(Address24::new(0x7E2000), &[0xAA, 0xBB, 0xCC, 0xDD]);
+		memory_map.write_slice(Address24::new(0x804310), &[0x41, 0x18, 0x00, 0x11, 0x7E]);
+		memory_map.write(Address24::new(0x004317), 0x7E);
+		// channel 2: not enabled
+		memory_map.write_slice(Address24::new(0x004320), &[0x00, 0x05, 0x00, 0x10, 0x7E]);
+		memory_map.write(Address24::new(0x00420C), 0x03);
+		assert_eq!(memory_map.read(Address24::new(0x00431F)), 0x00);
+		memory_map.write(Address24::new(0x00431B), 0x5A);
+		assert_eq!(memory_map.read(Address24::new(0x00431F)), 0x5A);
+
+		for i in 0..8 {
+			*line.lock().unwrap() = i;
+			memory_map.run_hdma_for_scanline(i);
+		}
+		assert_eq!(
+			*writes.lock().unwrap(),
+			vec![
+				(0, 0x00, 0x11),
+				(0, 0x18, 0xAA),
+				(0, 0x19, 0xBB),
+				(1, 0x18, 0xCC),
+				(1, 0x19, 0xDD),
+				(2, 0x00, 0x21),
+				(3, 0x00, 0x22),
+				(4, 0x00, 0x23),
+				(5, 0x00, 0x31),
+			]
+		);
+		assert_eq!(memory_map.read(Address24::new(0x004308)), 0x09);
+		assert_eq!(memory_map.read(Address24::new(0x004315)), 0x04);
+
+		// the next frame starts over
+		writes.lock().unwrap().clear();
+		memory_map.write(Address24::new(0x00420C), 0x01);
+		memory_map.run_hdma_for_scanline(0);
+		assert_eq!(*writes.lock().unwrap(), vec![(7, 0x00, 0x11)]);
+	}
+}
@@ -0,0 +1,69 @@
+use core::ops::RangeInclusive;
+
+#[cfg(feature = "std")]
+use super::RegisterWindow;
+use crate::address::Address24;
+use crate::cartridge::{Enhancement, ROMType};
+
+/// Placement of the data and status registers of a DSP-n chip on its board.
+///
+/// The registers are mirrored in `banks` and the same banks from `$80`,
+/// with the status register where the offset has `status_bit` set and the data register elsewhere.
+/// ```
+/// # use sneslib::address::Address24;
+/// # use sneslib::cartridge::*;
+/// # use sneslib::memory::DspBoard;
+/// let board = DspBoard::detect(Enhancement::DSP1, ROMType::HiROM, 0x100000).unwrap();
+/// assert_eq!(board.banks, 0x00..=0x1F);
+/// assert!(!board.is_status(Address24::new(0x006000)));
+/// assert!(board.is_status(Address24::new(0x807000)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DspBoard {
+	pub banks: RangeInclusive<u8>,
+	pub offsets: RangeInclusive<u16>,
+	pub status_bit: u16,
+}
+
+impl DspBoard {
+	/// Detects the board from the enhancement chip, the ROM type and the ROM size in bytes,
+	/// or returns `None` if the cartridge has no DSP-n.
+	pub fn detect(enhancement: Enhancement, rom_type: ROMType, rom_size: usize) -> Option<Self> {
+		let (banks, offsets, status_bit) = match (enhancement, rom_type) {
+			(Enhancement::DSP2, ROMType::LoROM) | (Enhancement::DSP3, ROMType::LoROM) => {
+				(0x20..=0x3F, 0x8000..=0xFFFF, 0x4000)
+			}
+			(Enhancement::DSP1, ROMType::LoROM) if rom_size > 0x100000 => {
+				(0x60..=0x6F, 0x0000..=0x7FFF, 0x4000)
+			}
+			(Enhancement::DSP1, ROMType::LoROM) | (Enhancement::DSP4, ROMType::LoROM) => {
+				(0x30..=0x3F, 0x8000..=0xFFFF, 0x4000)
+			}
+			(Enhancement::DSP1, ROMType::HiROM) => (0x00..=0x1F, 0x6000..=0x7FFF, 0x1000),
+			_ => return None,
+		};
+		Some(Self {
+			banks,
+			offsets,
+			status_bit,
+		})
+	}
+
+	/// Returns `true` if `address` selects the status register rather than the data register.
+	#[inline]
+	pub fn is_status(&self, address: Address24) -> bool {
+		u16::from(address.get_lower_address16()) & self.status_bit != 0
+	}
+
+	#[cfg(feature = "std")]
+	pub(super) fn registers(&self) -> Vec<RegisterWindow> {
+		let upper = self.banks.start() | 0x80..=self.banks.end() | 0x80;
+		[self.banks.clone(), upper]
+			.iter()
+			.map(|banks| RegisterWindow {
+				banks: banks.clone(),
+				offsets: self.offsets.clone(),
+			})
+			.collect()
+	}
+}
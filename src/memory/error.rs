@@ -0,0 +1,83 @@
+use core::fmt;
+
+use crate::address::Address24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+	/// A memory of the snapshot differs in size from the memory map, e.g. as taken from another cartridge.
+	/// An absent memory has a size of zero.
+	SizeMismatch {
+		memory: &'static str,
+		expected: usize,
+		found: usize,
+	},
+	/// A change of the ROM lies beyond the ROM, holding its offset.
+	RomOutOfRange(usize),
+}
+
+impl fmt::Display for SnapshotError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use SnapshotError::*;
+		match self {
+			SizeMismatch {
+				memory,
+				expected,
+				found,
+			} => write!(
+				f,
+				"{} of {} bytes in the snapshot does not match {} bytes",
+				memory, found, expected
+			),
+			RomOutOfRange(offset) => write!(f, "ROM change at ${:X} lies beyond the ROM", offset),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnapshotError {}
+
+/// A write rejected in strict mode, see [`MemoryMap::try_write`](super::MemoryMap::try_write).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryError {
+	/// A write to an address mapped to neither memory nor a handler.
+	UnmappedWrite { address: Address24, value: u8 },
+	/// A write to read-only memory, e.g. ROM, a read-only register
+	/// or an address protected by [`MemoryMap::protect`](super::MemoryMap::protect).
+	ProtectedWrite { address: Address24, value: u8 },
+}
+
+impl fmt::Display for MemoryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use MemoryError::*;
+		match self {
+			UnmappedWrite { address, value } => {
+				write!(f, "write of ${:02X} to unmapped address {}", value, address)
+			}
+			ProtectedWrite { address, value } => {
+				write!(
+					f,
+					"write of ${:02X} to read-only address {}",
+					value, address
+				)
+			}
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MemoryError {}
+
+/// Receives the writes rejected in strict mode, see [`MemoryMap::set_fault_handler`](super::MemoryMap::set_fault_handler).
+pub trait FaultHandler: Send {
+	fn fault(&mut self, error: MemoryError);
+}
+
+impl<F> FaultHandler for F
+where
+	F: FnMut(MemoryError) + Send,
+{
+	#[inline]
+	fn fault(&mut self, error: MemoryError) {
+		self(error)
+	}
+}
@@ -0,0 +1,100 @@
+//! Locks working without `std`, spinning instead of parking the thread.
+
+/// Lock of the standard library, ignoring poisoning as the state it guards stays consistent
+/// even if a handler panics.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub(super) struct Mutex<T>(std::sync::Mutex<T>);
+
+#[cfg(feature = "std")]
+pub(super) type MutexGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+
+#[cfg(feature = "std")]
+impl<T> Mutex<T> {
+	#[inline]
+	pub(super) fn new(value: T) -> Self {
+		Self(std::sync::Mutex::new(value))
+	}
+
+	#[inline]
+	pub(super) fn lock(&self) -> MutexGuard<'_, T> {
+		self.0
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+}
+
+/// Spin lock, for targets without threads to park.
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Default)]
+pub(super) struct Mutex<T>(spin::Mutex<T>);
+
+#[cfg(not(feature = "std"))]
+pub(super) type MutexGuard<'a, T> = spin::MutexGuard<'a, T>;
+
+#[cfg(not(feature = "std"))]
+impl<T> Mutex<T> {
+	#[inline]
+	pub(super) fn new(value: T) -> Self {
+		Self(spin::Mutex::new(value))
+	}
+
+	#[inline]
+	pub(super) fn lock(&self) -> MutexGuard<'_, T> {
+		self.0.lock()
+	}
+}
+
+/// Cell initialized once, by the first of the threads racing to initialize it.
+#[cfg(feature = "std")]
+pub(super) struct Once<T>(std::sync::OnceLock<T>);
+
+#[cfg(feature = "std")]
+impl<T> Once<T> {
+	#[inline]
+	pub(super) fn new() -> Self {
+		Self(std::sync::OnceLock::new())
+	}
+
+	#[inline]
+	pub(super) fn get(&self) -> Option<&T> {
+		self.0.get()
+	}
+
+	#[inline]
+	pub(super) fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+		self.0.get_or_init(f)
+	}
+
+	#[inline]
+	pub(super) fn take(&mut self) -> Option<T> {
+		self.0.take()
+	}
+}
+
+/// Cell initialized once, spinning while another thread initializes it.
+#[cfg(not(feature = "std"))]
+pub(super) struct Once<T>(spin::Once<T>);
+
+#[cfg(not(feature = "std"))]
+impl<T> Once<T> {
+	#[inline]
+	pub(super) fn new() -> Self {
+		Self(spin::Once::new())
+	}
+
+	#[inline]
+	pub(super) fn get(&self) -> Option<&T> {
+		self.0.get()
+	}
+
+	#[inline]
+	pub(super) fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+		self.0.call_once(f)
+	}
+
+	#[inline]
+	pub(super) fn take(&mut self) -> Option<T> {
+		core::mem::take(&mut self.0).try_into_inner()
+	}
+}
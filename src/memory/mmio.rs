@@ -0,0 +1,155 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use super::lock::Mutex;
+use crate::address::Address24;
+
+/// Hardware registers handling accesses to the addresses mapped to them,
+/// e.g. the PPU at `$2100-$213F` or the APU ports at `$2140-$217F`.
+pub trait MmioHandler: Send {
+	fn read(&mut self, address: Address24) -> u8;
+	fn write(&mut self, address: Address24, value: u8);
+}
+
+/// Identifies a handler registered to a [`MemoryMap`](super::MemoryMap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MmioId(pub(crate) usize);
+
+/// Offsets in a range of banks dispatched to a handler, or to none if unmapped.
+#[derive(Debug, Clone)]
+pub(crate) struct MmioRegion {
+	pub(crate) banks: RangeInclusive<u8>,
+	pub(crate) offsets: RangeInclusive<u16>,
+	pub(crate) id: Option<MmioId>,
+}
+
+impl MmioRegion {
+	#[inline]
+	pub(crate) fn contains(&self, address: Address24) -> bool {
+		self.banks.contains(&address.bank())
+			&& self
+				.offsets
+				.contains(&u16::from(address.get_lower_address16()))
+	}
+}
+
+/// Registered handlers and the regions mapped to them.
+#[derive(Default)]
+pub(crate) struct Mmio {
+	handlers: Vec<Arc<Mutex<Box<dyn MmioHandler>>>>,
+	regions: Vec<MmioRegion>,
+}
+
+impl Mmio {
+	pub(crate) fn register(&mut self, handler: Box<dyn MmioHandler>) -> MmioId {
+		self.handlers.push(Arc::new(Mutex::new(handler)));
+		MmioId(self.handlers.len() - 1)
+	}
+
+	/// Returns a copy with the same regions, sharing the handlers.
+	pub(crate) fn fork(&self) -> Self {
+		Self {
+			handlers: self.handlers.clone(),
+			regions: self.regions.clone(),
+		}
+	}
+
+	pub(crate) fn map(&mut self, region: MmioRegion) {
+		if let Some(id) = region.id {
+			assert!(id.0 < self.handlers.len(), "unknown MMIO handler");
+		}
+		// the latest region takes precedence
+		self.regions.insert(0, region);
+	}
+
+	/// Hides the regions previously mapped to `offsets` in each of `banks`.
+	pub(crate) fn unmap(&mut self, banks: RangeInclusive<u8>, offsets: RangeInclusive<u16>) {
+		let covered = |region: &MmioRegion| {
+			banks.contains(region.banks.start())
+				&& banks.contains(region.banks.end())
+				&& offsets.contains(region.offsets.start())
+				&& offsets.contains(region.offsets.end())
+		};
+		self.regions.retain(|region| !covered(region));
+		if self.regions.iter().any(|region| {
+			region.banks.start() <= banks.end()
+				&& banks.start() <= region.banks.end()
+				&& region.offsets.start() <= offsets.end()
+				&& offsets.start() <= region.offsets.end()
+		}) {
+			self.map(MmioRegion {
+				banks,
+				offsets,
+				id: None,
+			});
+		}
+	}
+
+	#[inline]
+	pub(crate) fn find(&self, address: Address24) -> Option<MmioId> {
+		self.regions
+			.iter()
+			.find(|region| region.contains(address))
+			.and_then(|region| region.id)
+	}
+
+	pub(crate) fn read(&self, id: MmioId, address: Address24) -> u8 {
+		self.handlers[id.0].lock().read(address)
+	}
+
+	pub(crate) fn write(&self, id: MmioId, address: Address24, value: u8) {
+		self.handlers[id.0].lock().write(address, value)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::MemoryMap;
+	use std::sync::{Arc, Mutex};
+
+	/// Records writes and reads back the low byte of the address.
+	struct Registers(Arc<Mutex<Vec<(Address24, u8)>>>);
+
+	impl MmioHandler for Registers {
+		fn read(&mut self, address: Address24) -> u8 {
+			address.low()
+		}
+
+		fn write(&mut self, address: Address24, value: u8) {
+			self.0.lock().unwrap().push((address, value));
+		}
+	}
+
+	#[test]
+	fn mmio() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let writes = Arc::new(Mutex::new(Vec::new()));
+		let id = memory_map.register_mmio(Registers(writes.clone()));
+		memory_map.map_mmio(id, 0x00..=0x3F, 0x2100..=0x21FF);
+		memory_map.map_mmio(id, 0x80..=0xBF, 0x2100..=0x21FF);
+
+		assert_eq!(memory_map.read(Address24::new(0x002134)), 0x34);
+		assert_eq!(memory_map.read(Address24::new(0xBF21FF)), 0xFF);
+		assert_eq!(memory_map.read(Address24::new(0x402100)), 0xFF);
+		memory_map.write(Address24::new(0x802100), 0x0F);
+		memory_map.write(Address24::new(0x002200), 0x01);
+		assert_eq!(
+			*writes.lock().unwrap(),
+			vec![(Address24::new(0x802100), 0x0F)]
+		);
+
+		// mapped over WRAM
+		memory_map.write(Address24::new(0x7E0000), 0x12);
+		let id = memory_map.register_mmio(Registers(writes.clone()));
+		memory_map.map_mmio(id, 0x7E..=0x7E, 0x0000..=0x0000);
+		assert_eq!(memory_map.read(Address24::new(0x7E0000)), 0x00);
+		assert_eq!(memory_map.read(Address24::new(0x000000)), 0x12);
+		memory_map.write(Address24::new(0x7E0000), 0x34);
+		assert_eq!(writes.lock().unwrap().len(), 2);
+	}
+}
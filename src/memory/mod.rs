@@ -1,263 +1,2154 @@
-use std::sync::atomic::{self, AtomicU8};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Range, RangeInclusive};
+use core::sync::atomic::{self, AtomicU8};
 
-use crate::address::Address24;
-use crate::cartridge::{Cartridge, ROMType};
+use crate::address::{Address16, Address24, AddressRange24};
+use crate::cartridge::ROMType;
+#[cfg(feature = "std")]
+use crate::cartridge::{Cartridge, Enhancement};
+use lock::Mutex;
+use paged::PagedMemory;
+
+pub use bus::Bus;
+pub use dsp::DspBoard;
+pub use error::{FaultHandler, MemoryError, SnapshotError};
+pub use mmio::{MmioHandler, MmioId};
+pub use ports::PortRegisters;
+pub use resolve::{MappedTarget, MemoryRegion};
+pub use scan::{MemoryScanner, Refinement, ScanValue};
+pub use snapshot::{MemDelta, MemorySnapshot};
+pub use stats::{AccessCounts, StatsGranularity};
+pub use trace::{Access, AccessKind, TraceSink};
+pub use value::{BankWrap, FromLeBytes, ToLeBytes};
+
+mod bus;
+#[cfg(feature = "std")]
+mod cx4;
+mod dma;
+mod dsp;
+pub mod error;
+mod lock;
+pub mod mmio;
+mod paged;
+mod ports;
+mod resolve;
+#[cfg(feature = "std")]
+mod sa1;
+mod scan;
+mod snapshot;
+mod spc7110;
+#[cfg(feature = "std")]
+mod srm;
+mod stats;
+#[cfg(feature = "std")]
+mod superfx;
+mod table;
+pub mod trace;
+mod value;
 
 const PAGE_SIZE: usize = 64 * 1024;
 const MAP_SIZE: usize = 256 * PAGE_SIZE;
+const WRAM_SIZE: usize = 2 * PAGE_SIZE;
+
+type Ram = Box<[AtomicU8]>;
+
+/// Memory owned by a memory map.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+	ROM = 1,
+	WRAM,
+	SRAM,
+	IRAM,
+	/// ROM switched by the Super MMC bank registers.
+	MMCROM,
+	/// The Super MMC bank registers.
+	MMC,
+	/// SPC7110 data ROM switched by its bank registers.
+	DataROM,
+	/// The SPC7110 data ROM bank registers.
+	DataBanks,
+	/// The DMA registers.
+	DMA,
+	/// Registers with side effects, e.g. the WRAM and VRAM ports, by their offset from `$2100`.
+	Port,
+}
+
+/// An entry of the mapping tables, packing the mapped memory into the upper byte
+/// and the offset within it into the lower 24 bits, or zero if unmapped.
+///
+/// A writable entry protected by [`MemoryMap::protect`] has its top bit set, so it maps no memory until unprotected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+struct Entry(u32);
+
+impl Entry {
+	const UNMAPPED: Self = Entry(0);
+	const PROTECTED: u32 = 1 << 31;
+
+	#[inline]
+	fn new(source: Source, offset: usize) -> Self {
+		debug_assert!(offset <= 0xFFFFFF);
+		Entry((source as u32) << 24 | offset as u32)
+	}
+
+	#[inline]
+	fn source(self) -> Option<Source> {
+		match self.0 >> 24 {
+			1 => Some(Source::ROM),
+			2 => Some(Source::WRAM),
+			3 => Some(Source::SRAM),
+			4 => Some(Source::IRAM),
+			5 => Some(Source::MMCROM),
+			6 => Some(Source::MMC),
+			7 => Some(Source::DataROM),
+			8 => Some(Source::DataBanks),
+			9 => Some(Source::DMA),
+			10 => Some(Source::Port),
+			_ => None,
+		}
+	}
+
+	#[inline]
+	fn offset(self) -> usize {
+		(self.0 & 0xFFFFFF) as usize
+	}
 
-type ReadableMemory = Box<[Option<*const AtomicU8>]>;
-type WritableMemory = Box<[Option<*const AtomicU8>]>;
-type RAM = Box<[AtomicU8]>;
-type ROM = Box<[AtomicU8]>;
+	#[inline]
+	fn is_protected(self) -> bool {
+		self.0 & Self::PROTECTED != 0
+	}
+}
+
+/// How reads from addresses mapped to neither memory nor a handler are answered.
+#[derive(Clone, Default)]
+pub enum UnmappedPolicy {
+	/// The memory data register, holding the last value driven on the bus.
+	#[default]
+	OpenBus,
+	/// A constant value.
+	Constant(u8),
+	/// Panics in debug builds to catch stray reads, and answers with open bus in release builds.
+	PanicInDebug,
+	/// The value returned by a callback given the address.
+	Callback(Arc<dyn Fn(Address24) -> u8 + Send + Sync>),
+}
+
+impl fmt::Debug for UnmappedPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::OpenBus => f.write_str("OpenBus"),
+			Self::Constant(value) => f.debug_tuple("Constant").field(value).finish(),
+			Self::PanicInDebug => f.write_str("PanicInDebug"),
+			Self::Callback(_) => f.write_str("Callback(..)"),
+		}
+	}
+}
 
+/// Memory ordering of the reads and writes of memory through a memory map, see [`MemoryMap::set_ordering`].
+///
+/// Each byte is accessed atomically regardless, so the ordering only matters to threads
+/// handing over data through memory, e.g. a CPU core and a coprocessor core running on their own threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AccessOrdering {
+	/// No ordering with other accesses, the fastest for a single thread.
+	#[default]
+	Relaxed,
+	/// Reads acquire and writes release, ordering the accesses before a write before the read of its value.
+	AcquireRelease,
+	/// A single order of all accesses seen alike by all threads.
+	SeqCst,
+}
+
+impl AccessOrdering {
+	#[inline]
+	fn load(self) -> atomic::Ordering {
+		match self {
+			AccessOrdering::Relaxed => atomic::Ordering::Relaxed,
+			AccessOrdering::AcquireRelease => atomic::Ordering::Acquire,
+			AccessOrdering::SeqCst => atomic::Ordering::SeqCst,
+		}
+	}
+
+	#[inline]
+	fn store(self) -> atomic::Ordering {
+		match self {
+			AccessOrdering::Relaxed => atomic::Ordering::Relaxed,
+			AccessOrdering::AcquireRelease => atomic::Ordering::Release,
+			AccessOrdering::SeqCst => atomic::Ordering::SeqCst,
+		}
+	}
+}
+
+/// Banks and offsets of the registers of an enhancement chip, see [`MemoryMap::map_chip_registers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterWindow {
+	pub banks: RangeInclusive<u8>,
+	pub offsets: RangeInclusive<u16>,
+}
+
+/// Address space of the CPU, mapping each 24-bit address to the memory, port or handler answering it.
+///
+/// A memory map is `Send` and `Sync`, so threads may share it, e.g. as a [`SharedMemoryMap`]
+/// for a debugger to read memory while the core runs. Each byte of memory is an atomic, so accesses
+/// from several threads race only at the byte level: a read sees a byte as it was before or after
+/// a concurrent write, never torn, whereas a value of several bytes, e.g. read by [`read16`](Self::read16),
+/// may mix bytes from before and after. Accesses are ordered with each other as set by [`set_ordering`](Self::set_ordering).
+///
+/// The ports, handlers, fault handler and trace are each behind a lock, so their calls never overlap.
+/// Mapping memory takes `&mut self`, excluding any concurrent access.
 pub struct MemoryMap {
-	readable: ReadableMemory,
-	writable: WritableMemory,
-	rom: ROM,
-	wram: RAM,
-	sram: Option<RAM>,
+	readable: table::Table,
+	writable: table::Table,
+	rom: PagedMemory,
+	wram: PagedMemory,
+	sram: Option<PagedMemory>,
+	#[cfg(feature = "std")]
+	sram_file: Option<srm::SramFile>,
+	iram: Option<Ram>,
+	mmc: [AtomicU8; 4],
+	data_banks: [AtomicU8; 3],
+	dma: Ram,
+	vram: PagedMemory,
+	cgram: Ram,
+	oam: Ram,
+	ports: Mutex<PortRegisters>,
+	mmio: mmio::Mmio,
+	chip_registers: Vec<RegisterWindow>,
+	dsp_board: Option<DspBoard>,
+	unmapped_policy: UnmappedPolicy,
+	ordering: AccessOrdering,
+	mdr: AtomicU8,
+	fault_handler: Option<Arc<Mutex<Box<dyn FaultHandler>>>>,
+	trace: trace::Trace,
+	stats: stats::Stats,
 }
 
+/// Memory map shared between threads, e.g. by a core writing memory and a debugger reading it.
+/// ```
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// # use sneslib::address::Address24;
+/// # use sneslib::cartridge::ROMType;
+/// # use sneslib::memory::*;
+/// let memory_map: SharedMemoryMap = Arc::new(MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0));
+/// let core = Arc::clone(&memory_map);
+/// thread::spawn(move || core.write(Address24::new(0x7E0010), 0x12))
+///     .join()
+///     .unwrap();
+/// assert_eq!(memory_map.read(Address24::new(0x000010)), 0x12);
+/// ```
+pub type SharedMemoryMap = Arc<MemoryMap>;
+
+// a memory map is shared between threads as is, see `SharedMemoryMap`
+const _: fn() = || {
+	fn assert_send_sync<T: Send + Sync>() {}
+	assert_send_sync::<MemoryMap>();
+};
+
+/// Memory mapped into the address space by [`MemoryMap::map`],
+/// `len` bytes from offset `src` into the memory placed at address `dst`.
 #[derive(Debug, Clone, Copy)]
 pub enum MapInfo {
-	ROM { src: usize, dst: usize, len: usize },
-	WRAM { src: usize, dst: usize, len: usize },
-	SRAM { src: usize, dst: usize, len: usize },
+	/// ROM, read-only.
+	ROM {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	WRAM {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	SRAM {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	IRAM {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	/// ROM of the 1MB block selected by the Super MMC bank register `area`, 0 to 3 for `CXB` to `FXB`.
+	///
+	/// A LoROM area keeps the block of its index while bit 7 of the register is clear.
+	MMCROM {
+		area: usize,
+		lorom: bool,
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	/// The Super MMC bank registers from `src`, write-only.
+	MMC {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	/// SPC7110 data ROM of the 1MB block selected by the bank register `area`, 0 to 2 for `$4831` to `$4833`.
+	DataROM {
+		area: usize,
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	/// The SPC7110 data ROM bank registers from `src`.
+	DataBanks {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	/// The DMA registers from `src`, the 16 registers of each channel followed by `HDMAEN`, which is write-only.
+	DMA {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
+	/// The port registers from `src`, the offset of the first from `$2100`, each readable and writable as on hardware.
+	Port {
+		src: usize,
+		dst: usize,
+		len: usize,
+	},
 }
 
-fn new_ram(n: usize) -> RAM {
-	(0..n)
-		.map(|_| AtomicU8::default())
-		.collect::<Vec<_>>()
-		.into_boxed_slice()
+/// Updates the entries of `table` at `offsets` in each of `banks`.
+fn update_entries(
+	table: &mut table::Table,
+	banks: RangeInclusive<u8>,
+	offsets: &RangeInclusive<u16>,
+	f: impl Fn(&mut Entry),
+) {
+	for bank in banks {
+		let bank = (bank as usize) << 16;
+		let range = bank | *offsets.start() as usize..(bank | *offsets.end() as usize) + 1;
+		table.update(range, |_, e| f(e));
+	}
+	table.share_pages();
 }
 
-impl MemoryMap {
-	pub fn from_cartridge(cartridge: Cartridge, hint: Option<ROMType>) -> Self {
-		let wram = new_ram(2 * PAGE_SIZE);
-		let sram = None;
-		let rom = cartridge
-			.rom
-			.iter()
-			.map(|&b| AtomicU8::new(b))
-			.collect::<Vec<_>>()
-			.into_boxed_slice();
-		let writable = vec![None; MAP_SIZE].into_boxed_slice();
-		let readable = vec![None; MAP_SIZE].into_boxed_slice();
-
-		let mut memory_map = Self {
-			readable,
-			writable,
-			rom,
-			wram,
-			sram,
-		};
+/// Lays out the 128KB of WRAM and the port and DMA registers, as for every cartridge.
+fn system_map_info() -> Vec<MapInfo> {
+	let mut map_info = wram_map_info();
 
-		let mut map_info = Vec::new();
+	// port and DMA registers
+	map_info.extend(ports::map_info());
+	map_info.extend(dma::map_info());
+	map_info
+}
 
-		// WRMA
-		// $xx:0000-$xx:1FFF
-		map_info.extend((0x00..=0x3F).chain(0x80..=0xBF).map(|i| MapInfo::WRAM {
+/// Lays out the 128KB of WRAM.
+fn wram_map_info() -> Vec<MapInfo> {
+	// WRAM
+	// $xx:0000-$xx:1FFF
+	let mut map_info = (0x00..=0x3F)
+		.chain(0x80..=0xBF)
+		.map(|i| MapInfo::WRAM {
 			src: 0,
 			dst: i << 16,
 			len: 0x2000,
-		}));
+		})
+		.collect::<Vec<_>>();
 
-		// $7E-$7F
-		map_info.push(MapInfo::WRAM {
-			src: 0,
-			dst: 0x7E0000,
-			len: memory_map.wram.len(),
-		});
+	// $7E-$7F
+	map_info.push(MapInfo::WRAM {
+		src: 0,
+		dst: 0x7E0000,
+		len: WRAM_SIZE,
+	});
+	map_info
+}
 
-		match hint {
-			Some(ROMType::LoROM) => {
-				assert!(memory_map.rom.len() <= 0x400000);
-				// ROM
-				map_info.extend(
-					(0x00..=0x7D)
-						.chain(0x80..=0xFF)
-						.filter(|&i| (i & 0x7F) * 0x8000 < memory_map.rom.len())
-						.map(|i| MapInfo::ROM {
-							src: (i & 0x7F) * 0x8000,
-							dst: i << 16 | 0x8000,
-							len: 0x8000,
-						}),
-				);
+/// Lays out the ROM and SRAM of a cartridge without an enhancement chip mapping them.
+fn rom_map_info(rom_type: ROMType, rom_len: usize, sram_size: Option<usize>) -> Vec<MapInfo> {
+	let mut map_info = Vec::new();
+	match rom_type {
+		ROMType::LoROM => {
+			assert!(rom_len <= 0x400000);
+			// ROM
+			map_info.extend(
+				(0x00..=0x7D)
+					.chain(0x80..=0xFF)
+					.filter(|&i| (i & 0x7F) * 0x8000 < rom_len)
+					.map(|i| MapInfo::ROM {
+						src: (i & 0x7F) * 0x8000,
+						dst: i << 16 | 0x8000,
+						len: 0x8000,
+					}),
+			);
 
-				if let Some(sram_size) = memory_map.sram.as_ref().map(|sram| sram.len()) {
-					// with SRAM
-					if sram_size <= 0x8000 {
-						// small SRAM
-						map_info.extend(
-							(0x70..=0x7D)
-								.chain(0xF0..=0xFF)
-								.flat_map(|i| (i << 16..i << 16 | 0x8000).step_by(sram_size))
-								.map(|dst| MapInfo::SRAM {
-									src: 0,
-									dst,
-									len: sram_size,
-								}),
-						);
-					} else {
-						// large SRAM
-						map_info.extend(
-							(0x700000..=0x7DFFFF)
-								.chain(0xF00000..=0xFFFFFF)
-								.step_by(sram_size)
-								.map(|dst| MapInfo::SRAM {
-									src: 0,
-									dst,
-									len: sram_size,
-								}),
-						);
-					}
-				} else {
-					// without SRAM
-					// ROM mirror
-					map_info.extend(
-						(0x40..=0x7D)
-							.chain(0xC0..=0xFF)
-							.filter(|&i| (i & 0x7F) * 0x8000 < memory_map.rom.len())
-							.map(|i| MapInfo::ROM {
-								src: (i & 0x7F) * 0x8000,
-								dst: i << 16,
-								len: 0x8000,
-							}),
-					);
-				}
-			}
-			Some(ROMType::HiROM) => {
-				assert!(memory_map.sram.as_ref().map(|sram| sram.len()).unwrap_or(0) <= 0x2000);
-				// ROM
-				map_info.extend(
-					(0x00..=0x3F)
-						.chain(0x80..=0xBF)
-						.filter(|&i| (i & 0x3F) << 16 | 0x8000 < memory_map.rom.len())
-						.map(|i| MapInfo::ROM {
-							src: (i & 0x3F) << 16 | 0x8000,
-							dst: i << 16 | 0x8000,
-							len: 0x8000,
-						}),
-				);
+			if let Some(sram_size) = sram_size {
+				// with SRAM
+				map_info.extend(sram_windows(
+					(0x70..=0x7D).chain(0xF0..=0xFF),
+					0x0F,
+					0x0000..0x8000,
+					sram_size,
+				));
+			} else {
+				// without SRAM
+				// ROM mirror
 				map_info.extend(
 					(0x40..=0x7D)
 						.chain(0xC0..=0xFF)
-						.filter(|&i| (i & 0x3F) << 16 < memory_map.rom.len())
+						.filter(|&i| (i & 0x7F) * 0x8000 < rom_len)
 						.map(|i| MapInfo::ROM {
-							src: (i & 0x3F) << 16,
+							src: (i & 0x7F) * 0x8000,
 							dst: i << 16,
-							len: 0x10000,
+							len: 0x8000,
 						}),
 				);
+			}
+		}
+		ROMType::HiROM => {
+			// ROM
+			map_info.extend(
+				(0x00..=0x3F)
+					.chain(0x80..=0xBF)
+					.filter(|&i| (i & 0x3F) << 16 | 0x8000 < rom_len)
+					.map(|i| MapInfo::ROM {
+						src: (i & 0x3F) << 16 | 0x8000,
+						dst: i << 16 | 0x8000,
+						len: 0x8000,
+					}),
+			);
+			map_info.extend(
+				(0x40..=0x7D)
+					.chain(0xC0..=0xFF)
+					.filter(|&i| (i & 0x3F) << 16 < rom_len)
+					.map(|i| MapInfo::ROM {
+						src: (i & 0x3F) << 16,
+						dst: i << 16,
+						len: 0x10000,
+					}),
+			);
 
-				if let Some(sram_size) = memory_map.sram.as_ref().map(|sram| sram.len()) {
-					// with SRAM
-					map_info.extend(
-						(0x20..=0x3F)
-							.chain(0xA0..=0xBF)
-							.flat_map(|i| (i << 16 | 0x6000..i << 16 | 0x8000).step_by(sram_size))
-							.map(|dst| MapInfo::SRAM {
-								src: 0,
-								dst,
-								len: sram_size,
-							}),
-					);
-				}
+			if let Some(sram_size) = sram_size {
+				// with SRAM
+				map_info.extend(sram_windows(
+					(0x20..=0x3F).chain(0xA0..=0xBF),
+					0x1F,
+					0x6000..0x8000,
+					sram_size,
+				));
 			}
-			None => {
-				todo!()
+		}
+		ROMType::ExHiROM => {
+			// ROM, banks $80-$FF hold the first 4MB and banks $00-$7D the rest
+			let src = |i: usize| (!i & 0x80) << 15 | (i & 0x3F) << 16;
+			map_info.extend(
+				(0x00..=0x3F)
+					.chain(0x80..=0xBF)
+					.filter(|&i| src(i) | 0x8000 < rom_len)
+					.map(|i| MapInfo::ROM {
+						src: src(i) | 0x8000,
+						dst: i << 16 | 0x8000,
+						len: 0x8000,
+					}),
+			);
+			map_info.extend(
+				(0x40..=0x7D)
+					.chain(0xC0..=0xFF)
+					.filter(|&i| src(i) < rom_len)
+					.map(|i| MapInfo::ROM {
+						src: src(i),
+						dst: i << 16,
+						len: 0x10000,
+					}),
+			);
+
+			if let Some(sram_size) = sram_size {
+				// with SRAM
+				map_info.extend(sram_windows(0x80..=0xBF, 0x3F, 0x6000..0x8000, sram_size));
 			}
 		}
+	}
+	// cut the chunks at the end of a ROM image of a size not a multiple of them
+	for info in map_info.iter_mut() {
+		if let MapInfo::ROM { src, len, .. } = info {
+			*len = (*len).min(rom_len - *src);
+		}
+	}
+	map_info
+}
 
+fn new_ram(n: usize) -> Ram {
+	(0..n)
+		.map(|_| AtomicU8::default())
+		.collect::<Vec<_>>()
+		.into_boxed_slice()
+}
+
+/// Maps `window` of each bank to consecutive chunks of SRAM, mirroring SRAM to fill the windows.
+///
+/// The chunk of a bank is selected by the bank number masked with `bank_mask`.
+fn sram_windows(
+	banks: impl Iterator<Item = usize>,
+	bank_mask: usize,
+	window: Range<usize>,
+	sram_size: usize,
+) -> impl Iterator<Item = MapInfo> {
+	let (start, window_len) = (window.start, window.len());
+	let len = window_len.min(sram_size);
+	banks.flat_map(move |i| {
+		window
+			.clone()
+			.step_by(len)
+			.map(move |offset| MapInfo::SRAM {
+				src: ((i & bank_mask) * window_len + offset - start) % sram_size,
+				dst: i << 16 | offset,
+				len,
+			})
+	})
+}
+
+impl MemoryMap {
+	/// Returns a memory map with zeroed ROM, WRAM and SRAM of the given sizes in bytes,
+	/// zero for no SRAM, and nothing mapped, to lay out with [`map`](#method.map), e.g. for a custom mapper.
+	///
+	/// The ROM is filled through [`rom`](#method.rom).
+	/// ```
+	/// # use std::sync::atomic::Ordering;
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::memory::*;
+	/// let mut memory_map = MemoryMap::empty(0x8000, 0x20000, 0);
+	/// for (dst, &src) in memory_map.rom().iter().zip([0x78, 0x18].iter()) {
+	///     dst.store(src, Ordering::Relaxed);
+	/// }
+	/// memory_map.map(&[
+	///     MapInfo::ROM { src: 0, dst: 0x008000, len: 0x8000 },
+	///     MapInfo::WRAM { src: 0, dst: 0x7E0000, len: 0x20000 },
+	/// ]);
+	/// assert_eq!(memory_map.read(Address24::new(0x008001)), 0x18);
+	/// memory_map.write(Address24::new(0x7E0000), 0x12);
+	/// assert_eq!(memory_map.read(Address24::new(0x7E0000)), 0x12);
+	/// ```
+	pub fn empty(rom_size: usize, wram_size: usize, sram_size: usize) -> Self {
+		Self {
+			readable: table::Table::default(),
+			writable: table::Table::default(),
+			rom: PagedMemory::new(rom_size),
+			wram: PagedMemory::new(wram_size),
+			sram: match sram_size {
+				0 => None,
+				n => Some(PagedMemory::new(n)),
+			},
+			#[cfg(feature = "std")]
+			sram_file: None,
+			iram: None,
+			mmc: [0, 1, 2, 3].map(AtomicU8::new),
+			data_banks: [0, 1, 2].map(AtomicU8::new),
+			dma: new_ram(dma::REGISTERS_SIZE),
+			vram: PagedMemory::new(ports::VRAM_SIZE),
+			cgram: new_ram(ports::CGRAM_SIZE),
+			oam: new_ram(ports::OAM_SIZE),
+			ports: Mutex::default(),
+			mmio: mmio::Mmio::default(),
+			chip_registers: Vec::new(),
+			dsp_board: None,
+			unmapped_policy: UnmappedPolicy::default(),
+			ordering: AccessOrdering::default(),
+			mdr: AtomicU8::new(0),
+			fault_handler: None,
+			trace: trace::Trace::default(),
+			stats: stats::Stats::default(),
+		}
+	}
+
+	/// Maps a ROM image laid out as `rom_type` with `sram_size` bytes of SRAM, zero for none, as
+	/// [`with_sram_size`](#method.with_sram_size) maps a cartridge without an enhancement chip,
+	/// e.g. for a ROM image synthesized by a test that would not pass the checks of [`Cartridge::new`].
+	///
+	/// # Panics
+	/// Panics if a LoROM image is larger than 4MB.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::ROMType;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0x78, 0x18], ROMType::HiROM, 0x2000);
+	/// assert_eq!(memory_map.read(Address24::new(0xC00001)), 0x18);
+	/// memory_map.write(Address24::new(0x306000), 0x12);
+	/// assert_eq!(memory_map.dump_sram().unwrap()[0], 0x12);
+	/// ```
+	pub fn from_parts(rom: Vec<u8>, rom_type: ROMType, sram_size: usize) -> Self {
+		let mut memory_map = Self::empty(rom.len(), WRAM_SIZE, sram_size);
+		memory_map.rom.write_slice(0, &rom);
+		let mut map_info = system_map_info();
+		map_info.extend(rom_map_info(
+			rom_type,
+			rom.len(),
+			memory_map.sram.as_ref().map(PagedMemory::len),
+		));
 		memory_map.map(&map_info);
+		memory_map
+	}
 
+	/// Returns a memory map with only the 128KB of WRAM mapped, at `$7E-$7F` and mirrored at `$xx:0000-$xx:1FFF`,
+	/// e.g. to run code copied into WRAM.
+	pub fn wram_only() -> Self {
+		let mut memory_map = Self::empty(0, WRAM_SIZE, 0);
+		memory_map.map(&wram_map_info());
 		memory_map
 	}
 
-	fn map(&mut self, info: &[MapInfo]) {
+	/// Maps the cartridge with the SRAM size declared by its header,
+	/// or the Game Pak RAM size declared by the extended header of a SuperFX cartridge.
+	#[cfg(feature = "std")]
+	pub fn from_cartridge(cartridge: Cartridge, hint: Option<ROMType>) -> Self {
+		let sram_size = match cartridge.enhancement() {
+			Enhancement::SuperFX => superfx::ram_size(&cartridge),
+			_ => cartridge.sram_size(),
+		};
+		Self::with_sram_size(cartridge, hint, sram_size)
+	}
+
+	/// Maps the cartridge with an explicit SRAM size in bytes, zero for no SRAM,
+	/// overriding the size declared by its header.
+	///
+	/// SA-1, SuperFX and SPC7110 cartridges are mapped as seen from the CPU regardless of `hint`,
+	/// with the SRAM as BW-RAM or Game Pak RAM of the former two.
+	#[cfg(feature = "std")]
+	pub fn with_sram_size(cartridge: Cartridge, hint: Option<ROMType>, sram_size: usize) -> Self {
+		let sa1 = cartridge.sa1_config();
+		let enhancement = cartridge.enhancement();
+		let mut memory_map = Self::empty(cartridge.rom.len(), WRAM_SIZE, sram_size);
+		memory_map.rom.write_slice(0, &cartridge.rom);
+		if let Some(config) = sa1 {
+			memory_map.iram = Some(new_ram(config.iram_size));
+			memory_map.mmc = config.mmc_banks.map(AtomicU8::new);
+		}
+
+		let mut map_info = system_map_info();
+
+		let rom_type = hint.unwrap_or_else(|| cartridge.detect_rom_type().0);
+		let sram_size = memory_map.sram.as_ref().map(|sram| sram.len());
+		if let Some(config) = sa1 {
+			map_info.extend(sa1::map_info(&config, sram_size));
+		} else if enhancement == Enhancement::SuperFX {
+			map_info.extend(superfx::map_info(memory_map.rom.len(), sram_size));
+			memory_map.chip_registers = superfx::registers();
+		} else if enhancement == Enhancement::SPC7110 {
+			map_info.extend(spc7110::map_info(memory_map.rom.len(), sram_size));
+			memory_map.chip_registers = spc7110::registers();
+		} else {
+			map_info.extend(rom_map_info(rom_type, memory_map.rom.len(), sram_size));
+		}
+
+		memory_map.dsp_board = DspBoard::detect(enhancement, rom_type, memory_map.rom.len());
+		if let Some(board) = &memory_map.dsp_board {
+			memory_map.chip_registers = board.registers();
+		} else if enhancement == Enhancement::CX4 {
+			memory_map.chip_registers = cx4::registers();
+		}
+
+		memory_map.map(&map_info);
+		// reserve the registers for handlers
+		for window in memory_map.chip_registers.clone() {
+			memory_map.unmap_memory(window.banks, window.offsets);
+		}
+
+		memory_map
+	}
+
+	/// Maps memory as laid out by `info` in order, replacing the memory mapped there before
+	/// and taking precedence over handlers, as with mappings laid out for a cartridge.
+	///
+	/// Each byte is mapped as readable and writable as its memory, e.g. ROM read-only.
+	///
+	/// # Panics
+	/// Panics if a mapping lies beyond its memory or the address space,
+	/// or maps SRAM or I-RAM the memory map does not have.
+	pub fn map(&mut self, info: &[MapInfo]) {
 		for &info in info.iter() {
-			match info {
-				MapInfo::ROM { src, dst, len } => {
-					let src = src..src.checked_add(len).unwrap();
-					let dst = dst..dst.checked_add(len).unwrap();
-					for (readable, src) in self.readable[dst.clone()]
-						.iter_mut()
-						.zip(self.rom[src].iter())
-					{
-						*readable = Some(src as *const _);
-					}
+			let (source, src, dst, len) = match info {
+				MapInfo::ROM { src, dst, len } => (Source::ROM, src, dst, len),
+				MapInfo::WRAM { src, dst, len } => (Source::WRAM, src, dst, len),
+				MapInfo::SRAM { src, dst, len } => (Source::SRAM, src, dst, len),
+				MapInfo::IRAM { src, dst, len } => (Source::IRAM, src, dst, len),
+				MapInfo::MMCROM {
+					area,
+					lorom,
+					src,
+					dst,
+					len,
+				} => {
+					assert!(area < 4 && src + len <= 0x100000);
+					let src = (lorom as usize) << 22 | area << 20 | src;
+					(Source::MMCROM, src, dst, len)
 				}
-				MapInfo::WRAM { src, dst, len } => {
-					let src = src..src.checked_add(len).unwrap();
-					let dst = dst..dst.checked_add(len).unwrap();
-					for ((readable, writable), src) in self.readable[dst.clone()]
-						.iter_mut()
-						.zip(self.writable[dst].iter_mut())
-						.zip(self.wram[src].iter())
-					{
-						*readable = Some(src as *const _);
-						*writable = Some(src as *const _);
-					}
-				}
-				MapInfo::SRAM { src, dst, len } => {
-					let src = src..src.checked_add(len).unwrap();
-					let dst = dst..dst.checked_add(len).unwrap();
-					for ((readable, writable), src) in self.readable[dst.clone()]
-						.iter_mut()
-						.zip(self.writable[dst].iter_mut())
-						.zip(self.sram.as_ref().unwrap()[src].iter())
-					{
-						*readable = Some(src as *const _);
-						*writable = Some(src as *const _);
-					}
+				MapInfo::MMC { src, dst, len } => (Source::MMC, src, dst, len),
+				MapInfo::DataROM {
+					area,
+					src,
+					dst,
+					len,
+				} => {
+					assert!(area < 3 && src + len <= 0x100000);
+					(Source::DataROM, area << 20 | src, dst, len)
 				}
+				MapInfo::DataBanks { src, dst, len } => (Source::DataBanks, src, dst, len),
+				MapInfo::DMA { src, dst, len } => (Source::DMA, src, dst, len),
+				MapInfo::Port { src, dst, len } => (Source::Port, src, dst, len),
+			};
+			match self.memory_len(source) {
+				Some(memory_len) => assert!(
+					src.checked_add(len).unwrap() <= memory_len,
+					"mapping beyond the memory"
+				),
+				None => assert!(
+					!matches!(source, Source::SRAM | Source::IRAM),
+					"mapping absent memory"
+				),
 			}
+			let dst = dst..dst.checked_add(len).unwrap();
+			let access = |i| match source {
+				Source::ROM | Source::MMCROM | Source::DataROM => (true, false),
+				Source::MMC => (false, true),
+				// `HDMAEN` is write-only
+				Source::DMA if i == dma::HDMAEN => (false, true),
+				Source::Port => ports::access(i),
+				_ => (true, true),
+			};
+			let start = dst.start;
+			self.readable.update(dst.clone(), |index, e| {
+				let i = src + index - start;
+				*e = if access(i).0 {
+					Entry::new(source, i)
+				} else {
+					Entry::UNMAPPED
+				};
+			});
+			self.writable.update(dst, |index, e| {
+				let i = src + index - start;
+				*e = if access(i).1 {
+					Entry::new(source, i)
+				} else {
+					Entry::UNMAPPED
+				};
+			});
 		}
+		self.readable.share_pages();
+		self.writable.share_pages();
+	}
+
+	/// Unmaps `offsets` in each of `banks` from memory and handlers alike,
+	/// leaving reads there to the [`UnmappedPolicy`].
+	pub fn unmap(&mut self, banks: RangeInclusive<u8>, offsets: RangeInclusive<u16>) {
+		self.unmap_memory(banks.clone(), offsets.clone());
+		self.mmio.unmap(banks, offsets);
 	}
 
+	/// Returns the memory of `source`, or `None` if it is absent, banked or paged.
+	#[inline]
+	fn memory(&self, source: Source) -> Option<&[AtomicU8]> {
+		match source {
+			Source::IRAM => self.iram.as_deref(),
+			Source::ROM | Source::WRAM | Source::SRAM => None,
+			Source::MMCROM | Source::DataROM | Source::Port => None,
+			Source::MMC => Some(&self.mmc),
+			Source::DataBanks => Some(&self.data_banks),
+			Source::DMA => Some(&self.dma),
+		}
+	}
+
+	/// Returns the size of the memory of `source`, or `None` if it is absent or banked.
+	fn memory_len(&self, source: Source) -> Option<usize> {
+		match source {
+			Source::ROM => Some(self.rom.len()),
+			Source::WRAM => Some(self.wram.len()),
+			Source::SRAM => self.sram.as_ref().map(PagedMemory::len),
+			_ => self.memory(source).map(<[_]>::len),
+		}
+	}
+
+	/// Returns the offset into the memory of `source` an entry offset refers to, as switched by the bank registers.
+	#[inline]
+	fn switch(&self, source: Source, offset: usize) -> usize {
+		match source {
+			Source::MMCROM => {
+				let area = offset >> 20 & 0x03;
+				let bank = self.mmc[area].load(atomic::Ordering::Relaxed);
+				let block = if offset & 1 << 22 != 0 && bank & 0x80 == 0 {
+					area
+				} else {
+					bank as usize & 0x07
+				};
+				block << 20 | offset & 0xFFFFF
+			}
+			Source::DataROM => {
+				let bank = self.data_banks[offset >> 20].load(atomic::Ordering::Relaxed);
+				let block = spc7110::PROGRAM_ROM_SIZE + ((bank as usize & 0x07) << 20);
+				block | offset & 0xFFFFF
+			}
+			_ => offset,
+		}
+	}
+
+	/// Returns the memory an entry is mapped to and the offset within it, or `None` if unmapped.
+	///
+	/// Paged memory is returned a page at a time.
+	#[inline]
+	fn locate(&self, entry: Entry) -> Option<(&[AtomicU8], usize)> {
+		let source = entry.source()?;
+		let offset = self.switch(source, entry.offset());
+		match source {
+			Source::ROM | Source::MMCROM | Source::DataROM => self.rom.locate(offset),
+			Source::WRAM => self.wram.locate(offset),
+			Source::SRAM => self.sram.as_ref()?.locate(offset),
+			_ => Some((self.memory(source)?, offset)),
+		}
+	}
+
+	/// Returns the memory an entry is mapped to as [`locate`](#method.locate) does,
+	/// copying the page first if shared with a fork.
+	#[inline]
+	fn locate_for_write(&self, entry: Entry) -> Option<(&[AtomicU8], usize)> {
+		let source = entry.source()?;
+		let offset = self.switch(source, entry.offset());
+		match source {
+			Source::ROM | Source::MMCROM | Source::DataROM => self.rom.locate_for_write(offset),
+			Source::WRAM => self.wram.locate_for_write(offset),
+			Source::SRAM => self.sram.as_ref()?.locate_for_write(offset),
+			_ => self.locate(entry),
+		}
+	}
+
+	/// Returns the byte an entry is mapped to, or `None` if unmapped.
+	#[inline]
+	fn get(&self, entry: Entry) -> Option<&AtomicU8> {
+		let source = entry.source()?;
+		let offset = self.switch(source, entry.offset());
+		match source {
+			Source::ROM | Source::MMCROM | Source::DataROM => self.rom.get(offset),
+			Source::WRAM => self.wram.get(offset),
+			Source::SRAM => self.sram.as_ref()?.get(offset),
+			_ => self.memory(source)?.get(offset),
+		}
+	}
+
+	/// Returns the byte an entry is mapped to for writing, or `None` if unmapped.
+	#[inline]
+	fn get_for_write(&self, entry: Entry) -> Option<&AtomicU8> {
+		let source = entry.source()?;
+		let offset = self.switch(source, entry.offset());
+		match source {
+			Source::ROM | Source::MMCROM | Source::DataROM => self.rom.get_for_write(offset),
+			Source::WRAM => self.wram.get_for_write(offset),
+			Source::SRAM => self.sram.as_ref()?.get_for_write(offset),
+			_ => self.get(entry),
+		}
+	}
+
+	/// Registers a hardware register handler, which receives accesses once mapped by [`map_mmio`](#method.map_mmio).
+	pub fn register_mmio<H>(&mut self, handler: H) -> MmioId
+	where
+		H: MmioHandler + 'static,
+	{
+		self.mmio.register(Box::new(handler))
+	}
+
+	/// Dispatches accesses to `offsets` in each of `banks` to a registered handler.
+	///
+	/// The handler takes precedence over memory and handlers previously mapped there.
+	///
+	/// # Panics
+	/// Panics if the handler is not registered to this memory map.
+	pub fn map_mmio(
+		&mut self,
+		id: MmioId,
+		banks: RangeInclusive<u8>,
+		offsets: RangeInclusive<u16>,
+	) {
+		self.unmap_memory(banks.clone(), offsets.clone());
+		self.mmio.map(mmio::MmioRegion {
+			banks,
+			offsets,
+			id: Some(id),
+		});
+	}
+
+	fn unmap_memory(&mut self, banks: RangeInclusive<u8>, offsets: RangeInclusive<u16>) {
+		let unmap = |e: &mut Entry| *e = Entry::UNMAPPED;
+		update_entries(&mut self.readable, banks.clone(), &offsets, unmap);
+		update_entries(&mut self.writable, banks, &offsets, unmap);
+	}
+
+	/// Makes `offsets` in each of `banks` read-only, dropping writes there until [`unprotect`](#method.unprotect)ed,
+	/// as ROM and read-only registers already are.
+	///
+	/// Protects handlers as well as memory, until a handler is mapped over them.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let mut memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// memory_map.protect(0x7E..=0x7E, 0x0000..=0x00FF);
+	/// memory_map.write(Address24::new(0x7E0010), 0x12);
+	/// assert_eq!(memory_map.read(Address24::new(0x7E0010)), 0x00);
+	/// memory_map.unprotect(0x7E..=0x7E, 0x0000..=0x00FF);
+	/// memory_map.write(Address24::new(0x7E0010), 0x12);
+	/// assert_eq!(memory_map.read(Address24::new(0x7E0010)), 0x12);
+	/// ```
+	pub fn protect(&mut self, banks: RangeInclusive<u8>, offsets: RangeInclusive<u16>) {
+		update_entries(&mut self.writable, banks, &offsets, |e| {
+			e.0 |= Entry::PROTECTED
+		});
+	}
+
+	/// Lifts the protection of `offsets` in each of `banks`, restoring their writable memory and handlers.
+	pub fn unprotect(&mut self, banks: RangeInclusive<u8>, offsets: RangeInclusive<u16>) {
+		update_entries(&mut self.writable, banks, &offsets, |e| {
+			e.0 &= !Entry::PROTECTED
+		});
+	}
+
+	/// Returns the registers of the enhancement chip, empty if the cartridge has none or they are not supported.
+	#[inline]
+	pub fn chip_registers(&self) -> &[RegisterWindow] {
+		&self.chip_registers
+	}
+
+	/// Dispatches accesses to the registers of the enhancement chip to a registered handler,
+	/// e.g. a high-level emulation of the chip.
+	///
+	/// # Panics
+	/// Panics if the handler is not registered to this memory map.
+	pub fn map_chip_registers(&mut self, id: MmioId) {
+		for window in self.chip_registers.clone() {
+			self.map_mmio(id, window.banks, window.offsets);
+		}
+	}
+
+	/// Returns the placement of the DSP-n registers, or `None` if the cartridge has no DSP-n.
+	#[inline]
+	pub fn dsp_board(&self) -> Option<&DspBoard> {
+		self.dsp_board.as_ref()
+	}
+
+	/// Returns the ROM, e.g. to fill that of a memory map made by [`empty`](#method.empty).
+	///
+	/// While shared with a fork, the ROM is first copied, see [`fork`](#method.fork).
+	#[inline]
+	pub fn rom(&self) -> &[AtomicU8] {
+		self.rom.as_slice()
+	}
+
+	/// Returns the WRAM, 128KB unless made by [`empty`](#method.empty).
+	///
+	/// While shared with a fork, the WRAM is first copied, see [`fork`](#method.fork).
+	#[inline]
+	pub fn wram(&self) -> &[AtomicU8] {
+		self.wram.as_slice()
+	}
+
+	/// Returns the SRAM, or `None` if the cartridge has none.
+	///
+	/// While shared with a fork, the SRAM is first copied, see [`fork`](#method.fork).
+	#[inline]
+	pub fn sram(&self) -> Option<&[AtomicU8]> {
+		self.sram.as_ref().map(PagedMemory::as_slice)
+	}
+
+	/// Returns the SRAM for exclusive access, or `None` if the cartridge has none.
+	#[inline]
+	pub fn sram_mut(&mut self) -> Option<&mut [u8]> {
+		let sram = self.sram.as_mut()?.as_mut_slice();
+		// SAFETY: `AtomicU8` has the same in-memory representation as `u8`,
+		// and the exclusive borrow rules out concurrent atomic accesses.
+		Some(unsafe { &mut *(sram as *mut [AtomicU8] as *mut [u8]) })
+	}
+
+	/// Copies a save into SRAM, returning the number of bytes copied.
+	///
+	/// A save shorter than SRAM leaves the rest of SRAM as is, and a longer one is cut off.
+	pub fn load_sram(&self, data: &[u8]) -> usize {
+		self.sram.as_ref().map_or(0, |sram| {
+			let len = sram.len().min(data.len());
+			sram.write_slice(0, &data[..len]);
+			len
+		})
+	}
+
+	/// Copies the SRAM contents, or returns `None` if the cartridge has no SRAM.
+	pub fn dump_sram(&self) -> Option<Vec<u8>> {
+		self.sram.as_ref().map(PagedMemory::to_vec)
+	}
+
+	/// Copies the WRAM contents, e.g. to hash them or compare them between frames.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// memory_map.write(Address24::new(0x7F0000), 0x12);
+	/// let wram = memory_map.dump_wram();
+	/// assert_eq!((wram.len(), wram[0x10000]), (0x20000, 0x12));
+	/// ```
+	pub fn dump_wram(&self) -> Vec<u8> {
+		self.wram.to_vec()
+	}
+
+	/// Copies the ROM contents, including changes written through [`rom`](#method.rom).
+	pub fn dump_rom(&self) -> Vec<u8> {
+		self.rom.to_vec()
+	}
+
+	/// Returns the I-RAM of an SA-1 cartridge, or `None` for other cartridges.
+	#[inline]
+	pub fn iram(&self) -> Option<&[AtomicU8]> {
+		self.iram.as_deref()
+	}
+
+	/// Returns the Super MMC bank registers `CXB`, `DXB`, `EXB` and `FXB`,
+	/// written at `$2220-$2223` and only mapped for SA-1 cartridges.
+	pub fn mmc_banks(&self) -> [u8; 4] {
+		[0, 1, 2, 3].map(|i| self.mmc[i].load(atomic::Ordering::Relaxed))
+	}
+
+	/// Sets a Super MMC bank register, 0 to 3 for `CXB` to `FXB`, switching the ROM block mapped into its area.
+	///
+	/// # Panics
+	/// Panics if `index` is greater than 3.
+	#[inline]
+	pub fn set_mmc_bank(&self, index: usize, value: u8) {
+		self.mmc[index].store(value, atomic::Ordering::Relaxed);
+	}
+
+	/// Returns the SPC7110 data ROM bank registers at `$4831-$4833`,
+	/// selecting the 1MB blocks of data ROM mapped at `$D0-$DF`, `$E0-$EF` and `$F0-$FF`.
+	pub fn data_rom_banks(&self) -> [u8; 3] {
+		[0, 1, 2].map(|i| self.data_banks[i].load(atomic::Ordering::Relaxed))
+	}
+
+	/// Sets an SPC7110 data ROM bank register, 0 to 2 for `$4831` to `$4833`.
+	///
+	/// # Panics
+	/// Panics if `index` is greater than 2.
+	#[inline]
+	pub fn set_data_rom_bank(&self, index: usize, value: u8) {
+		self.data_banks[index].store(value, atomic::Ordering::Relaxed);
+	}
+
+	/// Starts recording reads and writes into a ring buffer keeping the latest `capacity` accesses,
+	/// replacing any running trace.
+	///
+	/// Without a running trace, accesses only pay for checking a flag.
+	pub fn start_trace(&self, capacity: usize) {
+		self.trace.start_buffer(capacity);
+	}
+
+	/// Starts streaming reads and writes to `sink`, replacing any running trace.
+	///
+	/// The sink runs without the trace locked, so it may access the memory map,
+	/// but accesses made while it runs, including its own, are not recorded.
+	pub fn start_trace_sink<S>(&self, sink: S)
+	where
+		S: TraceSink + 'static,
+	{
+		self.trace.start_sink(Box::new(sink));
+	}
+
+	/// Stops the trace, returning the accesses left in its ring buffer.
+	pub fn stop_trace(&self) -> Vec<Access> {
+		self.trace.stop()
+	}
+
+	/// Takes the accesses recorded in the ring buffer so far, oldest first.
+	pub fn take_trace(&self) -> Vec<Access> {
+		self.trace.take()
+	}
+
+	/// Returns `true` if a trace is running.
+	#[inline]
+	pub fn is_tracing(&self) -> bool {
+		self.trace.is_enabled()
+	}
+
+	/// Starts counting the reads, writes and instruction fetches of each page or byte of memory afresh,
+	/// replacing any counts so far.
+	///
+	/// Accesses are counted by the memory and offset they resolve to, adding up mirrors,
+	/// whereas accesses of ports, handlers and unmapped addresses are not counted.
+	/// Without statistics running, accesses only pay for checking a flag.
+	pub fn start_statistics(&self, granularity: StatsGranularity) {
+		let sizes = [
+			self.rom.len(),
+			self.wram.len(),
+			self.sram.as_ref().map_or(0, PagedMemory::len),
+			self.iram.as_ref().map_or(0, |iram| iram.len()),
+		];
+		self.stats.start(granularity, sizes);
+	}
+
+	/// Stops counting accesses, keeping the counts so far.
+	pub fn stop_statistics(&self) {
+		self.stats.stop();
+	}
+
+	/// Returns `true` if accesses are being counted.
+	#[inline]
+	pub fn is_counting(&self) -> bool {
+		self.stats.is_enabled()
+	}
+
+	/// Returns the accesses counted since statistics were last started, keyed by the memory
+	/// and the offset of each page or byte accessed, e.g. to visualize the code and data used by a game.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// memory_map.start_statistics(StatsGranularity::Page);
+	/// memory_map.fetch(Address24::new(0x808123));
+	/// memory_map.read(Address24::new(0x0081FF));
+	/// let heatmap = memory_map.heatmap();
+	/// assert_eq!(heatmap[&(MemoryRegion::ROM, 0x100)].total(), 2);
+	/// ```
+	pub fn heatmap(&self) -> BTreeMap<(MemoryRegion, usize), AccessCounts> {
+		self.stats.heatmap()
+	}
+
+	/// Returns how reads from unmapped addresses are answered.
+	#[inline]
+	pub fn unmapped_policy(&self) -> &UnmappedPolicy {
+		&self.unmapped_policy
+	}
+
+	/// Sets how reads from unmapped addresses are answered, e.g. to keep open bus out of golden test data.
+	/// ```
+	/// # use std::sync::Arc;
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let mut memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// memory_map.set_unmapped_policy(UnmappedPolicy::Callback(Arc::new(|address| address.low())));
+	/// assert_eq!(memory_map.read(Address24::new(0x004210)), 0x10);
+	/// ```
+	#[inline]
+	pub fn set_unmapped_policy(&mut self, policy: UnmappedPolicy) {
+		self.unmapped_policy = policy;
+	}
+
+	/// Returns the memory ordering of reads and writes of memory.
+	#[inline]
+	pub fn ordering(&self) -> AccessOrdering {
+		self.ordering
+	}
+
+	/// Sets the memory ordering of reads and writes of memory, [`AccessOrdering::Relaxed`] by default.
+	///
+	/// Accesses of the port registers and handlers are ordered by their locks regardless.
+	#[inline]
+	pub fn set_ordering(&mut self, ordering: AccessOrdering) {
+		self.ordering = ordering;
+	}
+
+	/// Returns the memory data register, the last value read or written.
+	#[inline]
+	pub fn mdr(&self) -> u8 {
+		self.mdr.load(atomic::Ordering::Relaxed)
+	}
+
+	/// Sets the memory data register, e.g. to the last byte of an instruction fetched by a CPU core.
+	#[inline]
+	pub fn set_mdr(&self, value: u8) {
+		self.mdr.store(value, atomic::Ordering::Relaxed);
+	}
+
+	/// Reads a byte, updating the memory data register.
 	#[inline]
 	pub fn read(&self, offset: Address24) -> u8 {
-		unsafe {
-			if let Some(p) = self
-				.readable
-				.get_unchecked(Into::<usize>::into(offset))
-				.clone()
+		self.read_as(offset, AccessKind::Read)
+	}
+
+	/// Reads a byte fetched by a CPU core as part of an instruction, as [`read`](#method.read) does
+	/// but traced and counted as an instruction fetch.
+	#[inline]
+	pub fn fetch(&self, offset: Address24) -> u8 {
+		self.read_as(offset, AccessKind::Execute)
+	}
+
+	#[inline]
+	fn read_as(&self, offset: Address24, kind: AccessKind) -> u8 {
+		let entry = self.readable.get(offset.into());
+		let value = if let Some(b) = self.get(entry) {
+			b.load(self.ordering.load())
+		} else if entry.source() == Some(Source::Port) {
+			self.read_port(entry.offset())
+		} else if let Some(id) = self.mmio.find(offset) {
+			self.mmio.read(id, offset)
+		} else {
+			self.read_unmapped(offset)
+		};
+		self.set_mdr(value);
+		if self.trace.is_enabled() {
+			self.trace.record(offset, value, kind);
+		}
+		if self.stats.is_enabled() {
+			self.count(entry, kind);
+		}
+		value
+	}
+
+	#[cold]
+	#[inline(never)]
+	fn count(&self, entry: Entry, kind: AccessKind) {
+		if let Some(source) = entry.source() {
+			self.stats
+				.count(source.into(), self.switch(source, entry.offset()), kind);
+		}
+	}
+
+	#[cold]
+	fn read_unmapped(&self, offset: Address24) -> u8 {
+		match &self.unmapped_policy {
+			UnmappedPolicy::OpenBus => self.mdr(),
+			UnmappedPolicy::Constant(value) => *value,
+			UnmappedPolicy::PanicInDebug => {
+				if cfg!(debug_assertions) {
+					panic!("read from unmapped address {}", offset);
+				}
+				self.mdr()
+			}
+			UnmappedPolicy::Callback(callback) => callback(offset),
+		}
+	}
+
+	/// Reads a little-endian word, the high byte carrying into the next bank as with long and absolute indexed addressing.
+	#[inline]
+	pub fn read16(&self, offset: Address24) -> u16 {
+		self.read_le(offset, 2, Address24::add_crossing_bank) as u16
+	}
+
+	/// Reads a little-endian word, the high byte wrapping within the bank as with direct page and stack addressing.
+	#[inline]
+	pub fn read16_wrapping_bank(&self, offset: Address24) -> u16 {
+		self.read_le(offset, 2, Address24::add_wrapping_bank) as u16
+	}
+
+	/// Reads a little-endian 24-bit value, carrying into the next bank.
+	#[inline]
+	pub fn read24(&self, offset: Address24) -> Address24 {
+		Address24::new(self.read_le(offset, 3, Address24::add_crossing_bank))
+	}
+
+	/// Reads a little-endian 24-bit value, wrapping within the bank as with `[dp]` pointers.
+	#[inline]
+	pub fn read24_wrapping_bank(&self, offset: Address24) -> Address24 {
+		Address24::new(self.read_le(offset, 3, Address24::add_wrapping_bank))
+	}
+
+	#[inline]
+	fn read_le(
+		&self,
+		offset: Address24,
+		len: u16,
+		add: fn(Address24, Address16) -> Address24,
+	) -> u32 {
+		(0..len).fold(0, |r, i| {
+			r | (self.read(add(offset, Address16::new(i))) as u32) << (8 * i)
+		})
+	}
+
+	/// Reads consecutive bytes into `buf`, carrying into the next bank and wrapping at the end of the address space.
+	///
+	/// Runs of contiguously mapped memory are copied at once,
+	/// and the memory data register is left holding the last byte.
+	pub fn read_into(&self, offset: Address24, buf: &mut [u8]) {
+		let offset: usize = offset.into();
+		// copy byte by byte while tracing or counting to record every access
+		let recording = self.trace.is_enabled() || self.stats.is_enabled();
+		let mut done = 0;
+		while done < buf.len() {
+			let address = (offset + done) % MAP_SIZE;
+			let len = (buf.len() - done).min(MAP_SIZE - address);
+			match self
+				.run(&self.readable, address, len, false)
+				.filter(|_| !recording)
 			{
-				debug_assert!(
-					self.wram.as_ptr_range().contains(&p)
-						|| self.rom.as_ptr_range().contains(&p)
-						|| self
-							.sram
-							.as_ref()
-							.map_or(false, |sram| sram.as_ptr_range().contains(&p))
-				);
-				(*p).load(atomic::Ordering::SeqCst)
-			} else {
-				0x55
+				Some(run) => {
+					for (dst, src) in buf[done..].iter_mut().zip(run.iter()) {
+						*dst = src.load(self.ordering.load());
+					}
+					done += run.len();
+				}
+				None => {
+					buf[done] = self.read(Address24::new(address as u32));
+					done += 1;
+				}
 			}
 		}
+		if let Some(&last) = buf.last() {
+			self.set_mdr(last);
+		}
+	}
+
+	/// Reads the addresses of `range` in order, updating the memory data register.
+	pub fn iter_range(&self, range: AddressRange24) -> impl ExactSizeIterator<Item = u8> + '_ {
+		range.iter().map(move |address| self.read(address))
+	}
+
+	/// Returns the memory `range` is mapped to, or `None` unless the whole range is readable memory
+	/// mapped to consecutive bytes, e.g. when crossing from ROM into open bus, into a mirror
+	/// or into the next 4KB page of memory shared with forks, see [`fork`](#method.fork).
+	/// A page shared with a fork is first copied, so writes through the slice never show through to the forks.
+	///
+	/// The memory is accessed directly, bypassing the memory data register and the trace.
+	pub fn read_contiguous(&self, range: AddressRange24) -> Option<&[AtomicU8]> {
+		let len = range.size() as usize;
+		self.run(&self.readable, range.start().into(), len, true)
+			.filter(|run| run.len() == len)
 	}
 
+	/// Returns the memory mapped contiguously in `table` from `offset`, at most `len` bytes long,
+	/// or `None` if `offset` is not mapped to memory. Paged memory is copied first if shared when `write` is set.
+	fn run(
+		&self,
+		table: &table::Table,
+		offset: usize,
+		len: usize,
+		write: bool,
+	) -> Option<&[AtomicU8]> {
+		let first = table.get(offset);
+		let (memory, start) = if write {
+			self.locate_for_write(first)?
+		} else {
+			self.locate(first)?
+		};
+		let run = memory.get(start..)?;
+		let len = (0..len.min(run.len()))
+			.take_while(|&i| table.get(offset + i).0 == first.0 + i as u32)
+			.count();
+		Some(&run[..len])
+	}
+
+	/// Writes a byte, updating the memory data register.
+	///
+	/// A write to an unmapped or read-only address is dropped,
+	/// unless in strict mode with a fault handler set by [`set_fault_handler`](#method.set_fault_handler).
 	#[inline]
 	pub fn write(&self, offset: Address24, value: u8) {
-		unsafe {
-			if let Some(p) = self
-				.writable
-				.get_unchecked(Into::<usize>::into(offset))
-				.clone()
+		if let Err(error) = self.try_write(offset, value) {
+			if let Some(handler) = &self.fault_handler {
+				handler.lock().fault(error);
+			}
+		}
+	}
+
+	/// Writes a byte as [`write`](#method.write) does, but returns an error
+	/// instead of dropping a write to an unmapped or read-only address.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// assert_eq!(memory_map.try_write(Address24::new(0x7E0000), 0x12), Ok(()));
+	/// assert_eq!(
+	///     memory_map.try_write(Address24::new(0x008000), 0x12),
+	///     Err(MemoryError::ProtectedWrite { address: Address24::new(0x008000), value: 0x12 })
+	/// );
+	/// ```
+	#[inline]
+	pub fn try_write(&self, offset: Address24, value: u8) -> Result<(), MemoryError> {
+		self.set_mdr(value);
+		if self.trace.is_enabled() {
+			self.trace.record(offset, value, AccessKind::Write);
+		}
+		let index: usize = offset.into();
+		let entry = self.writable.get(index);
+		if let Some(b) = self.get_for_write(entry) {
+			b.store(value, self.ordering.store());
+			if self.stats.is_enabled() {
+				self.count(entry, AccessKind::Write);
+			}
+		} else if entry.source() == Some(Source::Port) {
+			self.write_port(entry.offset(), value);
+		} else if entry.is_protected() || self.readable.get(index) != Entry::UNMAPPED {
+			return Err(MemoryError::ProtectedWrite {
+				address: offset,
+				value,
+			});
+		} else if let Some(id) = self.mmio.find(offset) {
+			self.mmio.write(id, offset, value);
+		} else {
+			return Err(MemoryError::UnmappedWrite {
+				address: offset,
+				value,
+			});
+		}
+		Ok(())
+	}
+
+	/// Enters strict mode, passing the writes [`write`](#method.write) would drop to `handler`,
+	/// e.g. to break into a debugger on a stray write.
+	pub fn set_fault_handler<H>(&mut self, handler: H)
+	where
+		H: FaultHandler + 'static,
+	{
+		self.fault_handler = Some(Arc::new(Mutex::new(Box::new(handler))));
+	}
+
+	/// Leaves strict mode, dropping writes to unmapped and read-only addresses again.
+	pub fn clear_fault_handler(&mut self) {
+		self.fault_handler = None;
+	}
+
+	/// Writes a little-endian word, the high byte carrying into the next bank.
+	#[inline]
+	pub fn write16(&self, offset: Address24, value: u16) {
+		self.write(offset, value as u8);
+		self.write(
+			offset.add_crossing_bank(Address16::new(1)),
+			(value >> 8) as u8,
+		);
+	}
+
+	/// Writes a little-endian word, the high byte wrapping within the bank.
+	#[inline]
+	pub fn write16_wrapping_bank(&self, offset: Address24, value: u16) {
+		self.write(offset, value as u8);
+		self.write(
+			offset.add_wrapping_bank(Address16::new(1)),
+			(value >> 8) as u8,
+		);
+	}
+
+	/// Writes consecutive bytes, carrying into the next bank and wrapping at the end of the address space.
+	///
+	/// Runs of contiguously mapped memory are copied at once, writes to unmapped addresses are ignored
+	/// and the memory data register is left holding the last byte.
+	pub fn write_slice(&self, offset: Address24, data: &[u8]) {
+		let offset: usize = offset.into();
+		let recording = self.trace.is_enabled() || self.stats.is_enabled();
+		let mut done = 0;
+		while done < data.len() {
+			let address = (offset + done) % MAP_SIZE;
+			let len = (data.len() - done).min(MAP_SIZE - address);
+			match self
+				.run(&self.writable, address, len, true)
+				.filter(|_| !recording)
 			{
-				debug_assert!(
-					self.wram.as_ptr_range().contains(&p)
-						|| self.rom.as_ptr_range().contains(&p)
-						|| self
-							.sram
-							.as_ref()
-							.map_or(false, |sram| sram.as_ptr_range().contains(&p))
-				);
-				(*p).store(value, atomic::Ordering::SeqCst);
+				Some(run) => {
+					for (dst, &src) in run.iter().zip(data[done..].iter()) {
+						dst.store(src, self.ordering.store());
+					}
+					done += run.len();
+				}
+				None => {
+					self.write(Address24::new(address as u32), data[done]);
+					done += 1;
+				}
 			}
 		}
+		if let Some(&last) = data.last() {
+			self.set_mdr(last);
+		}
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::test::make_rom_with;
+	use crate::cartridge::{CartridgeBuilder, TestFlags};
+	use std::sync::Mutex;
+
+	/// Builds a cartridge with `bytes` written from the header at `$xFC0` plus `offset`.
+	fn with_header(rom_type: ROMType, size: usize, offset: usize, bytes: &[u8]) -> Cartridge {
+		let header = rom_type.header_offset();
+		let rom = make_rom_with(rom_type, size, |rom| {
+			rom[header + offset..][..bytes.len()].copy_from_slice(bytes)
+		});
+		Cartridge::new(rom, TestFlags::default()).unwrap()
+	}
+
+	/// Reads back the low byte of the address.
+	struct Registers;
+
+	impl MmioHandler for Registers {
+		fn read(&mut self, address: Address24) -> u8 {
+			address.low()
+		}
+
+		fn write(&mut self, _address: Address24, _value: u8) {}
+	}
+
+	#[test]
+	fn sram() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.build()
+			.unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge.clone(), None);
+		assert_eq!(memory_map.sram().map(<[_]>::len), Some(0x800));
+		memory_map.write(Address24::new(0x700000), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0x700800)), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0xF07000)), 0x12);
+
+		assert_eq!(memory_map.load_sram(&[1, 2, 3]), 3);
+		assert_eq!(memory_map.read(Address24::new(0x700002)), 3);
+		memory_map.sram_mut().unwrap()[0x7FF] = 0xFF;
+		let dump = memory_map.dump_sram().unwrap();
+		assert_eq!(&dump[..4], &[1, 2, 3, 0]);
+		assert_eq!(dump[0x7FF], 0xFF);
+		assert_eq!(memory_map.load_sram(&[0; 0x1000]), 0x800);
+
+		let memory_map = MemoryMap::with_sram_size(cartridge.clone(), None, 0x2000);
+		memory_map.write(Address24::new(0x701FFF), 0x34);
+		assert_eq!(memory_map.dump_sram().unwrap()[0x1FFF], 0x34);
+
+		let memory_map = MemoryMap::with_sram_size(cartridge, None, 0);
+		assert!(memory_map.sram().is_none());
+		memory_map.write(Address24::new(0x700000), 0x56);
+		assert_eq!(memory_map.read(Address24::new(0x700000)), 0x56);
+		assert_eq!(memory_map.dump_sram(), None);
+		assert_eq!(memory_map.load_sram(&[1]), 0);
+	}
+
+	#[test]
+	fn read16() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.code([0x78, 0x18, 0xFB])
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert_eq!(memory_map.read16(Address24::new(0x008000)), 0x1878);
+		assert_eq!(
+			memory_map.read24(Address24::new(0x808000)),
+			Address24::new(0xFB1878)
+		);
+
+		memory_map.write(Address24::new(0x7E0000), 0x12);
+		memory_map.write(Address24::new(0x7E0001), 0x13);
+		memory_map.write(Address24::new(0x7EFFFF), 0x34);
+		memory_map.write(Address24::new(0x7EFFFE), 0x35);
+		memory_map.write(Address24::new(0x7F0000), 0x56);
+		memory_map.write(Address24::new(0x7F0001), 0x57);
+		assert_eq!(
+			memory_map.read16_wrapping_bank(Address24::new(0x7EFFFF)),
+			0x1234
+		);
+		assert_eq!(memory_map.read16(Address24::new(0x7EFFFF)), 0x5634);
+		assert_eq!(
+			memory_map.read24_wrapping_bank(Address24::new(0x7EFFFE)),
+			Address24::new(0x123435)
+		);
+		assert_eq!(
+			memory_map.read24_wrapping_bank(Address24::new(0x7EFFFF)),
+			Address24::new(0x131234)
+		);
+		assert_eq!(
+			memory_map.read24(Address24::new(0x7EFFFF)),
+			Address24::new(0x575634)
+		);
+		assert_eq!(memory_map.mdr(), 0x57);
+	}
+
+	#[test]
+	fn slice() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.code([0x78, 0x18, 0xFB])
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let data = (0..0x3000).map(|i| i as u8).collect::<Vec<_>>();
+		memory_map.write_slice(Address24::new(0x7EF000), &data);
+		assert_eq!(memory_map.read(Address24::new(0x7EFFFF)), 0xFF);
+		assert_eq!(memory_map.read(Address24::new(0x7F0000)), 0x00);
+		assert_eq!(memory_map.read(Address24::new(0x001000)), 0x00);
+		let mut buf = vec![0; 0x3000];
+		memory_map.read_into(Address24::new(0x7EF000), &mut buf);
+		assert_eq!(buf, data);
+		assert_eq!(memory_map.mdr(), 0xFF);
+
+		// SRAM is mirrored every 2KB
+		memory_map.write_slice(Address24::new(0x6FFFFE), &[1, 2, 3, 4]);
+		let mut buf = [0; 4];
+		memory_map.read_into(Address24::new(0x700800), &mut buf);
+		assert_eq!(buf, [3, 4, 0, 0]);
+
+		// unmapped addresses read as open bus and the address space wraps around
+		memory_map.write_slice(Address24::new(0x7E0000), &[0x0F, 0x10]);
+		let mut buf = [0; 4];
+		memory_map.read_into(Address24::new(0xFFFFFE), &mut buf);
+		assert_eq!(&buf[2..], &[0x0F, 0x10]);
+		memory_map.write_slice(Address24::new(0x002000), &[0x12]);
+		let mut buf = [0; 4];
+		memory_map.read_into(Address24::new(0x007FFE), &mut buf);
+		assert_eq!(buf, [0x12, 0x12, 0x78, 0x18]);
+
+		memory_map.write16(Address24::new(0x7EFFFF), 0x1234);
+		assert_eq!(memory_map.read16(Address24::new(0x7EFFFF)), 0x1234);
+		memory_map.write16_wrapping_bank(Address24::new(0x7EFFFF), 0x5678);
+		assert_eq!(
+			memory_map.read16_wrapping_bank(Address24::new(0x7EFFFF)),
+			0x5678
+		);
+		assert_eq!(memory_map.read(Address24::new(0x7F0000)), 0x12);
+	}
+
+	#[test]
+	fn send_sync() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		std::thread::scope(|scope| {
+			scope.spawn(|| memory_map.write(Address24::new(0x7E0000), 0x12));
+		});
+		assert_eq!(memory_map.read(Address24::new(0x000000)), 0x12);
+	}
+
+	#[test]
+	fn shared() {
+		use std::sync::atomic::AtomicBool;
+		use std::thread;
+
+		let mut memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+		memory_map.set_ordering(AccessOrdering::AcquireRelease);
+		let memory_map: SharedMemoryMap = Arc::new(memory_map);
+		let (flag, data) = (Address24::new(0x7E0000), Address24::new(0x7E0100));
+		let done = Arc::new(AtomicBool::new(false));
+
+		// a core filling a buffer and raising a flag, waiting for it to be lowered
+		let core = {
+			let memory_map = Arc::clone(&memory_map);
+			thread::spawn(move || {
+				for i in 1..=100 {
+					memory_map.write_slice(data, &[i; 0x100]);
+					memory_map.write(flag, i);
+					while memory_map.read(flag) != 0 {
+						thread::yield_now();
+					}
+				}
+			})
+		};
+		// debuggers peeking at the buffer meanwhile, seeing whole bytes of any fill
+		let debuggers = (0..2)
+			.map(|_| {
+				let (memory_map, done) = (Arc::clone(&memory_map), Arc::clone(&done));
+				thread::spawn(move || {
+					let mut buf = [0; 0x100];
+					while !done.load(atomic::Ordering::Relaxed) {
+						memory_map.read_into(data, &mut buf);
+						assert!(buf.iter().all(|&b| b <= 100));
+					}
+				})
+			})
+			.collect::<Vec<_>>();
+
+		// the whole buffer is seen once the flag is seen raised
+		for i in 1..=100 {
+			while memory_map.read(flag) != i {
+				thread::yield_now();
+			}
+			let mut buf = [0; 0x100];
+			memory_map.read_into(data, &mut buf);
+			assert_eq!(buf, [i; 0x100]);
+			memory_map.write(flag, 0);
+		}
+		core.join().unwrap();
+		done.store(true, atomic::Ordering::Relaxed);
+		for debugger in debuggers {
+			debugger.join().unwrap();
+		}
+	}
+
+	#[test]
+	fn sa1() {
+		let cartridge = with_header(ROMType::LoROM, 0x400000, 0x15, &[0x23, 0x35, 0x0C, 0x05]);
+		let rom = cartridge.rom().to_vec();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let read = |address| memory_map.read(Address24::new(address));
+
+		// ROM
+		assert_eq!(memory_map.mmc_banks(), [0, 1, 2, 3]);
+		assert_eq!(read(0x008000), rom[0x000000]);
+		assert_eq!(read(0x208000), rom[0x100000]);
+		assert_eq!(read(0xBF8001), rom[0x3F8001]);
+		assert_eq!(read(0xC00000), rom[0x000000]);
+		assert_eq!(read(0xEF1234), rom[0x2F1234]);
+		memory_map.set_mmc_bank(0, 0x03);
+		assert_eq!(read(0x008000), rom[0x000000]);
+		assert_eq!(read(0xC00001), rom[0x300001]);
+		memory_map.write(Address24::new(0x802220), 0x82);
+		assert_eq!(memory_map.mmc_banks(), [0x82, 1, 2, 3]);
+		assert_eq!(read(0x008000), rom[0x200000]);
+		assert_eq!(read(0xC00000), rom[0x200000]);
+		assert_eq!(read(0x002220), rom[0x200000]);
+
+		// I-RAM
+		memory_map.write(Address24::new(0x003000), 0x12);
+		assert_eq!(read(0xBF3000), 0x12);
+		assert_eq!(
+			memory_map.iram().unwrap()[0].load(atomic::Ordering::SeqCst),
+			0x12
+		);
+		memory_map.write(Address24::new(0x0037FF), 0x34);
+		assert_eq!(read(0x8037FF), 0x34);
+		assert_eq!(read(0x003800), 0x34);
+
+		// BW-RAM
+		assert_eq!(memory_map.sram().map(<[_]>::len), Some(0x8000));
+		memory_map.write(Address24::new(0x400000), 0x56);
+		assert_eq!(read(0x006000), 0x56);
+		assert_eq!(read(0x4F8000), 0x56);
+		memory_map.write(Address24::new(0xBF7FFF), 0x78);
+		assert_eq!(read(0x401FFF), 0x78);
+		memory_map.write(Address24::new(0x008000), 0x9A);
+		assert_eq!(read(0x008000), rom[0x200000]);
+
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert!(memory_map.iram().is_none());
+		memory_map.write(Address24::new(0x002220), 0x05);
+		assert_eq!(memory_map.mmc_banks(), [0, 1, 2, 3]);
+	}
+
+	#[test]
+	fn superfx() {
+		let cartridge = with_header(ROMType::LoROM, 0x100000, 0x15, &[0x20, 0x13, 0x0A, 0x00]);
+		let rom = cartridge.rom().to_vec();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let read = |memory_map: &MemoryMap, address| memory_map.read(Address24::new(address));
+
+		// ROM
+		assert_eq!(read(&memory_map, 0x008000), rom[0x000000]);
+		assert_eq!(read(&memory_map, 0x9F8123), rom[0x0F8123]);
+		assert_eq!(read(&memory_map, 0x3F8000), rom[0x0F8000]);
+		assert_eq!(read(&memory_map, 0x4F1234), rom[0x0F1234]);
+		assert_eq!(read(&memory_map, 0xDFFFFF), rom[0x0FFFFF]);
+
+		// Game Pak RAM
+		assert_eq!(memory_map.sram().map(<[_]>::len), Some(0x8000));
+		memory_map.write(Address24::new(0x701FFF), 0x12);
+		assert_eq!(read(&memory_map, 0x007FFF), 0x12);
+		assert_eq!(read(&memory_map, 0xBF7FFF), 0x12);
+		assert_eq!(read(&memory_map, 0x719FFF), 0x12);
+
+		// GSU registers
+		assert_eq!(memory_map.chip_registers().len(), 2);
+		assert_eq!(read(&memory_map, 0x003030), 0x12);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_chip_registers(id);
+		assert_eq!(read(&memory_map, 0x003030), 0x30);
+		assert_eq!(read(&memory_map, 0xBF34FF), 0xFF);
+		assert_eq!(read(&memory_map, 0x003500), 0xFF);
+
+		let mut cartridge = with_header(
+			ROMType::LoROM,
+			0x80000,
+			0x15,
+			&[0x20, 0x15, 0x09, 0x00, 0x01, 0x33],
+		);
+		cartridge.rom_mut()[0x7FBD] = 0x06;
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert_eq!(memory_map.sram().map(<[_]>::len), Some(0x10000));
+		assert_eq!(read(&memory_map, 0x3F8000), rom[0x078000]);
+
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		assert!(MemoryMap::from_cartridge(cartridge, None)
+			.chip_registers()
+			.is_empty());
+	}
+
+	#[test]
+	fn spc7110() {
+		let mut cartridge = with_header(ROMType::HiROM, 0x500000, 0x15, &[0x3A, 0xF9, 0x0D, 0x03]);
+		cartridge.rom_mut()[0xFFBF] = 0x00;
+		cartridge.fix_checksum();
+		let rom = cartridge.rom().to_vec();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let read = |memory_map: &MemoryMap, address| memory_map.read(Address24::new(address));
+
+		// program ROM
+		assert_eq!(read(&memory_map, 0x008000), rom[0x008000]);
+		assert_eq!(read(&memory_map, 0x8F8000), rom[0x0F8000]);
+		assert_eq!(read(&memory_map, 0x1F8000), rom[0x0F8000]);
+		assert_eq!(read(&memory_map, 0xCF1234), rom[0x0F1234]);
+
+		// data ROM
+		assert_eq!(memory_map.data_rom_banks(), [0, 1, 2]);
+		assert_eq!(read(&memory_map, 0xD00000), rom[0x100000]);
+		assert_eq!(read(&memory_map, 0xEF1234), rom[0x2F1234]);
+		assert_eq!(read(&memory_map, 0xFFFFFF), rom[0x3FFFFF]);
+		memory_map.write(Address24::new(0x004833), 0x03);
+		assert_eq!(read(&memory_map, 0x804833), 0x03);
+		assert_eq!(read(&memory_map, 0xF01234), rom[0x401234]);
+		memory_map.set_data_rom_bank(0, 0x01);
+		assert_eq!(memory_map.data_rom_banks(), [1, 1, 3]);
+		assert_eq!(read(&memory_map, 0xD00000), rom[0x200000]);
+
+		// SRAM
+		assert_eq!(memory_map.sram().map(<[_]>::len), Some(0x2000));
+		memory_map.write(Address24::new(0x006000), 0x12);
+		assert_eq!(read(&memory_map, 0xBF6000), 0x12);
+
+		// registers
+		assert_eq!(memory_map.chip_registers().len(), 5);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_chip_registers(id);
+		assert_eq!(read(&memory_map, 0x501234), 0x34);
+		assert_eq!(read(&memory_map, 0x004800), 0x00);
+		assert_eq!(read(&memory_map, 0x004832), 0x01);
+		assert_eq!(read(&memory_map, 0x804842), 0x42);
+	}
+
+	#[test]
+	fn dsp() {
+		let cartridge = with_header(ROMType::LoROM, 0x80000, 0x16, &[0x03]);
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let board = memory_map.dsp_board().unwrap().clone();
+		assert_eq!(board.banks, 0x30..=0x3F);
+		assert_eq!(memory_map.chip_registers().len(), 2);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_chip_registers(id);
+		assert_eq!(memory_map.read(Address24::new(0x308012)), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0xBFC034)), 0x34);
+		assert_eq!(memory_map.read(Address24::new(0x2F8056)), 0x34);
+		assert!(board.is_status(Address24::new(0xBFC034)));
+
+		let cartridge = with_header(ROMType::LoROM, 0x200000, 0x16, &[0x05]);
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert_eq!(memory_map.dsp_board().unwrap().banks, 0x60..=0x6F);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_chip_registers(id);
+		assert_eq!(memory_map.read(Address24::new(0xEF4078)), 0x78);
+
+		let cartridge = with_header(ROMType::HiROM, 0x100000, 0x16, &[0x05]);
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert_eq!(memory_map.dsp_board().unwrap().offsets, 0x6000..=0x7FFF);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_chip_registers(id);
+		assert_eq!(memory_map.read(Address24::new(0x1F709A)), 0x9A);
+		memory_map.write(Address24::new(0x206000), 0xBC);
+		assert_eq!(memory_map.read(Address24::new(0x206000)), 0xBC);
+
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		assert_eq!(MemoryMap::from_cartridge(cartridge, None).dsp_board(), None);
+	}
+
+	#[test]
+	fn cx4() {
+		let mut cartridge = with_header(ROMType::LoROM, 0x100000, 0x16, &[0xF3, 0x0A, 0x00]);
+		cartridge.rom_mut()[0x7FBF] = 0x10;
+		cartridge.fix_checksum();
+		let rom = cartridge.rom().to_vec();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert_eq!(memory_map.read(Address24::new(0x9F8123)), rom[0x0F8123]);
+		assert_eq!(memory_map.chip_registers().len(), 2);
+		assert_eq!(memory_map.dsp_board(), None);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_chip_registers(id);
+		assert_eq!(memory_map.read(Address24::new(0x006012)), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0xBF7F4C)), 0x4C);
+	}
+
+	#[test]
+	fn range() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.code([0x78, 0x18, 0xFB])
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let range =
+			|start, end| AddressRange24::new(Address24::new(start), Address24::new(end)).unwrap();
+		assert_eq!(
+			memory_map
+				.iter_range(range(0x808000, 0x808002))
+				.collect::<Vec<_>>(),
+			vec![0x78, 0x18, 0xFB]
+		);
+		assert_eq!(
+			memory_map
+				.iter_range(range(0x007FFF, 0x008001))
+				.collect::<Vec<_>>(),
+			vec![0xFB, 0x78, 0x18]
+		);
+		assert_eq!(memory_map.mdr(), 0x18);
+
+		let rom = memory_map
+			.read_contiguous(range(0x008000, 0x008FFF))
+			.unwrap();
+		assert_eq!(rom.len(), 0x1000);
+		assert_eq!(rom[2].load(atomic::Ordering::SeqCst), 0xFB);
+		assert!(memory_map
+			.read_contiguous(range(0x008000, 0x009000))
+			.is_none());
+		memory_map.write(Address24::new(0x7E1FFF), 0x12);
+		let wram = memory_map
+			.read_contiguous(range(0x001FFF, 0x001FFF))
+			.unwrap();
+		assert_eq!(wram[0].load(atomic::Ordering::SeqCst), 0x12);
+		assert!(memory_map
+			.read_contiguous(range(0x7EF000, 0x7EFFFF))
+			.is_some());
+		// into the next page
+		assert!(memory_map
+			.read_contiguous(range(0x7EFFFF, 0x7F0000))
+			.is_none());
+		assert!(memory_map
+			.read_contiguous(range(0x001FFF, 0x002000))
+			.is_none());
+		assert!(memory_map
+			.read_contiguous(range(0x00FFFF, 0x018000))
+			.is_none());
+	}
+
+	#[test]
+	fn open_bus() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.code([0x78, 0x18])
+			.build()
+			.unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		assert!(matches!(
+			memory_map.unmapped_policy(),
+			UnmappedPolicy::OpenBus
+		));
+		assert_eq!(memory_map.read(Address24::new(0x008001)), 0x18);
+		assert_eq!(memory_map.read(Address24::new(0x004210)), 0x18);
+		memory_map.write(Address24::new(0x7E0000), 0x42);
+		assert_eq!(memory_map.read(Address24::new(0x002000)), 0x42);
+		memory_map.set_mdr(0x12);
+		assert_eq!(memory_map.read(Address24::new(0x002000)), 0x12);
+
+		memory_map.set_unmapped_policy(UnmappedPolicy::Constant(0x55));
+		assert_eq!(memory_map.read(Address24::new(0x002000)), 0x55);
+		assert_eq!(memory_map.mdr(), 0x55);
+
+		memory_map
+			.set_unmapped_policy(UnmappedPolicy::Callback(Arc::new(|address| address.bank())));
+		assert_eq!(memory_map.read(Address24::new(0x402000)), 0x40);
+		assert_eq!(memory_map.mdr(), 0x40);
+		// handlers are not unmapped
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_mmio(id, 0x40..=0x40, 0x2000..=0x2000);
+		assert_eq!(memory_map.read(Address24::new(0x402000)), 0x00);
+	}
+
+	#[test]
+	fn custom() {
+		let mut memory_map = MemoryMap::empty(0x10000, 0x2000, 0x800);
+		assert_eq!(memory_map.read(Address24::new(0x008000)), 0x00);
+		memory_map.rom.write_slice(0x8000, &[0x12, 0x34]);
+		memory_map.map(&[
+			MapInfo::ROM {
+				src: 0x8000,
+				dst: 0x008000,
+				len: 0x8000,
+			},
+			MapInfo::ROM {
+				src: 0x8000,
+				dst: 0x808000,
+				len: 0x8000,
+			},
+			MapInfo::WRAM {
+				src: 0,
+				dst: 0x000000,
+				len: 0x2000,
+			},
+			MapInfo::SRAM {
+				src: 0,
+				dst: 0x006000,
+				len: 0x800,
+			},
+			// the last two registers of channel 7 and `HDMAEN`
+			MapInfo::DMA {
+				src: 0x7E,
+				dst: 0x00437E,
+				len: 3,
+			},
+		]);
+		assert_eq!(memory_map.read16(Address24::new(0x808000)), 0x3412);
+		memory_map.write(Address24::new(0x001FFF), 0x56);
+		memory_map.write(Address24::new(0x0067FF), 0x78);
+		assert_eq!(
+			memory_map.wram()[0x1FFF].load(atomic::Ordering::SeqCst),
+			0x56
+		);
+		assert_eq!(
+			memory_map.sram().unwrap()[0x7FF].load(atomic::Ordering::SeqCst),
+			0x78
+		);
+		memory_map.write(Address24::new(0x004380), 0x01);
+		assert_eq!(memory_map.read(Address24::new(0x00437F)), 0x00);
+		assert!(!memory_map.resolve(Address24::new(0x004380)).readable);
+
+		// ROM over WRAM is read-only
+		memory_map.map(&[MapInfo::ROM {
+			src: 0x8000,
+			dst: 0x000000,
+			len: 0x1000,
+		}]);
+		assert_eq!(
+			memory_map.try_write(Address24::new(0x000000), 0x9A),
+			Err(MemoryError::ProtectedWrite {
+				address: Address24::new(0x000000),
+				value: 0x9A
+			})
+		);
+		assert_eq!(memory_map.read(Address24::new(0x000001)), 0x34);
+		assert_eq!(memory_map.read(Address24::new(0x001000)), 0x00);
+
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_mmio(id, 0x00..=0x3F, 0x2100..=0x21FF);
+		memory_map.unmap(0x00..=0x00, 0x8000..=0xFFFF);
+		memory_map.unmap(0x00..=0x00, 0x2100..=0x213F);
+		memory_map.set_unmapped_policy(UnmappedPolicy::Constant(0xFF));
+		assert_eq!(memory_map.read(Address24::new(0x008000)), 0xFF);
+		assert_eq!(memory_map.read(Address24::new(0x808000)), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0x002134)), 0xFF);
+		assert_eq!(memory_map.read(Address24::new(0x002140)), 0x40);
+		assert_eq!(memory_map.read(Address24::new(0x012134)), 0x34);
+		memory_map.unmap(0x00..=0x3F, 0x2100..=0x21FF);
+		assert_eq!(memory_map.read(Address24::new(0x012134)), 0xFF);
+	}
+
+	#[test]
+	fn parts() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.code([0x78, 0x18])
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge.clone(), None);
+		let parts = MemoryMap::from_parts(cartridge.rom().to_vec(), ROMType::LoROM, 0x800);
+		for &address in [0x008000, 0x808001, 0x40FFFF, 0x700000, 0x002118, 0x00420C].iter() {
+			assert_eq!(
+				parts.resolve(Address24::new(address)),
+				memory_map.resolve(Address24::new(address))
+			);
+		}
+		// the header checks do not apply
+		let parts = MemoryMap::from_parts(vec![0xEA; 0x100], ROMType::LoROM, 0);
+		assert_eq!(parts.read(Address24::new(0x0080FF)), 0xEA);
+		assert!(parts.sram().is_none());
+
+		let memory_map = MemoryMap::wram_only();
+		memory_map.write(Address24::new(0x001234), 0x56);
+		assert_eq!(memory_map.read(Address24::new(0x7E1234)), 0x56);
+		assert_eq!(memory_map.wram().len(), 0x20000);
+		assert_eq!(
+			memory_map.resolve(Address24::new(0x002118)).region,
+			MemoryRegion::Unmapped
+		);
+		assert_eq!(
+			memory_map.resolve(Address24::new(0x008000)).region,
+			MemoryRegion::Unmapped
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "mapping absent memory")]
+	fn map_absent() {
+		let mut memory_map = MemoryMap::empty(0x8000, 0x20000, 0);
+		memory_map.map(&[MapInfo::SRAM {
+			src: 0,
+			dst: 0x700000,
+			len: 0x8000,
+		}]);
+	}
+
+	#[test]
+	#[cfg_attr(debug_assertions, should_panic(expected = "unmapped address $00:2000"))]
+	fn panic_in_debug() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		memory_map.set_unmapped_policy(UnmappedPolicy::PanicInDebug);
+		assert_eq!(memory_map.read(Address24::new(0x7E0000)), 0x00);
+		memory_map.set_mdr(0x12);
+		assert_eq!(memory_map.read(Address24::new(0x002000)), 0x12);
+	}
+
+	#[test]
+	fn strict() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.build()
+			.unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_mmio(id, 0x00..=0x00, 0x2140..=0x217F);
+		let protected = |address| {
+			Err(MemoryError::ProtectedWrite {
+				address: Address24::new(address),
+				value: 0x12,
+			})
+		};
+		assert_eq!(memory_map.try_write(Address24::new(0x700000), 0x12), Ok(()));
+		assert_eq!(memory_map.try_write(Address24::new(0x002140), 0x12), Ok(()));
+		assert_eq!(
+			memory_map.try_write(Address24::new(0x80FFFF), 0x12),
+			protected(0x80FFFF)
+		);
+		// RDOAM
+		assert_eq!(
+			memory_map.try_write(Address24::new(0x002138), 0x12),
+			protected(0x002138)
+		);
+		assert_eq!(
+			memory_map.try_write(Address24::new(0x002000), 0x12),
+			Err(MemoryError::UnmappedWrite {
+				address: Address24::new(0x002000),
+				value: 0x12
+			})
+		);
+
+		memory_map.protect(0x70..=0x70, 0x0000..=0x00FF);
+		memory_map.protect(0x00..=0x00, 0x2140..=0x2140);
+		assert_eq!(
+			memory_map.try_write(Address24::new(0x700000), 0x34),
+			Err(MemoryError::ProtectedWrite {
+				address: Address24::new(0x700000),
+				value: 0x34
+			})
+		);
+		assert_eq!(memory_map.try_write(Address24::new(0x700100), 0x12), Ok(()));
+		assert_eq!(
+			memory_map.try_write(Address24::new(0x002140), 0x12),
+			protected(0x002140)
+		);
+		assert_eq!(memory_map.try_write(Address24::new(0x002141), 0x12), Ok(()));
+		assert_eq!(memory_map.read(Address24::new(0x700000)), 0x12);
+		let target = memory_map.resolve(Address24::new(0x7000FF));
+		assert!(target.readable && !target.writable);
+		assert!(!memory_map.resolve(Address24::new(0x002140)).writable);
+
+		let faults = Arc::new(Mutex::new(Vec::new()));
+		let recorded = faults.clone();
+		memory_map.set_fault_handler(move |error| recorded.lock().unwrap().push(error));
+		memory_map.write_slice(Address24::new(0x7000FE), &[0x56, 0x78, 0x9A]);
+		memory_map.write16(Address24::new(0x001FFF), 0x1234);
+		assert_eq!(
+			*faults.lock().unwrap(),
+			vec![
+				MemoryError::ProtectedWrite {
+					address: Address24::new(0x7000FE),
+					value: 0x56
+				},
+				MemoryError::ProtectedWrite {
+					address: Address24::new(0x7000FF),
+					value: 0x78
+				},
+				MemoryError::UnmappedWrite {
+					address: Address24::new(0x002000),
+					value: 0x12
+				},
+			]
+		);
+		assert_eq!(memory_map.read(Address24::new(0x700100)), 0x9A);
+		assert_eq!(memory_map.read(Address24::new(0x001FFF)), 0x34);
+
+		memory_map.unprotect(0x70..=0x70, 0x0000..=0x00FF);
+		memory_map.clear_fault_handler();
+		memory_map.write(Address24::new(0x700000), 0x56);
+		memory_map.write(Address24::new(0x002000), 0x56);
+		assert_eq!(memory_map.read(Address24::new(0x700000)), 0x56);
+		assert_eq!(faults.lock().unwrap().len(), 3);
 	}
 }
@@ -0,0 +1,472 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use super::lock::{Mutex, Once};
+use super::{new_ram, stats, trace, MemoryMap, Ram};
+
+/// Size of the pages copied on write by a fork.
+pub(super) const PAGE_SIZE: usize = 0x1000;
+
+fn copy_bytes(src: &[AtomicU8], dst: &[AtomicU8]) {
+	for (dst, src) in dst.iter().zip(src.iter()) {
+		dst.store(src.load(Ordering::SeqCst), Ordering::SeqCst);
+	}
+}
+
+/// Memory shared with forks of the memory map, see [`MemoryMap::fork`](super::MemoryMap::fork).
+///
+/// While shared, the memory is written to a copy of its own instead, each 4KB page being copied
+/// on its first write, so writes never show through to the forks.
+pub(super) struct PagedMemory {
+	/// The contents as of the last fork, shared with the forks made since.
+	shared: Arc<[AtomicU8]>,
+	/// Whether no fork shares `shared`, which is then written in place.
+	unique: bool,
+	/// The copy written while `shared` is shared, allocated on the first write.
+	copy: Once<Ram>,
+	/// The pages of `copy` holding the contents, each set once copied.
+	copied: Box<[Once<()>]>,
+}
+
+impl PagedMemory {
+	pub(super) fn new(len: usize) -> Self {
+		Self::from_shared(new_ram(len).into(), true)
+	}
+
+	fn from_shared(shared: Arc<[AtomicU8]>, unique: bool) -> Self {
+		Self {
+			copied: (0..shared.len().div_ceil(PAGE_SIZE))
+				.map(|_| Once::new())
+				.collect(),
+			shared,
+			unique,
+			copy: Once::new(),
+		}
+	}
+
+	/// Takes the pages written since the last fork into the shared contents,
+	/// which are written in place again once the forks sharing them are dropped.
+	fn settle(&mut self) {
+		if let Some(copy) = self.copy.take() {
+			for page in 0..self.copied.len() {
+				if self.copied[page].take().is_none() {
+					let range = self.page_range(page);
+					copy_bytes(&self.shared[range.clone()], &copy[range]);
+				}
+			}
+			self.shared = copy.into();
+		}
+		self.unique = Arc::get_mut(&mut self.shared).is_some();
+	}
+
+	/// Returns a copy sharing the contents until either is written.
+	pub(super) fn fork(&mut self) -> Self {
+		self.settle();
+		self.unique = false;
+		Self::from_shared(Arc::clone(&self.shared), false)
+	}
+
+	#[inline]
+	pub(super) fn len(&self) -> usize {
+		self.shared.len()
+	}
+
+	#[inline]
+	fn page_range(&self, page: usize) -> Range<usize> {
+		page * PAGE_SIZE..(page * PAGE_SIZE + PAGE_SIZE).min(self.len())
+	}
+
+	/// Returns the memory holding the contents of `page`.
+	#[inline]
+	fn current(&self, page: usize) -> &[AtomicU8] {
+		match self.copy.get() {
+			Some(copy) if self.copied[page].get().is_some() => copy,
+			_ => &self.shared,
+		}
+	}
+
+	/// Returns the memory to write `page` to, copying the page first if shared with a fork.
+	#[inline]
+	fn current_for_write(&self, page: usize) -> &[AtomicU8] {
+		if self.unique {
+			return &self.shared;
+		}
+		let copy = self.copy.get_or_init(|| new_ram(self.len()));
+		self.copied[page].get_or_init(|| {
+			let range = self.page_range(page);
+			copy_bytes(&self.shared[range.clone()], &copy[range]);
+		});
+		copy
+	}
+
+	/// Returns the page holding `offset`, cut off at the end of the memory, and the offset within it,
+	/// or `None` if `offset` is out of bounds.
+	#[inline]
+	pub(super) fn locate(&self, offset: usize) -> Option<(&[AtomicU8], usize)> {
+		if offset >= self.len() {
+			return None;
+		}
+		let page = self.page_range(offset / PAGE_SIZE);
+		let memory = &self.current(offset / PAGE_SIZE)[page.clone()];
+		Some((memory, offset - page.start))
+	}
+
+	/// Returns the page holding `offset` as [`locate`](#method.locate) does, copying it first if shared with a fork.
+	#[inline]
+	pub(super) fn locate_for_write(&self, offset: usize) -> Option<(&[AtomicU8], usize)> {
+		if offset >= self.len() {
+			return None;
+		}
+		let page = self.page_range(offset / PAGE_SIZE);
+		let memory = &self.current_for_write(offset / PAGE_SIZE)[page.clone()];
+		Some((memory, offset - page.start))
+	}
+
+	/// Returns the byte at `offset`, or `None` if out of bounds.
+	#[inline]
+	pub(super) fn get(&self, offset: usize) -> Option<&AtomicU8> {
+		if offset < self.len() {
+			Some(&self.current(offset / PAGE_SIZE)[offset])
+		} else {
+			None
+		}
+	}
+
+	/// Returns the byte at `offset` as [`get`](#method.get) does, copying its page first if shared with a fork.
+	#[inline]
+	pub(super) fn get_for_write(&self, offset: usize) -> Option<&AtomicU8> {
+		if offset < self.len() {
+			Some(&self.current_for_write(offset / PAGE_SIZE)[offset])
+		} else {
+			None
+		}
+	}
+
+	/// Returns the contents, copying the pages not copied yet if shared with a fork,
+	/// so writes through the slice never show through to the forks.
+	pub(super) fn as_slice(&self) -> &[AtomicU8] {
+		if self.unique {
+			return &self.shared;
+		}
+		for page in 0..self.copied.len() {
+			self.current_for_write(page);
+		}
+		self.copy.get_or_init(|| new_ram(self.len()))
+	}
+
+	/// Returns the contents for exclusive access, copying them first if shared with a fork.
+	pub(super) fn as_mut_slice(&mut self) -> &mut [AtomicU8] {
+		self.settle();
+		if !self.unique {
+			let copy = new_ram(self.len());
+			copy_bytes(&self.shared, &copy);
+			self.shared = copy.into();
+			self.unique = true;
+		}
+		Arc::get_mut(&mut self.shared).unwrap()
+	}
+
+	/// Reads the byte at `offset`.
+	///
+	/// # Panics
+	/// Panics if `offset` is out of bounds.
+	#[inline]
+	pub(super) fn read(&self, offset: usize) -> u8 {
+		self.get(offset)
+			.expect("offset out of bounds")
+			.load(Ordering::SeqCst)
+	}
+
+	/// Writes the byte at `offset`.
+	///
+	/// # Panics
+	/// Panics if `offset` is out of bounds.
+	#[inline]
+	pub(super) fn write(&self, offset: usize, value: u8) {
+		self.get_for_write(offset)
+			.expect("offset out of bounds")
+			.store(value, Ordering::SeqCst);
+	}
+
+	/// Writes consecutive bytes from `offset`.
+	///
+	/// # Panics
+	/// Panics if the bytes do not fit from `offset`.
+	pub(super) fn write_slice(&self, offset: usize, data: &[u8]) {
+		assert!(offset + data.len() <= self.len(), "offset out of bounds");
+		let mut done = 0;
+		while done < data.len() {
+			let (page, start) = self.locate_for_write(offset + done).unwrap();
+			for (dst, &src) in page[start..].iter().zip(data[done..].iter()) {
+				dst.store(src, Ordering::SeqCst);
+			}
+			done += page.len() - start;
+		}
+	}
+
+	/// Returns the 4KB pages of the memory in order, the last one cut to the length.
+	pub(super) fn pages(&self) -> impl ExactSizeIterator<Item = &[AtomicU8]> + '_ {
+		(0..self.copied.len()).map(move |page| &self.current(page)[self.page_range(page)])
+	}
+
+	/// Copies the contents.
+	pub(super) fn to_vec(&self) -> Vec<u8> {
+		self.pages()
+			.flat_map(|page| page.iter())
+			.map(|b| b.load(Ordering::SeqCst))
+			.collect()
+	}
+}
+
+fn copy_ram(ram: &[AtomicU8]) -> Ram {
+	ram.iter()
+		.map(|b| AtomicU8::new(b.load(Ordering::SeqCst)))
+		.collect()
+}
+
+fn copy_registers<const N: usize>(registers: &[AtomicU8; N]) -> [AtomicU8; N] {
+	let mut copy = [(); N].map(|_| AtomicU8::new(0));
+	for (dst, src) in copy.iter_mut().zip(registers.iter()) {
+		*dst.get_mut() = src.load(Ordering::Relaxed);
+	}
+	copy
+}
+
+impl MemoryMap {
+	/// Returns a copy of the memory map for a speculative run, e.g. to search inputs ahead.
+	///
+	/// The ROM, WRAM, SRAM and VRAM are shared with the copy in 4KB pages,
+	/// a page only being copied on its first write by either memory map,
+	/// while the other memory, the registers and the mapping are copied.
+	/// Handlers are shared as they cannot be copied, the copy has no battery save file attached
+	/// and is not tracing.
+	///
+	/// Forking takes `&mut self`, so no write to the memory map can race with the sharing of its pages.
+	/// The pages written since a fork are taken back into the shared memory by the next fork.
+	///
+	/// Memory borrowed from the memory map, e.g. by [`read_contiguous`](#method.read_contiguous)
+	/// or [`wram`](#method.wram), is copied first, so it can be written through without affecting the copy.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let mut memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// memory_map.write(Address24::new(0x7E0000), 0x12);
+	/// let fork = memory_map.fork();
+	/// fork.write(Address24::new(0x7E0000), 0x34);
+	/// assert_eq!(memory_map.read(Address24::new(0x7E0000)), 0x12);
+	/// assert_eq!(fork.read(Address24::new(0x7E0000)), 0x34);
+	/// ```
+	pub fn fork(&mut self) -> Self {
+		Self {
+			readable: self.readable.clone(),
+			writable: self.writable.clone(),
+			rom: self.rom.fork(),
+			wram: self.wram.fork(),
+			sram: self.sram.as_mut().map(PagedMemory::fork),
+			#[cfg(feature = "std")]
+			sram_file: None,
+			iram: self.iram.as_deref().map(copy_ram),
+			mmc: copy_registers(&self.mmc),
+			data_banks: copy_registers(&self.data_banks),
+			dma: copy_ram(&self.dma),
+			vram: self.vram.fork(),
+			cgram: copy_ram(&self.cgram),
+			oam: copy_ram(&self.oam),
+			ports: Mutex::new(self.port_registers()),
+			mmio: self.mmio.fork(),
+			chip_registers: self.chip_registers.clone(),
+			dsp_board: self.dsp_board.clone(),
+			unmapped_policy: self.unmapped_policy.clone(),
+			ordering: self.ordering,
+			mdr: AtomicU8::new(self.mdr()),
+			fault_handler: self.fault_handler.clone(),
+			trace: trace::Trace::default(),
+			stats: stats::Stats::default(),
+		}
+	}
+}
+
+impl fmt::Debug for PagedMemory {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PagedMemory")
+			.field("len", &self.len())
+			.field("unique", &self.unique)
+			.finish_non_exhaustive()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::address::{Address24, AddressRange24};
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::{AccessOrdering, MmioHandler};
+	use crate::patch::PatchRecord;
+	use std::sync::Mutex;
+
+	/// Counts the writes.
+	struct Counter(Arc<Mutex<usize>>);
+
+	impl MmioHandler for Counter {
+		fn read(&mut self, _: Address24) -> u8 {
+			0
+		}
+
+		fn write(&mut self, _: Address24, _: u8) {
+			*self.0.lock().unwrap() += 1;
+		}
+	}
+
+	#[test]
+	fn paged() {
+		let mut memory = PagedMemory::new(PAGE_SIZE + 0x10);
+		memory.write_slice(PAGE_SIZE - 1, &[1, 2, 3]);
+		assert_eq!(memory.read(PAGE_SIZE), 2);
+		let page = |memory: &PagedMemory, offset| memory.locate(offset).unwrap().0.as_ptr();
+		assert_eq!(memory.locate(PAGE_SIZE + 1).unwrap().1, 1);
+		assert_eq!(memory.locate(PAGE_SIZE).unwrap().0.len(), 0x10);
+		assert!(memory.locate(PAGE_SIZE + 0x10).is_none());
+
+		let fork = memory.fork();
+		let first = page(&memory, 0);
+		assert_eq!(page(&fork, 0), first);
+		fork.write(0, 0xFF);
+		assert_ne!(page(&fork, 0), first);
+		assert_eq!((memory.read(0), fork.read(0)), (0, 0xFF));
+		assert_eq!(fork.read(PAGE_SIZE - 1), 1);
+		assert_eq!(page(&fork, PAGE_SIZE), page(&memory, PAGE_SIZE));
+
+		// written in place again once settled without forks
+		drop(fork);
+		memory.write(1, 0xEE);
+		assert_ne!(page(&memory, 0), first);
+		memory.settle();
+		let settled = page(&memory, 0);
+		memory.write(2, 0xDD);
+		assert_eq!(page(&memory, 0), settled);
+		assert_eq!(&memory.to_vec()[..3], &[0, 0xEE, 0xDD]);
+		assert_eq!(memory.to_vec().len(), PAGE_SIZE + 0x10);
+		let pages = memory.pages().map(<[AtomicU8]>::len).collect::<Vec<_>>();
+		assert_eq!(pages, vec![PAGE_SIZE, 0x10]);
+
+		// slices of shared memory are copies
+		let fork = memory.fork();
+		fork.as_slice()[PAGE_SIZE].store(0xCC, Ordering::SeqCst);
+		assert_eq!((memory.read(PAGE_SIZE), fork.read(PAGE_SIZE)), (2, 0xCC));
+		*memory.as_mut_slice()[PAGE_SIZE].get_mut() = 0xBB;
+		assert_eq!((memory.read(PAGE_SIZE), fork.read(PAGE_SIZE)), (0xBB, 0xCC));
+	}
+
+	#[test]
+	fn concurrent_fork() {
+		let mut memory = PagedMemory::new(PAGE_SIZE * 4);
+		let shared = memory.fork();
+		std::thread::scope(|scope| {
+			for thread in 0..2 {
+				let shared = &shared;
+				scope.spawn(move || {
+					for offset in (thread..PAGE_SIZE * 4).step_by(2) {
+						shared.write(offset, 0xFF);
+					}
+				});
+			}
+			// forks made while the other memory writes its shared pages never see the writes
+			for i in 0..64 {
+				memory.write(i * 0x100, 0x11);
+				let fork = memory.fork();
+				assert!(fork.to_vec().iter().all(|&b| b == 0 || b == 0x11));
+				assert_eq!(fork.read(i * 0x100), 0x11);
+			}
+		});
+		assert!(shared.to_vec().iter().all(|&b| b == 0xFF));
+		assert!(memory.to_vec().iter().all(|&b| b == 0 || b == 0x11));
+	}
+
+	#[test]
+	fn fork() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x2000)
+			.code([0x78, 0x18])
+			.build()
+			.unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let writes = Arc::new(Mutex::new(0));
+		let id = memory_map.register_mmio(Counter(writes.clone()));
+		memory_map.map_mmio(id, 0x00..=0x00, 0x2100..=0x213F);
+		memory_map.write_slice(Address24::new(0x7E0FFE), &[1, 2, 3, 4]);
+		memory_map.write(Address24::new(0x700000), 0x56);
+		memory_map.write(Address24::new(0x004300), 0x78);
+		memory_map.set_mmc_bank(0, 0x05);
+
+		let range =
+			|start, end| AddressRange24::new(Address24::new(start), Address24::new(end)).unwrap();
+		assert_eq!(memory_map.ordering(), AccessOrdering::Relaxed);
+		memory_map.set_ordering(AccessOrdering::SeqCst);
+		let mut fork = memory_map.fork();
+		assert!(!fork.is_tracing());
+		assert_eq!(fork.ordering(), AccessOrdering::SeqCst);
+		let mut data = [0; 4];
+		fork.read_into(Address24::new(0x7E0FFE), &mut data);
+		assert_eq!(data, [1, 2, 3, 4]);
+		assert_eq!(fork.read(Address24::new(0x700000)), 0x56);
+		assert_eq!(fork.read(Address24::new(0x004300)), 0x78);
+		assert_eq!(fork.mmc_banks()[0], 0x05);
+		// borrowed memory is copied, so writing through it leaves the other map alone
+		let rom = fork.read_contiguous(range(0x008000, 0x008001)).unwrap();
+		assert_ne!(
+			rom.as_ptr(),
+			memory_map
+				.read_contiguous(range(0x008000, 0x008001))
+				.unwrap()
+				.as_ptr()
+		);
+		rom[0].store(0x99, Ordering::SeqCst);
+		assert_eq!(fork.read(Address24::new(0x008000)), 0x99);
+		assert_ne!(memory_map.read(Address24::new(0x008000)), 0x99);
+		// runs stop at the end of a page
+		assert!(fork.read_contiguous(range(0x7E0FFE, 0x7E1001)).is_none());
+
+		fork.write_slice(Address24::new(0x7E0FFF), &[0xAA, 0xBB]);
+		fork.write(Address24::new(0x700000), 0xCC);
+		fork.write(Address24::new(0x004300), 0xDD);
+		memory_map.write(Address24::new(0x7E1001), 0xEE);
+		fork.write(Address24::new(0x002100), 0);
+		memory_map.read_into(Address24::new(0x7E0FFE), &mut data);
+		assert_eq!(data, [1, 2, 3, 0xEE]);
+		fork.read_into(Address24::new(0x7E0FFE), &mut data);
+		assert_eq!(data, [1, 0xAA, 0xBB, 4]);
+		assert_eq!(fork.wram()[0x0FFF].load(Ordering::SeqCst), 0xAA);
+		assert_eq!(memory_map.wram()[0x0FFF].load(Ordering::SeqCst), 2);
+		assert_eq!(memory_map.read(Address24::new(0x700000)), 0x56);
+		assert_eq!(memory_map.read(Address24::new(0x004300)), 0x78);
+		// handlers are shared
+		assert_eq!(*writes.lock().unwrap(), 1);
+
+		// ROM changes and mappings are not shared
+		let mut snapshot = fork.snapshot();
+		snapshot.rom.push(PatchRecord {
+			offset: 0,
+			old: vec![0x78],
+			new: vec![0xEA],
+		});
+		fork.restore(&snapshot).unwrap();
+		assert_eq!(fork.read(Address24::new(0x008000)), 0xEA);
+		assert_eq!(memory_map.read(Address24::new(0x008000)), 0x78);
+		let id = fork.register_mmio(Counter(writes.clone()));
+		fork.map_mmio(id, 0x7E..=0x7E, 0x0000..=0x0000);
+		fork.write(Address24::new(0x7E0000), 0xFF);
+		assert_eq!(*writes.lock().unwrap(), 2);
+		memory_map.write(Address24::new(0x7E0000), 0xFF);
+		assert_eq!(memory_map.read(Address24::new(0x7E0000)), 0xFF);
+		assert_eq!(*writes.lock().unwrap(), 2);
+
+		// no battery save file
+		fork.flush().unwrap();
+		assert_eq!(fork.sram_file(), None);
+	}
+}
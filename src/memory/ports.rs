@@ -0,0 +1,427 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::lock::MutexGuard;
+use super::{MapInfo, MemoryMap};
+
+// B-bus registers, by their offset from `$2100`
+const OAMADDL: usize = 0x02;
+const OAMADDH: usize = 0x03;
+const OAMDATA: usize = 0x04;
+const VMAIN: usize = 0x15;
+const VMADDL: usize = 0x16;
+const VMADDH: usize = 0x17;
+const VMDATAL: usize = 0x18;
+const VMDATAH: usize = 0x19;
+const CGADD: usize = 0x21;
+const CGDATA: usize = 0x22;
+const RDOAM: usize = 0x38;
+const RDVRAML: usize = 0x39;
+const RDVRAMH: usize = 0x3A;
+const RDCGRAM: usize = 0x3B;
+/// WRAM data port, accessing WRAM at the port address and incrementing it.
+const WMDATA: usize = 0x80;
+const WMADDL: usize = 0x81;
+const WMADDM: usize = 0x82;
+const WMADDH: usize = 0x83;
+
+pub(super) const VRAM_SIZE: usize = 0x10000;
+pub(super) const CGRAM_SIZE: usize = 0x200;
+pub(super) const OAM_SIZE: usize = 0x220;
+
+/// Internal state of the port registers, see [`MemoryMap::port_registers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct PortRegisters {
+	/// The 17-bit WRAM address set by `WMADDL`, `WMADDM` and `WMADDH` at `$2181-$2183`.
+	pub wram_address: u32,
+	/// `VMAIN` at `$2115`, selecting the address increment and translation of the VRAM port.
+	pub vmain: u8,
+	/// The VRAM word address set by `VMADDL` and `VMADDH` at `$2116-$2117`.
+	pub vram_address: u16,
+	/// The VRAM word prefetched for reads from `RDVRAML` and `RDVRAMH` at `$2139-$213A`.
+	pub vram_latch: u16,
+	/// The CGRAM byte address, twice the word address set by `CGADD` at `$2121`.
+	pub cgram_address: u16,
+	/// The low byte of a color written to `CGDATA` at `$2122`, waiting for the high byte.
+	pub cgram_latch: u8,
+	/// `OAMADDL` and `OAMADDH` at `$2102-$2103`, the word address in the lower 9 bits
+	/// and the priority rotation flag in bit 15.
+	pub oam_reload_address: u16,
+	/// The OAM byte address accessed by the next access to `OAMDATA` or `RDOAM`.
+	pub oam_address: u16,
+	/// The even byte written to `OAMDATA` at `$2104`, waiting for the odd byte of the low table.
+	pub oam_latch: u8,
+}
+
+/// Lays out the port registers in `$00-$3F/$80-$BF`: the OAM, VRAM and CGRAM ports of the PPU
+/// with their write-only address and data registers at `$2102-$2104`, `$2115-$2119` and `$2121-$2122`
+/// and read-only data registers at `$2138-$213B`, `WMDATA` at `$2180` and the write-only `WMADD` at `$2181-$2183`.
+pub(super) fn map_info() -> impl Iterator<Item = MapInfo> {
+	const REGISTERS: [(usize, usize); 6] = [
+		(OAMADDL, 3),
+		(VMAIN, 5),
+		(CGADD, 2),
+		(RDOAM, 4),
+		(WMDATA, 1),
+		(WMADDL, 3),
+	];
+	(0x00..=0x3F).chain(0x80..=0xBF).flat_map(|i: usize| {
+		REGISTERS.iter().map(move |&(src, len)| MapInfo::Port {
+			src,
+			dst: i << 16 | 0x2100 | src,
+			len,
+		})
+	})
+}
+
+/// Returns whether a port register can be read and written.
+#[inline]
+pub(super) fn access(register: usize) -> (bool, bool) {
+	match register {
+		WMDATA => (true, true),
+		RDOAM..=RDCGRAM => (true, false),
+		_ => (false, true),
+	}
+}
+
+/// Applies the address translation of `VMAIN`, rotating the lower 8, 9 or 10 bits of the word address left by 3.
+fn translate_vram_address(vmain: u8, address: u16) -> u16 {
+	let bits = match vmain >> 2 & 0x03 {
+		0 => return address,
+		n => 7 + n as u32,
+	};
+	let mask = (1 << bits) - 1;
+	let rotated = (address & mask) << 3 & mask | (address & mask) >> (bits - 3);
+	address & !mask | rotated
+}
+
+/// Returns the byte offset of a word of VRAM, the word address mirroring within the 32K words.
+#[inline]
+fn vram_offset(address: u16) -> usize {
+	(address as usize & 0x7FFF) << 1
+}
+
+/// Returns the OAM offset of a byte address, mirroring the high table through `$200-$3FF`.
+#[inline]
+fn oam_offset(address: u16) -> usize {
+	match address as usize & 0x3FF {
+		offset @ 0x000..=0x1FF => offset,
+		offset => 0x200 | offset & 0x1F,
+	}
+}
+
+impl MemoryMap {
+	/// Returns the 64KB of VRAM, accessed through `$2116-$2119` and `$2139-$213A`.
+	///
+	/// While shared with a fork, the VRAM is first copied, see [`fork`](#method.fork).
+	#[inline]
+	pub fn vram(&self) -> &[AtomicU8] {
+		self.vram.as_slice()
+	}
+
+	/// Returns the 512 bytes of CGRAM holding 256 colors, accessed through `$2121-$2122` and `$213B`.
+	#[inline]
+	pub fn cgram(&self) -> &[AtomicU8] {
+		&self.cgram
+	}
+
+	/// Returns the 544 bytes of OAM, the 512-byte low table followed by the 32-byte high table,
+	/// accessed through `$2102-$2104` and `$2138`.
+	#[inline]
+	pub fn oam(&self) -> &[AtomicU8] {
+		&self.oam
+	}
+
+	#[inline]
+	fn lock_ports(&self) -> MutexGuard<'_, PortRegisters> {
+		self.ports.lock()
+	}
+
+	/// Returns the internal state of the port registers.
+	pub fn port_registers(&self) -> PortRegisters {
+		*self.lock_ports()
+	}
+
+	/// Sets the internal state of the port registers, e.g. when loading a save state.
+	pub fn set_port_registers(&self, registers: &PortRegisters) {
+		*self.lock_ports() = *registers;
+	}
+
+	/// Returns the WRAM address accessed by the next access to `WMDATA` at `$2180`.
+	#[inline]
+	pub fn wram_port_address(&self) -> u32 {
+		self.lock_ports().wram_address
+	}
+
+	/// Sets the WRAM address accessed by the next access to `WMDATA`, wrapping within the 128KB of WRAM.
+	#[inline]
+	pub fn set_wram_port_address(&self, address: u32) {
+		self.lock_ports().wram_address = address & 0x1FFFF;
+	}
+
+	/// Reloads the OAM address from `OAMADDL` and `OAMADDH`, as the PPU does at the start of vertical blanking.
+	pub fn reload_oam_address(&self) {
+		let mut ports = self.lock_ports();
+		ports.oam_address = (ports.oam_reload_address & 0x1FF) << 1;
+	}
+
+	/// Reads a port register, see [`access`].
+	pub(super) fn read_port(&self, register: usize) -> u8 {
+		let mut ports = self.lock_ports();
+		let ports = &mut *ports;
+		match register {
+			RDOAM => {
+				let value = self.oam[oam_offset(ports.oam_address)].load(Ordering::SeqCst);
+				ports.oam_address = (ports.oam_address + 1) & 0x3FF;
+				value
+			}
+			RDVRAML | RDVRAMH => {
+				let high = register == RDVRAMH;
+				let value = ports.vram_latch.to_le_bytes()[high as usize];
+				if high == (ports.vmain & 0x80 != 0) {
+					ports.vram_latch = self.prefetch_vram(ports);
+					self.increment_vram_address(ports);
+				}
+				value
+			}
+			RDCGRAM => {
+				let value = self.cgram[ports.cgram_address as usize].load(Ordering::SeqCst);
+				ports.cgram_address = (ports.cgram_address + 1) & 0x1FF;
+				value
+			}
+			WMDATA => {
+				let value = self
+					.wram
+					.get(ports.wram_address as usize)
+					.map_or_else(|| self.mdr(), |b| b.load(Ordering::SeqCst));
+				ports.wram_address = (ports.wram_address + 1) & 0x1FFFF;
+				value
+			}
+			_ => unreachable!("port register ${:02X} is write-only", register),
+		}
+	}
+
+	/// Writes a port register, see [`access`].
+	pub(super) fn write_port(&self, register: usize, value: u8) {
+		let mut ports = self.lock_ports();
+		let ports = &mut *ports;
+		match register {
+			OAMADDL | OAMADDH => {
+				ports.oam_reload_address = if register == OAMADDL {
+					ports.oam_reload_address & 0xFF00 | value as u16
+				} else {
+					ports.oam_reload_address & 0x00FF | (value as u16 & 0x81) << 8
+				};
+				ports.oam_address = (ports.oam_reload_address & 0x1FF) << 1;
+			}
+			OAMDATA => {
+				let address = ports.oam_address;
+				if address >= 0x200 {
+					self.oam[oam_offset(address)].store(value, Ordering::SeqCst);
+				} else if address & 1 == 0 {
+					ports.oam_latch = value;
+				} else {
+					// the low table is written a word at a time
+					self.oam[address as usize - 1].store(ports.oam_latch, Ordering::SeqCst);
+					self.oam[address as usize].store(value, Ordering::SeqCst);
+				}
+				ports.oam_address = (address + 1) & 0x3FF;
+			}
+			VMAIN => ports.vmain = value,
+			VMADDL | VMADDH => {
+				let [low, high] = ports.vram_address.to_le_bytes();
+				ports.vram_address = if register == VMADDL {
+					u16::from_le_bytes([value, high])
+				} else {
+					u16::from_le_bytes([low, value])
+				};
+				ports.vram_latch = self.prefetch_vram(ports);
+			}
+			VMDATAL | VMDATAH => {
+				let high = register == VMDATAH;
+				let address = translate_vram_address(ports.vmain, ports.vram_address);
+				self.vram.write(vram_offset(address) | high as usize, value);
+				if high == (ports.vmain & 0x80 != 0) {
+					self.increment_vram_address(ports);
+				}
+			}
+			CGADD => ports.cgram_address = (value as u16) << 1,
+			CGDATA => {
+				let address = ports.cgram_address as usize;
+				if address & 1 == 0 {
+					ports.cgram_latch = value;
+				} else {
+					// colors are 15-bit, bit 7 of the high byte is not stored
+					self.cgram[address - 1].store(ports.cgram_latch, Ordering::SeqCst);
+					self.cgram[address].store(value & 0x7F, Ordering::SeqCst);
+				}
+				ports.cgram_address = (ports.cgram_address + 1) & 0x1FF;
+			}
+			WMDATA => {
+				if let Some(b) = self.wram.get_for_write(ports.wram_address as usize) {
+					b.store(value, Ordering::SeqCst);
+				}
+				ports.wram_address = (ports.wram_address + 1) & 0x1FFFF;
+			}
+			WMADDL => ports.wram_address = ports.wram_address & 0x1FF00 | value as u32,
+			WMADDM => ports.wram_address = ports.wram_address & 0x100FF | (value as u32) << 8,
+			WMADDH => {
+				ports.wram_address = ports.wram_address & 0x0FFFF | (value as u32 & 0x01) << 16
+			}
+			_ => unreachable!("port register ${:02X} is read-only", register),
+		}
+	}
+
+	/// Returns the VRAM word at the translated port address.
+	fn prefetch_vram(&self, ports: &PortRegisters) -> u16 {
+		let offset = vram_offset(translate_vram_address(ports.vmain, ports.vram_address));
+		u16::from_le_bytes([self.vram.read(offset), self.vram.read(offset | 1)])
+	}
+
+	/// Advances the VRAM port address by the increment of `VMAIN`, 1, 32 or 128 words.
+	fn increment_vram_address(&self, ports: &mut PortRegisters) {
+		let increment = [1, 32, 128, 128][ports.vmain as usize & 0x03];
+		ports.vram_address = ports.vram_address.wrapping_add(increment);
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::address::Address24;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::MemoryRegion;
+
+	fn memory_map() -> MemoryMap {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		MemoryMap::from_cartridge(cartridge, None)
+	}
+
+	#[test]
+	fn wram_port() {
+		let memory_map = memory_map();
+		memory_map.write_slice(Address24::new(0x002181), &[0x34, 0x12, 0xFF]);
+		assert_eq!(memory_map.wram_port_address(), 0x11234);
+		memory_map.write(Address24::new(0x002180), 0xAB);
+		memory_map.write(Address24::new(0x802180), 0xCD);
+		assert_eq!(memory_map.read16(Address24::new(0x7F1234)), 0xCDAB);
+		assert_eq!(memory_map.wram_port_address(), 0x11236);
+
+		// write-only address
+		memory_map.set_mdr(0x5A);
+		assert_eq!(memory_map.read(Address24::new(0x002181)), 0x5A);
+
+		memory_map.write(Address24::new(0x002183), 0x00);
+		assert_eq!(memory_map.wram_port_address(), 0x01236);
+		memory_map.write(Address24::new(0x002183), 0x01);
+		memory_map.write(Address24::new(0x002181), 0x34);
+		assert_eq!(memory_map.read(Address24::new(0x002180)), 0xAB);
+		assert_eq!(memory_map.read(Address24::new(0x002180)), 0xCD);
+
+		// wraps at the end of WRAM
+		memory_map.set_wram_port_address(0x1FFFF);
+		memory_map.write(Address24::new(0x002180), 0xEF);
+		assert_eq!(memory_map.wram_port_address(), 0);
+		assert_eq!(memory_map.read(Address24::new(0x7FFFFF)), 0xEF);
+
+		let target = memory_map.resolve(Address24::new(0x002180));
+		assert_eq!((target.region, target.offset), (MemoryRegion::Port, 0x80));
+		assert!(target.readable && target.writable);
+		let target = memory_map.resolve(Address24::new(0x002183));
+		assert!(!target.readable && target.writable);
+	}
+
+	#[test]
+	fn vram_port() {
+		let memory_map = memory_map();
+		let vram = |offset: usize| memory_map.vram()[offset].load(Ordering::SeqCst);
+
+		// increment after the high byte
+		memory_map.write_slice(Address24::new(0x002115), &[0x80, 0x00, 0x10]);
+		memory_map.write_slice(Address24::new(0x002118), &[0x12, 0x34]);
+		memory_map.write(Address24::new(0x002118), 0x56);
+		assert_eq!(
+			(vram(0x2000), vram(0x2001), vram(0x2002)),
+			(0x12, 0x34, 0x56)
+		);
+		assert_eq!(memory_map.port_registers().vram_address, 0x1001);
+
+		// increment by 32 after the low byte
+		memory_map.write_slice(Address24::new(0x002115), &[0x01, 0x00, 0x00]);
+		memory_map.write(Address24::new(0x002118), 0x78);
+		memory_map.write(Address24::new(0x002118), 0x9A);
+		assert_eq!((vram(0x0000), vram(0x0040)), (0x78, 0x9A));
+
+		// reads return the word prefetched before incrementing, so the first word is read twice
+		memory_map.write_slice(Address24::new(0x002115), &[0x80, 0x00, 0x10]);
+		let words = (0..3)
+			.map(|_| memory_map.read16(Address24::new(0x002139)))
+			.collect::<Vec<_>>();
+		assert_eq!(words, vec![0x3412, 0x3412, 0x0056]);
+		assert_eq!(memory_map.port_registers().vram_address, 0x1003);
+
+		// translation, address $0001 accessing word $0008
+		memory_map.write_slice(Address24::new(0x002115), &[0x84, 0x01, 0x00]);
+		memory_map.write_slice(Address24::new(0x002118), &[0xBC, 0xDE]);
+		assert_eq!((vram(0x0010), vram(0x0011)), (0xBC, 0xDE));
+		assert_eq!(translate_vram_address(0x04, 0xFF20), 0xFF01);
+		assert_eq!(translate_vram_address(0x08, 0xFF40), 0xFE05);
+		assert_eq!(translate_vram_address(0x0C, 0x0080), 0x0001);
+		assert_eq!(translate_vram_address(0x0C, 0x007F), 0x03F8);
+
+		// write-only
+		memory_map.set_mdr(0xA5);
+		assert_eq!(memory_map.read(Address24::new(0x002118)), 0xA5);
+	}
+
+	#[test]
+	fn cgram_port() {
+		let memory_map = memory_map();
+		memory_map.write(Address24::new(0x002121), 0x10);
+		memory_map.write_slice(Address24::new(0x002122), &[0x1F]);
+		// the low byte waits for the high byte
+		assert_eq!(memory_map.cgram()[0x20].load(Ordering::SeqCst), 0x00);
+		memory_map.write(Address24::new(0x002122), 0xFC);
+		assert_eq!(memory_map.cgram()[0x20].load(Ordering::SeqCst), 0x1F);
+		assert_eq!(memory_map.cgram()[0x21].load(Ordering::SeqCst), 0x7C);
+
+		memory_map.write(Address24::new(0x002121), 0x10);
+		assert_eq!(memory_map.read(Address24::new(0x00213B)), 0x1F);
+		assert_eq!(memory_map.read(Address24::new(0x00213B)), 0x7C);
+		assert_eq!(memory_map.port_registers().cgram_address, 0x22);
+	}
+
+	#[test]
+	fn oam_port() {
+		let memory_map = memory_map();
+		let oam = |offset: usize| memory_map.oam()[offset].load(Ordering::SeqCst);
+
+		memory_map.write_slice(Address24::new(0x002102), &[0x01, 0x00]);
+		memory_map.write(Address24::new(0x002104), 0x11);
+		assert_eq!(oam(0x02), 0x00);
+		memory_map.write(Address24::new(0x002104), 0x22);
+		assert_eq!((oam(0x02), oam(0x03)), (0x11, 0x22));
+
+		// the high table is written a byte at a time and mirrored
+		memory_map.write_slice(Address24::new(0x002102), &[0x00, 0x81]);
+		assert_eq!(memory_map.port_registers().oam_reload_address, 0x8100);
+		memory_map.write(Address24::new(0x002104), 0x33);
+		assert_eq!(oam(0x200), 0x33);
+		memory_map.set_port_registers(&PortRegisters {
+			oam_address: 0x3E1,
+			..memory_map.port_registers()
+		});
+		memory_map.write(Address24::new(0x002104), 0x44);
+		assert_eq!(oam(0x201), 0x44);
+		assert_eq!(memory_map.read(Address24::new(0x002138)), 0x00);
+		assert_eq!(memory_map.port_registers().oam_address, 0x3E3);
+
+		memory_map.reload_oam_address();
+		assert_eq!(memory_map.read(Address24::new(0x002138)), 0x33);
+		assert_eq!(memory_map.port_registers().oam_address, 0x201);
+		memory_map.write_slice(Address24::new(0x002102), &[0x01, 0x00]);
+		assert_eq!(memory_map.read(Address24::new(0x002138)), 0x11);
+		assert_eq!(memory_map.read(Address24::new(0x002138)), 0x22);
+	}
+}
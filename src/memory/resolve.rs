@@ -0,0 +1,194 @@
+use alloc::vec::Vec;
+
+use super::{Entry, MemoryMap, MmioId, Source, MAP_SIZE};
+use crate::address::Address24;
+
+/// Memory or handler an address is mapped to.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemoryRegion {
+	ROM,
+	WRAM,
+	SRAM,
+	/// I-RAM of an SA-1 cartridge.
+	IRAM,
+	/// Super MMC bank registers of an SA-1 cartridge.
+	MMCBanks,
+	/// Data ROM bank registers of an SPC7110 cartridge.
+	DataROMBanks,
+	/// DMA registers at `$420C` and `$4300-$437F`.
+	DMARegisters,
+	/// A built-in port register, e.g. of the WRAM or VRAM port, with the offset holding its offset from `$2100`.
+	Port,
+	/// A registered hardware register handler.
+	Mmio(MmioId),
+	Unmapped,
+}
+
+impl From<Source> for MemoryRegion {
+	fn from(source: Source) -> Self {
+		match source {
+			Source::ROM | Source::MMCROM | Source::DataROM => MemoryRegion::ROM,
+			Source::WRAM => MemoryRegion::WRAM,
+			Source::SRAM => MemoryRegion::SRAM,
+			Source::IRAM => MemoryRegion::IRAM,
+			Source::MMC => MemoryRegion::MMCBanks,
+			Source::DataBanks => MemoryRegion::DataROMBanks,
+			Source::DMA => MemoryRegion::DMARegisters,
+			Source::Port => MemoryRegion::Port,
+		}
+	}
+}
+
+/// What an address is mapped to, see [`MemoryMap::resolve`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MappedTarget {
+	pub region: MemoryRegion,
+	/// Offset into the memory of the region, zero for handlers and unmapped addresses.
+	pub offset: usize,
+	pub readable: bool,
+	pub writable: bool,
+}
+
+impl MemoryMap {
+	/// Reports the memory an address is mapped to, as switched by the current bank registers,
+	/// the offset into it and whether it is readable and writable there.
+	///
+	/// Memory takes precedence over a handler, and a handler over being unmapped, as with accesses.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// let target = memory_map.resolve(Address24::new(0x80C010));
+	/// assert_eq!((target.region, target.offset), (MemoryRegion::ROM, 0x4010));
+	/// assert!(target.readable && !target.writable);
+	/// ```
+	pub fn resolve(&self, address: Address24) -> MappedTarget {
+		let index: usize = address.into();
+		let (readable, writable) = (self.readable.get(index), self.writable.get(index));
+		let entry = if readable != Entry::UNMAPPED {
+			readable
+		} else {
+			writable
+		};
+		let located = match entry.source() {
+			Some(Source::Port) => true,
+			_ => self.locate(entry).is_some(),
+		};
+		if let (Some(source), true) = (entry.source(), located) {
+			let offset = self.switch(source, entry.offset());
+			return MappedTarget {
+				region: source.into(),
+				offset,
+				readable: readable == entry,
+				writable: writable == entry,
+			};
+		}
+		match self.mmio.find(address) {
+			Some(id) => MappedTarget {
+				region: MemoryRegion::Mmio(id),
+				offset: 0,
+				readable: true,
+				writable: !writable.is_protected(),
+			},
+			None => MappedTarget {
+				region: MemoryRegion::Unmapped,
+				offset: 0,
+				readable: false,
+				writable: false,
+			},
+		}
+	}
+
+	/// Returns every address currently resolving to `offset` into the memory of `region`,
+	/// covering all mirrors of a byte as laid out by the memory map.
+	///
+	/// Handlers and unmapped addresses are looked up with an offset of zero, see [`MappedTarget::offset`].
+	pub fn addresses_of(&self, region: MemoryRegion, offset: usize) -> Vec<Address24> {
+		(0..MAP_SIZE as u32)
+			.map(Address24::new)
+			.filter(|&address| {
+				let target = self.resolve(address);
+				target.region == region && target.offset == offset
+			})
+			.collect()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::MmioHandler;
+
+	struct Registers;
+
+	impl MmioHandler for Registers {
+		fn read(&mut self, _address: Address24) -> u8 {
+			0
+		}
+
+		fn write(&mut self, _address: Address24, _value: u8) {}
+	}
+
+	#[test]
+	fn resolve() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.build()
+			.unwrap();
+		let mut memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let resolve = |memory_map: &MemoryMap, address| {
+			let target = memory_map.resolve(Address24::new(address));
+			(
+				target.region,
+				target.offset,
+				target.readable,
+				target.writable,
+			)
+		};
+		assert_eq!(
+			resolve(&memory_map, 0x80FFFF),
+			(MemoryRegion::ROM, 0x7FFF, true, false)
+		);
+		assert_eq!(
+			resolve(&memory_map, 0x001234),
+			(MemoryRegion::WRAM, 0x1234, true, true)
+		);
+		assert_eq!(
+			resolve(&memory_map, 0x7F0000),
+			(MemoryRegion::WRAM, 0x10000, true, true)
+		);
+		assert_eq!(
+			resolve(&memory_map, 0xF00801),
+			(MemoryRegion::SRAM, 0x0001, true, true)
+		);
+		assert_eq!(
+			resolve(&memory_map, 0x002100),
+			(MemoryRegion::Unmapped, 0, false, false)
+		);
+
+		let id = memory_map.register_mmio(Registers);
+		memory_map.map_mmio(id, 0x00..=0x00, 0x2100..=0x2100);
+		assert_eq!(
+			resolve(&memory_map, 0x002100),
+			(MemoryRegion::Mmio(id), 0, true, true)
+		);
+
+		assert_eq!(
+			memory_map.addresses_of(MemoryRegion::ROM, 0x1000),
+			vec![Address24::new(0x009000), Address24::new(0x809000)]
+		);
+		let wram = memory_map.addresses_of(MemoryRegion::WRAM, 0x10);
+		assert_eq!(wram.len(), 129);
+		assert_eq!(wram[64], Address24::new(0x7E0010));
+		let sram = memory_map.addresses_of(MemoryRegion::SRAM, 0x01);
+		assert_eq!(sram.len(), 30 * 16);
+		assert_eq!(sram[1], Address24::new(0x700801));
+		assert_eq!(
+			memory_map.addresses_of(MemoryRegion::Mmio(id), 0),
+			vec![Address24::new(0x002100)]
+		);
+	}
+}
@@ -0,0 +1,66 @@
+use super::MapInfo;
+use crate::cartridge::SA1Config;
+
+/// Lays out an SA-1 cartridge as seen from the CPU.
+///
+/// The Super MMC maps ROM at `$00-$3F/$80-$BF:8000-FFFF` and `$C0-$FF`, BW-RAM at `$40-$4F`
+/// with a block of it at `$00-$3F/$80-$BF:6000-7FFF`, and I-RAM at `$00-$3F/$80-$BF:3000-37FF`.
+pub(super) fn map_info(config: &SA1Config, sram_size: Option<usize>) -> Vec<MapInfo> {
+	let banks = || (0x00..=0x3F).chain(0x80..=0xBF);
+	let mut map_info = Vec::new();
+
+	// ROM
+	map_info.extend(banks().map(|i: usize| MapInfo::MMCROM {
+		area: (i >> 5 & 0x01) | (i >> 6 & 0x02),
+		lorom: true,
+		src: (i & 0x1F) * 0x8000,
+		dst: i << 16 | 0x8000,
+		len: 0x8000,
+	}));
+	map_info.extend((0xC0..=0xFF).map(|i: usize| MapInfo::MMCROM {
+		area: i >> 4 & 0x03,
+		lorom: false,
+		src: (i & 0x0F) << 16,
+		dst: i << 16,
+		len: 0x10000,
+	}));
+
+	// I-RAM and the bank registers
+	map_info.extend(banks().flat_map(|i| {
+		[
+			MapInfo::IRAM {
+				src: 0,
+				dst: i << 16 | 0x3000,
+				len: config.iram_size,
+			},
+			MapInfo::MMC {
+				src: 0,
+				dst: i << 16 | 0x2220,
+				len: 4,
+			},
+		]
+	}));
+
+	// BW-RAM
+	if let Some(sram_size) = sram_size {
+		let block = config.bwram_block as usize * 0x2000;
+		let len = sram_size.min(0x2000);
+		map_info.extend(banks().flat_map(|i| {
+			(0x6000..0x8000)
+				.step_by(len)
+				.map(move |offset| MapInfo::SRAM {
+					src: (block + offset - 0x6000) % sram_size,
+					dst: i << 16 | offset,
+					len,
+				})
+		}));
+		map_info.extend(super::sram_windows(
+			0x40..=0x4F,
+			0x0F,
+			0x0000..0x10000,
+			sram_size,
+		));
+	}
+
+	map_info
+}
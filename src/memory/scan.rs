@@ -0,0 +1,255 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::{MemoryMap, MemoryRegion, Source};
+use crate::address::Address24;
+
+/// Value searched for by [`MemoryScanner::find`], stored little-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanValue {
+	U8(u8),
+	U16(u16),
+}
+
+impl ScanValue {
+	fn to_bytes(self) -> Vec<u8> {
+		match self {
+			ScanValue::U8(value) => vec![value],
+			ScanValue::U16(value) => value.to_le_bytes().to_vec(),
+		}
+	}
+}
+
+/// How the candidates of a [`MemoryScanner`] are narrowed down by comparing their values with the previous scan,
+/// values of more than a byte being compared as little-endian integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Refinement {
+	Changed,
+	Unchanged,
+	Increased,
+	Decreased,
+}
+
+/// Offset into the WRAM or SRAM of a candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Candidate {
+	sram: bool,
+	offset: usize,
+}
+
+/// Search of WRAM and SRAM for the location of a value, e.g. the health of the player,
+/// narrowed down over successive scans of the memory as the value changes.
+/// ```
+/// # use sneslib::address::Address24;
+/// # use sneslib::cartridge::*;
+/// # use sneslib::memory::*;
+/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+/// memory_map.write(Address24::new(0x7E0010), 3);
+/// memory_map.write(Address24::new(0x7E0020), 3);
+/// let mut scanner = MemoryScanner::find(&memory_map, ScanValue::U8(3));
+/// assert_eq!(scanner.len(), 2);
+///
+/// memory_map.write(Address24::new(0x7E0010), 2);
+/// scanner.refine(&memory_map, Refinement::Decreased);
+/// assert_eq!(scanner.hits(&memory_map), vec![Address24::new(0x7E0010)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryScanner {
+	width: usize,
+	candidates: Vec<Candidate>,
+	/// WRAM and SRAM as of the previous scan.
+	wram: Vec<u8>,
+	sram: Vec<u8>,
+}
+
+impl MemoryScanner {
+	/// Starts with every value of `width` bytes, to narrow down a value not known but seen changing.
+	///
+	/// # Panics
+	/// Panics if `width` is zero.
+	pub fn unknown(memory: &MemoryMap, width: usize) -> Self {
+		Self::scan(memory, width, |_| true)
+	}
+
+	/// Searches for a value.
+	pub fn find(memory: &MemoryMap, value: ScanValue) -> Self {
+		Self::find_bytes(memory, &value.to_bytes())
+	}
+
+	/// Searches for a sequence of bytes.
+	///
+	/// # Panics
+	/// Panics if `pattern` is empty.
+	pub fn find_bytes(memory: &MemoryMap, pattern: &[u8]) -> Self {
+		Self::scan(memory, pattern.len(), |bytes| bytes == pattern)
+	}
+
+	fn scan(memory: &MemoryMap, width: usize, filter: impl Fn(&[u8]) -> bool) -> Self {
+		assert!(width > 0, "scanning values of no bytes");
+		let wram = memory.dump_wram();
+		let sram = memory.dump_sram().unwrap_or_default();
+		let matches = |data: &[u8], sram| {
+			data.windows(width)
+				.enumerate()
+				.filter(|(_, bytes)| filter(bytes))
+				.map(move |(offset, _)| Candidate { sram, offset })
+				.collect::<Vec<_>>()
+		};
+		let mut candidates = matches(&wram, false);
+		candidates.extend(matches(&sram, true));
+		Self {
+			width,
+			candidates,
+			wram,
+			sram,
+		}
+	}
+
+	/// Returns the number of candidates left.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.candidates.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.candidates.is_empty()
+	}
+
+	/// Keeps the candidates whose value compares with the previous scan as `refinement` requires,
+	/// taking the current memory as the previous scan of the next refinement.
+	pub fn refine(&mut self, memory: &MemoryMap, refinement: Refinement) {
+		let wram = memory.dump_wram();
+		let sram = memory.dump_sram().unwrap_or_default();
+		let width = self.width;
+		let (old_wram, old_sram) = (&self.wram, &self.sram);
+		self.candidates.retain(|candidate| {
+			let (old, new) = if candidate.sram {
+				(old_sram, &sram)
+			} else {
+				(old_wram, &wram)
+			};
+			let range = candidate.offset..candidate.offset + width;
+			let ordering = match (old.get(range.clone()), new.get(range)) {
+				(Some(old), Some(new)) => new.iter().rev().cmp(old.iter().rev()),
+				// memory of another size, e.g. after loading another cartridge
+				_ => return false,
+			};
+			match refinement {
+				Refinement::Changed => ordering != Ordering::Equal,
+				Refinement::Unchanged => ordering == Ordering::Equal,
+				Refinement::Increased => ordering == Ordering::Greater,
+				Refinement::Decreased => ordering == Ordering::Less,
+			}
+		});
+		self.wram = wram;
+		self.sram = sram;
+	}
+
+	/// Returns an address of each candidate, leaving out candidates not mapped to an address.
+	///
+	/// The address is taken from banks `$40-$7F` first, then `$00-$3F`, `$C0-$FF` and `$80-$BF`,
+	/// e.g. `$7E:xxxx` or `$7F:xxxx` for WRAM and `$70:xxxx` for the SRAM of a LoROM cartridge.
+	pub fn hits(&self, memory: &MemoryMap) -> Vec<Address24> {
+		let mut wram = vec![None; self.wram.len()];
+		let mut sram = vec![None; self.sram.len()];
+		let banks = (0x40..=0x7F)
+			.chain(0x00..=0x3F)
+			.chain(0xC0..=0xFF)
+			.chain(0x80..=0xBF);
+		for bank in banks {
+			for address in bank << 16..(bank + 1) << 16 {
+				let entry = memory.readable.get(address);
+				let addresses = match entry.source() {
+					Some(Source::WRAM) => &mut wram,
+					Some(Source::SRAM) => &mut sram,
+					_ => continue,
+				};
+				if let Some(slot @ None) = addresses.get_mut(entry.offset()) {
+					*slot = Some(Address24::new(address as u32));
+				}
+			}
+		}
+		self.candidates
+			.iter()
+			.filter_map(|candidate| {
+				if candidate.sram {
+					sram[candidate.offset]
+				} else {
+					wram[candidate.offset]
+				}
+			})
+			.collect()
+	}
+
+	/// Returns the memory and offset of each candidate.
+	pub fn offsets(&self) -> impl ExactSizeIterator<Item = (MemoryRegion, usize)> + '_ {
+		self.candidates.iter().map(|candidate| {
+			let region = if candidate.sram {
+				MemoryRegion::SRAM
+			} else {
+				MemoryRegion::WRAM
+			};
+			(region, candidate.offset)
+		})
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+
+	#[test]
+	fn scan() {
+		let cartridge = CartridgeBuilder::new(ROMType::HiROM)
+			.sram_size(0x2000)
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		memory_map.write16(Address24::new(0x7E0100), 0x1234);
+		memory_map.write16(Address24::new(0x7FFFFE), 0x1234);
+		memory_map.write16(Address24::new(0x206010), 0x1234);
+		let mut scanner = MemoryScanner::find(&memory_map, ScanValue::U16(0x1234));
+		assert_eq!(
+			scanner.hits(&memory_map),
+			vec![
+				Address24::new(0x7E0100),
+				Address24::new(0x7FFFFE),
+				Address24::new(0x206010)
+			]
+		);
+		assert_eq!(
+			scanner.offsets().collect::<Vec<_>>(),
+			vec![
+				(MemoryRegion::WRAM, 0x100),
+				(MemoryRegion::WRAM, 0x1FFFE),
+				(MemoryRegion::SRAM, 0x10)
+			]
+		);
+
+		// the high byte carries
+		memory_map.write16(Address24::new(0x7E0100), 0x1300);
+		memory_map.write16(Address24::new(0x206010), 0x1233);
+		scanner.refine(&memory_map, Refinement::Changed);
+		assert_eq!(scanner.len(), 2);
+		let mut increased = scanner.clone();
+		increased.refine(&memory_map, Refinement::Unchanged);
+		assert_eq!(increased.len(), 2);
+		memory_map.write(Address24::new(0x206010), 0x34);
+		memory_map.write(Address24::new(0x7E0101), 0x12);
+		increased.refine(&memory_map, Refinement::Increased);
+		assert_eq!(increased.hits(&memory_map), vec![Address24::new(0x206010)]);
+		scanner.refine(&memory_map, Refinement::Decreased);
+		assert_eq!(scanner.hits(&memory_map), vec![Address24::new(0x7E0100)]);
+
+		let scanner = MemoryScanner::find_bytes(&memory_map, &[0x00, 0x12, 0x00]);
+		assert_eq!(scanner.hits(&memory_map), vec![Address24::new(0x7E0100)]);
+		let mut scanner = MemoryScanner::unknown(&memory_map, 1);
+		assert_eq!(scanner.len(), 0x20000 + 0x2000);
+		memory_map.write(Address24::new(0x000042), 0x01);
+		scanner.refine(&memory_map, Refinement::Changed);
+		assert_eq!(scanner.hits(&memory_map), vec![Address24::new(0x7E0042)]);
+	}
+}
@@ -0,0 +1,275 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::{MemoryMap, MemoryRegion, PagedMemory, PortRegisters, SnapshotError};
+use crate::patch::{self, PatchRecord};
+
+/// Contents of the memory of a [`MemoryMap`], taken by [`MemoryMap::snapshot`] for save states
+/// or to reset memory between test cases.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+	pub wram: Vec<u8>,
+	pub sram: Option<Vec<u8>>,
+	pub iram: Option<Vec<u8>>,
+	pub mmc_banks: [u8; 4],
+	pub data_rom_banks: [u8; 3],
+	/// The DMA registers followed by the internal HDMA state.
+	pub dma: Vec<u8>,
+	pub vram: Vec<u8>,
+	pub cgram: Vec<u8>,
+	pub oam: Vec<u8>,
+	pub ports: PortRegisters,
+	pub mdr: u8,
+	/// Changes of the ROM from a base ROM, empty unless taken by [`MemoryMap::snapshot_with_rom`].
+	pub rom: Vec<PatchRecord>,
+}
+
+/// Run of WRAM or SRAM bytes differing between two snapshots, see [`MemorySnapshot::diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MemDelta {
+	/// [`MemoryRegion::WRAM`] or [`MemoryRegion::SRAM`].
+	pub region: MemoryRegion,
+	pub offset: usize,
+	pub old: Vec<u8>,
+	pub new: Vec<u8>,
+}
+
+impl MemorySnapshot {
+	/// Compares the WRAM and SRAM with those of a later snapshot, coalescing adjacent changed bytes into one run,
+	/// e.g. to find the game state changed between two frames.
+	///
+	/// Memory differing in size is compared as ROMs are by [`patch::diff`], absent SRAM being empty.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// let before = memory_map.snapshot();
+	/// memory_map.write16(Address24::new(0x7E0100), 0x1234);
+	/// let deltas = before.diff(&memory_map.snapshot());
+	/// assert_eq!(deltas, vec![MemDelta {
+	///     region: MemoryRegion::WRAM,
+	///     offset: 0x100,
+	///     old: vec![0x00, 0x00],
+	///     new: vec![0x34, 0x12],
+	/// }]);
+	/// ```
+	pub fn diff(&self, other: &MemorySnapshot) -> Vec<MemDelta> {
+		let delta = |region| {
+			move |record: PatchRecord| MemDelta {
+				region,
+				offset: record.offset,
+				old: record.old,
+				new: record.new,
+			}
+		};
+		patch::diff(&self.wram, &other.wram)
+			.into_iter()
+			.map(delta(MemoryRegion::WRAM))
+			.chain(
+				patch::diff(
+					self.sram.as_deref().unwrap_or_default(),
+					other.sram.as_deref().unwrap_or_default(),
+				)
+				.into_iter()
+				.map(delta(MemoryRegion::SRAM)),
+			)
+			.collect()
+	}
+}
+
+fn load(memory: &[AtomicU8]) -> Vec<u8> {
+	memory.iter().map(|b| b.load(Ordering::SeqCst)).collect()
+}
+
+fn store(memory: &[AtomicU8], data: &[u8]) {
+	for (dst, &src) in memory.iter().zip(data.iter()) {
+		dst.store(src, Ordering::SeqCst);
+	}
+}
+
+fn check(
+	memory: &'static str,
+	expected: Option<usize>,
+	found: Option<&[u8]>,
+) -> Result<(), SnapshotError> {
+	let (expected, found) = (expected.unwrap_or(0), found.map_or(0, <[_]>::len));
+	if expected == found {
+		Ok(())
+	} else {
+		Err(SnapshotError::SizeMismatch {
+			memory,
+			expected,
+			found,
+		})
+	}
+}
+
+impl MemoryMap {
+	/// Copies the RAM, the PPU memory, the bank, DMA and port registers and the memory data register.
+	pub fn snapshot(&self) -> MemorySnapshot {
+		MemorySnapshot {
+			wram: self.wram.to_vec(),
+			sram: self.dump_sram(),
+			iram: self.iram.as_deref().map(load),
+			mmc_banks: self.mmc_banks(),
+			data_rom_banks: self.data_rom_banks(),
+			dma: load(&self.dma),
+			vram: self.vram.to_vec(),
+			cgram: load(&self.cgram),
+			oam: load(&self.oam),
+			ports: self.port_registers(),
+			mdr: self.mdr(),
+			rom: Vec::new(),
+		}
+	}
+
+	/// Copies the memory as [`snapshot`](#method.snapshot) does, along with the changes of the ROM from `base`,
+	/// e.g. the ROM before applying patches or cheats to the cartridge.
+	pub fn snapshot_with_rom(&self, base: &[u8]) -> MemorySnapshot {
+		MemorySnapshot {
+			rom: patch::diff(base, &self.rom.to_vec()),
+			..self.snapshot()
+		}
+	}
+
+	/// Restores the memory from a snapshot, applying its changes of the ROM.
+	///
+	/// Leaves the memory as is if the snapshot does not fit.
+	pub fn restore(&self, snapshot: &MemorySnapshot) -> Result<(), SnapshotError> {
+		check("WRAM", Some(self.wram.len()), Some(&snapshot.wram))?;
+		check(
+			"SRAM",
+			self.sram.as_ref().map(PagedMemory::len),
+			snapshot.sram.as_deref(),
+		)?;
+		check(
+			"I-RAM",
+			self.iram.as_ref().map(|iram| iram.len()),
+			snapshot.iram.as_deref(),
+		)?;
+		check("DMA", Some(self.dma.len()), Some(&snapshot.dma))?;
+		check("VRAM", Some(self.vram.len()), Some(&snapshot.vram))?;
+		check("CGRAM", Some(self.cgram.len()), Some(&snapshot.cgram))?;
+		check("OAM", Some(self.oam.len()), Some(&snapshot.oam))?;
+		if let Some(record) = snapshot
+			.rom
+			.iter()
+			.find(|record| record.offset + record.new.len() > self.rom.len())
+		{
+			return Err(SnapshotError::RomOutOfRange(record.offset));
+		}
+
+		self.wram.write_slice(0, &snapshot.wram);
+		if let (Some(sram), Some(data)) = (&self.sram, &snapshot.sram) {
+			sram.write_slice(0, data);
+		}
+		if let (Some(iram), Some(data)) = (&self.iram, &snapshot.iram) {
+			store(iram, data);
+		}
+		for record in snapshot.rom.iter() {
+			self.rom.write_slice(record.offset, &record.new);
+		}
+		store(&self.mmc, &snapshot.mmc_banks);
+		store(&self.data_banks, &snapshot.data_rom_banks);
+		store(&self.dma, &snapshot.dma);
+		self.vram.write_slice(0, &snapshot.vram);
+		store(&self.cgram, &snapshot.cgram);
+		store(&self.oam, &snapshot.oam);
+		self.set_port_registers(&snapshot.ports);
+		self.set_mdr(snapshot.mdr);
+		Ok(())
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::address::Address24;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+
+	#[test]
+	fn snapshot() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.code([0x78, 0x18])
+			.build()
+			.unwrap();
+		let base = cartridge.rom().to_vec();
+		let mut patched = cartridge.clone();
+		patched.rom_mut()[0] = 0xEA;
+		let memory_map = MemoryMap::from_cartridge(patched, None);
+		memory_map.write(Address24::new(0x7E1234), 0x12);
+		memory_map.write(Address24::new(0x004302), 0x56);
+		memory_map.write(Address24::new(0x700010), 0x34);
+		let snapshot = memory_map.snapshot_with_rom(&base);
+		assert_eq!(snapshot.wram[0x1234], 0x12);
+		assert_eq!(snapshot.sram.as_ref().unwrap()[0x10], 0x34);
+		assert_eq!(snapshot.iram, None);
+		assert_eq!(snapshot.mdr, 0x34);
+		assert_eq!(
+			snapshot.rom,
+			vec![PatchRecord {
+				offset: 0,
+				old: vec![0x78],
+				new: vec![0xEA]
+			}]
+		);
+		assert!(memory_map.snapshot().rom.is_empty());
+
+		let serialized = bincode::serialize(&snapshot).unwrap();
+		let snapshot: MemorySnapshot = bincode::deserialize(&serialized).unwrap();
+
+		memory_map.write(Address24::new(0x7E1234), 0x56);
+		memory_map.write(Address24::new(0x700010), 0x78);
+		memory_map.write(Address24::new(0x004302), 0x9A);
+		memory_map.restore(&snapshot).unwrap();
+		assert_eq!(memory_map.read(Address24::new(0x004302)), 0x56);
+		assert_eq!(memory_map.read(Address24::new(0x001234)), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0x700010)), 0x34);
+
+		let memory_map = MemoryMap::from_cartridge(cartridge.clone(), None);
+		memory_map.restore(&snapshot).unwrap();
+		assert_eq!(memory_map.read(Address24::new(0x008000)), 0xEA);
+
+		let memory_map = MemoryMap::with_sram_size(cartridge, None, 0);
+		assert_eq!(
+			memory_map.snapshot().diff(&snapshot),
+			vec![
+				MemDelta {
+					region: MemoryRegion::WRAM,
+					offset: 0x1234,
+					old: vec![0x00],
+					new: vec![0x12],
+				},
+				MemDelta {
+					region: MemoryRegion::SRAM,
+					offset: 0,
+					old: vec![],
+					new: snapshot.sram.clone().unwrap(),
+				},
+			]
+		);
+		assert_eq!(
+			memory_map.restore(&snapshot),
+			Err(SnapshotError::SizeMismatch {
+				memory: "SRAM",
+				expected: 0,
+				found: 0x800
+			})
+		);
+		assert_eq!(memory_map.read(Address24::new(0x001234)), 0x00);
+		let mut snapshot = memory_map.snapshot();
+		snapshot.rom.push(PatchRecord {
+			offset: 0x8000,
+			old: vec![],
+			new: vec![0],
+		});
+		assert_eq!(
+			memory_map.restore(&snapshot),
+			Err(SnapshotError::RomOutOfRange(0x8000))
+		);
+	}
+}
@@ -0,0 +1,80 @@
+#[cfg(feature = "std")]
+use super::{sram_windows, MapInfo, RegisterWindow};
+
+/// Size of the program ROM preceding the data ROM.
+pub(super) const PROGRAM_ROM_SIZE: usize = 0x100000;
+
+/// Returns the decompressed data port at `$50` and the registers at `$00-$3F/$80-$BF:4800-4842`,
+/// leaving out the data ROM bank registers at `$4831-$4833` handled by the memory map.
+#[cfg(feature = "std")]
+pub(super) fn registers() -> Vec<RegisterWindow> {
+	let mut registers = vec![RegisterWindow {
+		banks: 0x50..=0x50,
+		offsets: 0x0000..=0xFFFF,
+	}];
+	for banks in [0x00..=0x3F, 0x80..=0xBF].iter() {
+		registers.push(RegisterWindow {
+			banks: banks.clone(),
+			offsets: 0x4800..=0x4830,
+		});
+		registers.push(RegisterWindow {
+			banks: banks.clone(),
+			offsets: 0x4834..=0x4842,
+		});
+	}
+	registers
+}
+
+/// Lays out an SPC7110 cartridge as seen from the CPU.
+///
+/// The 1MB program ROM is mapped as HiROM at `$00-$3F/$80-$BF:8000-FFFF` and `$C0-$CF`,
+/// and the data ROM following it at `$D0-$FF` in 1MB blocks selected by `$4831-$4833`.
+/// The SRAM is mapped at `$00-$3F/$80-$BF:6000-7FFF`.
+#[cfg(feature = "std")]
+pub(super) fn map_info(rom_size: usize, sram_size: Option<usize>) -> Vec<MapInfo> {
+	let banks = || (0x00..=0x3F).chain(0x80..=0xBF);
+	let program_size = rom_size.min(PROGRAM_ROM_SIZE);
+	let mut map_info = Vec::new();
+
+	// program ROM
+	map_info.extend(
+		banks()
+			.map(|i: usize| (i, (i & 0x0F) << 16 | 0x8000))
+			.filter(|&(_, src)| src < program_size)
+			.map(|(i, src)| MapInfo::ROM {
+				src,
+				dst: i << 16 | 0x8000,
+				len: 0x8000,
+			}),
+	);
+	map_info.extend(
+		(0xC0..=0xCF)
+			.map(|i: usize| (i, (i & 0x0F) << 16))
+			.filter(|&(_, src)| src < program_size)
+			.map(|(i, src)| MapInfo::ROM {
+				src,
+				dst: i << 16,
+				len: 0x10000,
+			}),
+	);
+
+	// data ROM and its bank registers
+	map_info.extend((0xD0..=0xFF).map(|i: usize| MapInfo::DataROM {
+		area: (i >> 4) - 0x0D,
+		src: (i & 0x0F) << 16,
+		dst: i << 16,
+		len: 0x10000,
+	}));
+	map_info.extend(banks().map(|i| MapInfo::DataBanks {
+		src: 0,
+		dst: i << 16 | 0x4831,
+		len: 3,
+	}));
+
+	// SRAM
+	if let Some(sram_size) = sram_size {
+		map_info.extend(sram_windows(banks(), 0x00, 0x6000..0x8000, sram_size));
+	}
+
+	map_info
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::MemoryMap;
+
+/// Battery save file backing the SRAM, with the contents last loaded from or saved to it.
+pub(crate) struct SramFile {
+	path: PathBuf,
+	saved: Vec<u8>,
+}
+
+impl MemoryMap {
+	/// Backs the SRAM with a `.srm` battery save file, loading it if it exists.
+	///
+	/// The SRAM is saved to the file by [`flush`](#method.flush) and when the memory map is dropped,
+	/// only if it changed since loaded or last saved. Does nothing if the cartridge has no SRAM.
+	pub fn attach_sram_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+		if self.sram.is_none() {
+			return Ok(());
+		}
+		let path = path.as_ref();
+		match fs::read(path) {
+			Ok(data) => {
+				self.load_sram(&data);
+			}
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+			Err(e) => return Err(e),
+		}
+		self.sram_file = Some(SramFile {
+			path: path.into(),
+			saved: self.dump_sram().unwrap_or_default(),
+		});
+		Ok(())
+	}
+
+	/// Returns the path of the battery save file backing the SRAM, if attached.
+	pub fn sram_file(&self) -> Option<&Path> {
+		self.sram_file.as_ref().map(|file| file.path.as_path())
+	}
+
+	/// Saves the SRAM to the attached battery save file if it changed,
+	/// returning `true` if the file was written.
+	pub fn flush(&mut self) -> io::Result<bool> {
+		if self.sram_file.is_none() {
+			return Ok(false);
+		}
+		let sram = self.dump_sram().unwrap_or_default();
+		match &mut self.sram_file {
+			Some(file) if file.saved != sram => {
+				fs::write(&file.path, &sram)?;
+				file.saved = sram;
+				Ok(true)
+			}
+			_ => Ok(false),
+		}
+	}
+}
+
+impl Drop for MemoryMap {
+	fn drop(&mut self) {
+		// errors cannot be reported here, call `flush` to handle them
+		let _ = self.flush();
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::address::Address24;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+
+	#[test]
+	fn srm() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.build()
+			.unwrap();
+		let path = std::env::temp_dir().join(format!("sneslib-{}.srm", std::process::id()));
+		let _ = fs::remove_file(&path);
+
+		let mut memory_map = MemoryMap::from_cartridge(cartridge.clone(), None);
+		memory_map.attach_sram_file(&path).unwrap();
+		assert_eq!(memory_map.sram_file(), Some(path.as_path()));
+		assert!(!memory_map.flush().unwrap());
+		assert!(!path.exists());
+		memory_map.write(Address24::new(0x700001), 0x12);
+		assert!(memory_map.flush().unwrap());
+		assert!(!memory_map.flush().unwrap());
+		memory_map.write(Address24::new(0x700002), 0x34);
+		drop(memory_map);
+
+		let mut memory_map = MemoryMap::from_cartridge(cartridge.clone(), None);
+		memory_map.attach_sram_file(&path).unwrap();
+		assert_eq!(memory_map.read(Address24::new(0x700001)), 0x12);
+		assert_eq!(memory_map.read(Address24::new(0x700002)), 0x34);
+		memory_map.sram_mut().unwrap()[0x7FF] = 0x56;
+		assert!(memory_map.flush().unwrap());
+		drop(memory_map);
+		let saved = fs::read(&path);
+		fs::remove_file(&path).unwrap();
+		let saved = saved.unwrap();
+		assert_eq!(saved.len(), 0x800);
+		assert_eq!(&saved[..3], &[0x00, 0x12, 0x34]);
+		assert_eq!(saved[0x7FF], 0x56);
+
+		let mut memory_map = MemoryMap::with_sram_size(cartridge, None, 0);
+		memory_map.attach_sram_file(&path).unwrap();
+		assert_eq!(memory_map.sram_file(), None);
+		assert!(!memory_map.flush().unwrap());
+	}
+}
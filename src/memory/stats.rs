@@ -0,0 +1,216 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::lock::Mutex;
+use super::{AccessKind, MemoryRegion};
+
+/// Memories whose accesses are counted, in the order of their counters.
+const REGIONS: [MemoryRegion; 4] = [
+	MemoryRegion::ROM,
+	MemoryRegion::WRAM,
+	MemoryRegion::SRAM,
+	MemoryRegion::IRAM,
+];
+
+/// Bytes counted together by the access statistics of a [`MemoryMap`](super::MemoryMap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatsGranularity {
+	/// 256-byte pages, as the direct page, keeping 24 bytes of counters per 256 bytes of memory.
+	Page,
+	/// Single bytes, keeping 24 bytes of counters per byte of memory.
+	Byte,
+}
+
+impl StatsGranularity {
+	#[inline]
+	fn size(self) -> usize {
+		match self {
+			StatsGranularity::Page => 0x100,
+			StatsGranularity::Byte => 1,
+		}
+	}
+}
+
+/// Accesses of a page or byte of memory counted by the access statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct AccessCounts {
+	pub reads: u64,
+	pub writes: u64,
+	/// Instruction fetches, see [`MemoryMap::fetch`](super::MemoryMap::fetch).
+	pub executes: u64,
+}
+
+impl AccessCounts {
+	/// Returns the number of accesses of any kind.
+	#[inline]
+	pub fn total(&self) -> u64 {
+		self.reads + self.writes + self.executes
+	}
+}
+
+#[derive(Default)]
+struct State {
+	granularity: Option<StatsGranularity>,
+	/// Counters of each memory of `REGIONS`, one per page or byte.
+	counts: [Vec<AccessCounts>; 4],
+}
+
+/// Access statistics, only locked while enabled.
+#[derive(Default)]
+pub(crate) struct Stats {
+	enabled: AtomicBool,
+	state: Mutex<State>,
+}
+
+impl Stats {
+	#[inline]
+	pub(crate) fn is_enabled(&self) -> bool {
+		self.enabled.load(Ordering::Relaxed)
+	}
+
+	pub(crate) fn count(&self, region: MemoryRegion, offset: usize, kind: AccessKind) {
+		let mut state = self.state.lock();
+		let size = match state.granularity {
+			Some(granularity) => granularity.size(),
+			None => return,
+		};
+		let counts = REGIONS
+			.iter()
+			.position(|&r| r == region)
+			.and_then(|i| state.counts[i].get_mut(offset / size));
+		if let Some(counts) = counts {
+			match kind {
+				AccessKind::Read => counts.reads += 1,
+				AccessKind::Write => counts.writes += 1,
+				AccessKind::Execute => counts.executes += 1,
+			}
+		}
+	}
+
+	/// Starts counting afresh for memories of `sizes` bytes, in the order of `REGIONS`.
+	pub(crate) fn start(&self, granularity: StatsGranularity, sizes: [usize; 4]) {
+		let mut state = self.state.lock();
+		let size = granularity.size();
+		state.granularity = Some(granularity);
+		state.counts = sizes.map(|len| vec![AccessCounts::default(); len.div_ceil(size)]);
+		self.enabled.store(true, Ordering::Relaxed);
+	}
+
+	pub(crate) fn stop(&self) {
+		let _state = self.state.lock();
+		self.enabled.store(false, Ordering::Relaxed);
+	}
+
+	pub(crate) fn heatmap(&self) -> BTreeMap<(MemoryRegion, usize), AccessCounts> {
+		let state = self.state.lock();
+		let size = match state.granularity {
+			Some(granularity) => granularity.size(),
+			None => return BTreeMap::new(),
+		};
+		REGIONS
+			.iter()
+			.zip(state.counts.iter())
+			.flat_map(|(&region, counts)| {
+				counts
+					.iter()
+					.enumerate()
+					.filter(|(_, counts)| counts.total() > 0)
+					.map(move |(i, &counts)| ((region, i * size), counts))
+			})
+			.collect()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::address::Address24;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::MemoryMap;
+
+	#[test]
+	fn heatmap() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x2000)
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		memory_map.read(Address24::new(0x808000));
+		assert!(memory_map.heatmap().is_empty());
+
+		memory_map.start_statistics(StatsGranularity::Byte);
+		memory_map.fetch(Address24::new(0x808000));
+		memory_map.fetch(Address24::new(0x008000));
+		memory_map.read(Address24::new(0x808001));
+		// mirrors count alike, ports and unmapped addresses not at all
+		memory_map.write16(Address24::new(0x000010), 0x1234);
+		memory_map.write(Address24::new(0x7E0010), 0x56);
+		memory_map.write(Address24::new(0x702000), 0x78);
+		memory_map.read(Address24::new(0x002100));
+		memory_map.read(Address24::new(0x005000));
+		let heatmap = memory_map.heatmap();
+		assert_eq!(
+			heatmap.into_iter().collect::<Vec<_>>(),
+			vec![
+				(
+					(MemoryRegion::ROM, 0),
+					AccessCounts {
+						executes: 2,
+						..AccessCounts::default()
+					}
+				),
+				(
+					(MemoryRegion::ROM, 1),
+					AccessCounts {
+						reads: 1,
+						..AccessCounts::default()
+					}
+				),
+				(
+					(MemoryRegion::WRAM, 0x10),
+					AccessCounts {
+						writes: 2,
+						..AccessCounts::default()
+					}
+				),
+				(
+					(MemoryRegion::WRAM, 0x11),
+					AccessCounts {
+						writes: 1,
+						..AccessCounts::default()
+					}
+				),
+				(
+					(MemoryRegion::SRAM, 0),
+					AccessCounts {
+						writes: 1,
+						..AccessCounts::default()
+					}
+				),
+			]
+		);
+
+		// bulk copies count every byte
+		memory_map.start_statistics(StatsGranularity::Page);
+		let mut buf = [0; 0x180];
+		memory_map.read_into(Address24::new(0x7E0080), &mut buf);
+		memory_map.write_slice(Address24::new(0x7E00FF), &[0; 2]);
+		memory_map.stop_statistics();
+		memory_map.read(Address24::new(0x7E0000));
+		let heatmap = memory_map.heatmap();
+		assert_eq!(heatmap.len(), 2);
+		assert_eq!(
+			heatmap[&(MemoryRegion::WRAM, 0)],
+			AccessCounts {
+				reads: 0x80,
+				writes: 1,
+				executes: 0
+			}
+		);
+		assert_eq!(heatmap[&(MemoryRegion::WRAM, 0x100)].total(), 0x101);
+	}
+}
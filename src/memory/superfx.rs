@@ -0,0 +1,77 @@
+use super::{sram_windows, MapInfo, RegisterWindow};
+use crate::cartridge::Cartridge;
+
+/// Game Pak RAM size of cartridges declaring none, e.g. Star Fox predating the extended header.
+const DEFAULT_RAM_SIZE: usize = 0x8000;
+
+/// Returns the Game Pak RAM size declared by the extended header, or else by the header.
+pub(super) fn ram_size(cartridge: &Cartridge) -> usize {
+	cartridge
+		.extended_header()
+		.and_then(|header| header.expansion_ram_size_bytes())
+		.filter(|&size| size > 0)
+		.unwrap_or_else(|| match cartridge.sram_size() {
+			0 => DEFAULT_RAM_SIZE,
+			size => size,
+		})
+}
+
+/// Returns the GSU registers at `$00-$3F/$80-$BF:3000-34FF`.
+pub(super) fn registers() -> Vec<RegisterWindow> {
+	vec![
+		RegisterWindow {
+			banks: 0x00..=0x3F,
+			offsets: 0x3000..=0x34FF,
+		},
+		RegisterWindow {
+			banks: 0x80..=0xBF,
+			offsets: 0x3000..=0x34FF,
+		},
+	]
+}
+
+/// Lays out a SuperFX cartridge as seen from the CPU.
+///
+/// Up to 2MB of ROM appears both as LoROM at `$00-$3F/$80-$BF:8000-FFFF` and as HiROM at `$40-$5F/$C0-$DF`,
+/// mirrored if smaller. The Game Pak RAM is mapped at `$70-$71` with its first 8KB at `$00-$3F/$80-$BF:6000-7FFF`.
+pub(super) fn map_info(rom_size: usize, sram_size: Option<usize>) -> Vec<MapInfo> {
+	let mut map_info = Vec::new();
+
+	// ROM
+	let mirror = |src: usize| src % rom_size.max(1);
+	map_info.extend(
+		(0x00..=0x3F)
+			.chain(0x80..=0xBF)
+			.map(|i: usize| (i, mirror((i & 0x3F) * 0x8000)))
+			.filter(|&(_, src)| src + 0x8000 <= rom_size)
+			.map(|(i, src)| MapInfo::ROM {
+				src,
+				dst: i << 16 | 0x8000,
+				len: 0x8000,
+			}),
+	);
+	map_info.extend(
+		(0x40..=0x5F)
+			.chain(0xC0..=0xDF)
+			.map(|i: usize| (i, mirror((i & 0x1F) << 16)))
+			.filter(|&(_, src)| src + 0x10000 <= rom_size)
+			.map(|(i, src)| MapInfo::ROM {
+				src,
+				dst: i << 16,
+				len: 0x10000,
+			}),
+	);
+
+	// Game Pak RAM
+	if let Some(sram_size) = sram_size {
+		map_info.extend(sram_windows(0x70..=0x71, 0x01, 0x0000..0x10000, sram_size));
+		map_info.extend(sram_windows(
+			(0x00..=0x3F).chain(0x80..=0xBF),
+			0x00,
+			0x6000..0x8000,
+			sram_size,
+		));
+	}
+
+	map_info
+}
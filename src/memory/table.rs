@@ -0,0 +1,183 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec;
+use core::ops::Range;
+
+use super::{Entry, MAP_SIZE};
+
+/// Number of entries in a page of a table.
+const PAGE_SIZE: usize = 0x1000;
+
+/// Entries of a page of the address space.
+#[derive(Debug, Clone)]
+struct Page {
+	/// Entry of the first address, followed by that entry plus the offset within the page masked with `mask`
+	/// unless the entries are laid out otherwise.
+	first: Entry,
+	/// 0 for the same entry throughout, e.g. unmapped, or all ones for entries mapping consecutive bytes.
+	mask: u32,
+	/// Entries laid out otherwise, shared with forks and mirrors until changed.
+	entries: Option<Arc<[Entry; PAGE_SIZE]>>,
+}
+
+impl Page {
+	/// Returns the page of `entries`, compacted into the first entry if they allow.
+	fn new(entries: Arc<[Entry; PAGE_SIZE]>) -> Self {
+		let first = entries[0];
+		let follows = |mask| {
+			entries
+				.iter()
+				.enumerate()
+				.all(|(i, e)| e.0 == first.0 + (mask & i as u32))
+		};
+		if follows(0) {
+			Page::fill(first)
+		} else if first.offset() + PAGE_SIZE <= 0x1000000 && follows(u32::MAX) {
+			Page {
+				first,
+				mask: u32::MAX,
+				entries: None,
+			}
+		} else {
+			Page {
+				first,
+				mask: 0,
+				entries: Some(entries),
+			}
+		}
+	}
+
+	fn fill(entry: Entry) -> Self {
+		Page {
+			first: entry,
+			mask: 0,
+			entries: None,
+		}
+	}
+
+	fn to_entries(&self) -> Arc<[Entry; PAGE_SIZE]> {
+		match &self.entries {
+			Some(entries) => Arc::clone(entries),
+			None => {
+				let mut entries = [self.first; PAGE_SIZE];
+				for (i, e) in entries.iter_mut().enumerate() {
+					e.0 += self.mask & i as u32;
+				}
+				Arc::new(entries)
+			}
+		}
+	}
+}
+
+/// Mapping table from each address to an entry, split into 4KB pages of which only those
+/// not compacted into a single entry are allocated, once for all pages alike.
+#[derive(Debug, Clone)]
+pub(super) struct Table {
+	pages: Box<[Page]>,
+}
+
+impl Default for Table {
+	fn default() -> Self {
+		Self {
+			pages: vec![Page::fill(Entry::UNMAPPED); MAP_SIZE / PAGE_SIZE].into_boxed_slice(),
+		}
+	}
+}
+
+impl Table {
+	#[inline]
+	pub(super) fn get(&self, index: usize) -> Entry {
+		let (page, offset) = (&self.pages[index / PAGE_SIZE], index % PAGE_SIZE);
+		match &page.entries {
+			Some(entries) => entries[offset],
+			None => Entry(page.first.0 + (page.mask & offset as u32)),
+		}
+	}
+
+	/// Updates the entries of `range` by calling `f` with the index and entry of each.
+	pub(super) fn update(&mut self, range: Range<usize>, mut f: impl FnMut(usize, &mut Entry)) {
+		assert!(range.end <= MAP_SIZE);
+		let mut index = range.start;
+		while index < range.end {
+			let (page, start) = (index / PAGE_SIZE, index % PAGE_SIZE);
+			let base = page * PAGE_SIZE;
+			let end = (range.end - base).min(PAGE_SIZE);
+			let mut entries = self.pages[page].to_entries();
+			for (i, entry) in Arc::make_mut(&mut entries)[start..end]
+				.iter_mut()
+				.enumerate()
+			{
+				f(base + start + i, entry);
+			}
+			self.pages[page] = Page::new(entries);
+			index = base + end;
+		}
+	}
+
+	/// Shares the entries of pages laid out alike, e.g. mirrors in several banks.
+	pub(super) fn share_pages(&mut self) {
+		let mut shared = BTreeSet::new();
+		for entries in self
+			.pages
+			.iter_mut()
+			.filter_map(|page| page.entries.as_mut())
+		{
+			match shared.get(entries) {
+				Some(first) => *entries = Arc::clone(first),
+				None => {
+					shared.insert(Arc::clone(entries));
+				}
+			}
+		}
+	}
+
+	/// Returns the number of pages allocated, the others being compacted into a single entry.
+	#[cfg(all(test, feature = "std"))]
+	pub(super) fn allocated_pages(&self) -> usize {
+		self.pages
+			.iter()
+			.filter_map(|page| page.entries.as_ref())
+			.map(Arc::as_ptr)
+			.collect::<BTreeSet<_>>()
+			.len()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::{MemoryMap, Source};
+
+	#[test]
+	fn table() {
+		let mut table = Table::default();
+		assert_eq!(table.get(MAP_SIZE - 1), Entry::UNMAPPED);
+		table.update(0x7E0000..0x800000, |i, e| {
+			*e = Entry::new(Source::WRAM, i - 0x7E0000)
+		});
+		assert_eq!(table.allocated_pages(), 0);
+		assert_eq!(table.get(0x7FFFFF), Entry::new(Source::WRAM, 0x1FFFF));
+
+		table.update(0x7E0800..0x7E0801, |_, e| *e = Entry::UNMAPPED);
+		assert_eq!(table.allocated_pages(), 1);
+		assert_eq!(table.get(0x7E0800), Entry::UNMAPPED);
+		assert_eq!(table.get(0x7E0801), Entry::new(Source::WRAM, 0x801));
+		let fork = table.clone();
+		table.update(0x7E0800..0x7E0801, |_, e| {
+			*e = Entry::new(Source::WRAM, 0x800)
+		});
+		assert_eq!(table.allocated_pages(), 0);
+		assert_eq!(fork.get(0x7E0800), Entry::UNMAPPED);
+
+		// mirrors share their pages
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.sram_size(0x800)
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let pages = memory_map.readable.allocated_pages() + memory_map.writable.allocated_pages();
+		assert!(pages <= 8);
+	}
+}
@@ -0,0 +1,227 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::lock::Mutex;
+use crate::address::Address24;
+
+/// Direction of a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccessKind {
+	Read,
+	Write,
+	/// An instruction fetch, see [`MemoryMap::fetch`](super::MemoryMap::fetch).
+	Execute,
+}
+
+/// A memory access recorded by the trace of a [`MemoryMap`](super::MemoryMap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Access {
+	/// Number of accesses recorded before this one.
+	pub sequence: u64,
+	pub address: Address24,
+	pub value: u8,
+	pub kind: AccessKind,
+}
+
+/// Receives the accesses recorded by the trace as they happen.
+pub trait TraceSink: Send {
+	fn record(&mut self, access: Access);
+}
+
+impl<F> TraceSink for F
+where
+	F: FnMut(Access) + Send,
+{
+	#[inline]
+	fn record(&mut self, access: Access) {
+		self(access)
+	}
+}
+
+enum Tracer {
+	/// Keeps the latest `capacity` accesses.
+	Buffer {
+		accesses: VecDeque<Access>,
+		capacity: usize,
+	},
+	/// Streams the accesses to a sink, taken out while it runs so it can access the memory map.
+	Sink(Option<Box<dyn TraceSink>>),
+}
+
+#[derive(Default)]
+struct State {
+	sequence: u64,
+	tracer: Option<Tracer>,
+}
+
+/// Access trace, only locked while enabled.
+#[derive(Default)]
+pub(crate) struct Trace {
+	enabled: AtomicBool,
+	state: Mutex<State>,
+}
+
+impl Trace {
+	#[inline]
+	pub(crate) fn is_enabled(&self) -> bool {
+		self.enabled.load(Ordering::Relaxed)
+	}
+
+	#[cold]
+	#[inline(never)]
+	pub(crate) fn record(&self, address: Address24, value: u8, kind: AccessKind) {
+		let mut state = self.state.lock();
+		let access = Access {
+			sequence: state.sequence,
+			address,
+			value,
+			kind,
+		};
+		let sink = match &mut state.tracer {
+			Some(Tracer::Buffer { accesses, capacity }) => {
+				if accesses.len() == *capacity {
+					accesses.pop_front();
+				}
+				if *capacity > 0 {
+					accesses.push_back(access);
+				}
+				None
+			}
+			// accesses made while the sink runs, e.g. by the sink itself, are not recorded
+			Some(Tracer::Sink(sink)) => match sink.take() {
+				Some(sink) => Some(sink),
+				None => return,
+			},
+			None => return,
+		};
+		state.sequence += 1;
+		drop(state);
+
+		// the sink runs without the lock held, then is put back unless the trace was replaced
+		if let Some(mut sink) = sink {
+			sink.record(access);
+			if let Some(Tracer::Sink(slot @ None)) = &mut self.state.lock().tracer {
+				*slot = Some(sink);
+			}
+		}
+	}
+
+	fn start(&self, tracer: Tracer) {
+		let mut state = self.state.lock();
+		state.tracer = Some(tracer);
+		self.enabled.store(true, Ordering::Relaxed);
+	}
+
+	pub(crate) fn start_buffer(&self, capacity: usize) {
+		self.start(Tracer::Buffer {
+			accesses: VecDeque::with_capacity(capacity),
+			capacity,
+		});
+	}
+
+	pub(crate) fn start_sink(&self, sink: Box<dyn TraceSink>) {
+		self.start(Tracer::Sink(Some(sink)));
+	}
+
+	pub(crate) fn stop(&self) -> Vec<Access> {
+		let mut state = self.state.lock();
+		self.enabled.store(false, Ordering::Relaxed);
+		match state.tracer.take() {
+			Some(Tracer::Buffer { accesses, .. }) => accesses.into(),
+			_ => Vec::new(),
+		}
+	}
+
+	pub(crate) fn take(&self) -> Vec<Access> {
+		let mut state = self.state.lock();
+		match &mut state.tracer {
+			Some(Tracer::Buffer { accesses, .. }) => accesses.drain(..).collect(),
+			_ => Vec::new(),
+		}
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+	use crate::memory::MemoryMap;
+	use std::sync::{Arc, Mutex};
+
+	#[test]
+	fn trace() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM)
+			.code([0x78, 0x18])
+			.build()
+			.unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		memory_map.read(Address24::new(0x008000));
+		assert!(!memory_map.is_tracing());
+		assert!(memory_map.take_trace().is_empty());
+
+		memory_map.start_trace(3);
+		assert!(memory_map.is_tracing());
+		memory_map.read16(Address24::new(0x008000));
+		memory_map.write_slice(Address24::new(0x7E0000), &[1, 2]);
+		let access = |sequence, address, value, kind| Access {
+			sequence,
+			address: Address24::new(address),
+			value,
+			kind,
+		};
+		assert_eq!(
+			memory_map.take_trace(),
+			vec![
+				access(1, 0x008001, 0x18, AccessKind::Read),
+				access(2, 0x7E0000, 1, AccessKind::Write),
+				access(3, 0x7E0001, 2, AccessKind::Write),
+			]
+		);
+		memory_map.read(Address24::new(0x000001));
+		assert_eq!(
+			memory_map.stop_trace(),
+			vec![access(4, 0x000001, 2, AccessKind::Read)]
+		);
+		memory_map.read(Address24::new(0x000001));
+		assert!(!memory_map.is_tracing());
+		assert!(memory_map.stop_trace().is_empty());
+
+		let accesses = Arc::new(Mutex::new(Vec::new()));
+		let sink = accesses.clone();
+		memory_map.start_trace_sink(move |access| sink.lock().unwrap().push(access));
+		let mut buf = [0; 2];
+		memory_map.read_into(Address24::new(0x7E0000), &mut buf);
+		assert!(memory_map.take_trace().is_empty());
+		memory_map.stop_trace();
+		assert_eq!(
+			*accesses.lock().unwrap(),
+			vec![
+				access(5, 0x7E0000, 1, AccessKind::Read),
+				access(6, 0x7E0001, 2, AccessKind::Read),
+			]
+		);
+
+		// a sink may read the memory map it traces, its own reads going unrecorded
+		let memory_map = Arc::new(memory_map);
+		let (sink, traced) = (accesses.clone(), memory_map.clone());
+		accesses.lock().unwrap().clear();
+		memory_map.start_trace_sink(move |access: Access| {
+			let value = traced.read(Address24::new(0x7E0001));
+			sink.lock().unwrap().push(Access { value, ..access });
+		});
+		memory_map.write(Address24::new(0x7E0000), 3);
+		memory_map.read(Address24::new(0x7E0000));
+		memory_map.stop_trace();
+		assert_eq!(
+			*accesses.lock().unwrap(),
+			vec![
+				access(7, 0x7E0000, 2, AccessKind::Write),
+				access(8, 0x7E0000, 2, AccessKind::Read),
+			]
+		);
+	}
+}
@@ -0,0 +1,181 @@
+use core::convert::TryInto;
+
+use super::MemoryMap;
+use crate::address::{Address16, Address24};
+
+/// Size in bytes of the largest value read or written by [`MemoryMap::read_value`] and [`MemoryMap::write_value`].
+const MAX_SIZE: usize = 4;
+
+/// Values read little-endian by [`MemoryMap::read_value`].
+pub trait FromLeBytes: Sized {
+	/// Size of the value in bytes, at most 4.
+	const SIZE: usize;
+
+	/// Makes the value from its `SIZE` bytes, the least significant first.
+	fn from_le_bytes(bytes: &[u8]) -> Self;
+}
+
+/// Values written little-endian by [`MemoryMap::write_value`], as many bytes as they are read from.
+pub trait ToLeBytes: FromLeBytes {
+	/// Stores the `SIZE` bytes of the value, the least significant first.
+	fn to_le_bytes(&self, bytes: &mut [u8]);
+}
+
+macro_rules! impl_le_bytes {
+	($($ty:ty),+) => {
+		$(
+			impl FromLeBytes for $ty {
+				const SIZE: usize = core::mem::size_of::<$ty>();
+
+				#[inline]
+				fn from_le_bytes(bytes: &[u8]) -> Self {
+					<$ty>::from_le_bytes(bytes.try_into().unwrap())
+				}
+			}
+
+			impl ToLeBytes for $ty {
+				#[inline]
+				fn to_le_bytes(&self, bytes: &mut [u8]) {
+					bytes.copy_from_slice(&<$ty>::to_le_bytes(*self));
+				}
+			}
+		)+
+	};
+}
+
+impl_le_bytes![u8, i8, u16, i16, u32, i32];
+
+impl FromLeBytes for Address16 {
+	const SIZE: usize = 2;
+
+	#[inline]
+	fn from_le_bytes(bytes: &[u8]) -> Self {
+		Address16::new(<u16 as FromLeBytes>::from_le_bytes(bytes))
+	}
+}
+
+impl ToLeBytes for Address16 {
+	#[inline]
+	fn to_le_bytes(&self, bytes: &mut [u8]) {
+		ToLeBytes::to_le_bytes(&u16::from(*self), bytes);
+	}
+}
+
+impl FromLeBytes for Address24 {
+	const SIZE: usize = 3;
+
+	#[inline]
+	fn from_le_bytes(bytes: &[u8]) -> Self {
+		Address24::new(u32::from(bytes[0]) | u32::from(bytes[1]) << 8 | u32::from(bytes[2]) << 16)
+	}
+}
+
+impl ToLeBytes for Address24 {
+	#[inline]
+	fn to_le_bytes(&self, bytes: &mut [u8]) {
+		bytes.copy_from_slice(&u32::from(*self).to_le_bytes()[..3]);
+	}
+}
+
+/// How a value spanning the end of a bank continues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BankWrap {
+	/// Into the next bank, as with long and absolute indexed addressing.
+	Cross,
+	/// From the start of the same bank, as with direct page and stack addressing.
+	Wrap,
+}
+
+impl BankWrap {
+	#[inline]
+	fn add(self, offset: Address24, i: usize) -> Address24 {
+		let i = Address16::new(i as u16);
+		match self {
+			BankWrap::Cross => offset.add_crossing_bank(i),
+			BankWrap::Wrap => offset.add_wrapping_bank(i),
+		}
+	}
+}
+
+impl MemoryMap {
+	/// Reads a little-endian value byte by byte, updating the memory data register,
+	/// e.g. a field of a structure of a game in RAM.
+	/// ```
+	/// # use sneslib::address::Address24;
+	/// # use sneslib::cartridge::*;
+	/// # use sneslib::memory::*;
+	/// let memory_map = MemoryMap::from_parts(vec![0; 0x8000], ROMType::LoROM, 0);
+	/// memory_map.write_value(Address24::new(0x7E0010), -2i16, BankWrap::Cross);
+	/// assert_eq!(memory_map.read_value::<u16>(Address24::new(0x7E0010), BankWrap::Cross), 0xFFFE);
+	/// memory_map.write_value(Address24::new(0x7EFFFF), Address24::new(0x123456), BankWrap::Wrap);
+	/// assert_eq!(memory_map.read(Address24::new(0x7E0000)), 0x34);
+	/// ```
+	///
+	/// # Panics
+	/// Panics if `T` is larger than 4 bytes.
+	#[inline]
+	pub fn read_value<T: FromLeBytes>(&self, offset: Address24, wrap: BankWrap) -> T {
+		let mut bytes = [0; MAX_SIZE];
+		let bytes = &mut bytes[..T::SIZE];
+		for (i, b) in bytes.iter_mut().enumerate() {
+			*b = self.read(wrap.add(offset, i));
+		}
+		T::from_le_bytes(bytes)
+	}
+
+	/// Writes a little-endian value byte by byte, the least significant first, updating the memory data register.
+	///
+	/// # Panics
+	/// Panics if `T` is larger than 4 bytes.
+	#[inline]
+	pub fn write_value<T: ToLeBytes>(&self, offset: Address24, value: T, wrap: BankWrap) {
+		let mut bytes = [0; MAX_SIZE];
+		let bytes = &mut bytes[..T::SIZE];
+		value.to_le_bytes(bytes);
+		for (i, &b) in bytes.iter().enumerate() {
+			self.write(wrap.add(offset, i), b);
+		}
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{CartridgeBuilder, ROMType};
+
+	#[test]
+	fn value() {
+		let cartridge = CartridgeBuilder::new(ROMType::LoROM).build().unwrap();
+		let memory_map = MemoryMap::from_cartridge(cartridge, None);
+		let offset = Address24::new(0x7EFFFE);
+		memory_map.write_value(offset, 0x12345678u32, BankWrap::Cross);
+		assert_eq!(memory_map.read16(Address24::new(0x7F0000)), 0x1234);
+		assert_eq!(
+			memory_map.read_value::<u32>(offset, BankWrap::Cross),
+			0x12345678
+		);
+		assert_eq!(memory_map.read_value::<i32>(offset, BankWrap::Wrap), 0x5678);
+		assert_eq!(memory_map.read_value::<u8>(offset, BankWrap::Wrap), 0x78);
+		assert_eq!(memory_map.read_value::<i8>(offset, BankWrap::Wrap), 0x78);
+		assert_eq!(
+			memory_map.read_value::<Address16>(offset, BankWrap::Cross),
+			Address16::new(0x5678)
+		);
+		assert_eq!(
+			memory_map.read_value::<Address24>(offset, BankWrap::Cross),
+			Address24::new(0x345678)
+		);
+		assert_eq!(
+			memory_map.read24_wrapping_bank(offset),
+			memory_map.read_value(offset, BankWrap::Wrap)
+		);
+
+		memory_map.write_value(offset, -1i16, BankWrap::Wrap);
+		memory_map.write_value(offset, Address16::new(0xABCD), BankWrap::Wrap);
+		assert_eq!(
+			memory_map.read_value::<i16>(offset, BankWrap::Cross),
+			-0x5433
+		);
+		assert_eq!(memory_map.mdr(), 0xAB);
+	}
+}
@@ -0,0 +1,25 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchError {
+	/// The patch does not start with the magic of its format.
+	InvalidHeader,
+	/// The patch ends in the middle of a record or before its end marker.
+	UnexpectedEnd,
+	/// A change lies beyond the largest offset the format can address.
+	OffsetTooLarge(usize),
+}
+
+impl fmt::Display for PatchError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use PatchError::*;
+		match self {
+			InvalidHeader => write!(f, "invalid patch header"),
+			UnexpectedEnd => write!(f, "unexpected end of patch"),
+			OffsetTooLarge(offset) => write!(f, "offset ${:X} is too large for the patch", offset),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PatchError {}
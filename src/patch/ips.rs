@@ -0,0 +1,271 @@
+use alloc::vec::Vec;
+
+use super::{diff, PatchError};
+#[cfg(feature = "std")]
+use crate::cartridge::Cartridge;
+
+const MAGIC: &[u8] = b"PATCH";
+/// Offset reading as `EOF`, marking the end of the records.
+const EOF: usize = 0x454F46;
+const MAX_OFFSET: usize = 0xFFFFFF;
+/// Record size limit, one below the format limit to leave room for moving a record off `EOF`.
+const MAX_RECORD_SIZE: usize = 0xFFFE;
+/// Shortest run written as an RLE record, which takes 8 bytes instead of 5 plus the run.
+const MIN_RLE_SIZE: usize = 9;
+/// Size of the offset and size of a record.
+const RECORD_HEADER_SIZE: usize = 5;
+
+struct Reader<'a> {
+	patch: &'a [u8],
+	position: usize,
+}
+
+impl Reader<'_> {
+	fn read(&mut self, len: usize) -> Result<&[u8], PatchError> {
+		let bytes = self
+			.patch
+			.get(self.position..self.position + len)
+			.ok_or(PatchError::UnexpectedEnd)?;
+		self.position += len;
+		Ok(bytes)
+	}
+
+	fn read_be(&mut self, len: usize) -> Result<usize, PatchError> {
+		Ok(self.read(len)?.iter().fold(0, |r, &b| r << 8 | b as usize))
+	}
+}
+
+/// Applies an IPS patch to a ROM image, growing it as records write past its end.
+///
+/// RLE records and the truncation extension, a 3-byte size following `EOF`, are supported.
+/// ```
+/// # use sneslib::patch::ips;
+/// let mut rom = vec![0; 4];
+/// ips::apply(&mut rom, b"PATCH\x00\x00\x01\x00\x02\xAA\xBBEOF").unwrap();
+/// assert_eq!(rom, [0, 0xAA, 0xBB, 0]);
+/// ```
+pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<(), PatchError> {
+	let mut reader = Reader { patch, position: 0 };
+	if reader.read(MAGIC.len()).ok() != Some(MAGIC) {
+		return Err(PatchError::InvalidHeader);
+	}
+	loop {
+		let offset = reader.read_be(3)?;
+		if offset == EOF {
+			if let Ok(size) = reader.read_be(3) {
+				rom.truncate(size);
+			}
+			return Ok(());
+		}
+		let size = reader.read_be(2)?;
+		let (size, rle) = if size == 0 {
+			(reader.read_be(2)?, Some(reader.read(1)?[0]))
+		} else {
+			(size, None)
+		};
+		if rom.len() < offset + size {
+			rom.resize(offset + size, 0);
+		}
+		let target = &mut rom[offset..offset + size];
+		match rle {
+			Some(value) => target.iter_mut().for_each(|b| *b = value),
+			None => target.copy_from_slice(reader.read(size)?),
+		}
+	}
+}
+
+fn push_be(patch: &mut Vec<u8>, value: usize, len: usize) {
+	patch.extend_from_slice(&value.to_be_bytes()[core::mem::size_of::<usize>() - len..]);
+}
+
+fn push_data(patch: &mut Vec<u8>, new: &[u8], offset: usize, len: usize) {
+	// a record at $454F46 would read as the end marker, so it starts a byte earlier
+	let (offset, len) = if offset == EOF {
+		(offset - 1, len + 1)
+	} else {
+		(offset, len)
+	};
+	push_be(patch, offset, 3);
+	push_be(patch, len, 2);
+	patch.extend_from_slice(&new[offset..offset + len]);
+}
+
+fn push_rle(patch: &mut Vec<u8>, new: &[u8], offset: usize, len: usize) {
+	if offset == EOF {
+		push_data(patch, new, offset, 1);
+		return push_rle(patch, new, offset + 1, len - 1);
+	}
+	push_be(patch, offset, 3);
+	push_be(patch, 0, 2);
+	push_be(patch, len, 2);
+	patch.push(new[offset]);
+}
+
+/// Creates an IPS patch turning `old` into `new`.
+///
+/// Changes separated by fewer unchanged bytes than a record header are merged into one record,
+/// runs of at least 9 equal bytes are written as RLE records,
+/// and a shorter `new` is recorded with the truncation extension.
+/// ```
+/// # use sneslib::patch::ips;
+/// let old = vec![0; 0x100];
+/// let mut new = old.clone();
+/// new[0x10..0x20].copy_from_slice(&[0xFF; 0x10]);
+/// let patch = ips::create(&old, &new).unwrap();
+/// assert_eq!(patch, b"PATCH\x00\x00\x10\x00\x00\x00\x10\xFFEOF");
+///
+/// let mut rom = old.clone();
+/// ips::apply(&mut rom, &patch).unwrap();
+/// assert_eq!(rom, new);
+/// ```
+pub fn create(old: &[u8], new: &[u8]) -> Result<Vec<u8>, PatchError> {
+	// unchanged gaps shorter than a record header are cheaper to rewrite than to skip
+	let mut ranges: Vec<(usize, usize)> = Vec::new();
+	for record in diff(old, new)
+		.iter()
+		.filter(|record| !record.new.is_empty())
+	{
+		let end = record.offset + record.new.len();
+		match ranges.last_mut() {
+			Some(last) if record.offset - last.1 <= RECORD_HEADER_SIZE => last.1 = end,
+			_ => ranges.push((record.offset, end)),
+		}
+	}
+
+	let mut patch = MAGIC.to_vec();
+	for &(start, end) in ranges.iter() {
+		if end - 1 > MAX_OFFSET {
+			return Err(PatchError::OffsetTooLarge(end - 1));
+		}
+
+		let mut data_start = start;
+		let mut i = start;
+		while i < end {
+			let run = new[i..end].iter().take_while(|&&b| b == new[i]).count();
+			if run < MIN_RLE_SIZE {
+				i += run;
+				continue;
+			}
+			for start in (data_start..i).step_by(MAX_RECORD_SIZE) {
+				push_data(&mut patch, new, start, (i - start).min(MAX_RECORD_SIZE));
+			}
+			for start in (i..i + run).step_by(MAX_RECORD_SIZE) {
+				push_rle(
+					&mut patch,
+					new,
+					start,
+					(i + run - start).min(MAX_RECORD_SIZE),
+				);
+			}
+			i += run;
+			data_start = i;
+		}
+		for start in (data_start..end).step_by(MAX_RECORD_SIZE) {
+			push_data(&mut patch, new, start, (end - start).min(MAX_RECORD_SIZE));
+		}
+	}
+	patch.extend_from_slice(b"EOF");
+	if new.len() < old.len() {
+		push_be(&mut patch, new.len(), 3);
+	}
+	Ok(patch)
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Applies an IPS patch to the ROM without a copier header.
+	///
+	/// The tests are not run again, see [`apply`].
+	pub fn apply_ips(&mut self, patch: &[u8]) -> Result<(), PatchError> {
+		self.rom_changed();
+		apply(&mut self.rom, patch)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{test::make_rom, ROMType, TestFlags};
+
+	#[test]
+	fn apply() {
+		let mut rom = vec![0; 8];
+		let patch = b"PATCH\x00\x00\x02\x00\x00\x00\x03\x11\x00\x00\x0A\x00\x01\x22EOF";
+		super::apply(&mut rom, patch).unwrap();
+		assert_eq!(rom, [0, 0, 0x11, 0x11, 0x11, 0, 0, 0, 0, 0, 0x22]);
+
+		super::apply(&mut rom, b"PATCHEOF\x00\x00\x04").unwrap();
+		assert_eq!(rom, [0, 0, 0x11, 0x11]);
+
+		assert_eq!(
+			super::apply(&mut rom, b"IPS\x00\x00\x00"),
+			Err(PatchError::InvalidHeader)
+		);
+		assert_eq!(
+			super::apply(&mut rom, b"PATCH\x00\x00\x00\x00\x02\xAA"),
+			Err(PatchError::UnexpectedEnd)
+		);
+		assert_eq!(
+			super::apply(&mut rom, b"PATCH\x00\x00\x00\x00\x01\xAA"),
+			Err(PatchError::UnexpectedEnd)
+		);
+	}
+
+	#[test]
+	fn create() {
+		let old = make_rom(ROMType::LoROM, 0x80000);
+		let mut new = old.clone();
+		new[0x100..0x104].copy_from_slice(&[1, 2, 3, 4]);
+		new[0x200..0x20100].iter_mut().for_each(|b| *b = 0xEA);
+		new[0x20100..0x20110].copy_from_slice(&[0x42; 0x10]);
+		new.extend_from_slice(&[0x55; 0x20]);
+		let patch = super::create(&old, &new).unwrap();
+		assert!(patch.len() < 0x100);
+		let mut patched = old.clone();
+		super::apply(&mut patched, &patch).unwrap();
+		assert_eq!(patched, new);
+
+		// truncation
+		let patch = super::create(&new, &old).unwrap();
+		assert!(patch.ends_with(b"EOF\x08\x00\x00"));
+		super::apply(&mut patched, &patch).unwrap();
+		assert_eq!(patched, old);
+
+		// records at the end marker offset and longer than a record
+		let old = vec![0; 0x480000];
+		let mut new = old.clone();
+		new[EOF..EOF + 0x10].iter_mut().for_each(|b| *b = 0x10);
+		new[EOF + 0x100..EOF + 0x20100]
+			.iter_mut()
+			.enumerate()
+			.for_each(|(i, b)| *b = i as u8 | 1);
+		let patch = super::create(&old, &new).unwrap();
+		let mut patched = old.clone();
+		super::apply(&mut patched, &patch).unwrap();
+		assert_eq!(patched, new);
+		let mut new = old.clone();
+		new[EOF] = 1;
+		let patch = super::create(&old, &new).unwrap();
+		assert_eq!(&patch[5..10], b"\x45\x4F\x45\x00\x02");
+
+		assert_eq!(
+			super::create(&[], &vec![1; 0x1000001]),
+			Err(PatchError::OffsetTooLarge(0x1000000))
+		);
+	}
+
+	#[test]
+	fn apply_ips() {
+		let rom = make_rom(ROMType::HiROM, 0x80000);
+		let original = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let mut modified = original.clone();
+		modified.rom_mut()[0x1234] ^= 0xFF;
+		modified.fix_checksum();
+
+		let patch = super::create(original.rom(), modified.rom()).unwrap();
+		let mut patched = original.clone();
+		patched.apply_ips(&patch).unwrap();
+		assert_eq!(patched.rom(), modified.rom());
+		assert_eq!(patched.diff(&modified), vec![]);
+	}
+}
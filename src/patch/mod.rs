@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::cartridge::Cartridge;
+
+pub use error::PatchError;
+
+pub mod error;
+pub mod ips;
+
+/// Run of bytes differing between two ROMs.
+///
+/// If the ROMs differ in size, the record at the end of the shorter ROM
+/// holds the bytes only present in the longer one, with `old` or `new` being longer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PatchRecord {
+	pub offset: usize,
+	pub old: Vec<u8>,
+	pub new: Vec<u8>,
+}
+
+/// Compares two ROM images, coalescing adjacent differing bytes into one record.
+/// ```
+/// # use sneslib::patch::*;
+/// let records = diff(&[0, 1, 2, 3, 4], &[0, 9, 9, 3, 4, 5]);
+/// assert_eq!(records, vec![
+///     PatchRecord { offset: 1, old: vec![1, 2], new: vec![9, 9] },
+///     PatchRecord { offset: 5, old: vec![], new: vec![5] },
+/// ]);
+/// ```
+pub fn diff(old: &[u8], new: &[u8]) -> Vec<PatchRecord> {
+	let common = old.len().min(new.len());
+	let mut records = Vec::new();
+	let mut i = 0;
+	while i < common {
+		if old[i] == new[i] {
+			i += 1;
+			continue;
+		}
+		let start = i;
+		while i < common && old[i] != new[i] {
+			i += 1;
+		}
+		records.push(PatchRecord {
+			offset: start,
+			old: old[start..i].to_vec(),
+			new: new[start..i].to_vec(),
+		});
+	}
+
+	if old.len() != new.len() {
+		let (old, new) = (&old[common..], &new[common..]);
+		match records.last_mut() {
+			Some(last) if last.offset + last.old.len() == common => {
+				last.old.extend_from_slice(old);
+				last.new.extend_from_slice(new);
+			}
+			_ => records.push(PatchRecord {
+				offset: common,
+				old: old.to_vec(),
+				new: new.to_vec(),
+			}),
+		}
+	}
+	records
+}
+
+#[cfg(feature = "std")]
+impl Cartridge {
+	/// Compares the ROM with the ROM of a modified cartridge, both without copier headers.
+	///
+	/// See [`diff`].
+	#[inline]
+	pub fn diff(&self, other: &Cartridge) -> Vec<PatchRecord> {
+		diff(self.rom(), other.rom())
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+	use crate::cartridge::{test::make_rom, ROMType, TestFlags};
+
+	#[test]
+	fn diff() {
+		assert_eq!(super::diff(&[1, 2, 3], &[1, 2, 3]), vec![]);
+		assert_eq!(
+			super::diff(&[1, 2, 3], &[1, 9]),
+			vec![PatchRecord {
+				offset: 1,
+				old: vec![2, 3],
+				new: vec![9],
+			}]
+		);
+		assert_eq!(
+			super::diff(&[1, 2, 3], &[9, 2]),
+			vec![
+				PatchRecord {
+					offset: 0,
+					old: vec![1],
+					new: vec![9],
+				},
+				PatchRecord {
+					offset: 2,
+					old: vec![3],
+					new: vec![],
+				},
+			]
+		);
+
+		let rom = make_rom(ROMType::LoROM, 0x80000);
+		let original = Cartridge::new(&rom, TestFlags::default()).unwrap();
+		let mut modified = original.clone();
+		modified.rom_mut()[0x100..0x104].copy_from_slice(&[0xEA; 4]);
+		modified.rom_mut()[0x105] ^= 0xFF;
+		modified.fix_checksum();
+
+		let records = original.diff(&modified);
+		assert_eq!(records[0].offset, 0x100);
+		assert_eq!(records[0].new, vec![0xEA; 4]);
+		assert_eq!(&records[0].old[..], &rom[0x100..0x104]);
+		assert_eq!(records[1].offset, 0x105);
+		assert!(records[2..]
+			.iter()
+			.all(|record| (0x7FDC..0x7FE0).contains(&record.offset)));
+
+		let mut patched = rom;
+		for record in records.iter() {
+			assert_eq!(record.old.len(), record.new.len());
+			patched[record.offset..record.offset + record.new.len()].copy_from_slice(&record.new);
+		}
+		assert_eq!(patched, modified.rom());
+	}
+}